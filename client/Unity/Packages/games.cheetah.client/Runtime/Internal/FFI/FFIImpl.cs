@@ -16,6 +16,38 @@ namespace Games.Cheetah.Client.Internal.FFI
                 disconnectTimeInSec, out clientId);
         }
 
+        public byte CreateClientWithToken(ulong connectionId, string serverAddress, ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId)
+        {
+            return FFIMethods.CreateClientWithToken(connectionId, serverAddress, ref memberToken, ref sessionKey,
+                disconnectTimeInSec, out clientId);
+        }
+
+        public byte CreateSingleThreadedClient(ulong connectionId, string serverAddress, ulong memberId, ulong roomId,
+            ref NetworkBuffer userPrivateKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId)
+        {
+            return FFIMethods.CreateSingleThreadedClient(connectionId, serverAddress, memberId, roomId, ref userPrivateKey,
+                disconnectTimeInSec, out clientId);
+        }
+
+        public byte CreateSingleThreadedClientWithToken(ulong connectionId, string serverAddress, ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId)
+        {
+            return FFIMethods.CreateSingleThreadedClientWithToken(connectionId, serverAddress, ref memberToken, ref sessionKey,
+                disconnectTimeInSec, out clientId);
+        }
+
+        public byte Cycle(ushort clientId, ulong nowInMs)
+        {
+            return FFIMethods.Cycle(clientId, nowInMs);
+        }
+
         public byte GetConnectionStatus(ushort clientId, out ConnectionStatus status)
         {
             return FFIMethods.GetConnectionStatus(clientId, out status);
@@ -26,12 +58,27 @@ namespace Games.Cheetah.Client.Internal.FFI
             return FFIMethods.GetStatistics(clientId, out clientStatistics);
         }
 
+        public byte GetConnectionQuality(ushort clientId, out ConnectionQuality quality)
+        {
+            return FFIMethods.GetConnectionQuality(clientId, out quality);
+        }
+
+        public byte SetConnectionQualityListener(ushort clientId, ConnectionQualityListener listener)
+        {
+            return FFIMethods.SetConnectionQualityListener(clientId, listener);
+        }
+
 
         public unsafe byte Receive(ushort clientId, S2CCommand* commands, ref ushort count)
         {
             return FFIMethods.Receive(clientId, commands, ref count);
         }
 
+        public byte ReceiveWithCallbacks(ushort clientId, ref S2CCommandCallbacks callbacks)
+        {
+            return FFIMethods.ReceiveWithCallbacks(clientId, ref callbacks);
+        }
+
         public byte DestroyClient(ushort clientId)
         {
             return FFIMethods.DestroyClient(clientId);
@@ -57,6 +104,26 @@ namespace Games.Cheetah.Client.Internal.FFI
             return FFIMethods.SetChannelType(clientId, reliabilityGuarantees, group);
         }
 
+        public byte SetCompression(ushort clientId, bool compression)
+        {
+            return FFIMethods.SetCompression(clientId, compression);
+        }
+
+        public byte SetReconnectPolicy(ushort clientId, ulong initialDelayInMs, ulong maxDelayInMs, uint maxAttempts)
+        {
+            return FFIMethods.SetReconnectPolicy(clientId, initialDelayInMs, maxDelayInMs, maxAttempts);
+        }
+
+        public byte DisableReconnect(ushort clientId)
+        {
+            return FFIMethods.DisableReconnect(clientId);
+        }
+
+        public byte IsResynchronizing(ushort clientId, out bool result)
+        {
+            return FFIMethods.IsResynchronizing(clientId, out result);
+        }
+
         public byte SetRttEmulation(ushort clientId, ulong rttInMs, double rttDispersion)
         {
             return FFIMethods.SetRttEmulation(clientId, rttInMs, rttDispersion);
@@ -67,6 +134,31 @@ namespace Games.Cheetah.Client.Internal.FFI
             return FFIMethods.SetDropEmulation(clientId, dropProbability, dropTimeInMs);
         }
 
+        public byte SetBandwidthEmulation(ushort clientId, ulong bytesPerSecond, ulong queueSizeInBytes)
+        {
+            return FFIMethods.SetBandwidthEmulation(clientId, bytesPerSecond, queueSizeInBytes);
+        }
+
+        public byte SetDuplicateEmulation(ushort clientId, double duplicateProbability)
+        {
+            return FFIMethods.SetDuplicateEmulation(clientId, duplicateProbability);
+        }
+
+        public byte SetReorderEmulation(ushort clientId, double reorderProbability, ulong reorderDelayInMs)
+        {
+            return FFIMethods.SetReorderEmulation(clientId, reorderProbability, reorderDelayInMs);
+        }
+
+        public byte SetBurstLossEmulation(ushort clientId, double goodToBadProbability, double badToGoodProbability, double goodLossProbability, double badLossProbability)
+        {
+            return FFIMethods.SetBurstLossEmulation(clientId, goodToBadProbability, badToGoodProbability, goodLossProbability, badLossProbability);
+        }
+
+        public byte SetEmulationPreset(ushort clientId, NetworkConditionsPreset preset)
+        {
+            return FFIMethods.SetEmulationPreset(clientId, (byte)preset);
+        }
+
         public byte ResetEmulation(ushort clientId)
         {
             return FFIMethods.ResetEmulation(clientId);
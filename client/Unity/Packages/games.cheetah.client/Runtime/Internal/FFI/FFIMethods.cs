@@ -27,6 +27,39 @@ namespace Games.Cheetah.Client.Internal.FFI
             out ushort clientId
         );
 
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "create_client_with_token")]
+        public static extern byte CreateClientWithToken(
+            ulong connectionId,
+            [MarshalAs(UnmanagedType.LPStr)] string serverAddress,
+            ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId
+        );
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "create_single_threaded_client")]
+        public static extern byte CreateSingleThreadedClient(
+            ulong connectionId,
+            [MarshalAs(UnmanagedType.LPStr)] string serverAddress,
+            ulong memberId,
+            ulong roomId,
+            ref NetworkBuffer userPrivateKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId
+        );
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "create_single_threaded_client_with_token")]
+        public static extern byte CreateSingleThreadedClientWithToken(
+            ulong connectionId,
+            [MarshalAs(UnmanagedType.LPStr)] string serverAddress,
+            ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId
+        );
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "cycle")]
+        public static extern byte Cycle(ushort clientId, ulong nowInMs);
 
         [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "get_connection_status")]
         public static extern byte GetConnectionStatus(ushort clientId, out ConnectionStatus status);
@@ -34,10 +67,19 @@ namespace Games.Cheetah.Client.Internal.FFI
         [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "get_statistics")]
         public static extern byte GetStatistics(ushort clientId, out Statistics clientStatistics);
 
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "get_connection_quality")]
+        public static extern byte GetConnectionQuality(ushort clientId, out ConnectionQuality quality);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_connection_quality_listener")]
+        public static extern byte SetConnectionQualityListener(ushort clientId, ConnectionQualityListener listener);
+
 
         [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "receive")]
         public static extern unsafe byte Receive(ushort clientId, S2CCommand* commands, ref ushort count);
 
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "receive_with_callbacks")]
+        public static extern byte ReceiveWithCallbacks(ushort clientId, ref S2CCommandCallbacks callbacks);
+
 
         [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "destroy_client")]
         public static extern byte DestroyClient(ushort clientId);
@@ -56,12 +98,39 @@ namespace Games.Cheetah.Client.Internal.FFI
         public static extern byte SetChannelType(ushort clientId, ReliabilityGuarantees reliabilityGuarantees,
             byte group);
 
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_compression")]
+        public static extern byte SetCompression(ushort clientId, [MarshalAs(UnmanagedType.I1)] bool compression);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_reconnect_policy")]
+        public static extern byte SetReconnectPolicy(ushort clientId, ulong initialDelayInMs, ulong maxDelayInMs, uint maxAttempts);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "disable_reconnect")]
+        public static extern byte DisableReconnect(ushort clientId);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "is_resynchronizing")]
+        public static extern byte IsResynchronizing(ushort clientId, [MarshalAs(UnmanagedType.I1)] out bool result);
+
         [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_rtt_emulation")]
         public static extern byte SetRttEmulation(ushort clientId, ulong rttInMs, double rttDispersion);
 
         [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_drop_emulation")]
         public static extern byte SetDropEmulation(ushort clientId, double dropProbability, ulong dropTimeInMs);
 
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_bandwidth_emulation")]
+        public static extern byte SetBandwidthEmulation(ushort clientId, ulong bytesPerSecond, ulong queueSizeInBytes);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_duplicate_emulation")]
+        public static extern byte SetDuplicateEmulation(ushort clientId, double duplicateProbability);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_reorder_emulation")]
+        public static extern byte SetReorderEmulation(ushort clientId, double reorderProbability, ulong reorderDelayInMs);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_burst_loss_emulation")]
+        public static extern byte SetBurstLossEmulation(ushort clientId, double goodToBadProbability, double badToGoodProbability, double goodLossProbability, double badLossProbability);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "set_emulation_preset")]
+        public static extern byte SetEmulationPreset(ushort clientId, byte preset);
+
         [DllImport(Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "reset_emulation")]
         public static extern byte ResetEmulation(ushort clientId);
 
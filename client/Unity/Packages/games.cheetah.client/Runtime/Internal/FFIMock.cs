@@ -48,6 +48,34 @@ namespace Games.Cheetah.Client.Internal
             return 0;
         }
 
+        public byte CreateClientWithToken(ulong connectionId, string serverAddress, ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey,
+            ulong disconnectTimeInSec, out ushort clientId)
+        {
+            clientIdGenerator++;
+            clientId = clientIdGenerator;
+            return 0;
+        }
+
+        public byte CreateSingleThreadedClient(ulong connectionId, string serverAddress, ulong memberId, ulong roomId,
+            ref NetworkBuffer userPrivateKey,
+            ulong disconnectTimeInSec, out ushort clientId)
+        {
+            return CreateClient(connectionId, serverAddress, memberId, roomId, ref userPrivateKey, disconnectTimeInSec, out clientId);
+        }
+
+        public byte CreateSingleThreadedClientWithToken(ulong connectionId, string serverAddress, ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey,
+            ulong disconnectTimeInSec, out ushort clientId)
+        {
+            return CreateClientWithToken(connectionId, serverAddress, ref memberToken, ref sessionKey, disconnectTimeInSec, out clientId);
+        }
+
+        public byte Cycle(ushort clientId, ulong nowInMs)
+        {
+            return 0;
+        }
+
         public byte GetConnectionStatus(ushort clientId, out ConnectionStatus status)
         {
             status = ConnectionStatus.Connected;
@@ -60,6 +88,17 @@ namespace Games.Cheetah.Client.Internal
             return 0;
         }
 
+        public byte GetConnectionQuality(ushort clientId, out ConnectionQuality quality)
+        {
+            quality = ConnectionQuality.Unknown;
+            return 0;
+        }
+
+        public byte SetConnectionQualityListener(ushort clientId, ConnectionQualityListener listener)
+        {
+            return 0;
+        }
+
         public unsafe byte Receive(ushort clientId, S2CCommand* commands, ref ushort count)
         {
             var i = 0;
@@ -74,6 +113,11 @@ namespace Games.Cheetah.Client.Internal
             return 0;
         }
 
+        public byte ReceiveWithCallbacks(ushort clientId, ref S2CCommandCallbacks callbacks)
+        {
+            return 0;
+        }
+
         public byte DestroyClient(ushort clientId)
         {
             return 0;
@@ -99,6 +143,27 @@ namespace Games.Cheetah.Client.Internal
             return 0;
         }
 
+        public byte SetCompression(ushort clientId, bool compression)
+        {
+            return 0;
+        }
+
+        public byte SetReconnectPolicy(ushort clientId, ulong initialDelayInMs, ulong maxDelayInMs, uint maxAttempts)
+        {
+            return 0;
+        }
+
+        public byte DisableReconnect(ushort clientId)
+        {
+            return 0;
+        }
+
+        public byte IsResynchronizing(ushort clientId, out bool result)
+        {
+            result = false;
+            return 0;
+        }
+
         public byte SetRttEmulation(ushort clientId, ulong rttInMs, double rttDispersion)
         {
             return 0;
@@ -109,6 +174,31 @@ namespace Games.Cheetah.Client.Internal
             return 0;
         }
 
+        public byte SetBandwidthEmulation(ushort clientId, ulong bytesPerSecond, ulong queueSizeInBytes)
+        {
+            return 0;
+        }
+
+        public byte SetDuplicateEmulation(ushort clientId, double duplicateProbability)
+        {
+            return 0;
+        }
+
+        public byte SetReorderEmulation(ushort clientId, double reorderProbability, ulong reorderDelayInMs)
+        {
+            return 0;
+        }
+
+        public byte SetBurstLossEmulation(ushort clientId, double goodToBadProbability, double badToGoodProbability, double goodLossProbability, double badLossProbability)
+        {
+            return 0;
+        }
+
+        public byte SetEmulationPreset(ushort clientId, NetworkConditionsPreset preset)
+        {
+            return 0;
+        }
+
         public byte ResetEmulation(ushort clientId)
         {
             return 0;
@@ -17,16 +17,57 @@ namespace Games.Cheetah.Client.Internal
             out ushort clientId
         );
 
+        byte CreateClientWithToken(
+            ulong connectionId,
+            string serverAddress,
+            ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId
+        );
+
+        byte CreateSingleThreadedClient(
+            ulong connectionId,
+            string serverAddress,
+            ulong memberId,
+            ulong roomId,
+            ref NetworkBuffer userPrivateKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId
+        );
+
+        byte CreateSingleThreadedClientWithToken(
+            ulong connectionId,
+            string serverAddress,
+            ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey,
+            ulong disconnectTimeInSec,
+            out ushort clientId
+        );
+
+        byte Cycle(ushort clientId, ulong nowInMs);
         byte GetConnectionStatus(ushort clientId, out ConnectionStatus status);
         byte GetStatistics(ushort clientId, out Statistics clientStatistics);
+        byte GetConnectionQuality(ushort clientId, out ConnectionQuality quality);
+        byte SetConnectionQualityListener(ushort clientId, ConnectionQualityListener listener);
         unsafe byte Receive(ushort clientId, S2CCommand* commands, ref ushort count);
+        byte ReceiveWithCallbacks(ushort clientId, ref S2CCommandCallbacks callbacks);
         byte DestroyClient(ushort clientId);
         byte DestroyClientWithoutDisconnect(ushort clientId);
         byte AttachToRoom(ushort clientId);
         byte DetachFromRoom(ushort clientId);
         byte SetChannelType(ushort clientId, ReliabilityGuarantees reliabilityGuarantees, byte group);
+        byte SetCompression(ushort clientId, bool compression);
+        byte SetReconnectPolicy(ushort clientId, ulong initialDelayInMs, ulong maxDelayInMs, uint maxAttempts);
+        byte DisableReconnect(ushort clientId);
+        byte IsResynchronizing(ushort clientId, out bool result);
         byte SetRttEmulation(ushort clientId, ulong rttInMs, double rttDispersion);
         byte SetDropEmulation(ushort clientId, double dropProbability, ulong dropTimeInMs);
+        byte SetBandwidthEmulation(ushort clientId, ulong bytesPerSecond, ulong queueSizeInBytes);
+        byte SetDuplicateEmulation(ushort clientId, double duplicateProbability);
+        byte SetReorderEmulation(ushort clientId, double reorderProbability, ulong reorderDelayInMs);
+        byte SetBurstLossEmulation(ushort clientId, double goodToBadProbability, double badToGoodProbability, double goodLossProbability, double badLossProbability);
+        byte SetEmulationPreset(ushort clientId, NetworkConditionsPreset preset);
         byte ResetEmulation(ushort clientId);
         void GetLastErrorMsg(ref NetworkBuffer buffer);
         byte GetServerTime(ushort clientId, out ulong time);
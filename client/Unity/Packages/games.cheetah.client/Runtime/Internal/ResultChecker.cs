@@ -17,6 +17,7 @@ namespace Games.Cheetah.Client.Internal
                 case 3: throw new ConnectionStatusMutexError(GetLastError());
                 case 4: throw new SendTaskError(GetLastError());
                 case 5: throw new CreateClientError(GetLastError());
+                case 10: throw new WrongEmulationParametersError(GetLastError());
             }
         }
 
@@ -71,4 +72,11 @@ namespace Games.Cheetah.Client.Internal
         {
         }
     }
+
+    public class WrongEmulationParametersError : Exception
+    {
+        public WrongEmulationParametersError(string msg) : base(msg)
+        {
+        }
+    }
 }
\ No newline at end of file
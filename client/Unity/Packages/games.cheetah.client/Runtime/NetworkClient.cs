@@ -1,4 +1,5 @@
 using System;
+using System.Diagnostics;
 using Games.Cheetah.Client.Codec;
 using Games.Cheetah.Client.Internal;
 using Games.Cheetah.Client.Internal.FFI;
@@ -26,8 +27,15 @@ namespace Games.Cheetah.Client
         private bool enableClientLog = true;
         private ReliabilityGuaranteesChannel currentReliabilityGuaranteesChannel;
         private NetworkBuffer buffer;
+        private readonly bool singleThreaded;
+        private readonly Stopwatch cycleStopwatch = Stopwatch.StartNew();
         internal readonly S2CCommand[] s2cCommands = new S2CCommand[1024];
         internal ushort S2CCommandsCount;
+
+        /// <summary>
+        /// Ссылка на обработчик, чтобы делегат не был удален сборщиком мусора
+        /// </summary>
+        private ConnectionQualityListener connectionQualityListener;
         public Writer Writer { get; }
         public Reader Reader { get; }
 
@@ -43,6 +51,13 @@ namespace Games.Cheetah.Client
          *   - идентификатор соединения, изначально 0, если потребуется снова присоединится к данному клиенту на сервере,
          *     то connectionId должен быть +1 к предыдущему. Данный механизм используется только для переподключения клиента при краше игры.
          *
+         * singleThreaded
+         *   - клиент без сетевого потока (WebGL, платформы без потоков), сетевой обмен выполняется в Update
+         *
+         * webSocket
+         *   - подключение через WebSocket listener сервера вместо UDP, serverUdpPort - порт WebSocket listener
+         *   - только нативные платформы и ws:// без TLS, в WebGL сборках не работает
+         *
          */
         public NetworkClient(
             ulong connectionId,
@@ -52,11 +67,45 @@ namespace Games.Cheetah.Client
             ulong roomId,
             byte[] privateUserKey,
             CodecRegistry codecRegistry,
-            ulong disconnectTimeInSec
+            ulong disconnectTimeInSec,
+            bool singleThreaded = false,
+            bool webSocket = false
         ) : this(connectionId, new FFIImpl(), serverUdpHost, serverUdpPort, memberId, roomId,
             privateUserKey,
             codecRegistry,
-            disconnectTimeInSec)
+            disconnectTimeInSec,
+            null,
+            singleThreaded,
+            webSocket)
+        {
+        }
+
+        /// <summary>
+        /// Подключение по токену, выданному backend-ом, без предварительного создания пользователя на сервере.
+        /// Идентификаторы комнаты и пользователя берутся из токена.
+        /// </summary>
+        /// <param name="memberToken">токен пользователя</param>
+        /// <param name="sessionKey">сессионный ключ, выданный backend-ом вместе с токеном</param>
+        /// <param name="singleThreaded">без сетевого потока, сетевой обмен выполняется в <see cref="Update"/></param>
+        /// <param name="webSocket">подключение через WebSocket listener сервера вместо UDP</param>
+        public NetworkClient(
+            ulong connectionId,
+            string serverUdpHost,
+            ushort serverUdpPort,
+            byte[] memberToken,
+            byte[] sessionKey,
+            CodecRegistry codecRegistry,
+            ulong disconnectTimeInSec,
+            bool singleThreaded = false,
+            bool webSocket = false
+        ) : this(connectionId, new FFIImpl(), serverUdpHost, serverUdpPort, GetMemberIdFromToken(memberToken),
+            GetRoomIdFromToken(memberToken),
+            sessionKey,
+            codecRegistry,
+            disconnectTimeInSec,
+            memberToken,
+            singleThreaded,
+            webSocket)
         {
         }
 
@@ -69,9 +118,13 @@ namespace Games.Cheetah.Client
             ulong roomId,
             byte[] privateUserKey,
             CodecRegistry codecRegistry,
-            ulong disconnectTimeInSec)
+            ulong disconnectTimeInSec,
+            byte[] memberToken = null,
+            bool singleThreaded = false,
+            bool webSocket = false)
         {
             this.ffi = ffi;
+            this.singleThreaded = singleThreaded;
             this.serverUdpHost = serverUdpHost;
             this.serverUdpPort = serverUdpPort; // очищаем логи с предыдущего клиента
             CodecRegistry = codecRegistry;
@@ -81,14 +134,28 @@ namespace Games.Cheetah.Client
 
             var userPrivateKey = new NetworkBuffer(privateUserKey);
 
-            ResultChecker.Check(ffi.CreateClient(
-                connectionId,
-                serverUdpHost + ":" + serverUdpPort,
-                (ushort)memberId,
-                roomId,
-                ref userPrivateKey,
-                disconnectTimeInSec,
-                out Id));
+            if (memberToken == null)
+            {
+                ResultChecker.Check((singleThreaded ? (CreateClientFunction)ffi.CreateSingleThreadedClient : ffi.CreateClient)(
+                    connectionId,
+                    GetServerAddress(serverUdpHost, serverUdpPort, webSocket),
+                    (ushort)memberId,
+                    roomId,
+                    ref userPrivateKey,
+                    disconnectTimeInSec,
+                    out Id));
+            }
+            else
+            {
+                var memberTokenBuffer = new NetworkBuffer(memberToken);
+                ResultChecker.Check((singleThreaded ? (CreateClientWithTokenFunction)ffi.CreateSingleThreadedClientWithToken : ffi.CreateClientWithToken)(
+                    connectionId,
+                    GetServerAddress(serverUdpHost, serverUdpPort, webSocket),
+                    ref memberTokenBuffer,
+                    ref userPrivateKey,
+                    disconnectTimeInSec,
+                    out Id));
+            }
 
             Writer = new Writer(ffi, CodecRegistry, Id);
             Reader = new Reader(this, CodecRegistry);
@@ -96,6 +163,46 @@ namespace Games.Cheetah.Client
             SetReliabilityGuarantees(ReliabilityGuaranteesChannel.Default);
         }
 
+        private delegate byte CreateClientFunction(ulong connectionId, string serverAddress, ulong memberId, ulong roomId,
+            ref NetworkBuffer userPrivateKey, ulong disconnectTimeInSec, out ushort clientId);
+
+        private delegate byte CreateClientWithTokenFunction(ulong connectionId, string serverAddress, ref NetworkBuffer memberToken,
+            ref NetworkBuffer sessionKey, ulong disconnectTimeInSec, out ushort clientId);
+
+        /// <summary>
+        /// Токен: room_id, user_id, groups, expires_at, nonce (u64, big endian) и подпись,
+        /// идентификаторы пользователей подключенных по токену имеют установленный старший бит
+        /// </summary>
+        private static ulong GetMemberIdFromToken(byte[] memberToken)
+        {
+            return ReadBigEndianULong(memberToken, 8) | (1UL << 63);
+        }
+
+        private static ulong GetRoomIdFromToken(byte[] memberToken)
+        {
+            return ReadBigEndianULong(memberToken, 0);
+        }
+
+        private static ulong ReadBigEndianULong(byte[] source, int offset)
+        {
+            ulong result = 0;
+            for (var i = 0; i < 8; i++)
+            {
+                result = (result << 8) | source[offset + i];
+            }
+
+            return result;
+        }
+
+        /// <summary>
+        /// Адрес сервера в формате host:port, IPv6 адрес заключается в квадратные скобки
+        /// </summary>
+        private static string GetServerAddress(string host, ushort port, bool webSocket)
+        {
+            var address = host.Contains(":") && !host.StartsWith("[") ? $"[{host}]:{port}" : $"{host}:{port}";
+            return webSocket ? $"ws://{address}" : address;
+        }
+
         /// <summary>
         /// Отключить клиентские логи
         /// </summary>
@@ -107,9 +214,15 @@ namespace Games.Cheetah.Client
 
         /// <summary>
         /// Обновление состояние. Получение сетевых команд.
+        /// В однопоточном режиме также выполняется прием и отправка пакетов.
         /// </summary>
         public void Update()
         {
+            if (singleThreaded)
+            {
+                ResultChecker.Check(ffi.Cycle(Id, (ulong)cycleStopwatch.ElapsedMilliseconds));
+            }
+
             unsafe
             {
                 fixed (S2CCommand* commands = s2cCommands)
@@ -123,6 +236,21 @@ namespace Games.Cheetah.Client
         }
 
 
+        /// <summary>
+        /// Получение сетевых команд через обработчики, без копирования команд и без ограничения на их количество.
+        /// Используется вместо Update, Reader при этом не обновляется.
+        /// </summary>
+        public void Receive(ref S2CCommandCallbacks callbacks)
+        {
+            if (singleThreaded)
+            {
+                ResultChecker.Check(ffi.Cycle(Id, (ulong)cycleStopwatch.ElapsedMilliseconds));
+            }
+
+            ResultChecker.Check(ffi.ReceiveWithCallbacks(Id, ref callbacks));
+            NetworkClientLogs.CollectLogs(enableClientLog);
+        }
+
         public ConnectionStatus GetConnectionStatus()
         {
             try
@@ -142,6 +270,25 @@ namespace Games.Cheetah.Client
             return statistics;
         }
 
+        /// <summary>
+        /// Оценка качества соединения
+        /// </summary>
+        public ConnectionQuality GetConnectionQuality()
+        {
+            ResultChecker.Check(ffi.GetConnectionQuality(Id, out var quality));
+            return quality;
+        }
+
+        /// <summary>
+        /// Обработчик изменения оценки качества соединения, вызывается внутри Receive и Update (для однопоточного клиента),
+        /// null - удалить обработчик
+        /// </summary>
+        public void SetConnectionQualityListener(ConnectionQualityListener listener)
+        {
+            connectionQualityListener = listener;
+            ResultChecker.Check(ffi.SetConnectionQualityListener(Id, listener));
+        }
+
 
         /// <summary>
         /// Создать объект, принадлежащий пользователю
@@ -218,6 +365,39 @@ namespace Games.Cheetah.Client
                 reliabilityGuaranteesChannel.group));
         }
 
+        /// <summary>
+        /// Включить сжатие отправляемых команд
+        /// </summary>
+        public void SetCompression(bool compression)
+        {
+            ResultChecker.Check(ffi.SetCompression(Id, compression));
+        }
+
+
+        /// <summary>
+        /// Включить автоматическое переподключение при разрыве соединения.
+        /// Задержка перед попыткой растет экспоненциально, после переподключения клиент заново присоединяется к комнате.
+        /// </summary>
+        /// <param name="maxAttempts">максимальное количество попыток подряд, 0 - без ограничения</param>
+        public void EnableReconnect(TimeSpan initialDelay, TimeSpan maxDelay, uint maxAttempts = 0)
+        {
+            ResultChecker.Check(ffi.SetReconnectPolicy(Id, (ulong)initialDelay.TotalMilliseconds, (ulong)maxDelay.TotalMilliseconds, maxAttempts));
+        }
+
+        public void DisableReconnect()
+        {
+            ResultChecker.Check(ffi.DisableReconnect(Id));
+        }
+
+        /// <summary>
+        /// Команды последнего Update получены в процессе синхронизации состояния комнаты после переподключения
+        /// </summary>
+        public bool IsResynchronizing()
+        {
+            ResultChecker.Check(ffi.IsResynchronizing(Id, out var result));
+            return result;
+        }
+
 
         /// <summary>
         /// Сброс эмуляции параметров сети
@@ -244,6 +424,48 @@ namespace Games.Cheetah.Client
         {
             ResultChecker.Check(ffi.SetDropEmulation(Id, dropProbability, dropTimeInMs));
         }
+
+        /// <summary>
+        /// Задать ограничение пропускной способности канала,
+        /// пакеты не поместившиеся в очередь на передачу отбрасываются
+        /// </summary>
+        public void SetBandwidthEmulation(ulong bytesPerSecond, ulong queueSizeInBytes)
+        {
+            ResultChecker.Check(ffi.SetBandwidthEmulation(Id, bytesPerSecond, queueSizeInBytes));
+        }
+
+        /// <summary>
+        /// Задать вероятность дублирования пакета
+        /// </summary>
+        public void SetDuplicateEmulation(double duplicateProbability)
+        {
+            ResultChecker.Check(ffi.SetDuplicateEmulation(Id, duplicateProbability));
+        }
+
+        /// <summary>
+        /// Задать вероятность задержки пакета, пакеты отправленные позже обгоняют задержанный пакет
+        /// </summary>
+        public void SetReorderEmulation(double reorderProbability, ulong reorderDelayInMs)
+        {
+            ResultChecker.Check(ffi.SetReorderEmulation(Id, reorderProbability, reorderDelayInMs));
+        }
+
+        /// <summary>
+        /// Задать параметры потери пакетов сериями (модель Гилберта-Эллиота)
+        /// Подробнее смотрите в документации проекта
+        /// </summary>
+        public void SetBurstLossEmulation(double goodToBadProbability, double badToGoodProbability, double goodLossProbability, double badLossProbability)
+        {
+            ResultChecker.Check(ffi.SetBurstLossEmulation(Id, goodToBadProbability, badToGoodProbability, goodLossProbability, badLossProbability));
+        }
+
+        /// <summary>
+        /// Заменить все параметры эмуляции параметрами типичной сети
+        /// </summary>
+        public void SetEmulationPreset(NetworkConditionsPreset preset)
+        {
+            ResultChecker.Check(ffi.SetEmulationPreset(Id, preset));
+        }
     }
 
     public class ServerTimeNotDefinedException : Exception
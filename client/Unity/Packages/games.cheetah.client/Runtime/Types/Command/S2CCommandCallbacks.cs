@@ -0,0 +1,84 @@
+using System;
+using System.Runtime.InteropServices;
+using Games.Cheetah.Client.Types.Object;
+
+namespace Games.Cheetah.Client.Types.Command
+{
+    /// <summary>
+    /// Обработчики входящих команд по типам, альтернатива получению команд через массив S2CCommand.
+    /// Обработчик вызывается для каждой команды без ограничения на количество команд,
+    /// null - команды данного типа пропускаются.
+    /// Для IL2CPP обработчики должны быть статическими методами с атрибутом MonoPInvokeCallback.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    public struct S2CCommandCallbacks
+    {
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnCreate(ref S2CCommands.CreateObject command);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnCreated(ref S2CCommands.CreatedObject command);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnSetLong(ref S2CCommands.SetLong command);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnSetDouble(ref S2CCommands.SetDouble command);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnBinaryField(ref BinaryFieldRef command);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnDelete(ref NetworkObjectId objectId);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnDeleteField(ref S2CCommands.DeleteField command);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnMemberConnected(ref S2CCommands.MemberConnected command);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnMemberDisconnected(ref S2CCommands.MemberDisconnected command);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnResync();
+
+        public OnCreate onCreate;
+        public OnCreated onCreated;
+        public OnSetLong onSetLong;
+        public OnSetDouble onSetDouble;
+        public OnBinaryField onSetStructure;
+        public OnBinaryField onEvent;
+        public OnBinaryField onAddItem;
+        public OnDelete onDelete;
+        public OnDeleteField onDeleteField;
+        public OnMemberConnected onMemberConnected;
+        public OnMemberDisconnected onMemberDisconnected;
+
+        /// <summary>
+        /// Начало и окончание получения состояния комнаты после автоматического переподключения
+        /// </summary>
+        public OnResync onResyncBegin;
+
+        public OnResync onResyncEnd;
+    }
+
+    /// <summary>
+    /// Бинарное поле без копирования, данные доступны только во время вызова обработчика
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    public struct BinaryFieldRef
+    {
+        public NetworkObjectId objectId;
+        public ushort fieldId;
+        public IntPtr value;
+        public ushort len;
+
+        public unsafe ReadOnlySpan<byte> Value => new((void*)value, len);
+
+        public override string ToString()
+        {
+            return $"{nameof(objectId)}: {objectId}, {nameof(fieldId)}: {fieldId}, {nameof(len)}: {len}";
+        }
+    }
+}
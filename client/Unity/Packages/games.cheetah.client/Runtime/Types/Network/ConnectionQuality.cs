@@ -0,0 +1,26 @@
+using System.Runtime.InteropServices;
+
+namespace Games.Cheetah.Client.Types.Network
+{
+    /// <summary>
+    /// Оценка качества соединения для индикатора сети в интерфейсе игры
+    /// </summary>
+    public enum ConnectionQuality
+    {
+        /// <summary>
+        /// Недостаточно данных для оценки или соединение не установлено
+        /// </summary>
+        Unknown,
+        Excellent,
+        Good,
+        Fair,
+        Poor
+    }
+
+    /// <summary>
+    /// Обработчик изменения оценки качества соединения.
+    /// Для IL2CPP обработчик должен быть статическим методом с атрибутом MonoPInvokeCallback.
+    /// </summary>
+    [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+    public delegate void ConnectionQualityListener(ushort clientId, ConnectionQuality quality);
+}
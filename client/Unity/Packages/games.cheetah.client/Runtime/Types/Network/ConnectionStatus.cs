@@ -10,5 +10,11 @@ namespace Games.Cheetah.Client.Types.Network
         DisconnectedByClientStopped,
         DisconnectedByRoomDeleted,
         DisconnectedByMemberDeleted,
+        DisconnectedByServerShutdown,
+
+        /// <summary>
+        /// Соединение разорвано, выполняется автоматическое переподключение
+        /// </summary>
+        Reconnecting,
     }
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+namespace Games.Cheetah.Client.Types.Network
+{
+    /// <summary>
+    /// Типичные характеристики сетей для эмуляции
+    /// </summary>
+    public enum NetworkConditionsPreset
+    {
+        Wifi,
+
+        /// <summary>
+        /// Перегруженный Wi-Fi - большой разброс RTT, потери сериями, дублирование и перестановка пакетов
+        /// </summary>
+        CongestedWifi,
+        Lte,
+        ThreeG,
+        Edge
+    }
+}
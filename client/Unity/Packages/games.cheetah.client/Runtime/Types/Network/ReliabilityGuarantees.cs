@@ -35,7 +35,13 @@ namespace Games.Cheetah.Client.Types.Network
         /// - гарантия доставки
         /// - команды выполняются строго последовательно в рамках группы
         /// </summary>
-        ReliableSequence
+        ReliableSequence,
+
+        /// <summary>
+        /// - гарантия доставки
+        /// - для команд установки значения поля доставляется только последнее значение
+        /// </summary>
+        ReliableLatest
     }
 
 
@@ -72,6 +78,12 @@ namespace Games.Cheetah.Client.Types.Network
         /// </summary>
         public static readonly ReliabilityGuaranteesChannel UnreliableOrdered = new(ReliabilityGuarantees.UnreliableOrdered);
 
+        /// <summary>
+        /// - гарантия доставки
+        /// - для команд установки значения поля доставляется только последнее значение
+        /// </summary>
+        public static readonly ReliabilityGuaranteesChannel ReliableLatest = new(ReliabilityGuarantees.ReliableLatest);
+
         public static readonly ReliabilityGuaranteesChannel Default = ReliableSequence;
 
         public readonly ReliabilityGuarantees ReliabilityGuarantees;
@@ -35,10 +35,78 @@ namespace Games.Cheetah.Client.Types.Network
         /// </summary>
         public ulong SendSize;
 
+        /// <summary>
+        /// Размер отправленных команд без сжатия
+        /// </summary>
+        public ulong SendPayloadRawSize;
+
+        /// <summary>
+        /// Размер отправленных команд после сжатия
+        /// </summary>
+        public ulong SendPayloadSize;
+
+        /// <summary>
+        /// Размер принятых команд после распаковки
+        /// </summary>
+        public ulong ReceivePayloadRawSize;
+
+        /// <summary>
+        /// Размер принятых команд до распаковки
+        /// </summary>
+        public ulong ReceivePayloadSize;
+
+        /// <summary>
+        /// Минимальный RTT по последним замерам
+        /// </summary>
+        public ulong RttMinInMs;
+
+        /// <summary>
+        /// Средний RTT по последним замерам
+        /// </summary>
+        public ulong RttAvgInMs;
+
+        /// <summary>
+        /// Максимальный RTT по последним замерам
+        /// </summary>
+        public ulong RttMaxInMs;
+
+        /// <summary>
+        /// Среднее отклонение между соседними замерами RTT
+        /// </summary>
+        public ulong RttJitterInMs;
+
+        /// <summary>
+        /// Доля потерянных входящих пакетов, от 0 до 1
+        /// </summary>
+        public double PacketLoss;
+
+        /// <summary>
+        /// Количество повторно отправленных пакетов
+        /// </summary>
+        public ulong RetransmitCount;
+
+        /// <summary>
+        /// Количество отправленных надежных пакетов, ожидающих подтверждения
+        /// </summary>
+        public ulong UnackedReliableFrames;
+
+        /// <summary>
+        /// Время с момента получения последнего пакета
+        /// </summary>
+        public ulong SinceLastFrameInMs;
+
+        /// <summary>
+        /// Оценка качества соединения
+        /// </summary>
+        public ConnectionQuality ConnectionQuality;
+
         public override string ToString()
         {
             return
-                $"{nameof(LastFrameId)}: {LastFrameId}, {nameof(RttInMs)}: {RttInMs}, {nameof(ReceivePacketCount)}: {ReceivePacketCount}, {nameof(SendPacketCount)}: {SendPacketCount}, {nameof(ReceiveSize)}: {ReceiveSize}, {nameof(SendSize)}: {SendSize}";
+                $"{nameof(LastFrameId)}: {LastFrameId}, {nameof(RttInMs)}: {RttInMs}, {nameof(ReceivePacketCount)}: {ReceivePacketCount}, {nameof(SendPacketCount)}: {SendPacketCount}, {nameof(ReceiveSize)}: {ReceiveSize}, {nameof(SendSize)}: {SendSize}, " +
+                $"{nameof(SendPayloadRawSize)}: {SendPayloadRawSize}, {nameof(SendPayloadSize)}: {SendPayloadSize}, {nameof(ReceivePayloadRawSize)}: {ReceivePayloadRawSize}, {nameof(ReceivePayloadSize)}: {ReceivePayloadSize}, " +
+                $"{nameof(RttMinInMs)}: {RttMinInMs}, {nameof(RttAvgInMs)}: {RttAvgInMs}, {nameof(RttMaxInMs)}: {RttMaxInMs}, {nameof(RttJitterInMs)}: {RttJitterInMs}, {nameof(PacketLoss)}: {PacketLoss}, " +
+                $"{nameof(RetransmitCount)}: {RetransmitCount}, {nameof(UnackedReliableFrames)}: {UnackedReliableFrames}, {nameof(SinceLastFrameInMs)}: {SinceLastFrameInMs}, {nameof(ConnectionQuality)}: {ConnectionQuality}";
         }
     }
 }
\ No newline at end of file
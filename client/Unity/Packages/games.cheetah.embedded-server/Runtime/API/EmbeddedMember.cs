@@ -0,0 +1,17 @@
+namespace Games.Cheetah.EmbeddedServer.API
+{
+    /// <summary>
+    /// Пользователь, созданный напрямую во встроенном сервере, ключ используется клиентом для подключения
+    /// </summary>
+    public class EmbeddedMember
+    {
+        public readonly ulong Id;
+        public readonly byte[] PrivateKey;
+
+        public EmbeddedMember(ulong id, byte[] privateKey)
+        {
+            Id = id;
+            PrivateKey = privateKey;
+        }
+    }
+}
\ No newline at end of file
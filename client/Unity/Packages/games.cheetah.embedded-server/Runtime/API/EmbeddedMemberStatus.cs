@@ -0,0 +1,12 @@
+namespace Games.Cheetah.EmbeddedServer.API
+{
+    public enum EmbeddedMemberStatus
+    {
+        Created,
+        CreatedNotConnectedAndDeleted,
+        Connected,
+        Attached,
+        Detached,
+        Disconnected,
+    }
+}
\ No newline at end of file
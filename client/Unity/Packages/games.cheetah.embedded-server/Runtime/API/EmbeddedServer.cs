@@ -1,4 +1,5 @@
 using System;
+using System.Collections.Generic;
 using System.Net;
 using System.Net.Http;
 using Games.Cheetah.EmbeddedServer.FFI;
@@ -28,11 +29,17 @@ namespace Games.Cheetah.EmbeddedServer.API
     ///  - создаем необходимое количество пользователей в команте
     ///  - соединяемся с сервером от имени клиента(ов) для тестирования
     ///  - удаляем сервер для освобождения ресурсов
+    ///
+    /// Комнатами и пользователями можно управлять как через grpc, так и напрямую (CreateRoom, CreateMember, ...),
+    /// для локальной игры и LAN режима сервер можно запустить без tcp портов (WithoutManagementServices)
     /// </summary>
     public class EmbeddedServer
     {
         private readonly Server.Description description;
         private static string? errorMessage;
+        private static readonly List<ulong> rooms = new();
+        private static readonly List<(ulong, EmbeddedMemberStatus)> members = new();
+        private static string? dump;
 
         public EmbeddedServer(
             IPAddress internalGrpcAddress, ushort internalGrpcPort,
@@ -65,19 +72,162 @@ namespace Games.Cheetah.EmbeddedServer.API
         {
         }
 
+        private EmbeddedServer(Server.Description description)
+        {
+            this.description = description;
+        }
+
+        /// <summary>
+        /// Сервер только с игровым udp сокетом, grpc клиент для такого сервера недоступен
+        /// </summary>
+        public static EmbeddedServer WithoutManagementServices(IPAddress gameUdpAddress, ushort gameUdpPort = 0)
+        {
+            var description = new Server.Description();
+            var gameUdpSocket = NewBindSocket(gameUdpAddress, gameUdpPort);
+            if (!Server.RunNewServerWithoutManagementServices(ref description, OnError, ref gameUdpSocket))
+            {
+                throw new Exception("Cannot run embedded server. " + errorMessage);
+            }
+
+            return new EmbeddedServer(description);
+        }
+
+        /// <summary>
+        /// Создать комнату, template - шаблон комнаты в формате RON, null - пустая комната
+        /// </summary>
+        public ulong CreateRoom(string? template = null)
+        {
+            Check(Server.CreateRoom(description.id, out var roomId, OnError, template));
+            return roomId;
+        }
+
+        public void DeleteRoom(ulong roomId)
+        {
+            Check(Server.DeleteRoom(description.id, roomId, OnError));
+        }
+
+        public EmbeddedMember CreateMember(ulong roomId, ulong groups)
+        {
+            var member = new Server.Member();
+            Check(Server.CreateMember(description.id, roomId, groups, ref member, OnError));
+            return ToEmbeddedMember(ref member);
+        }
+
+        public EmbeddedMember CreateSuperMember(ulong roomId)
+        {
+            var member = new Server.Member();
+            Check(Server.CreateSuperMember(description.id, roomId, ref member, OnError));
+            return ToEmbeddedMember(ref member);
+        }
+
+        public void DeleteMember(ulong roomId, ulong memberId)
+        {
+            Check(Server.DeleteMember(description.id, roomId, memberId, OnError));
+        }
+
+        public List<ulong> GetRooms()
+        {
+            lock (rooms)
+            {
+                rooms.Clear();
+                Check(Server.GetRooms(description.id, OnRoom, OnError));
+                return new List<ulong>(rooms);
+            }
+        }
+
+        public List<(ulong, EmbeddedMemberStatus)> GetRoomMembers(ulong roomId)
+        {
+            lock (members)
+            {
+                members.Clear();
+                Check(Server.GetRoomMembers(description.id, roomId, OnMember, OnError));
+                return new List<(ulong, EmbeddedMemberStatus)>(members);
+            }
+        }
+
+        /// <summary>
+        /// Состояние комнаты в формате RON
+        /// </summary>
+        public string DumpRoom(ulong roomId)
+        {
+            Check(Server.DumpRoom(description.id, roomId, OnDump, OnError));
+            return dump!;
+        }
+
+        private static void Check(bool result)
+        {
+            if (!result)
+            {
+                throw new Exception("Embedded server error. " + errorMessage);
+            }
+        }
+
+        private static unsafe EmbeddedMember ToEmbeddedMember(ref Server.Member member)
+        {
+            var privateKey = new byte[32];
+            for (var i = 0; i < privateKey.Length; i++)
+            {
+                privateKey[i] = member.privateKey[i];
+            }
+
+            return new EmbeddedMember(member.id, privateKey);
+        }
+
+#if UNITY_5_3_OR_NEWER
+        [MonoPInvokeCallback(typeof(Server.OnRoom))]
+#endif
+        private static void OnRoom(ulong roomId)
+        {
+            rooms.Add(roomId);
+        }
+
+#if UNITY_5_3_OR_NEWER
+        [MonoPInvokeCallback(typeof(Server.OnMember))]
+#endif
+        private static void OnMember(ulong memberId, EmbeddedMemberStatus status)
+        {
+            members.Add((memberId, status));
+        }
+
+#if UNITY_5_3_OR_NEWER
+        [MonoPInvokeCallback(typeof(Server.OnDump))]
+#endif
+        private static void OnDump(string value)
+        {
+            dump = value;
+        }
+
 
         private static unsafe Server.BindSocket NewBindSocket(IPAddress bindAddress, ushort port)
         {
             var result = new Server.BindSocket();
-            var addressBytes = bindAddress.GetAddressBytes();
+            var addressBytes = bindAddress.MapToIPv6().GetAddressBytes();
             result.port = port;
-            result.bindAddress[0] = addressBytes[0];
-            result.bindAddress[1] = addressBytes[1];
-            result.bindAddress[2] = addressBytes[2];
-            result.bindAddress[3] = addressBytes[3];
+            for (var i = 0; i < addressBytes.Length; i++)
+            {
+                result.bindAddress[i] = addressBytes[i];
+            }
+
             return result;
         }
 
+        private static unsafe IPAddress ToIPAddress(byte* address)
+        {
+            var addressBytes = new byte[16];
+            for (var i = 0; i < addressBytes.Length; i++)
+            {
+                addressBytes[i] = address[i];
+            }
+
+            var result = new IPAddress(addressBytes);
+            return result.IsIPv4MappedToIPv6 ? result.MapToIPv4() : result;
+        }
+
+        private static Uri ToUri(IPAddress address, ushort port)
+        {
+            return new UriBuilder("http", address.ToString(), port).Uri;
+        }
+
 
         public RealtimeServerManagementService.RealtimeServerManagementServiceClient CreateGrpcClient()
         {
@@ -102,8 +252,10 @@ namespace Games.Cheetah.EmbeddedServer.API
         {
             unsafe
             {
-                return
-                    $"{description.gameIp[0]}.{description.gameIp[1]}.{description.gameIp[2]}.{description.gameIp[3]}";
+                fixed (byte* ip = description.gameIp)
+                {
+                    return ToIPAddress(ip).ToString();
+                }
             }
         }
 
@@ -116,9 +268,10 @@ namespace Games.Cheetah.EmbeddedServer.API
         {
             unsafe
             {
-                var ip =
-                    $"{description.debug_rest_service_ip[0]}.{description.debug_rest_service_ip[1]}.{description.debug_rest_service_ip[2]}.{description.debug_rest_service_ip[3]}";
-                return new Uri($"http://{ip}:{description.debug_rest_service_port}");
+                fixed (byte* ip = description.debug_rest_service_ip)
+                {
+                    return ToUri(ToIPAddress(ip), description.debug_rest_service_port);
+                }
             }
         }
 
@@ -126,9 +279,10 @@ namespace Games.Cheetah.EmbeddedServer.API
         {
             unsafe
             {
-                var ip =
-                    $"{description.internal_grpc_ip[0]}.{description.internal_grpc_ip[1]}.{description.internal_grpc_ip[2]}.{description.internal_grpc_ip[3]}";
-                return new Uri($"http://{ip}:{description.internal_grpc_port}");
+                fixed (byte* ip = description.internal_grpc_ip)
+                {
+                    return ToUri(ToIPAddress(ip), description.internal_grpc_port);
+                }
             }
         }
 
@@ -136,9 +290,10 @@ namespace Games.Cheetah.EmbeddedServer.API
         {
             unsafe
             {
-                var ip =
-                    $"{description.internal_webgrpc_ip[0]}.{description.internal_webgrpc_ip[1]}.{description.internal_webgrpc_ip[2]}.{description.internal_webgrpc_ip[3]}";
-                return new Uri($"http://{ip}:{description.internal_webgrpc_port}");
+                fixed (byte* ip = description.internal_webgrpc_ip)
+                {
+                    return ToUri(ToIPAddress(ip), description.internal_webgrpc_port);
+                }
             }
         }
 
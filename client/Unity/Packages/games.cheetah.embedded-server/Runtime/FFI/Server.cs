@@ -1,4 +1,7 @@
 using System.Runtime.InteropServices;
+using Games.Cheetah.EmbeddedServer.API;
+
+#nullable enable
 
 namespace Games.Cheetah.EmbeddedServer.FFI
 {
@@ -7,28 +10,31 @@ namespace Games.Cheetah.EmbeddedServer.FFI
         [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
         public delegate void OnServerError([MarshalAs(UnmanagedType.LPWStr)] string message);
 
+        /// <summary>
+        /// IP адреса передаются в формате IPv6 (16 байт), IPv4 адреса - как IPv4-mapped IPv6 (::ffff:a.b.c.d)
+        /// </summary>
         [StructLayout(LayoutKind.Sequential)]
         internal struct Description
         {
             [MarshalAs(UnmanagedType.U8)] internal ulong id;
 
-            internal unsafe fixed byte gameIp[4];
+            internal unsafe fixed byte gameIp[16];
             [MarshalAs(UnmanagedType.U2)] internal ushort gamePort;
 
-            internal unsafe fixed byte internal_grpc_ip[4];
+            internal unsafe fixed byte internal_grpc_ip[16];
             [MarshalAs(UnmanagedType.U2)] internal ushort internal_grpc_port;
 
-            internal unsafe fixed byte internal_webgrpc_ip[4];
+            internal unsafe fixed byte internal_webgrpc_ip[16];
             [MarshalAs(UnmanagedType.U2)] internal ushort internal_webgrpc_port;
 
-            internal unsafe fixed byte debug_rest_service_ip[4];
+            internal unsafe fixed byte debug_rest_service_ip[16];
             [MarshalAs(UnmanagedType.U2)] internal ushort debug_rest_service_port;
         }
 
         [StructLayout(LayoutKind.Sequential)]
         internal struct BindSocket
         {
-            internal unsafe fixed byte bindAddress[4];
+            internal unsafe fixed byte bindAddress[16];
             [MarshalAs(UnmanagedType.U2)] internal ushort port;
         }
 
@@ -40,7 +46,59 @@ namespace Games.Cheetah.EmbeddedServer.FFI
             ref BindSocket debugRestServiceSocket,
             ref BindSocket gameUdpSocket);
 
+        /// <summary>
+        /// Запуск сервера без grpc, grpc-web и debug rest сервисов, адреса и порты сервисов в описании - нулевые
+        /// </summary>
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "run_new_server_without_management_services")]
+        internal static extern bool RunNewServerWithoutManagementServices(ref Description description, OnServerError onServerError, ref BindSocket gameUdpSocket);
+
         [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "destroy_server")]
         internal static extern bool DestroyServer(ulong serverId);
+
+        [StructLayout(LayoutKind.Sequential)]
+        internal struct Member
+        {
+            [MarshalAs(UnmanagedType.U8)] internal ulong id;
+            internal unsafe fixed byte privateKey[32];
+        }
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnRoom(ulong roomId);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnMember(ulong memberId, EmbeddedMemberStatus status);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        public delegate void OnDump([MarshalAs(UnmanagedType.LPWStr)] string dump);
+
+        /// <summary>
+        /// Создать комнату, template - шаблон комнаты в формате RON, null - пустая комната
+        /// </summary>
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "create_room")]
+        internal static extern bool CreateRoom(ulong serverId, out ulong roomId, OnServerError onServerError, [MarshalAs(UnmanagedType.LPWStr)] string? template);
+
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "delete_room")]
+        internal static extern bool DeleteRoom(ulong serverId, ulong roomId, OnServerError onServerError);
+
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "create_member")]
+        internal static extern bool CreateMember(ulong serverId, ulong roomId, ulong groups, ref Member member, OnServerError onServerError);
+
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "create_super_member")]
+        internal static extern bool CreateSuperMember(ulong serverId, ulong roomId, ref Member member, OnServerError onServerError);
+
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "delete_member")]
+        internal static extern bool DeleteMember(ulong serverId, ulong roomId, ulong memberId, OnServerError onServerError);
+
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "get_rooms")]
+        internal static extern bool GetRooms(ulong serverId, OnRoom onRoom, OnServerError onServerError);
+
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "get_room_members")]
+        internal static extern bool GetRoomMembers(ulong serverId, ulong roomId, OnMember onMember, OnServerError onServerError);
+
+        /// <summary>
+        /// Состояние комнаты в формате RON
+        /// </summary>
+        [DllImport(Const.Library, CallingConvention = CallingConvention.Cdecl, EntryPoint = "dump_room")]
+        internal static extern bool DumpRoom(ulong serverId, ulong roomId, OnDump onDump, OnServerError onServerError);
     }
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+//!
+//! Сравнение способов получения команд через FFI
+//!
+//! - copy - копирование в массив [`S2CCommandFFI`], каждый элемент содержит буфер фиксированного размера
+//! - callbacks - вызов обработчиков по типам команд, бинарные данные передаются без копирования
+//!
+//! Запуск: `cargo bench -p cheetah-client --bench receive`
+//!
+//! Для 1024 команд (3/4 SetLong, 1/4 событий по 8 байт) копирование занимает ~700 мкс, обработчики - ~13 мкс,
+//! основное время копирования - заполнение буферов [`S2CCommandFFI`]
+//!
+use std::hint::black_box;
+
+use cheetah_client::ffi::client::{copy_commands, dispatch_commands, MAX_RECEIVE_COMMANDS};
+use cheetah_client::ffi::command::{BinaryFieldRefFFI, S2CCommandCallbacksFFI, S2CCommandFFI, S2CommandUnionFFI};
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::long::LongField;
+use cheetah_common::commands::types::structure::BinaryField;
+use cheetah_common::commands::CommandTypeId;
+use cheetah_common::room::buffer::Buffer;
+use cheetah_common::room::object::GameObjectId;
+use cheetah_common::room::owner::GameObjectOwner;
+use easybench::bench_gen_env;
+
+fn main() {
+	let commands = commands();
+	let mut out_commands = vec![
+		S2CCommandFFI {
+			command_type: CommandTypeId::CreateGameObject,
+			command: S2CommandUnionFFI { empty: () },
+		};
+		MAX_RECEIVE_COMMANDS
+	];
+	let copy = bench_gen_env(|| commands.clone(), |commands| black_box(copy_commands(commands.drain(..), &mut out_commands)));
+	println!("copy {} commands:      {}", commands.len(), copy);
+
+	let callbacks = S2CCommandCallbacksFFI {
+		on_set_long: Some(on_set_long),
+		on_event: Some(on_binary),
+		..Default::default()
+	};
+	let dispatch = bench_gen_env(|| commands.clone(), |commands| dispatch_commands(commands.drain(..), &callbacks));
+	println!("callbacks {} commands: {}", commands.len(), dispatch);
+	println!("speedup: {:.1}x", copy.ns_per_iter / dispatch.ns_per_iter);
+}
+
+///
+/// Типичный кадр - изменения числовых полей и небольшие события
+///
+fn commands() -> Vec<S2CCommand> {
+	let object_id = GameObjectId::new(1, GameObjectOwner::Room);
+	(0..MAX_RECEIVE_COMMANDS as i64)
+		.map(|value| {
+			if value % 4 == 0 {
+				S2CCommand::Event(BinaryField {
+					object_id,
+					field_id: 2,
+					value: Buffer::from([1, 2, 3, 4, 5, 6, 7, 8].as_slice()),
+				})
+			} else {
+				S2CCommand::SetLong(LongField { object_id, field_id: 1, value })
+			}
+		})
+		.collect()
+}
+
+extern "C" fn on_set_long(command: &LongField) {
+	black_box(command);
+}
+
+extern "C" fn on_binary(command: &BinaryFieldRefFFI) {
+	black_box(command);
+}
@@ -1,159 +1,265 @@
+use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReason;
 use cheetah_game_realtime_protocol::RoomMemberId;
-use std::slice;
+use std::collections::VecDeque;
+use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, SendError, Sender};
 use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::{iter, mem};
 
-use crate::clients::network_thread::C2SCommandWithChannel;
-use crate::clients::{ClientRequest, SharedClientStatistics};
-use crate::ffi::channel::Channel;
-use crate::ffi::command::S2CCommandFFI;
+use crate::clients::network_thread::{C2SCommandWithChannel, NetworkChannelManager};
+use crate::clients::reconnect::ReconnectPolicy;
+use crate::clients::room_state::RoomState;
+use crate::clients::{ClientEvent, ClientRequest, ConnectionQualityListener, NetworkEvent, SharedClientStatistics};
 use cheetah_common::commands::c2s::C2SCommand;
 use cheetah_common::commands::guarantees::{ChannelGroup, ReliabilityGuarantees};
 use cheetah_common::commands::s2c::S2CCommand;
 use cheetah_common::commands::types::create::CreateGameObject;
-use cheetah_common::commands::{BothDirectionCommand, CommandTypeId, CommandWithReliabilityGuarantees};
+use cheetah_common::commands::BothDirectionCommand;
+use cheetah_common::network::emulator::{Bandwidth, BurstLoss, NetworkConditionsPreset};
+use cheetah_common::network::quality::ConnectionQuality;
 use cheetah_common::network::ConnectionStatus;
 use cheetah_common::room::access::AccessGroups;
 use cheetah_common::room::object::GameObjectId;
 use cheetah_common::room::owner::GameObjectOwner;
 
 ///
-/// Взаимодействие с сетевым потоком клиента, через Sender
+/// Взаимодействие с сетевым клиентом из потока приложения
 ///
 pub struct ApplicationThreadClient {
 	member_id: RoomMemberId,
-	s2c_receiver: Receiver<CommandWithReliabilityGuarantees>,
-	pub(crate) handler: Option<JoinHandle<()>>,
+	network: ClientNetwork,
 	state: Arc<Mutex<ConnectionStatus>>,
 	server_time: Arc<Mutex<Option<u64>>>,
-	pub(crate) request_to_client: Sender<ClientRequest>,
 	channel: ReliabilityGuarantees,
 	game_object_id_generator: u32,
 	pub shared_statistics: SharedClientStatistics,
+	room_state: Option<RoomState>,
+	resynchronizing: bool,
+	///
+	/// Сигнал синхронизации, отложенный до следующего вызова [`Self::receive`]
+	///
+	pending_signal: Option<ClientEvent>,
+	connection_quality_listener: Option<ConnectionQualityListener>,
+	///
+	/// Последняя оценка качества соединения, переданная обработчику
+	///
+	notified_connection_quality: ConnectionQuality,
+}
+
+///
+/// Способ взаимодействия с сетевым клиентом
+///
+pub enum ClientNetwork {
+	///
+	/// Сетевой клиент работает в отдельном потоке, команды передаются через каналы
+	///
+	Thread {
+		handler: Option<JoinHandle<()>>,
+		request_to_client: Sender<ClientRequest>,
+		events: Receiver<NetworkEvent>,
+	},
+	///
+	/// Сетевой клиент работает в потоке приложения, приложение само вызывает [`ApplicationThreadClient::cycle`],
+	/// команды передаются напрямую в протокол, запросы выполняются со временем последнего цикла
+	///
+	SingleThreaded {
+		manager: Box<NetworkChannelManager>,
+		events: VecDeque<NetworkEvent>,
+		last_cycle_time: Instant,
+	},
 }
 
 impl Drop for ApplicationThreadClient {
 	fn drop(&mut self) {
-		self.handler.take().unwrap().join().unwrap();
+		if let ClientNetwork::Thread { handler, request_to_client, .. } = &mut self.network {
+			// закрываем канал запросов, сетевой поток завершается и без команды Close
+			let (closed_sender, _) = mpsc::channel();
+			drop(mem::replace(request_to_client, closed_sender));
+			handler.take().unwrap().join().unwrap();
+		}
 	}
 }
 
 impl ApplicationThreadClient {
-	pub fn new(
-		member_id: RoomMemberId,
-		handler: JoinHandle<()>,
-		state: Arc<Mutex<ConnectionStatus>>,
-		in_commands: Receiver<CommandWithReliabilityGuarantees>,
-		sender: Sender<ClientRequest>,
-		shared_statistics: SharedClientStatistics,
-		server_time: Arc<Mutex<Option<u64>>>,
-	) -> Self {
+	#[must_use]
+	pub fn new(member_id: RoomMemberId, network: ClientNetwork, state: Arc<Mutex<ConnectionStatus>>, shared_statistics: SharedClientStatistics, server_time: Arc<Mutex<Option<u64>>>) -> Self {
 		Self {
 			member_id,
-			s2c_receiver: in_commands,
-			handler: Some(handler),
+			network,
 			state,
 			server_time,
-			request_to_client: sender,
 			channel: ReliabilityGuarantees::ReliableSequence(ChannelGroup(0)),
 			game_object_id_generator: GameObjectId::CLIENT_OBJECT_ID_OFFSET,
 			shared_statistics,
+			room_state: None,
+			resynchronizing: false,
+			pending_signal: None,
+			connection_quality_listener: None,
+			notified_connection_quality: Default::default(),
+		}
+	}
+
+	///
+	/// Цикл сетевого клиента для однопоточного режима, возвращает false если клиент работает в отдельном потоке
+	///
+	pub fn cycle(&mut self, now: Instant) -> bool {
+		match &mut self.network {
+			ClientNetwork::Thread { .. } => false,
+			ClientNetwork::SingleThreaded { manager, events, last_cycle_time } => {
+				*last_cycle_time = now;
+				manager.cycle(now);
+				manager.take_events(|event| events.push_back(event));
+				self.notify_connection_quality();
+				true
+			}
 		}
 	}
 
 	pub fn set_protocol_time_offset(&mut self, time_offset: Duration) -> Result<(), SendError<ClientRequest>> {
-		self.request_to_client.send(ClientRequest::SetProtocolTimeOffsetForTest(time_offset))
+		self.request(ClientRequest::SetProtocolTimeOffsetForTest(time_offset))
 	}
 
 	pub fn send(&mut self, command: C2SCommand) -> Result<(), SendError<ClientRequest>> {
 		let command_with_channel = C2SCommandWithChannel { channel_type: self.channel, command };
 		tracing::debug!("c2s {:?}", command_with_channel);
-		self.request_to_client.send(ClientRequest::SendCommandToServer(command_with_channel))
+		self.request(ClientRequest::SendCommandToServer(command_with_channel))
 	}
 
-	pub fn get_connection_status(&self) -> Result<ConnectionStatus, PoisonError<MutexGuard<'_, ConnectionStatus>>> {
-		Ok(self.state.lock()?.clone())
+	pub fn close(&mut self, reason: DisconnectByCommandReason) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::Close(reason))
 	}
 
-	#[allow(clippy::unwrap_in_result)]
-	pub fn get_server_time(&self) -> Option<u64> {
-		*self.server_time.lock().unwrap()
+	fn request(&mut self, request: ClientRequest) -> Result<(), SendError<ClientRequest>> {
+		match &mut self.network {
+			ClientNetwork::Thread { request_to_client, .. } => request_to_client.send(request),
+			ClientNetwork::SingleThreaded { manager, last_cycle_time, .. } => {
+				if manager.is_closed() {
+					return Err(SendError(request));
+				}
+				manager.execute_request(request, *last_cycle_time);
+				Ok(())
+			}
+		}
 	}
 
-	pub fn set_current_channel(&mut self, channel: Channel, group: ChannelGroup) {
-		self.channel = match channel {
-			Channel::ReliableUnordered => ReliabilityGuarantees::ReliableUnordered,
-			Channel::UnreliableUnordered => ReliabilityGuarantees::UnreliableUnordered,
-			Channel::ReliableOrdered => ReliabilityGuarantees::ReliableOrdered(group),
-			Channel::UnreliableOrdered => ReliabilityGuarantees::UnreliableOrdered(group),
-			Channel::ReliableSequence => ReliabilityGuarantees::ReliableSequence(group),
+	fn next_event(&mut self) -> Option<NetworkEvent> {
+		match &mut self.network {
+			ClientNetwork::Thread { events, .. } => events.try_recv().ok(),
+			ClientNetwork::SingleThreaded { events, .. } => events.pop_front(),
 		}
 	}
 
-	pub unsafe fn receive(&mut self, commands: *mut S2CCommandFFI, count: &mut u16) {
-		*count = 0;
-		let commands: &mut [S2CCommandFFI] = slice::from_raw_parts_mut(commands, 1024);
+	///
+	/// Следующая команда или сигнал синхронизации, команды применяются к локальной копии состояния комнаты
+	///
+	pub fn next_client_event(&mut self) -> Option<ClientEvent> {
+		let event = match self.pending_signal.take() {
+			None => self.next_converted_event()?,
+			Some(signal) => signal,
+		};
+		self.apply_signal(&event);
+		Some(event)
+	}
 
-		while let Ok(command) = self.s2c_receiver.try_recv() {
-			tracing::debug!("s2c {:?}", command);
-			if let BothDirectionCommand::S2C(command) = command.command {
-				let command_ffi = &mut commands[*count as usize];
-				match command {
-					S2CCommand::Create(command) => {
-						command_ffi.command_type = CommandTypeId::CreateGameObject;
-						command_ffi.command.create = command;
-					}
-					S2CCommand::Created(command) => {
-						command_ffi.command_type = CommandTypeId::CreatedGameObject;
-						command_ffi.command.created = command;
+	fn next_converted_event(&mut self) -> Option<ClientEvent> {
+		loop {
+			match self.next_event()? {
+				NetworkEvent::Command(command) => {
+					tracing::debug!("s2c {:?}", command);
+					if let BothDirectionCommand::S2C(command) = command.command {
+						if let Some(room_state) = self.room_state.as_mut() {
+							room_state.apply(&command);
+						}
+						return Some(ClientEvent::Command(command));
 					}
+				}
+				NetworkEvent::ResyncBegin => return Some(ClientEvent::ResyncBegin),
+				NetworkEvent::ResyncEnd => return Some(ClientEvent::ResyncEnd),
+			}
+		}
+	}
 
-					S2CCommand::SetLong(command) => {
-						command_ffi.command_type = CommandTypeId::SetLong;
-						command_ffi.command.set_long = command;
-					}
-					S2CCommand::SetDouble(command) => {
-						command_ffi.command_type = CommandTypeId::SetDouble;
-						command_ffi.command.set_double = command;
-					}
-					S2CCommand::SetStructure(command) => {
-						command_ffi.command_type = CommandTypeId::SetStructure;
-						command_ffi.command.buffer_field = command.into();
-					}
+	fn apply_signal(&mut self, event: &ClientEvent) {
+		match event {
+			ClientEvent::Command(_) => {}
+			ClientEvent::ResyncBegin => self.resynchronizing = true,
+			ClientEvent::ResyncEnd => self.resynchronizing = false,
+		}
+	}
 
-					S2CCommand::Event(command) => {
-						command_ffi.command_type = CommandTypeId::SendEvent;
-						command_ffi.command.buffer_field = command.into();
-					}
-					S2CCommand::Delete(command) => {
-						command_ffi.command_type = CommandTypeId::DeleteObject;
-						command_ffi.command.game_object_id = command;
-					}
-					S2CCommand::DeleteField(command) => {
-						command_ffi.command_type = CommandTypeId::DeleteField;
-						command_ffi.command.delete_field = command;
-					}
-					S2CCommand::MemberConnected(command) => {
-						command_ffi.command_type = CommandTypeId::MemberConnected;
-						command_ffi.command.member_connect = command;
-					}
-					S2CCommand::MemberDisconnected(command) => {
-						command_ffi.command_type = CommandTypeId::MemberDisconnected;
-						command_ffi.command.member_disconnect = command;
-					}
-					S2CCommand::AddItem(command) => {
-						command_ffi.command_type = CommandTypeId::AddItem;
-						command_ffi.command.buffer_field = command.into();
-					}
+	///
+	/// Идет получение состояния комнаты после переподключения,
+	/// относится к командам, полученным последним вызовом [`Self::receive`]
+	///
+	#[must_use]
+	pub fn is_resynchronizing(&self) -> bool {
+		self.resynchronizing
+	}
+
+	#[must_use]
+	pub fn get_connection_quality(&self) -> ConnectionQuality {
+		*self.shared_statistics.connection_quality.lock().unwrap()
+	}
+
+	pub fn set_connection_quality_listener(&mut self, listener: Option<ConnectionQualityListener>) {
+		self.connection_quality_listener = listener;
+		self.notified_connection_quality = ConnectionQuality::Unknown;
+	}
+
+	///
+	/// Вызвать обработчик, если оценка качества соединения изменилась с момента последнего вызова
+	///
+	pub fn notify_connection_quality(&mut self) {
+		let quality = self.get_connection_quality();
+		if quality == self.notified_connection_quality {
+			return;
+		}
+		if let Some(listener) = self.connection_quality_listener.as_mut() {
+			self.notified_connection_quality = quality;
+			listener(quality);
+		}
+	}
+
+	pub fn get_connection_status(&self) -> Result<ConnectionStatus, PoisonError<MutexGuard<'_, ConnectionStatus>>> {
+		Ok(self.state.lock()?.clone())
+	}
+
+	#[allow(clippy::unwrap_in_result)]
+	pub fn get_server_time(&self) -> Option<u64> {
+		*self.server_time.lock().unwrap()
+	}
+
+	pub fn set_current_channel(&mut self, channel: ReliabilityGuarantees) {
+		self.channel = channel;
+	}
+
+	///
+	/// Забрать пришедшие команды, команды также применяются к локальной копии состояния комнаты (если она включена)
+	///
+	/// - все команды одного вызова находятся либо внутри, либо вне синхронизации после переподключения ([`Self::is_resynchronizing`]),
+	///   на сигнале синхронизации после уже возвращенных команд получение прерывается до следующего вызова
+	///
+	pub fn receive(&mut self) -> impl Iterator<Item = S2CCommand> + '_ {
+		self.notify_connection_quality();
+		let mut received = false;
+		if let Some(signal) = self.pending_signal.take() {
+			self.apply_signal(&signal);
+		}
+		iter::from_fn(move || loop {
+			match self.next_converted_event()? {
+				ClientEvent::Command(command) => {
+					received = true;
+					return Some(command);
 				}
-				*count += 1;
-				if *count == 1024 {
-					break;
+				signal if received => {
+					self.pending_signal = Some(signal);
+					return None;
 				}
+				signal => self.apply_signal(&signal),
 			}
-		}
+		})
 	}
 
 	pub fn create_game_object(&mut self, template: u16, access_group: u64) -> Result<GameObjectId, SendError<ClientRequest>> {
@@ -169,20 +275,80 @@ impl ApplicationThreadClient {
 	}
 
 	pub fn set_rtt_emulation(&mut self, rtt: Duration, rtt_dispersion: f64) -> Result<(), SendError<ClientRequest>> {
-		self.request_to_client.send(ClientRequest::ConfigureRttEmulation(rtt, rtt_dispersion))
+		self.request(ClientRequest::ConfigureRttEmulation(rtt, rtt_dispersion))
 	}
 
 	pub fn set_drop_emulation(&mut self, drop_probability: f64, drop_time: Duration) -> Result<(), SendError<ClientRequest>> {
-		self.request_to_client.send(ClientRequest::ConfigureDropEmulation(drop_probability, drop_time))
+		self.request(ClientRequest::ConfigureDropEmulation(drop_probability, drop_time))
+	}
+
+	pub fn set_bandwidth_emulation(&mut self, bandwidth: Bandwidth) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::ConfigureBandwidthEmulation(bandwidth))
+	}
+
+	pub fn set_duplicate_emulation(&mut self, duplicate_probability: f64) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::ConfigureDuplicateEmulation(duplicate_probability))
+	}
+
+	pub fn set_reorder_emulation(&mut self, reorder_probability: f64, reorder_delay: Duration) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::ConfigureReorderEmulation(reorder_probability, reorder_delay))
+	}
+
+	pub fn set_burst_loss_emulation(&mut self, burst_loss: BurstLoss) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::ConfigureBurstLossEmulation(burst_loss))
+	}
+
+	pub fn set_emulation_preset(&mut self, preset: NetworkConditionsPreset) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::ConfigureEmulationPreset(preset))
+	}
+
+	pub fn set_compression(&mut self, compression: bool) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::ConfigureCompression(compression))
+	}
+
+	pub fn set_reconnect_policy(&mut self, policy: Option<ReconnectPolicy>) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::ConfigureReconnect(policy))
 	}
 
 	pub fn reset_emulation(&mut self) -> Result<(), SendError<ClientRequest>> {
-		self.request_to_client.send(ClientRequest::ResetEmulation)
+		self.request(ClientRequest::ResetEmulation)
+	}
+
+	pub fn set_emulation_seed(&mut self, seed: u64) -> Result<(), SendError<ClientRequest>> {
+		self.request(ClientRequest::ConfigureEmulationSeed(seed))
 	}
 
 	pub fn attach_to_room(&mut self) -> Result<(), SendError<ClientRequest>> {
 		// удаляем все пришедшие команды (ситуация возникает при attach/detach)
-		while self.s2c_receiver.try_recv().is_ok() {}
+		while self.next_client_event().is_some() {}
+		// после attach сервер заново отправит все объекты комнаты
+		if let Some(room_state) = self.room_state.as_mut() {
+			room_state.clear();
+		}
 		self.send(C2SCommand::AttachToRoom)
 	}
+
+	///
+	/// Включить ведение локальной копии состояния комнаты, команды применяются при вызове [`Self::receive`]
+	/// или [`Self::update_room_state`]
+	///
+	pub fn enable_room_state(&mut self) {
+		if self.room_state.is_none() {
+			self.room_state.replace(Default::default());
+		}
+	}
+
+	pub fn get_room_state(&mut self) -> Option<&mut RoomState> {
+		self.room_state.as_mut()
+	}
+
+	///
+	/// Применить все пришедшие команды к локальной копии состояния комнаты,
+	/// используется вместо [`Self::receive`] если движку не нужны сами команды
+	///
+	pub fn update_room_state(&mut self) {
+		if self.room_state.is_some() {
+			self.receive().for_each(drop);
+		}
+	}
 }
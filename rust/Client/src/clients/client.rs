@@ -0,0 +1,472 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::mpsc::SendError;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{iter, thread};
+
+use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReason;
+use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
+use cheetah_game_realtime_protocol::frame::ConnectionId;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use thiserror::Error;
+
+use crate::clients::application_thread::{ApplicationThreadClient, ClientNetwork};
+use crate::clients::network_thread::{NetworkChannelManager, NetworkThread};
+use crate::clients::reconnect::ReconnectPolicy;
+use crate::clients::room_state::RoomState;
+use crate::clients::{ClientEvent, ClientRequest, ConnectionQualityListener, SharedClientStatistics};
+use cheetah_common::commands::c2s::C2SCommand;
+use cheetah_common::commands::guarantees::ReliabilityGuarantees;
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::create::C2SCreatedGameObject;
+use cheetah_common::commands::types::event::TargetEvent;
+use cheetah_common::commands::types::field::DeleteField;
+use cheetah_common::commands::types::float::{DoubleField, IncrementDouble};
+use cheetah_common::commands::types::long::{IncrementLong, LongField};
+use cheetah_common::commands::types::structure::BinaryField;
+use cheetah_common::network::emulator::{check_probability, Bandwidth, BurstLoss, NetworkConditionsPreset};
+use cheetah_common::network::quality::ConnectionQuality;
+use cheetah_common::network::socket::Transport;
+use cheetah_common::network::token::MemberToken;
+use cheetah_common::network::ConnectionStatus;
+use cheetah_common::room::access::AccessGroups;
+use cheetah_common::room::buffer::Buffer;
+use cheetah_common::room::field::{FieldId, FieldType};
+use cheetah_common::room::object::{GameObjectId, GameObjectTemplateId};
+
+///
+/// Клиент для работы с сервером из Rust кода, на нем построен FFI слой
+///
+/// - сетевой обмен выполняется в отдельном потоке, методы клиента только передают команды в этот поток
+/// - в однопоточном режиме ([`ClientMode::SingleThreaded`]) поток не создается,
+///   приложение вызывает [`Client::cycle`] в каждом кадре
+/// - пришедшие команды забираются через [`Client::receive`]
+/// - по умолчанию используется UDP, для сетей без UDP - [`Transport::WebSocket`] (только нативные платформы, см. [`Transport`])
+/// - при включенном переподключении ([`Client::set_reconnect_policy`]) клиент сам восстанавливает соединение
+///   и присоединяется к комнате, повторно полученное состояние комнаты отмечается сигналами синхронизации
+/// - при удалении клиента сетевой поток останавливается без уведомления сервера,
+///   для корректного отключения используется [`Client::disconnect`]
+///
+pub struct Client {
+	inner: ApplicationThreadClient,
+	///
+	/// Соответствие времени приложения (в миллисекундах) и [`Instant`] для [`Client::cycle_with_app_time`]
+	///
+	cycle_time_origin: Option<(u64, Instant)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClientMode {
+	///
+	/// Сетевой обмен в отдельном потоке
+	///
+	#[default]
+	Thread,
+	///
+	/// Без отдельного потока, для платформ где потоки недоступны (WebAssembly, некоторые консоли)
+	///
+	SingleThreaded,
+}
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+	#[error("Create client error {0}")]
+	Io(#[from] io::Error),
+	#[error("Wrong member token {0}")]
+	WrongMemberToken(String),
+	#[error("Network thread stopped")]
+	NetworkThreadStopped,
+	#[error("Connection status mutex error {0}")]
+	ConnectionStatusMutex(String),
+	#[error("Room state not enabled")]
+	RoomStateNotEnabled,
+	#[error("Cycle is available only for single threaded client")]
+	NotSingleThreaded,
+	#[error("Wrong emulation parameters {0}")]
+	WrongEmulationParameters(String),
+}
+
+impl From<SendError<ClientRequest>> for ClientError {
+	fn from(_: SendError<ClientRequest>) -> Self {
+		ClientError::NetworkThreadStopped
+	}
+}
+
+///
+/// Параметры подключения к серверу
+///
+/// - [`ConnectOptions::new`] - для ранее созданного пользователя, [`ConnectOptions::with_token`] - по токену
+/// - по умолчанию сетевой обмен в отдельном потоке через UDP, остальные параметры меняются через поля
+///
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+	pub connection_id: ConnectionId,
+	///
+	/// Для [`Transport::WebSocket`] - адрес WebSocket listener сервера
+	///
+	pub server_address: SocketAddr,
+	pub credentials: ConnectCredentials,
+	///
+	/// Ключ пользователя, при подключении по токену - сессионный ключ, выданный backend-ом вместе с токеном
+	///
+	pub private_key: MemberPrivateKey,
+	pub disconnect_timeout: Duration,
+	pub mode: ClientMode,
+	pub transport: Transport,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConnectCredentials {
+	Member {
+		member_id: RoomMemberId,
+		room_id: RoomId,
+	},
+	///
+	/// Идентификаторы комнаты и пользователя берутся из токена
+	///
+	Token(Vec<u8>),
+}
+
+impl ConnectOptions {
+	#[must_use]
+	pub fn new(server_address: SocketAddr, member_id: RoomMemberId, room_id: RoomId, private_key: MemberPrivateKey, disconnect_timeout: Duration) -> Self {
+		Self::with_credentials(server_address, ConnectCredentials::Member { member_id, room_id }, private_key, disconnect_timeout)
+	}
+
+	#[must_use]
+	pub fn with_token(server_address: SocketAddr, member_token: &[u8], private_key: MemberPrivateKey, disconnect_timeout: Duration) -> Self {
+		Self::with_credentials(server_address, ConnectCredentials::Token(member_token.to_vec()), private_key, disconnect_timeout)
+	}
+
+	fn with_credentials(server_address: SocketAddr, credentials: ConnectCredentials, private_key: MemberPrivateKey, disconnect_timeout: Duration) -> Self {
+		Self {
+			connection_id: Default::default(),
+			server_address,
+			credentials,
+			private_key,
+			disconnect_timeout,
+			mode: Default::default(),
+			transport: Default::default(),
+		}
+	}
+}
+
+impl Client {
+	pub fn connect(options: ConnectOptions) -> Result<Self, ClientError> {
+		let (member_id, room_id, member_token) = match options.credentials {
+			ConnectCredentials::Member { member_id, room_id } => (member_id, room_id, None),
+			ConnectCredentials::Token(member_token) => {
+				let member_token = MemberToken::decode(&member_token).map_err(|e| ClientError::WrongMemberToken(format!("{e:?}")))?;
+				(member_token.claims.member_id(), member_token.claims.room_id, Some(member_token))
+			}
+		};
+		let server_time = Arc::new(Mutex::new(None));
+		let state = Arc::new(Mutex::new(ConnectionStatus::Connecting));
+		let shared_statistics = SharedClientStatistics::default();
+		let now = Instant::now();
+
+		let mut manager = NetworkChannelManager::new(
+			options.connection_id,
+			options.server_address,
+			options.transport,
+			member_id,
+			room_id,
+			options.private_key,
+			Arc::clone(&state),
+			shared_statistics.clone(),
+			Arc::clone(&server_time),
+			options.disconnect_timeout,
+			now,
+		)?;
+
+		if let Some(member_token) = member_token {
+			manager.set_member_token(&member_token);
+		}
+
+		let network = match options.mode {
+			ClientMode::Thread => {
+				let (sender, receiver) = std::sync::mpsc::channel();
+				let (event_sender, event_receiver) = std::sync::mpsc::channel();
+				let network_thread = NetworkThread::new(manager, event_sender, receiver);
+				let handler = thread::Builder::new().name(format!("member({member_id:?})")).spawn(move || {
+					network_thread.run();
+				})?;
+				ClientNetwork::Thread {
+					handler: Some(handler),
+					request_to_client: sender,
+					events: event_receiver,
+				}
+			}
+			ClientMode::SingleThreaded => ClientNetwork::SingleThreaded {
+				manager: Box::new(manager),
+				events: Default::default(),
+				last_cycle_time: now,
+			},
+		};
+
+		Ok(Self {
+			inner: ApplicationThreadClient::new(member_id, network, state, shared_statistics, server_time),
+			cycle_time_origin: None,
+		})
+	}
+
+	///
+	/// Отключиться от сервера и остановить сетевой поток
+	///
+	pub fn disconnect(mut self) -> Result<(), ClientError> {
+		Ok(self.inner.close(DisconnectByCommandReason::ClientStopped)?)
+	}
+
+	///
+	/// Цикл сетевого клиента в однопоточном режиме: прием и отправка пакетов, вызывается приложением в каждом кадре
+	///
+	pub fn cycle(&mut self, now: Instant) -> Result<(), ClientError> {
+		if self.inner.cycle(now) {
+			Ok(())
+		} else {
+			Err(ClientError::NotSingleThreaded)
+		}
+	}
+
+	///
+	/// [`Client::cycle`] с монотонным временем приложения в миллисекундах,
+	/// время приложения связывается с [`Instant`] при первом вызове для клиента, далее используется разница времени
+	///
+	pub fn cycle_with_app_time(&mut self, now_in_ms: u64) -> Result<(), ClientError> {
+		let (origin_ms, origin_instant) = *self.cycle_time_origin.get_or_insert_with(|| (now_in_ms, Instant::now()));
+		let now = if now_in_ms >= origin_ms {
+			origin_instant.checked_add(Duration::from_millis(now_in_ms - origin_ms)).unwrap_or(origin_instant)
+		} else {
+			origin_instant.checked_sub(Duration::from_millis(origin_ms - now_in_ms)).unwrap_or(origin_instant)
+		};
+		self.cycle(now)
+	}
+
+	pub fn connection_status(&self) -> Result<ConnectionStatus, ClientError> {
+		self.inner.get_connection_status().map_err(|e| ClientError::ConnectionStatusMutex(format!("{e:?}")))
+	}
+
+	#[must_use]
+	pub fn server_time(&self) -> Option<u64> {
+		self.inner.get_server_time()
+	}
+
+	#[must_use]
+	pub fn statistics(&self) -> &SharedClientStatistics {
+		&self.inner.shared_statistics
+	}
+
+	///
+	/// Оценка качества соединения, обновляется сетевым циклом
+	///
+	#[must_use]
+	pub fn connection_quality(&self) -> ConnectionQuality {
+		self.inner.get_connection_quality()
+	}
+
+	///
+	/// Обработчик изменения оценки качества соединения, вызывается из [`Client::receive`], [`Client::receive_events`]
+	/// и [`Client::cycle`] в потоке приложения
+	///
+	pub fn set_connection_quality_listener(&mut self, listener: Option<ConnectionQualityListener>) {
+		self.inner.set_connection_quality_listener(listener);
+	}
+
+	///
+	/// Канал для отправки последующих команд
+	///
+	pub fn set_channel(&mut self, channel: ReliabilityGuarantees) {
+		self.inner.set_current_channel(channel);
+	}
+
+	///
+	/// Забрать пришедшие с сервера команды
+	///
+	pub fn receive(&mut self) -> impl Iterator<Item = S2CCommand> + '_ {
+		self.inner.receive()
+	}
+
+	///
+	/// Забрать пришедшие команды вместе с сигналами начала и окончания синхронизации после переподключения
+	///
+	pub fn receive_events(&mut self) -> impl Iterator<Item = ClientEvent> + '_ {
+		self.inner.notify_connection_quality();
+		iter::from_fn(move || self.inner.next_client_event())
+	}
+
+	///
+	/// Идет получение состояния комнаты после переподключения, относится к командам последнего вызова [`Client::receive`]
+	///
+	#[must_use]
+	pub fn is_resynchronizing(&self) -> bool {
+		self.inner.is_resynchronizing()
+	}
+
+	pub fn attach_to_room(&mut self) -> Result<(), ClientError> {
+		Ok(self.inner.attach_to_room()?)
+	}
+
+	pub fn detach_from_room(&mut self) -> Result<(), ClientError> {
+		self.send(C2SCommand::DetachFromRoom)
+	}
+
+	///
+	/// Начать создание объекта, после установки полей необходимо вызвать [`Client::created_object`]
+	///
+	pub fn create_object(&mut self, template: GameObjectTemplateId, access_groups: AccessGroups) -> Result<GameObjectId, ClientError> {
+		Ok(self.inner.create_game_object(template, access_groups.0)?)
+	}
+
+	pub fn created_object(&mut self, object_id: GameObjectId, room_owner: bool, singleton_key: Option<Buffer>) -> Result<(), ClientError> {
+		self.send(C2SCommand::CreatedGameObject(C2SCreatedGameObject::new(object_id, room_owner, singleton_key).into()))
+	}
+
+	pub fn delete_object(&mut self, object_id: GameObjectId) -> Result<(), ClientError> {
+		self.send(C2SCommand::Delete(object_id))
+	}
+
+	pub fn set_long(&mut self, object_id: GameObjectId, field_id: FieldId, value: i64) -> Result<(), ClientError> {
+		self.send(C2SCommand::SetLong(LongField { object_id, field_id, value }))
+	}
+
+	pub fn increment_long(&mut self, object_id: GameObjectId, field_id: FieldId, increment: i64) -> Result<(), ClientError> {
+		self.send(C2SCommand::IncrementLongValue(IncrementLong { object_id, field_id, increment }))
+	}
+
+	pub fn set_double(&mut self, object_id: GameObjectId, field_id: FieldId, value: f64) -> Result<(), ClientError> {
+		self.send(C2SCommand::SetDouble(DoubleField { object_id, field_id, value }))
+	}
+
+	pub fn increment_double(&mut self, object_id: GameObjectId, field_id: FieldId, increment: f64) -> Result<(), ClientError> {
+		self.send(C2SCommand::IncrementDouble(IncrementDouble { object_id, field_id, increment }))
+	}
+
+	pub fn set_structure(&mut self, object_id: GameObjectId, field_id: FieldId, value: Buffer) -> Result<(), ClientError> {
+		self.send(C2SCommand::SetStructure(BinaryField { object_id, field_id, value }.into()))
+	}
+
+	pub fn add_item(&mut self, object_id: GameObjectId, field_id: FieldId, value: Buffer) -> Result<(), ClientError> {
+		self.send(C2SCommand::AddItem(BinaryField { object_id, field_id, value }.into()))
+	}
+
+	pub fn send_event(&mut self, object_id: GameObjectId, field_id: FieldId, event: Buffer) -> Result<(), ClientError> {
+		self.send(C2SCommand::Event(BinaryField { object_id, field_id, value: event }.into()))
+	}
+
+	///
+	/// Отправить событие только одному пользователю комнаты
+	///
+	pub fn send_target_event(&mut self, target: RoomMemberId, object_id: GameObjectId, field_id: FieldId, event: Buffer) -> Result<(), ClientError> {
+		self.send(C2SCommand::TargetEvent(
+			TargetEvent {
+				target,
+				event: BinaryField { object_id, field_id, value: event },
+			}
+			.into(),
+		))
+	}
+
+	pub fn delete_field(&mut self, object_id: GameObjectId, field_id: FieldId, field_type: FieldType) -> Result<(), ClientError> {
+		self.send(C2SCommand::DeleteField(DeleteField { object_id, field_id, field_type }))
+	}
+
+	///
+	/// Включить ведение локальной копии состояния комнаты
+	///
+	pub fn enable_room_state(&mut self) {
+		self.inner.enable_room_state();
+	}
+
+	pub fn room_state(&mut self) -> Result<&mut RoomState, ClientError> {
+		self.inner.get_room_state().ok_or(ClientError::RoomStateNotEnabled)
+	}
+
+	///
+	/// Применить пришедшие команды к локальной копии состояния комнаты, без получения самих команд
+	///
+	pub fn update_room_state(&mut self) {
+		self.inner.update_room_state();
+	}
+
+	pub fn set_rtt_emulation(&mut self, rtt: Duration, rtt_dispersion: f64) -> Result<(), ClientError> {
+		Ok(self.inner.set_rtt_emulation(rtt, rtt_dispersion)?)
+	}
+
+	pub fn set_drop_emulation(&mut self, drop_probability: f64, drop_time: Duration) -> Result<(), ClientError> {
+		Ok(self.inner.set_drop_emulation(drop_probability, drop_time)?)
+	}
+
+	pub fn set_bandwidth_emulation(&mut self, bandwidth: Bandwidth) -> Result<(), ClientError> {
+		Ok(self.inner.set_bandwidth_emulation(bandwidth)?)
+	}
+
+	pub fn set_duplicate_emulation(&mut self, duplicate_probability: f64) -> Result<(), ClientError> {
+		check_emulation_probability(duplicate_probability)?;
+		Ok(self.inner.set_duplicate_emulation(duplicate_probability)?)
+	}
+
+	///
+	/// С заданной вероятностью пакет задерживается на reorder_delay, пакеты отправленные позже его обгоняют
+	///
+	pub fn set_reorder_emulation(&mut self, reorder_probability: f64, reorder_delay: Duration) -> Result<(), ClientError> {
+		check_emulation_probability(reorder_probability)?;
+		Ok(self.inner.set_reorder_emulation(reorder_probability, reorder_delay)?)
+	}
+
+	pub fn set_burst_loss_emulation(&mut self, burst_loss: BurstLoss) -> Result<(), ClientError> {
+		for probability in [
+			burst_loss.good_to_bad_probability,
+			burst_loss.bad_to_good_probability,
+			burst_loss.good_loss_probability,
+			burst_loss.bad_loss_probability,
+		] {
+			check_emulation_probability(probability)?;
+		}
+		Ok(self.inner.set_burst_loss_emulation(burst_loss)?)
+	}
+
+	///
+	/// Заменить все параметры эмуляции параметрами типичной сети
+	///
+	pub fn set_emulation_preset(&mut self, preset: NetworkConditionsPreset) -> Result<(), ClientError> {
+		Ok(self.inner.set_emulation_preset(preset)?)
+	}
+
+	///
+	/// Автоматическое переподключение при разрыве соединения, None - выключено (по умолчанию)
+	///
+	pub fn set_reconnect_policy(&mut self, policy: Option<ReconnectPolicy>) -> Result<(), ClientError> {
+		Ok(self.inner.set_reconnect_policy(policy)?)
+	}
+
+	pub fn reset_emulation(&mut self) -> Result<(), ClientError> {
+		Ok(self.inner.reset_emulation()?)
+	}
+
+	///
+	/// Начальное значение генератора случайных чисел эмулятора сети, для повторяемых тестов
+	///
+	pub fn set_emulation_seed(&mut self, seed: u64) -> Result<(), ClientError> {
+		Ok(self.inner.set_emulation_seed(seed)?)
+	}
+
+	pub fn set_compression(&mut self, compression: bool) -> Result<(), ClientError> {
+		Ok(self.inner.set_compression(compression)?)
+	}
+
+	///
+	/// Сдвиг времени протокола, используется в тестах для проверки таймаутов
+	///
+	pub fn set_protocol_time_offset(&mut self, time_offset: Duration) -> Result<(), ClientError> {
+		Ok(self.inner.set_protocol_time_offset(time_offset)?)
+	}
+
+	fn send(&mut self, command: C2SCommand) -> Result<(), ClientError> {
+		Ok(self.inner.send(command)?)
+	}
+}
+
+fn check_emulation_probability(probability: f64) -> Result<(), ClientError> {
+	check_probability(probability).map_err(|e| ClientError::WrongEmulationParameters(e.to_string()))
+}
@@ -1,7 +1,12 @@
 use crate::clients::network_thread::C2SCommandWithChannel;
+use crate::clients::reconnect::ReconnectPolicy;
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::CommandWithReliabilityGuarantees;
+use cheetah_common::network::emulator::{Bandwidth, BurstLoss, NetworkConditionsPreset};
+use cheetah_common::network::quality::ConnectionQuality;
 use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReason;
 use std::sync::atomic::AtomicU64;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 ///
 /// Сетевой клиент запускается в отдельном потоке, взаимодействие между потоками unity
 /// (application) и сетевым клиентом осуществляется с помощью `application_thread` и `network_thread`
@@ -10,8 +15,11 @@ use std::sync::Arc;
 use std::time::Duration;
 
 pub mod application_thread;
+pub mod client;
 pub mod network_thread;
+pub mod reconnect;
 pub mod registry;
+pub mod room_state;
 
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)]
@@ -19,11 +27,45 @@ pub enum ClientRequest {
 	SetProtocolTimeOffsetForTest(Duration),
 	ConfigureRttEmulation(Duration, f64),
 	ConfigureDropEmulation(f64, Duration),
+	ConfigureBandwidthEmulation(Bandwidth),
+	ConfigureDuplicateEmulation(f64),
+	ConfigureReorderEmulation(f64, Duration),
+	ConfigureBurstLossEmulation(BurstLoss),
+	ConfigureEmulationPreset(NetworkConditionsPreset),
+	ConfigureEmulationSeed(u64),
 	SendCommandToServer(C2SCommandWithChannel),
 	ResetEmulation,
+	ConfigureCompression(bool),
+	ConfigureReconnect(Option<ReconnectPolicy>),
 	Close(DisconnectByCommandReason),
 }
 
+///
+/// События сетевого клиента для потока приложения
+///
+#[derive(Debug, Clone)]
+pub enum NetworkEvent {
+	Command(CommandWithReliabilityGuarantees),
+	///
+	/// Соединение восстановлено, далее сервер заново отправляет состояние комнаты
+	///
+	ResyncBegin,
+	///
+	/// Состояние комнаты после переподключения получено
+	///
+	ResyncEnd,
+}
+
+///
+/// Команда с сервера или сигнал синхронизации после переподключения, для потока приложения
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientEvent {
+	Command(S2CCommand),
+	ResyncBegin,
+	ResyncEnd,
+}
+
 ///
 /// Общая статистика между сетевым потоком и потоком приложения
 ///
@@ -35,4 +77,34 @@ pub struct SharedClientStatistics {
 	pub send_packet_count: Arc<AtomicU64>,
 	pub recv_size: Arc<AtomicU64>,
 	pub send_size: Arc<AtomicU64>,
+	///
+	/// Размер отправленных/полученных команд до и после сжатия
+	///
+	pub send_payload_raw_size: Arc<AtomicU64>,
+	pub send_payload_size: Arc<AtomicU64>,
+	pub recv_payload_raw_size: Arc<AtomicU64>,
+	pub recv_payload_size: Arc<AtomicU64>,
+	///
+	/// Минимальный, средний и максимальный rtt и jitter по последним замерам
+	///
+	pub rtt_min_in_ms: Arc<AtomicU64>,
+	pub rtt_avg_in_ms: Arc<AtomicU64>,
+	pub rtt_max_in_ms: Arc<AtomicU64>,
+	pub rtt_jitter_in_ms: Arc<AtomicU64>,
+	///
+	/// Доля потерянных входящих пакетов (от 0 до 1), хранится как [`f64::to_bits`]
+	///
+	pub packet_loss: Arc<AtomicU64>,
+	pub retransmit_count: Arc<AtomicU64>,
+	///
+	/// Количество отправленных надежных фреймов, ожидающих подтверждения
+	///
+	pub unacked_reliable_frames: Arc<AtomicU64>,
+	pub since_last_frame_in_ms: Arc<AtomicU64>,
+	pub connection_quality: Arc<Mutex<ConnectionQuality>>,
 }
+
+///
+/// Обработчик изменения оценки качества соединения
+///
+pub type ConnectionQualityListener = Box<dyn FnMut(ConnectionQuality) + Send>;
@@ -1,36 +1,106 @@
 use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
+use cheetah_game_realtime_protocol::frame::disconnected_reason::DisconnectedReason;
 use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
 use cheetah_game_realtime_protocol::frame::ConnectionId;
 use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use std::mem;
 use std::net::SocketAddr;
 use std::ops::Add;
 use std::sync::atomic::Ordering;
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crate::clients::{ClientRequest, SharedClientStatistics};
+use crate::clients::reconnect::{Reconnect, ReconnectPolicy};
+use crate::clients::{ClientRequest, NetworkEvent, SharedClientStatistics};
 use cheetah_common::commands::c2s::C2SCommand;
-use cheetah_common::commands::guarantees::ReliabilityGuarantees;
-use cheetah_common::commands::{BothDirectionCommand, CommandWithReliabilityGuarantees};
+use cheetah_common::commands::guarantees::{ChannelGroup, ReliabilityGuarantees};
+use cheetah_common::commands::BothDirectionCommand;
+use cheetah_common::network::emulator::{Bandwidth, BurstLoss, NetworkConditionsPreset, NetworkLatencyEmulator};
+use cheetah_common::network::quality::{ConnectionQuality, RttStatistics};
+use cheetah_common::network::socket::Transport;
+use cheetah_common::network::token::MemberToken;
 use cheetah_common::network::{ConnectionStatus, NetworkChannel};
 
 ///
-/// Управление сетевым клиентом, связывает поток unity и поток сетевого клиента
+/// Управление сетевым клиентом, выполняется в отдельном потоке ([`NetworkThread`])
+/// или в потоке приложения для однопоточного режима
 ///
 #[derive(Debug)]
 pub struct NetworkChannelManager {
 	connection_status: Arc<Mutex<ConnectionStatus>>,
-	commands_from_server: Sender<CommandWithReliabilityGuarantees>,
 	channel: NetworkChannel,
-	request_from_controller: Receiver<ClientRequest>,
+	connect_params: ConnectParams,
 	protocol_time_offset_for_test: Option<Duration>,
 	shared_statistics: SharedClientStatistics,
-	running: bool,
+	closed: bool,
+	compression: bool,
+	reconnect_policy: Option<ReconnectPolicy>,
+	reconnect: Reconnect,
+	///
+	/// Настройки эмуляции сети, применяются повторно к каналу, созданному при переподключении
+	///
+	emulation: Vec<EmulationSettings>,
+	emulation_seed: Option<u64>,
+	///
+	/// Приложение присоединилось к комнате, после переподключения AttachToRoom отправляется повторно
+	///
+	attached: bool,
+	///
+	/// Время начала получения состояния комнаты после переподключения
+	///
+	resync_start_time: Option<Instant>,
+	signal: Option<NetworkEvent>,
+	last_cycle_time: Instant,
 	pub server_time: Arc<Mutex<Option<u64>>>,
 }
 
+///
+/// Параметры подключения, используются повторно при переподключении
+///
+#[derive(Debug, Clone)]
+struct ConnectParams {
+	connection_id: ConnectionId,
+	server_address: SocketAddr,
+	transport: Transport,
+	member_id: RoomMemberId,
+	room_id: RoomId,
+	private_key: MemberPrivateKey,
+	member_token: Option<MemberToken>,
+	disconnect_timeout: Duration,
+}
+
+///
+/// Настройка эмулятора сети, из запросов [`ClientRequest`]
+///
+#[derive(Debug, Clone, Copy)]
+enum EmulationSettings {
+	Rtt(Duration, f64),
+	Drop(f64, Duration),
+	Bandwidth(Bandwidth),
+	Duplicate(f64),
+	Reorder(f64, Duration),
+	BurstLoss(BurstLoss),
+	Preset(NetworkConditionsPreset),
+}
+
+///
+/// Минимальное время получения состояния комнаты после переподключения
+///
+const RESYNC_MIN_DURATION: Duration = Duration::from_millis(100);
+
+///
+/// Сетевой поток клиента, взаимодействует с потоком приложения через каналы
+///
+#[derive(Debug)]
+pub struct NetworkThread {
+	manager: NetworkChannelManager,
+	events: Sender<NetworkEvent>,
+	request_from_controller: Receiver<ClientRequest>,
+	running: bool,
+}
+
 #[derive(Debug)]
 pub struct C2SCommandWithChannel {
 	pub channel_type: ReliabilityGuarantees,
@@ -42,51 +112,174 @@ impl NetworkChannelManager {
 	pub fn new(
 		connection_id: ConnectionId,
 		server_address: SocketAddr,
+		transport: Transport,
 		member_id: RoomMemberId,
 		room_id: RoomId,
 		private_key: MemberPrivateKey,
-		in_commands: Sender<CommandWithReliabilityGuarantees>,
 		connection_status: Arc<Mutex<ConnectionStatus>>,
-		receiver: Receiver<ClientRequest>,
 		shared_statistics: SharedClientStatistics,
 		server_time: Arc<Mutex<Option<u64>>>,
-		disconnect_timeout_in_sec: u64,
+		disconnect_timeout: Duration,
+		now: Instant,
 	) -> std::io::Result<NetworkChannelManager> {
+		let connect_params = ConnectParams {
+			connection_id,
+			server_address,
+			transport,
+			member_id,
+			room_id,
+			private_key,
+			member_token: None,
+			disconnect_timeout,
+		};
 		Ok(NetworkChannelManager {
 			connection_status,
-			commands_from_server: in_commands,
-			channel: NetworkChannel::new(
-				connection_id,
-				false,
-				private_key,
-				member_id,
-				room_id,
-				server_address,
-				Instant::now(),
-				ProtocolConfiguration {
-					disconnect_timeout: Duration::from_secs(disconnect_timeout_in_sec),
-				},
-			)?,
-			request_from_controller: receiver,
+			channel: connect_params.create_channel(now)?,
+			connect_params,
 			protocol_time_offset_for_test: None,
 			shared_statistics,
-			running: false,
+			closed: false,
+			compression: false,
+			reconnect_policy: None,
+			reconnect: Reconnect::Idle,
+			emulation: Vec::new(),
+			emulation_seed: None,
+			attached: false,
+			resync_start_time: None,
+			signal: None,
+			last_cycle_time: now,
 			server_time,
 		})
 	}
 
-	pub fn run(mut self) {
-		self.running = true;
-		while self.running {
-			let now = self.get_now_time();
-			self.channel.cycle(now);
-			self.update_server_time();
-			self.commands_from_server();
-			self.request_from_controller();
-			self.update_state();
-			thread::sleep(Duration::from_millis(1));
+	pub fn set_member_token(&mut self, member_token: &MemberToken) {
+		self.channel.set_member_token(member_token);
+		self.connect_params.member_token = Some(member_token.clone());
+	}
+
+	///
+	/// Цикл сетевого клиента: прием/отправка пакетов, переподключение, обновление состояния и статистики
+	///
+	pub fn cycle(&mut self, now: Instant) {
+		let now = self.with_time_offset(now);
+		self.try_reconnect(now);
+		self.channel.cycle(now);
+		self.check_connection(now);
+		self.last_cycle_time = now;
+		self.update_server_time();
+		self.update_state();
+	}
+
+	///
+	/// Клиент закрыт командой [`ClientRequest::Close`]
+	///
+	#[must_use]
+	pub fn is_closed(&self) -> bool {
+		self.closed
+	}
+
+	///
+	/// Передать события текущего цикла: сигнал начала/окончания синхронизации и пришедшие с сервера команды
+	///
+	pub fn take_events(&mut self, mut consumer: impl FnMut(NetworkEvent)) {
+		let resync_completed = self.is_resync_completed();
+		let commands = self.channel.protocol.input_data_handler.get_ready_commands();
+		if let Some(signal) = self.signal.take() {
+			consumer(signal);
+		}
+		for command in commands {
+			consumer(NetworkEvent::Command(command.clone()));
+		}
+		if resync_completed && commands.is_empty() {
+			self.resync_start_time = None;
+			consumer(NetworkEvent::ResyncEnd);
+		}
+	}
+
+	///
+	/// Запуск попытки переподключения, если наступило ее время
+	///
+	fn try_reconnect(&mut self, now: Instant) {
+		let attempt = match self.reconnect {
+			Reconnect::Waiting { attempt, next_attempt_time } if now >= next_attempt_time => attempt,
+			_ => return,
+		};
+		self.reconnect = Reconnect::Attempting { attempt };
+		self.connect_params.connection_id += 1;
+		tracing::info!("[client] reconnect attempt {} with connection_id {}", attempt, self.connect_params.connection_id);
+		match self.connect_params.create_channel(now) {
+			Ok(channel) => {
+				self.channel = channel;
+				self.channel.protocol.output_data_producer.set_compression(self.compression);
+				if let Some(seed) = self.emulation_seed {
+					self.channel.socket_wrapper.set_emulator_seed(seed);
+				}
+				for settings in &self.emulation {
+					self.channel.socket_wrapper.config_emulator(|emulator| settings.apply(emulator));
+				}
+			}
+			Err(e) => {
+				tracing::error!("[client] error create channel for reconnect {:?}", e);
+				self.channel.state = ConnectionStatus::Disconnected(DisconnectedReason::IOError(format!("{e:?}")));
+			}
+		}
+	}
+
+	///
+	/// Планирование переподключения при разрыве соединения и повторное присоединение к комнате после переподключения
+	///
+	fn check_connection(&mut self, now: Instant) {
+		let policy = match self.reconnect_policy {
+			None => return,
+			Some(policy) => policy,
+		};
+		match &self.channel.state {
+			ConnectionStatus::Disconnected(DisconnectedReason::Timeout | DisconnectedReason::IOError(_)) if !self.closed => {
+				let attempt = match self.reconnect {
+					Reconnect::Idle => 0,
+					Reconnect::Attempting { attempt } => attempt + 1,
+					Reconnect::Waiting { .. } | Reconnect::Exhausted => return,
+				};
+				self.reconnect = if policy.can_attempt(attempt) {
+					Reconnect::Waiting {
+						attempt,
+						next_attempt_time: now + policy.delay(attempt),
+					}
+				} else {
+					tracing::error!("[client] reconnect attempts exhausted");
+					Reconnect::Exhausted
+				};
+			}
+			ConnectionStatus::Connected if self.reconnect.is_reconnecting() => {
+				tracing::info!("[client] reconnected");
+				self.reconnect = Reconnect::Idle;
+				if self.attached {
+					self.channel
+						.protocol
+						.output_data_producer
+						.add_command(ReliabilityGuarantees::ReliableSequence(ChannelGroup(0)), BothDirectionCommand::C2S(C2SCommand::AttachToRoom));
+					if self.resync_start_time.is_none() {
+						self.signal = Some(NetworkEvent::ResyncBegin);
+					}
+					self.resync_start_time = Some(now);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	///
+	/// Состояние комнаты считается полученным, если с момента отправки AttachToRoom прошло не меньше двух RTT
+	/// и в текущем цикле не пришло новых команд
+	///
+	fn is_resync_completed(&self) -> bool {
+		match self.resync_start_time {
+			None => false,
+			Some(start_time) => {
+				let rtt = self.channel.protocol.rtt.get_rtt().unwrap_or_default();
+				self.signal.is_none() && self.last_cycle_time.duration_since(start_time) >= (rtt * 2).max(RESYNC_MIN_DURATION)
+			}
 		}
-		tracing::info!("Close network_thread client");
 	}
 
 	fn update_server_time(&mut self) {
@@ -102,8 +295,7 @@ impl NetworkChannelManager {
 	///
 	/// Текущее время, с учетом коррекции для тестов
 	///
-	fn get_now_time(&mut self) -> Instant {
-		let now = Instant::now();
+	fn with_time_offset(&self, now: Instant) -> Instant {
 		if let Some(offset) = self.protocol_time_offset_for_test {
 			now.add(offset)
 		} else {
@@ -112,51 +304,68 @@ impl NetworkChannelManager {
 	}
 
 	///
-	/// Обработка команд с сервера
+	/// Выполнить запрос приложения, now - текущее время вызывающего (без коррекции для тестов)
 	///
-	fn commands_from_server(&mut self) {
-		let in_commands_from_protocol = self.channel.protocol.input_data_handler.get_ready_commands();
-		for command in in_commands_from_protocol {
-			match self.commands_from_server.send(command.clone()) {
-				Ok(_) => {}
-				Err(e) => {
-					self.running = false;
-					tracing::error!("[client] error send command from server {:?}", e);
+	pub fn execute_request(&mut self, request: ClientRequest, now: Instant) {
+		match request {
+			ClientRequest::Close(reason) => {
+				self.channel.protocol.disconnect_by_command.disconnect(reason);
+				self.channel.cycle(self.with_time_offset(now));
+				self.reconnect = Reconnect::Idle;
+				self.update_state();
+				self.closed = true;
+				tracing::info!("[client] ClientRequest::Close");
+			}
+			ClientRequest::SetProtocolTimeOffsetForTest(duration) => {
+				self.protocol_time_offset_for_test = Some(duration);
+			}
+			ClientRequest::ConfigureRttEmulation(rtt, rtt_dispersion) => self.configure_emulation(EmulationSettings::Rtt(rtt, rtt_dispersion)),
+			ClientRequest::ConfigureDropEmulation(drop_probability, drop_time) => self.configure_emulation(EmulationSettings::Drop(drop_probability, drop_time)),
+			ClientRequest::ConfigureBandwidthEmulation(bandwidth) => self.configure_emulation(EmulationSettings::Bandwidth(bandwidth)),
+			ClientRequest::ConfigureDuplicateEmulation(duplicate_probability) => self.configure_emulation(EmulationSettings::Duplicate(duplicate_probability)),
+			ClientRequest::ConfigureReorderEmulation(reorder_probability, reorder_delay) => self.configure_emulation(EmulationSettings::Reorder(reorder_probability, reorder_delay)),
+			ClientRequest::ConfigureBurstLossEmulation(burst_loss) => self.configure_emulation(EmulationSettings::BurstLoss(burst_loss)),
+			ClientRequest::ConfigureEmulationPreset(preset) => self.configure_emulation(EmulationSettings::Preset(preset)),
+			ClientRequest::ConfigureEmulationSeed(seed) => {
+				self.emulation_seed = Some(seed);
+				self.channel.socket_wrapper.set_emulator_seed(seed);
+			}
+			ClientRequest::ResetEmulation => {
+				self.emulation.clear();
+				self.channel.socket_wrapper.reset_emulator();
+			}
+			ClientRequest::ConfigureCompression(compression) => {
+				self.compression = compression;
+				self.channel.protocol.output_data_producer.set_compression(compression);
+			}
+			ClientRequest::ConfigureReconnect(policy) => {
+				self.reconnect_policy = policy;
+				if policy.is_none() || self.reconnect == Reconnect::Exhausted {
+					self.reconnect = Reconnect::Idle;
 				}
 			}
+			ClientRequest::SendCommandToServer(command) => {
+				match command.command {
+					C2SCommand::AttachToRoom => self.attached = true,
+					C2SCommand::DetachFromRoom => self.attached = false,
+					_ => {}
+				}
+				self.channel.protocol.output_data_producer.add_command(command.channel_type, BothDirectionCommand::C2S(command.command));
+			}
 		}
 	}
 
 	///
-	/// Обработка команд из контроллера
+	/// Применить настройку эмулятора и сохранить ее для повторного применения при переподключении,
+	/// сохраняется только последняя настройка каждого вида, пресет заменяет все предыдущие настройки
 	///
-	fn request_from_controller(&mut self) {
-		while let Ok(command) = self.request_from_controller.try_recv() {
-			match command {
-				ClientRequest::Close(reason) => {
-					self.channel.protocol.disconnect_by_command.disconnect(reason);
-					let now = Instant::now();
-					self.channel.cycle(now);
-					self.running = false;
-					tracing::info!("[client] ClientRequest::Close");
-				}
-				ClientRequest::SetProtocolTimeOffsetForTest(duration) => {
-					self.protocol_time_offset_for_test = Some(duration);
-				}
-				ClientRequest::ConfigureRttEmulation(rtt, rtt_dispersion) => self.channel.socket_wrapper.config_emulator(|emulator| {
-					emulator.configure_rtt(rtt, rtt_dispersion);
-				}),
-				ClientRequest::ConfigureDropEmulation(drop_probability, drop_time) => self.channel.socket_wrapper.config_emulator(|emulator| {
-					emulator.configure_drop(drop_probability, drop_time);
-				}),
-				ClientRequest::ResetEmulation => {
-					self.channel.socket_wrapper.reset_emulator();
-				}
-				ClientRequest::SendCommandToServer(command) => {
-					self.channel.protocol.output_data_producer.add_command(command.channel_type, BothDirectionCommand::C2S(command.command));
-				}
-			}
+	fn configure_emulation(&mut self, settings: EmulationSettings) {
+		match settings {
+			EmulationSettings::Preset(_) => self.emulation.clear(),
+			_ => self.emulation.retain(|stored| mem::discriminant(stored) != mem::discriminant(&settings)),
 		}
+		self.emulation.push(settings);
+		self.channel.socket_wrapper.config_emulator(|emulator| settings.apply(emulator));
 	}
 
 	///
@@ -169,15 +378,140 @@ impl NetworkChannelManager {
 		self.shared_statistics
 			.rtt_in_ms
 			.store(protocol.rtt.get_rtt().unwrap_or_else(|| Duration::from_millis(0)).as_millis() as u64, Ordering::Relaxed);
+		let rtt = RttStatistics::from_samples(protocol.rtt.rtt.iter());
+		let rtt_or_default = rtt.unwrap_or_default();
+		self.shared_statistics.rtt_min_in_ms.store(rtt_or_default.min.as_millis() as u64, Ordering::Relaxed);
+		self.shared_statistics.rtt_avg_in_ms.store(rtt_or_default.avg.as_millis() as u64, Ordering::Relaxed);
+		self.shared_statistics.rtt_max_in_ms.store(rtt_or_default.max.as_millis() as u64, Ordering::Relaxed);
+		self.shared_statistics.rtt_jitter_in_ms.store(rtt_or_default.jitter.as_millis() as u64, Ordering::Relaxed);
+
+		let quality_statistics = &self.channel.quality_statistics;
+		let packet_loss = quality_statistics.packet_loss();
+		let since_last_frame = self.last_cycle_time.saturating_duration_since(protocol.disconnect_by_timeout.last_in_frame_time);
+		self.shared_statistics.packet_loss.store(packet_loss.to_bits(), Ordering::Relaxed);
+		self.shared_statistics.retransmit_count.store(quality_statistics.retransmit_count, Ordering::Relaxed);
 		self.shared_statistics
-			.rtt_in_ms
-			.store(protocol.rtt.get_rtt().unwrap_or_else(|| Duration::from_millis(0)).as_millis() as u64, Ordering::Relaxed);
+			.unacked_reliable_frames
+			.store(quality_statistics.unacked_reliable_frames() as u64, Ordering::Relaxed);
+		self.shared_statistics.since_last_frame_in_ms.store(since_last_frame.as_millis() as u64, Ordering::Relaxed);
+		*self.shared_statistics.connection_quality.lock().unwrap() = if self.channel.state == ConnectionStatus::Connected && !self.reconnect.is_reconnecting() {
+			ConnectionQuality::rate(rtt, packet_loss, since_last_frame)
+		} else {
+			ConnectionQuality::Unknown
+		};
+
+		let send_payload = protocol.output_data_producer.statistics;
+		self.shared_statistics.send_payload_raw_size.store(send_payload.raw_size, Ordering::Relaxed);
+		self.shared_statistics.send_payload_size.store(send_payload.size, Ordering::Relaxed);
+		let recv_payload = protocol.input_data_handler.statistics;
+		self.shared_statistics.recv_payload_raw_size.store(recv_payload.raw_size, Ordering::Relaxed);
+		self.shared_statistics.recv_payload_size.store(recv_payload.size, Ordering::Relaxed);
 
 		let channel = &self.channel.socket_wrapper;
 		self.shared_statistics.recv_packet_count.store(channel.recv_packet_count, Ordering::Relaxed);
 		self.shared_statistics.send_packet_count.store(channel.send_packet_count, Ordering::Relaxed);
 		self.shared_statistics.send_size.store(channel.send_size, Ordering::Relaxed);
 		self.shared_statistics.recv_size.store(channel.recv_size, Ordering::Relaxed);
-		*self.connection_status.lock().unwrap() = self.channel.state.clone();
+		*self.connection_status.lock().unwrap() = if self.reconnect.is_reconnecting() {
+			ConnectionStatus::Reconnecting
+		} else {
+			self.channel.state.clone()
+		};
+	}
+}
+
+impl EmulationSettings {
+	fn apply(self, emulator: &mut NetworkLatencyEmulator) {
+		match self {
+			EmulationSettings::Rtt(rtt, rtt_dispersion) => emulator.configure_rtt(rtt, rtt_dispersion),
+			EmulationSettings::Drop(drop_probability, drop_time) => emulator.configure_drop(drop_probability, drop_time),
+			EmulationSettings::Bandwidth(bandwidth) => emulator.configure_bandwidth(bandwidth),
+			EmulationSettings::Duplicate(duplicate_probability) => emulator.configure_duplicate(duplicate_probability),
+			EmulationSettings::Reorder(reorder_probability, reorder_delay) => emulator.configure_reorder(reorder_probability, reorder_delay),
+			EmulationSettings::BurstLoss(burst_loss) => emulator.configure_burst_loss(burst_loss),
+			EmulationSettings::Preset(preset) => emulator.configure_preset(preset),
+		}
+	}
+}
+
+impl ConnectParams {
+	fn create_channel(&self, now: Instant) -> std::io::Result<NetworkChannel> {
+		let mut channel = NetworkChannel::new(
+			self.connection_id,
+			false,
+			self.private_key.clone(),
+			self.member_id,
+			self.room_id,
+			self.server_address,
+			self.transport.clone(),
+			now,
+			ProtocolConfiguration {
+				disconnect_timeout: self.disconnect_timeout,
+			},
+		)?;
+		if let Some(member_token) = &self.member_token {
+			channel.set_member_token(member_token);
+		}
+		Ok(channel)
+	}
+}
+
+impl NetworkThread {
+	#[must_use]
+	pub fn new(manager: NetworkChannelManager, events: Sender<NetworkEvent>, request_from_controller: Receiver<ClientRequest>) -> Self {
+		Self {
+			manager,
+			events,
+			request_from_controller,
+			running: false,
+		}
+	}
+
+	pub fn run(mut self) {
+		self.running = true;
+		while self.running {
+			let now = Instant::now();
+			self.manager.cycle(now);
+			self.events();
+			self.request_from_controller(now);
+			thread::sleep(Duration::from_millis(1));
+		}
+		tracing::info!("Close network_thread client");
+	}
+
+	///
+	/// Передача команд с сервера и сигналов синхронизации в поток приложения
+	///
+	fn events(&mut self) {
+		let events = &self.events;
+		let running = &mut self.running;
+		self.manager.take_events(|event| {
+			if let Err(e) = events.send(event) {
+				*running = false;
+				tracing::error!("[client] error send command from server {:?}", e);
+			}
+		});
+	}
+
+	///
+	/// Обработка команд из контроллера
+	///
+	fn request_from_controller(&mut self, now: Instant) {
+		loop {
+			let request = match self.request_from_controller.try_recv() {
+				Ok(request) => request,
+				Err(TryRecvError::Empty) => break,
+				Err(TryRecvError::Disconnected) => {
+					// клиент удален без отключения от сервера
+					self.running = false;
+					tracing::info!("[client] controller closed");
+					break;
+				}
+			};
+			self.manager.execute_request(request, now);
+			if self.manager.is_closed() {
+				self.running = false;
+			}
+		}
 	}
 }
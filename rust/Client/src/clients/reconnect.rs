@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+///
+/// Политика автоматического переподключения после разрыва соединения (таймаут или ошибка ввода-вывода)
+///
+/// - задержка перед попыткой растет экспоненциально от `initial_delay` до `max_delay`
+/// - каждая попытка использует новый идентификатор соединения и тот же ключ пользователя
+/// - пользователь, созданный заранее, должен существовать на сервере (сервер удаляет пользователя по своему таймауту),
+///   при подключении по токену пользователь создается сервером заново
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+	pub initial_delay: Duration,
+	pub max_delay: Duration,
+	///
+	/// Максимальное количество попыток подряд, None - без ограничения
+	///
+	pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+	fn default() -> Self {
+		Self {
+			initial_delay: Duration::from_millis(500),
+			max_delay: Duration::from_secs(10),
+			max_attempts: None,
+		}
+	}
+}
+
+impl ReconnectPolicy {
+	#[must_use]
+	pub fn delay(&self, attempt: u32) -> Duration {
+		self.initial_delay.saturating_mul(2_u32.saturating_pow(attempt)).min(self.max_delay)
+	}
+
+	#[must_use]
+	pub fn can_attempt(&self, attempt: u32) -> bool {
+		self.max_attempts.is_none_or(|max_attempts| attempt < max_attempts)
+	}
+}
+
+///
+/// Состояние переподключения, номер попытки начинается с 0
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Reconnect {
+	///
+	/// Соединение не разрывалось или восстановлено
+	///
+	#[default]
+	Idle,
+	///
+	/// Ожидание времени следующей попытки
+	///
+	Waiting { attempt: u32, next_attempt_time: Instant },
+	///
+	/// Попытка выполняется
+	///
+	Attempting { attempt: u32 },
+	///
+	/// Попытки исчерпаны, новые попытки начинаются только после установки политики переподключения
+	///
+	Exhausted,
+}
+
+impl Reconnect {
+	#[must_use]
+	pub fn is_reconnecting(&self) -> bool {
+		matches!(self, Reconnect::Waiting { .. } | Reconnect::Attempting { .. })
+	}
+}
@@ -1,4 +1,3 @@
-use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReason;
 use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
 use cheetah_game_realtime_protocol::frame::ConnectionId;
 use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
@@ -6,20 +5,26 @@ use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::net::SocketAddr;
 use std::str::FromStr;
-use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::{panic, thread};
 
 use fnv::FnvBuildHasher;
 
-use crate::clients::application_thread::ApplicationThreadClient;
-use crate::clients::network_thread::NetworkChannelManager;
-use crate::clients::{ClientRequest, SharedClientStatistics};
-use cheetah_common::network::ConnectionStatus;
+use crate::clients::client::{Client, ClientError, ClientMode, ConnectOptions};
+use cheetah_common::network::socket::Transport;
 use cheetah_common::tracer::Trace;
 
 pub type ClientId = u16;
 
+///
+/// Префикс адреса сервера для подключения через WebSocket
+///
+pub const WEBSOCKET_ADDRESS_PREFIX: &str = "ws://";
+///
+/// WebSocket с TLS не поддерживается, см. [`Transport::WebSocket`]
+///
+const SECURE_WEBSOCKET_ADDRESS_PREFIX: &str = "wss://";
+
 ///
 /// Реестр клиентов
 ///
@@ -29,12 +34,12 @@ pub type ClientId = u16;
 ///
 #[derive(Default)]
 pub struct Registry {
-	pub clients: HashMap<ClientId, ApplicationThreadClient, FnvBuildHasher>,
+	pub clients: HashMap<ClientId, Client, FnvBuildHasher>,
 	client_generator_id: ClientId,
 }
 
 impl Registry {
-	#[allow(clippy::unwrap_in_result)]
+	#[allow(clippy::too_many_arguments)]
 	pub fn create_client(
 		&mut self,
 		connection_id: ConnectionId,
@@ -43,44 +48,66 @@ impl Registry {
 		room_id: RoomId,
 		private_key: MemberPrivateKey,
 		disconnect_timeout_in_sec: u64,
-	) -> std::io::Result<ClientId> {
+		mode: ClientMode,
+	) -> Result<ClientId, ClientError> {
 		Self::set_panic_hook();
-
-		let server_time = Arc::new(Mutex::new(None));
-		let state = Arc::new(Mutex::new(ConnectionStatus::Connecting));
-		let state_cloned = Arc::clone(&state);
-		let shared_statistics = SharedClientStatistics::default();
-
-		let (sender, receiver) = std::sync::mpsc::channel();
-		let (in_command_sender, in_command_receiver) = std::sync::mpsc::channel();
-		let client = NetworkChannelManager::new(
+		let (server_address, transport) = Self::parse_address(server_address)?;
+		let client = Client::connect(ConnectOptions {
 			connection_id,
-			SocketAddr::from_str(server_address).map_err(|e| std::io::Error::new(ErrorKind::AddrNotAvailable, format!("{e:?}")))?,
-			member_id,
-			room_id,
-			private_key,
-			in_command_sender,
-			state,
-			receiver,
-			shared_statistics.clone(),
-			Arc::clone(&server_time),
-			disconnect_timeout_in_sec,
-		)?;
+			mode,
+			transport,
+			..ConnectOptions::new(server_address, member_id, room_id, private_key, Duration::from_secs(disconnect_timeout_in_sec))
+		})?;
+		Ok(self.register(client))
+	}
 
-		let handler = thread::Builder::new()
-			.name(format!("member({member_id:?})"))
-			.spawn(move || {
-				client.run();
-			})
-			.unwrap();
+	///
+	/// Создать клиента для подключения по токену
+	/// - идентификаторы комнаты и пользователя берутся из токена
+	/// - private_key - сессионный ключ, выданный backend-ом вместе с токеном
+	///
+	pub fn create_client_with_token(
+		&mut self,
+		connection_id: ConnectionId,
+		server_address: &str,
+		member_token: &[u8],
+		private_key: MemberPrivateKey,
+		disconnect_timeout_in_sec: u64,
+		mode: ClientMode,
+	) -> Result<ClientId, ClientError> {
+		Self::set_panic_hook();
+		let (server_address, transport) = Self::parse_address(server_address)?;
+		let client = Client::connect(ConnectOptions {
+			connection_id,
+			mode,
+			transport,
+			..ConnectOptions::with_token(server_address, member_token, private_key, Duration::from_secs(disconnect_timeout_in_sec))
+		})?;
+		Ok(self.register(client))
+	}
 
-		let application_thread_client = ApplicationThreadClient::new(member_id, handler, state_cloned, in_command_receiver, sender, shared_statistics, server_time);
+	fn register(&mut self, client: Client) -> ClientId {
 		self.client_generator_id += 1;
 		let client_id = self.client_generator_id;
-		self.clients.insert(client_id, application_thread_client);
-
+		self.clients.insert(client_id, client);
 		tracing::info!("[registry] create client({})", client_id);
-		Ok(client_id)
+		client_id
+	}
+
+	///
+	/// Адрес с префиксом ws:// - подключение через WebSocket, иначе через UDP
+	///
+	fn parse_address(server_address: &str) -> Result<(SocketAddr, Transport), ClientError> {
+		if server_address.starts_with(SECURE_WEBSOCKET_ADDRESS_PREFIX) {
+			return Err(std::io::Error::new(ErrorKind::Unsupported, format!("{SECURE_WEBSOCKET_ADDRESS_PREFIX} is not supported, use {WEBSOCKET_ADDRESS_PREFIX}")).into());
+		}
+		let (server_address, transport) = match server_address.strip_prefix(WEBSOCKET_ADDRESS_PREFIX) {
+			None => (server_address, Transport::Udp),
+			Some(server_address) => (server_address.trim_end_matches('/'), Transport::WebSocket),
+		};
+		SocketAddr::from_str(server_address)
+			.map(|server_address| (server_address, transport))
+			.map_err(|e| std::io::Error::new(ErrorKind::AddrNotAvailable, format!("{e:?}")).into())
 	}
 
 	fn set_panic_hook() {
@@ -95,20 +122,17 @@ impl Registry {
 		}));
 	}
 
-	pub fn destroy_client(&mut self, client_id: ClientId) -> Option<ApplicationThreadClient> {
+	pub fn destroy_client(&mut self, client_id: ClientId) -> bool {
 		match self.clients.remove(&client_id) {
-			None => None,
+			None => false,
 			Some(client) => {
-				client
-					.request_to_client
-					.send(ClientRequest::Close(DisconnectByCommandReason::ClientStopped))
-					.trace_err("Error send ClientStopped");
-				Some(client)
+				let _ = client.disconnect().trace_err("Error send ClientStopped");
+				true
 			}
 		}
 	}
 
-	pub fn destroy_client_without_disconnect(&mut self, client_id: ClientId) -> Option<ApplicationThreadClient> {
+	pub fn destroy_client_without_disconnect(&mut self, client_id: ClientId) -> Option<Client> {
 		self.clients.remove(&client_id)
 	}
 }
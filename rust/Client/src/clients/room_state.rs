@@ -0,0 +1,223 @@
+use std::collections::VecDeque;
+
+use fnv::FnvHashMap;
+
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::room::access::AccessGroups;
+use cheetah_common::room::buffer::Buffer;
+use cheetah_common::room::field::{FieldId, FieldType};
+use cheetah_common::room::object::{GameObjectId, GameObjectTemplateId};
+
+///
+/// Локальная копия состояния комнаты
+///
+/// - строится по входящим S2C командам, игровой движок может не вести собственную модель
+/// - изменения накапливаются в очереди, движок забирает их через [`RoomState::pop_change`]
+/// - повторные изменения поля заменяют ожидающее изменение на месте, поэтому без забора очередь ограничена
+///   количеством полей загруженных объектов и удалений объектов, движок должен забирать изменения каждый кадр
+///
+#[derive(Debug, Default)]
+pub struct RoomState {
+	objects: FnvHashMap<GameObjectId, GameObjectState>,
+	changes: VecDeque<RoomStateChange>,
+	///
+	/// Количество забранных изменений, позиция изменения в очереди - его номер минус popped
+	///
+	popped: u64,
+	///
+	/// Номера ожидающих изменений полей, для замены на месте
+	///
+	field_changes: FnvHashMap<(GameObjectId, FieldId, FieldType), u64>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct GameObjectState {
+	pub template: GameObjectTemplateId,
+	pub access_groups: AccessGroups,
+	///
+	/// Объект полностью загружен (получена команда Created)
+	///
+	pub created: bool,
+	pub longs: FnvHashMap<FieldId, i64>,
+	pub doubles: FnvHashMap<FieldId, f64>,
+	pub structures: FnvHashMap<FieldId, Buffer>,
+	pub items: FnvHashMap<FieldId, Vec<Buffer>>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoomStateChangeType {
+	ObjectCreated,
+	ObjectDeleted,
+	FieldChanged,
+	FieldDeleted,
+}
+
+///
+/// Изменение состояния комнаты, для изменений объекта поля field_id и field_type не используются
+///
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RoomStateChange {
+	pub change_type: RoomStateChangeType,
+	pub object_id: GameObjectId,
+	pub field_id: FieldId,
+	pub field_type: FieldType,
+}
+
+impl RoomState {
+	pub fn apply(&mut self, command: &S2CCommand) {
+		match command {
+			S2CCommand::Create(command) => {
+				self.objects.insert(
+					command.object_id,
+					GameObjectState {
+						template: command.template,
+						access_groups: command.access_groups,
+						..Default::default()
+					},
+				);
+			}
+			S2CCommand::Created(command) => {
+				if let Some(object) = self.objects.get_mut(&command.object_id) {
+					object.created = true;
+					self.add_object_change(RoomStateChangeType::ObjectCreated, command.object_id);
+				}
+			}
+			S2CCommand::SetLong(command) => {
+				if let Some(object) = self.objects.get_mut(&command.object_id) {
+					object.longs.insert(command.field_id, command.value);
+					self.add_field_change(RoomStateChangeType::FieldChanged, command.object_id, command.field_id, FieldType::Long);
+				}
+			}
+			S2CCommand::SetDouble(command) => {
+				if let Some(object) = self.objects.get_mut(&command.object_id) {
+					object.doubles.insert(command.field_id, command.value);
+					self.add_field_change(RoomStateChangeType::FieldChanged, command.object_id, command.field_id, FieldType::Double);
+				}
+			}
+			S2CCommand::SetStructure(command) => {
+				if let Some(object) = self.objects.get_mut(&command.object_id) {
+					object.structures.insert(command.field_id, command.value.clone());
+					self.add_field_change(RoomStateChangeType::FieldChanged, command.object_id, command.field_id, FieldType::Structure);
+				}
+			}
+			S2CCommand::AddItem(command) => {
+				if let Some(object) = self.objects.get_mut(&command.object_id) {
+					object.items.entry(command.field_id).or_default().push(command.value.clone());
+					self.add_field_change(RoomStateChangeType::FieldChanged, command.object_id, command.field_id, FieldType::Items);
+				}
+			}
+			S2CCommand::Delete(object_id) => {
+				if let Some(object) = self.objects.remove(object_id) {
+					// изменения удаленного объекта больше не интересны движку
+					let created_not_popped = self
+						.changes
+						.iter()
+						.any(|change| change.object_id == *object_id && change.change_type == RoomStateChangeType::ObjectCreated);
+					self.remove_object_changes(object_id);
+					if object.created && !created_not_popped {
+						self.add_object_change(RoomStateChangeType::ObjectDeleted, *object_id);
+					}
+				}
+			}
+			S2CCommand::DeleteField(command) => {
+				if let Some(object) = self.objects.get_mut(&command.object_id) {
+					let deleted = match command.field_type {
+						FieldType::Long => object.longs.remove(&command.field_id).is_some(),
+						FieldType::Double => object.doubles.remove(&command.field_id).is_some(),
+						FieldType::Structure => object.structures.remove(&command.field_id).is_some(),
+						FieldType::Items => object.items.remove(&command.field_id).is_some(),
+						FieldType::Event => false,
+					};
+					if deleted {
+						self.add_field_change(RoomStateChangeType::FieldDeleted, command.object_id, command.field_id, command.field_type);
+					}
+				}
+			}
+			S2CCommand::Event(_) | S2CCommand::MemberConnected(_) | S2CCommand::MemberDisconnected(_) => {}
+		}
+	}
+
+	pub fn get_object(&self, object_id: &GameObjectId) -> Option<&GameObjectState> {
+		self.objects.get(object_id)
+	}
+
+	///
+	/// Объекты, для которых получена команда Created
+	///
+	pub fn get_created_objects(&self) -> impl Iterator<Item = (&GameObjectId, &GameObjectState)> {
+		self.objects.iter().filter(|(_, object)| object.created)
+	}
+
+	///
+	/// Получить следующее изменение, повторные изменения одного и того же поля объединяются
+	///
+	pub fn pop_change(&mut self) -> Option<RoomStateChange> {
+		let change = self.changes.pop_front()?;
+		if Self::is_field_change(&change) {
+			self.field_changes.remove(&(change.object_id, change.field_id, change.field_type));
+		}
+		self.popped += 1;
+		Some(change)
+	}
+
+	pub fn clear(&mut self) {
+		self.objects.clear();
+		self.popped += self.changes.len() as u64;
+		self.changes.clear();
+		self.field_changes.clear();
+	}
+
+	fn add_object_change(&mut self, change_type: RoomStateChangeType, object_id: GameObjectId) {
+		self.add_change(RoomStateChange {
+			change_type,
+			object_id,
+			field_id: 0,
+			field_type: FieldType::Long,
+		});
+	}
+
+	fn add_field_change(&mut self, change_type: RoomStateChangeType, object_id: GameObjectId, field_id: FieldId, field_type: FieldType) {
+		// до команды Created объект загружается, движок получит его целиком по ObjectCreated
+		if !self.objects.get(&object_id).map(|object| object.created).unwrap_or(false) {
+			return;
+		}
+		self.add_change(RoomStateChange {
+			change_type,
+			object_id,
+			field_id,
+			field_type,
+		});
+	}
+
+	fn add_change(&mut self, change: RoomStateChange) {
+		if Self::is_field_change(&change) {
+			// последнее изменение поля заменяет предыдущее, например FieldDeleted после FieldChanged
+			let key = (change.object_id, change.field_id, change.field_type);
+			if let Some(number) = self.field_changes.get(&key) {
+				self.changes[(number - self.popped) as usize] = change;
+				return;
+			}
+			self.field_changes.insert(key, self.popped + self.changes.len() as u64);
+		}
+		self.changes.push_back(change);
+	}
+
+	///
+	/// Удалить ожидающие изменения объекта, номера оставшихся изменений полей пересчитываются
+	///
+	fn remove_object_changes(&mut self, object_id: &GameObjectId) {
+		self.changes.retain(|change| change.object_id != *object_id);
+		self.field_changes.clear();
+		for (position, change) in self.changes.iter().enumerate() {
+			if Self::is_field_change(change) {
+				self.field_changes.insert((change.object_id, change.field_id, change.field_type), self.popped + position as u64);
+			}
+		}
+	}
+
+	fn is_field_change(change: &RoomStateChange) -> bool {
+		change.change_type == RoomStateChangeType::FieldChanged || change.change_type == RoomStateChangeType::FieldDeleted
+	}
+}
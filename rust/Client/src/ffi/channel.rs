@@ -1,6 +1,6 @@
 use crate::clients::registry::ClientId;
 use crate::ffi::execute_with_client;
-use cheetah_common::commands::guarantees::ChannelGroup;
+use cheetah_common::commands::guarantees::{ChannelGroup, ReliabilityGuarantees};
 
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
@@ -10,12 +10,27 @@ pub enum Channel {
 	ReliableOrdered,
 	UnreliableOrdered,
 	ReliableSequence,
+	ReliableLatest,
 }
 
 #[no_mangle]
 pub extern "C" fn set_channel(client_id: ClientId, channel: Channel, group: u8) -> u8 {
 	execute_with_client(client_id, |client| {
-		client.set_current_channel(channel, ChannelGroup(group));
+		client.set_channel(channel.into_guarantees(ChannelGroup(group)));
 		Ok(())
 	})
 }
+
+impl Channel {
+	#[must_use]
+	pub fn into_guarantees(self, group: ChannelGroup) -> ReliabilityGuarantees {
+		match self {
+			Channel::ReliableUnordered => ReliabilityGuarantees::ReliableUnordered,
+			Channel::UnreliableUnordered => ReliabilityGuarantees::UnreliableUnordered,
+			Channel::ReliableOrdered => ReliabilityGuarantees::ReliableOrdered(group),
+			Channel::UnreliableOrdered => ReliabilityGuarantees::UnreliableOrdered(group),
+			Channel::ReliableSequence => ReliabilityGuarantees::ReliableSequence(group),
+			Channel::ReliableLatest => ReliabilityGuarantees::ReliableLatest,
+		}
+	}
+}
@@ -2,16 +2,34 @@ use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReas
 use cheetah_game_realtime_protocol::frame::disconnected_reason::DisconnectedReason;
 use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
 use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use std::cell::RefCell;
 use std::ffi::CStr;
 use std::os::raw::c_char;
+use std::slice;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
 
+use crate::clients::client::{ClientError as ClientApiError, ClientMode};
+use crate::clients::reconnect::ReconnectPolicy;
 use crate::clients::registry::ClientId;
-use crate::ffi::command::{BufferFFI, S2CCommandFFI};
+use crate::clients::ConnectionQualityListener;
+use crate::ffi::command::{BufferFFI, S2CCommandCallbacksFFI, S2CCommandFFI};
 use crate::ffi::{execute, execute_with_client, ClientError, LAST_ERROR};
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::network::emulator::{Bandwidth, BurstLoss, NetworkConditionsPreset};
+use cheetah_common::network::quality::ConnectionQuality;
 use cheetah_common::network::ConnectionStatus;
-use cheetah_common::room::buffer::Buffer;
+
+pub const MAX_RECEIVE_COMMANDS: usize = 1024;
+
+type ConnectionQualityListenerFFI = extern "C" fn(ClientId, ConnectionQuality);
+
+thread_local! {
+	///
+	/// Изменения качества соединения, накопленные под блокировкой реестра клиентов, обработчики вызываются после ее освобождения
+	///
+	static PENDING_CONNECTION_QUALITY: RefCell<Vec<(ConnectionQualityListenerFFI, ClientId, ConnectionQuality)>> = RefCell::new(Vec::new());
+}
 
 #[derive(Debug, Copy, Clone)]
 #[repr(C)]
@@ -30,43 +48,39 @@ pub enum ConnectionStatusFFI {
 	DisconnectedByClientStopped,
 	DisconnectedByRoomDeleted,
 	DisconnectedByMemberDeleted,
+	DisconnectedByServerShutdown,
+	///
+	/// Соединение разорвано, выполняется автоматическое переподключение
+	///
+	Reconnecting,
 }
 
 #[no_mangle]
 pub extern "C" fn get_connection_status(client_id: ClientId, result: &mut ConnectionStatusFFI) -> u8 {
 	execute_with_client(client_id, |client| {
-		let status = client.get_connection_status().map_err(|e| ClientError::ConnectionStatusMutexError(format!("{e:?}")));
-		match status {
-			Ok(status) => {
-				let ffi_status = match status {
-					ConnectionStatus::Connecting => ConnectionStatusFFI::Connecting,
-					ConnectionStatus::Connected => ConnectionStatusFFI::Connected,
-					ConnectionStatus::Disconnected(disconnect_reason) => match disconnect_reason {
-						DisconnectedReason::IOError(_) => ConnectionStatusFFI::DisconnectedByIOError,
-						DisconnectedReason::Timeout => ConnectionStatusFFI::DisconnectedByTimeout,
-						DisconnectedReason::Command(reason) => match reason {
-							DisconnectByCommandReason::ClientStopped => ConnectionStatusFFI::DisconnectedByClientStopped,
-							DisconnectByCommandReason::RoomDeleted => ConnectionStatusFFI::DisconnectedByRoomDeleted,
-							DisconnectByCommandReason::MemberDeleted => ConnectionStatusFFI::DisconnectedByMemberDeleted,
-						},
-						DisconnectedReason::RetransmitOverflow => ConnectionStatusFFI::RetransmitOverflow,
-					},
-				};
-				*result = ffi_status;
-
-				Ok(())
-			}
-			Err(e) => Err(e),
-		}
+		*result = match client.connection_status()? {
+			ConnectionStatus::Connecting => ConnectionStatusFFI::Connecting,
+			ConnectionStatus::Connected => ConnectionStatusFFI::Connected,
+			ConnectionStatus::Disconnected(disconnect_reason) => match disconnect_reason {
+				DisconnectedReason::IOError(_) => ConnectionStatusFFI::DisconnectedByIOError,
+				DisconnectedReason::Timeout => ConnectionStatusFFI::DisconnectedByTimeout,
+				DisconnectedReason::Command(reason) => match reason {
+					DisconnectByCommandReason::ClientStopped => ConnectionStatusFFI::DisconnectedByClientStopped,
+					DisconnectByCommandReason::RoomDeleted => ConnectionStatusFFI::DisconnectedByRoomDeleted,
+					DisconnectByCommandReason::MemberDeleted => ConnectionStatusFFI::DisconnectedByMemberDeleted,
+				},
+				DisconnectedReason::RetransmitOverflow => ConnectionStatusFFI::RetransmitOverflow,
+			},
+			ConnectionStatus::DisconnectedByServerShutdown => ConnectionStatusFFI::DisconnectedByServerShutdown,
+			ConnectionStatus::Reconnecting => ConnectionStatusFFI::Reconnecting,
+		};
+		Ok(())
 	})
 }
 
 #[no_mangle]
 pub extern "C" fn destroy_client(client: ClientId) -> u8 {
-	execute(|registry| match registry.destroy_client(client) {
-		None => Err(ClientError::ClientNotFound(client)),
-		Some(_) => Ok(()),
-	})
+	execute(|registry| if registry.destroy_client(client) { Ok(()) } else { Err(ClientError::ClientNotFound(client)) })
 }
 
 #[no_mangle]
@@ -77,18 +91,91 @@ pub extern "C" fn destroy_client_without_disconnect(client: ClientId) -> u8 {
 	})
 }
 
+///
+/// Забрать пришедшие команды, out_commands должен указывать на массив из [`MAX_RECEIVE_COMMANDS`] элементов
+///
 #[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn receive(client_id: ClientId, out_commands: *mut S2CCommandFFI, count: &mut u16) -> u8 {
-	execute_with_client(client_id, |client| unsafe {
-		client.receive(out_commands, count);
+	*count = 0;
+	let out_commands = unsafe { slice::from_raw_parts_mut(out_commands, MAX_RECEIVE_COMMANDS) };
+	let code = execute_with_client(client_id, |client| {
+		*count = copy_commands(client.receive(), out_commands);
+		Ok(())
+	});
+	notify_connection_quality_listeners();
+	code
+}
+
+///
+/// Забрать пришедшие команды через обработчики по типам команд, без копирования в массив и без ограничения на количество команд
+///
+/// - обработчики вызываются после освобождения реестра клиентов, из них можно вызывать другие FFI функции
+///
+#[no_mangle]
+pub extern "C" fn receive_with_callbacks(client_id: ClientId, callbacks: &S2CCommandCallbacksFFI) -> u8 {
+	let mut events = Vec::new();
+	let code = execute_with_client(client_id, |client| {
+		events.extend(client.receive_events());
+		Ok(())
+	});
+	notify_connection_quality_listeners();
+	events.iter().for_each(|event| callbacks.dispatch_event(event));
+	code
+}
+
+///
+/// Идет получение состояния комнаты после переподключения, относится к командам последнего вызова [`receive`]
+///
+#[no_mangle]
+pub extern "C" fn is_resynchronizing(client_id: ClientId, result: &mut bool) -> u8 {
+	execute_with_client(client_id, |client| {
+		*result = client.is_resynchronizing();
 		Ok(())
 	})
 }
 
+///
+/// Включить автоматическое переподключение, max_attempts = 0 - без ограничения количества попыток
+///
+#[no_mangle]
+pub extern "C" fn set_reconnect_policy(client_id: ClientId, initial_delay_in_ms: u64, max_delay_in_ms: u64, max_attempts: u32) -> u8 {
+	let policy = ReconnectPolicy {
+		initial_delay: Duration::from_millis(initial_delay_in_ms),
+		max_delay: Duration::from_millis(max_delay_in_ms),
+		max_attempts: if max_attempts == 0 { None } else { Some(max_attempts) },
+	};
+	execute_with_client(client_id, |client| Ok(client.set_reconnect_policy(Some(policy))?))
+}
+
+#[no_mangle]
+pub extern "C" fn disable_reconnect(client_id: ClientId) -> u8 {
+	execute_with_client(client_id, |client| Ok(client.set_reconnect_policy(None)?))
+}
+
+///
+/// Скопировать команды в массив, не более [`MAX_RECEIVE_COMMANDS`], остальные команды остаются в итераторе
+///
+#[allow(clippy::cast_possible_truncation)]
+pub fn copy_commands(commands: impl Iterator<Item = S2CCommand>, out_commands: &mut [S2CCommandFFI]) -> u16 {
+	let mut count = 0;
+	for (command, out) in commands.take(MAX_RECEIVE_COMMANDS).zip(out_commands.iter_mut()) {
+		*out = command.into();
+		count += 1;
+	}
+	count
+}
+
+pub fn dispatch_commands(commands: impl Iterator<Item = S2CCommand>, callbacks: &S2CCommandCallbacksFFI) {
+	for command in commands {
+		callbacks.dispatch(&command);
+	}
+}
+
 #[no_mangle]
 pub extern "C" fn get_server_time(client_id: ClientId, server_out_time: &mut u64) -> u8 {
 	execute_with_client(client_id, |client| {
-		*server_out_time = client.get_server_time().unwrap_or_default();
+		*server_out_time = client.server_time().unwrap_or_default();
 		Ok(())
 	})
 }
@@ -103,25 +190,117 @@ pub extern "C" fn set_drop_emulation(client_id: ClientId, drop_probability: f64,
 	execute_with_client(client_id, |client| Ok(client.set_drop_emulation(drop_probability, Duration::from_millis(drop_time_in_ms))?))
 }
 
+#[no_mangle]
+pub extern "C" fn set_bandwidth_emulation(client_id: ClientId, bytes_per_second: u64, queue_size_in_bytes: u64) -> u8 {
+	execute_with_client(client_id, |client| {
+		Ok(client.set_bandwidth_emulation(Bandwidth {
+			bytes_per_second,
+			queue_size_in_bytes,
+		})?)
+	})
+}
+
+#[no_mangle]
+pub extern "C" fn set_duplicate_emulation(client_id: ClientId, duplicate_probability: f64) -> u8 {
+	execute_with_client(client_id, |client| Ok(client.set_duplicate_emulation(duplicate_probability)?))
+}
+
+#[no_mangle]
+pub extern "C" fn set_reorder_emulation(client_id: ClientId, reorder_probability: f64, reorder_delay_in_ms: u64) -> u8 {
+	execute_with_client(client_id, |client| Ok(client.set_reorder_emulation(reorder_probability, Duration::from_millis(reorder_delay_in_ms))?))
+}
+
+#[no_mangle]
+pub extern "C" fn set_burst_loss_emulation(client_id: ClientId, good_to_bad_probability: f64, bad_to_good_probability: f64, good_loss_probability: f64, bad_loss_probability: f64) -> u8 {
+	execute_with_client(client_id, |client| {
+		Ok(client.set_burst_loss_emulation(BurstLoss {
+			good_to_bad_probability,
+			bad_to_good_probability,
+			good_loss_probability,
+			bad_loss_probability,
+		})?)
+	})
+}
+
+///
+/// preset - номер [`NetworkConditionsPreset`], для неизвестного номера возвращается ошибка
+///
+#[no_mangle]
+pub extern "C" fn set_emulation_preset(client_id: ClientId, preset: u8) -> u8 {
+	execute_with_client(client_id, |client| {
+		let preset = NetworkConditionsPreset::try_from(preset).map_err(|e| ClientApiError::WrongEmulationParameters(e.to_string()))?;
+		Ok(client.set_emulation_preset(preset)?)
+	})
+}
+
 #[no_mangle]
 pub extern "C" fn reset_emulation(client_id: ClientId) -> u8 {
 	execute_with_client(client_id, |client| Ok(client.reset_emulation()?))
 }
 
+#[no_mangle]
+pub extern "C" fn set_compression(client_id: ClientId, compression: bool) -> u8 {
+	execute_with_client(client_id, |client| Ok(client.set_compression(compression)?))
+}
+
 #[no_mangle]
 pub extern "C" fn get_statistics(client_id: ClientId, statistics: &mut Statistics) -> u8 {
 	execute_with_client(client_id, |client| {
-		let shared_statistics = &client.shared_statistics;
+		let shared_statistics = client.statistics();
 		statistics.last_frame_id = shared_statistics.current_frame_id.load(Ordering::Relaxed);
 		statistics.rtt_in_ms = shared_statistics.rtt_in_ms.load(Ordering::Relaxed);
 		statistics.recv_packet_count = shared_statistics.recv_packet_count.load(Ordering::Relaxed);
 		statistics.send_packet_count = shared_statistics.send_packet_count.load(Ordering::Relaxed);
 		statistics.recv_size = shared_statistics.recv_size.load(Ordering::Relaxed);
 		statistics.send_size = shared_statistics.send_size.load(Ordering::Relaxed);
+		statistics.send_payload_raw_size = shared_statistics.send_payload_raw_size.load(Ordering::Relaxed);
+		statistics.send_payload_size = shared_statistics.send_payload_size.load(Ordering::Relaxed);
+		statistics.recv_payload_raw_size = shared_statistics.recv_payload_raw_size.load(Ordering::Relaxed);
+		statistics.recv_payload_size = shared_statistics.recv_payload_size.load(Ordering::Relaxed);
+		statistics.rtt_min_in_ms = shared_statistics.rtt_min_in_ms.load(Ordering::Relaxed);
+		statistics.rtt_avg_in_ms = shared_statistics.rtt_avg_in_ms.load(Ordering::Relaxed);
+		statistics.rtt_max_in_ms = shared_statistics.rtt_max_in_ms.load(Ordering::Relaxed);
+		statistics.rtt_jitter_in_ms = shared_statistics.rtt_jitter_in_ms.load(Ordering::Relaxed);
+		statistics.packet_loss = f64::from_bits(shared_statistics.packet_loss.load(Ordering::Relaxed));
+		statistics.retransmit_count = shared_statistics.retransmit_count.load(Ordering::Relaxed);
+		statistics.unacked_reliable_frames = shared_statistics.unacked_reliable_frames.load(Ordering::Relaxed);
+		statistics.since_last_frame_in_ms = shared_statistics.since_last_frame_in_ms.load(Ordering::Relaxed);
+		statistics.connection_quality = client.connection_quality();
 		Ok(())
 	})
 }
 
+#[no_mangle]
+pub extern "C" fn get_connection_quality(client_id: ClientId, result: &mut ConnectionQuality) -> u8 {
+	execute_with_client(client_id, |client| {
+		*result = client.connection_quality();
+		Ok(())
+	})
+}
+
+///
+/// Обработчик изменения оценки качества соединения, вызывается из [`receive`], [`receive_with_callbacks`] и [`cycle`],
+/// None - удалить обработчик
+///
+/// - обработчик вызывается после освобождения реестра клиентов, из него можно вызывать другие FFI функции
+///
+#[no_mangle]
+pub extern "C" fn set_connection_quality_listener(client_id: ClientId, listener: Option<ConnectionQualityListenerFFI>) -> u8 {
+	execute_with_client(client_id, |client| {
+		client.set_connection_quality_listener(
+			listener.map(|listener| Box::new(move |quality| PENDING_CONNECTION_QUALITY.with(|pending| pending.borrow_mut().push((listener, client_id, quality)))) as ConnectionQualityListener),
+		);
+		Ok(())
+	})
+}
+
+fn notify_connection_quality_listeners() {
+	let pending = PENDING_CONNECTION_QUALITY.with(RefCell::take);
+	for (listener, client_id, quality) in pending {
+		listener(client_id, quality);
+	}
+}
+
 #[no_mangle]
 #[allow(clippy::cast_possible_truncation)]
 pub extern "C" fn get_last_error_msg(buffer: &mut BufferFFI) {
@@ -132,7 +311,7 @@ pub extern "C" fn get_last_error_msg(buffer: &mut BufferFFI) {
 	buffer.buffer[0..length].copy_from_slice(msg);
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 #[repr(C)]
 pub struct Statistics {
 	pub last_frame_id: u64,
@@ -141,10 +320,32 @@ pub struct Statistics {
 	pub send_packet_count: u64,
 	pub recv_size: u64,
 	pub send_size: u64,
+	pub send_payload_raw_size: u64,
+	pub send_payload_size: u64,
+	pub recv_payload_raw_size: u64,
+	pub recv_payload_size: u64,
+	pub rtt_min_in_ms: u64,
+	pub rtt_avg_in_ms: u64,
+	pub rtt_max_in_ms: u64,
+	pub rtt_jitter_in_ms: u64,
+	///
+	/// Доля потерянных входящих пакетов, от 0 до 1
+	///
+	pub packet_loss: f64,
+	pub retransmit_count: u64,
+	pub unacked_reliable_frames: u64,
+	pub since_last_frame_in_ms: u64,
+	pub connection_quality: ConnectionQuality,
 }
 
+///
+/// Создать клиента, адрес сервера в формате host:port, для подключения через WebSocket - ws://host:port
+///
+/// # Safety
+///
+/// `addr` должен указывать на C-строку, завершенную нулем
+///
 #[no_mangle]
-#[allow(clippy::missing_safety_doc)]
 #[allow(unsafe_op_in_unsafe_fn)]
 pub unsafe extern "C" fn create_client(
 	connection_id: u64,
@@ -156,11 +357,161 @@ pub unsafe extern "C" fn create_client(
 	out_client_id: &mut u16,
 ) -> u8 {
 	let server_address = CStr::from_ptr(addr).to_str().unwrap();
+	do_create_client(
+		connection_id,
+		server_address,
+		member_id,
+		room_id,
+		&private_key_from_buffer(private_key_buffer),
+		disconnect_time_in_sec,
+		ClientMode::Thread,
+		out_client_id,
+	)
+}
+
+///
+/// Создать клиента без сетевого потока, приложение должно вызывать [`cycle`] в каждом кадре
+///
+/// # Safety
+///
+/// `addr` должен указывать на C-строку, завершенную нулем
+///
+#[no_mangle]
+#[allow(unsafe_op_in_unsafe_fn)]
+pub unsafe extern "C" fn create_single_threaded_client(
+	connection_id: u64,
+	addr: *const c_char,
+	member_id: RoomMemberId,
+	room_id: RoomId,
+	private_key_buffer: &BufferFFI,
+	disconnect_time_in_sec: u64,
+	out_client_id: &mut u16,
+) -> u8 {
+	let server_address = CStr::from_ptr(addr).to_str().unwrap();
+	do_create_client(
+		connection_id,
+		server_address,
+		member_id,
+		room_id,
+		&private_key_from_buffer(private_key_buffer),
+		disconnect_time_in_sec,
+		ClientMode::SingleThreaded,
+		out_client_id,
+	)
+}
+
+///
+/// Создать клиента для подключения по токену, идентификаторы комнаты и пользователя берутся из токена
+///
+/// # Safety
+///
+/// `addr` должен указывать на C-строку, завершенную нулем
+///
+#[no_mangle]
+#[allow(unsafe_op_in_unsafe_fn)]
+pub unsafe extern "C" fn create_client_with_token(
+	connection_id: u64,
+	addr: *const c_char,
+	member_token_buffer: &BufferFFI,
+	private_key_buffer: &BufferFFI,
+	disconnect_time_in_sec: u64,
+	out_client_id: &mut u16,
+) -> u8 {
+	let server_address = CStr::from_ptr(addr).to_str().unwrap();
+	let member_token = match member_token_from_buffer(member_token_buffer) {
+		Ok(member_token) => member_token,
+		Err(e) => return e.store_error_and_get_code(),
+	};
+	do_create_client_with_token(
+		connection_id,
+		server_address,
+		member_token,
+		&private_key_from_buffer(private_key_buffer),
+		disconnect_time_in_sec,
+		ClientMode::Thread,
+		out_client_id,
+	)
+}
+
+///
+/// Создать клиента без сетевого потока для подключения по токену
+///
+/// # Safety
+///
+/// `addr` должен указывать на C-строку, завершенную нулем
+///
+#[no_mangle]
+#[allow(unsafe_op_in_unsafe_fn)]
+pub unsafe extern "C" fn create_single_threaded_client_with_token(
+	connection_id: u64,
+	addr: *const c_char,
+	member_token_buffer: &BufferFFI,
+	private_key_buffer: &BufferFFI,
+	disconnect_time_in_sec: u64,
+	out_client_id: &mut u16,
+) -> u8 {
+	let server_address = CStr::from_ptr(addr).to_str().unwrap();
+	let member_token = match member_token_from_buffer(member_token_buffer) {
+		Ok(member_token) => member_token,
+		Err(e) => return e.store_error_and_get_code(),
+	};
+	do_create_client_with_token(
+		connection_id,
+		server_address,
+		member_token,
+		&private_key_from_buffer(private_key_buffer),
+		disconnect_time_in_sec,
+		ClientMode::SingleThreaded,
+		out_client_id,
+	)
+}
+
+///
+/// Цикл клиента в однопоточном режиме, now_in_ms - монотонное время приложения в миллисекундах
+///
+/// - время приложения связывается с [`Instant`] при первом вызове для каждого клиента, далее используется разница времени
+/// - для клиента с сетевым потоком возвращается ошибка
+///
+#[no_mangle]
+pub extern "C" fn cycle(client_id: ClientId, now_in_ms: u64) -> u8 {
+	let code = execute_with_client(client_id, |client| Ok(client.cycle_with_app_time(now_in_ms)?));
+	notify_connection_quality_listeners();
+	code
+}
+
+fn private_key_from_buffer(private_key_buffer: &BufferFFI) -> MemberPrivateKey {
 	let mut private_key = [0; 32];
 	private_key.copy_from_slice(&private_key_buffer.buffer[0..32]);
-	do_create_client(connection_id, server_address, member_id, room_id, &private_key.as_slice().into(), disconnect_time_in_sec, out_client_id)
+	private_key.as_slice().into()
+}
+
+fn member_token_from_buffer(member_token_buffer: &BufferFFI) -> Result<&[u8], ClientError> {
+	let len = member_token_buffer.len as usize;
+	member_token_buffer
+		.buffer
+		.get(0..len)
+		.ok_or_else(|| ClientError::Client(ClientApiError::WrongMemberToken(format!("Wrong member token buffer size {len}"))))
+}
+
+pub fn do_create_client_with_token(
+	connection_id: u64,
+	server_address: &str,
+	member_token: &[u8],
+	private_key: &MemberPrivateKey,
+	disconnect_timeout_in_sec: u64,
+	mode: ClientMode,
+	out_client_id: &mut u16,
+) -> u8 {
+	execute(|api| {
+		api.create_client_with_token(connection_id, server_address, member_token, private_key.clone(), disconnect_timeout_in_sec, mode)
+			.map(|client_id| {
+				*out_client_id = client_id;
+				Ok(())
+			})?
+	})
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn do_create_client(
 	connection_id: u64,
 	server_address: &str,
@@ -168,10 +519,11 @@ pub fn do_create_client(
 	room_id: RoomId,
 	private_key: &MemberPrivateKey,
 	disconnect_timeout_in_sec: u64,
+	mode: ClientMode,
 	out_client_id: &mut u16,
 ) -> u8 {
 	execute(|api| {
-		api.create_client(connection_id, server_address, member_id, room_id, private_key.clone(), disconnect_timeout_in_sec)
+		api.create_client(connection_id, server_address, member_id, room_id, private_key.clone(), disconnect_timeout_in_sec, mode)
 			.map(|client_id| {
 				*out_client_id = client_id;
 				Ok(())
@@ -2,7 +2,8 @@ use std::fmt::{Debug, Formatter};
 
 use serde::{Deserialize, Serialize};
 
-use cheetah_common::commands::c2s::C2SCommand;
+use crate::clients::ClientEvent;
+use cheetah_common::commands::s2c::S2CCommand;
 use cheetah_common::commands::types::create::{CreateGameObject, GameObjectCreated};
 use cheetah_common::commands::types::field::DeleteField;
 use cheetah_common::commands::types::float::DoubleField;
@@ -14,9 +15,6 @@ use cheetah_common::room::buffer::{Buffer, MAX_BUFFER_SIZE};
 use cheetah_common::room::field::FieldId;
 use cheetah_common::room::object::GameObjectId;
 
-use crate::clients::registry::ClientId;
-use crate::ffi::execute_with_client;
-
 pub mod event;
 pub mod field;
 pub mod float_value;
@@ -26,10 +24,6 @@ pub mod object;
 pub mod room;
 pub mod structure;
 
-fn send_command(client_id: ClientId, command: C2SCommand) -> u8 {
-	execute_with_client(client_id, |client| Ok(client.send(command)?))
-}
-
 #[repr(C)]
 #[derive(Clone)]
 pub struct S2CCommandFFI {
@@ -37,6 +31,57 @@ pub struct S2CCommandFFI {
 	pub command: S2CommandUnionFFI,
 }
 
+impl From<S2CCommand> for S2CCommandFFI {
+	fn from(command: S2CCommand) -> Self {
+		match command {
+			S2CCommand::Create(command) => Self {
+				command_type: CommandTypeId::CreateGameObject,
+				command: S2CommandUnionFFI { create: command },
+			},
+			S2CCommand::Created(command) => Self {
+				command_type: CommandTypeId::CreatedGameObject,
+				command: S2CommandUnionFFI { created: command },
+			},
+			S2CCommand::SetLong(command) => Self {
+				command_type: CommandTypeId::SetLong,
+				command: S2CommandUnionFFI { set_long: command },
+			},
+			S2CCommand::SetDouble(command) => Self {
+				command_type: CommandTypeId::SetDouble,
+				command: S2CommandUnionFFI { set_double: command },
+			},
+			S2CCommand::SetStructure(command) => Self {
+				command_type: CommandTypeId::SetStructure,
+				command: S2CommandUnionFFI { buffer_field: command.into() },
+			},
+			S2CCommand::Event(command) => Self {
+				command_type: CommandTypeId::SendEvent,
+				command: S2CommandUnionFFI { buffer_field: command.into() },
+			},
+			S2CCommand::Delete(command) => Self {
+				command_type: CommandTypeId::DeleteObject,
+				command: S2CommandUnionFFI { game_object_id: command },
+			},
+			S2CCommand::DeleteField(command) => Self {
+				command_type: CommandTypeId::DeleteField,
+				command: S2CommandUnionFFI { delete_field: command },
+			},
+			S2CCommand::MemberConnected(command) => Self {
+				command_type: CommandTypeId::MemberConnected,
+				command: S2CommandUnionFFI { member_connect: command },
+			},
+			S2CCommand::MemberDisconnected(command) => Self {
+				command_type: CommandTypeId::MemberDisconnected,
+				command: S2CommandUnionFFI { member_disconnect: command },
+			},
+			S2CCommand::AddItem(command) => Self {
+				command_type: CommandTypeId::AddItem,
+				command: S2CommandUnionFFI { buffer_field: command.into() },
+			},
+		}
+	}
+}
+
 impl PartialEq for S2CCommandFFI {
 	fn eq(&self, other: &Self) -> bool {
 		unsafe {
@@ -65,6 +110,93 @@ impl Debug for S2CCommandFFI {
 	}
 }
 
+///
+/// Обработчики входящих команд по типам, альтернатива копированию команд в массив [`S2CCommandFFI`]
+///
+/// - обработчик вызывается для каждой команды, без ограничения на количество команд
+/// - null - команды данного типа пропускаются
+/// - данные команды (в том числе бинарные) доступны только во время вызова обработчика
+///
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+pub struct S2CCommandCallbacksFFI {
+	pub on_create: Option<extern "C" fn(&CreateGameObject)>,
+	pub on_created: Option<extern "C" fn(&GameObjectCreated)>,
+	pub on_set_long: Option<extern "C" fn(&LongField)>,
+	pub on_set_double: Option<extern "C" fn(&DoubleField)>,
+	pub on_set_structure: Option<extern "C" fn(&BinaryFieldRefFFI)>,
+	pub on_event: Option<extern "C" fn(&BinaryFieldRefFFI)>,
+	pub on_add_item: Option<extern "C" fn(&BinaryFieldRefFFI)>,
+	pub on_delete: Option<extern "C" fn(&GameObjectId)>,
+	pub on_delete_field: Option<extern "C" fn(&DeleteField)>,
+	pub on_member_connected: Option<extern "C" fn(&MemberConnected)>,
+	pub on_member_disconnected: Option<extern "C" fn(&MemberDisconnected)>,
+	///
+	/// Начало и окончание получения состояния комнаты после автоматического переподключения
+	///
+	pub on_resync_begin: Option<extern "C" fn()>,
+	pub on_resync_end: Option<extern "C" fn()>,
+}
+
+///
+/// Бинарное поле без копирования данных, value указывает на память команды
+///
+#[repr(C)]
+#[derive(Debug)]
+pub struct BinaryFieldRefFFI {
+	pub object_id: GameObjectId,
+	pub field_id: FieldId,
+	pub value: *const u8,
+	pub len: u16,
+}
+
+impl S2CCommandCallbacksFFI {
+	pub fn dispatch(&self, command: &S2CCommand) {
+		match command {
+			S2CCommand::Create(command) => Self::call(self.on_create, command),
+			S2CCommand::Created(command) => Self::call(self.on_created, command),
+			S2CCommand::SetLong(command) => Self::call(self.on_set_long, command),
+			S2CCommand::SetDouble(command) => Self::call(self.on_set_double, command),
+			S2CCommand::SetStructure(command) => Self::call(self.on_set_structure, &command.into()),
+			S2CCommand::Event(command) => Self::call(self.on_event, &command.into()),
+			S2CCommand::AddItem(command) => Self::call(self.on_add_item, &command.into()),
+			S2CCommand::Delete(command) => Self::call(self.on_delete, command),
+			S2CCommand::DeleteField(command) => Self::call(self.on_delete_field, command),
+			S2CCommand::MemberConnected(command) => Self::call(self.on_member_connected, command),
+			S2CCommand::MemberDisconnected(command) => Self::call(self.on_member_disconnected, command),
+		}
+	}
+
+	pub fn dispatch_event(&self, event: &ClientEvent) {
+		let callback = match event {
+			ClientEvent::Command(command) => return self.dispatch(command),
+			ClientEvent::ResyncBegin => self.on_resync_begin,
+			ClientEvent::ResyncEnd => self.on_resync_end,
+		};
+		if let Some(callback) = callback {
+			callback();
+		}
+	}
+
+	fn call<T>(callback: Option<extern "C" fn(&T)>, value: &T) {
+		if let Some(callback) = callback {
+			callback(value);
+		}
+	}
+}
+
+impl From<&BinaryField> for BinaryFieldRefFFI {
+	#[allow(clippy::cast_possible_truncation)]
+	fn from(value: &BinaryField) -> Self {
+		Self {
+			object_id: value.object_id,
+			field_id: value.field_id,
+			value: value.value.buffer.as_ptr(),
+			len: value.value.buffer.len() as u16,
+		}
+	}
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub union S2CommandUnionFFI {
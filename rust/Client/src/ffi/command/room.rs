@@ -1,7 +1,4 @@
-use cheetah_common::commands::c2s::C2SCommand;
-
 use crate::clients::registry::ClientId;
-use crate::ffi::command::send_command;
 use crate::ffi::execute_with_client;
 
 #[no_mangle]
@@ -11,5 +8,5 @@ pub extern "C" fn attach_to_room(client_id: ClientId) -> u8 {
 
 #[no_mangle]
 pub extern "C" fn detach_from_room(client_id: ClientId) -> u8 {
-	send_command(client_id, C2SCommand::DetachFromRoom)
+	execute_with_client(client_id, |client| Ok(client.detach_from_room()?))
 }
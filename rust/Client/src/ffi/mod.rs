@@ -1,32 +1,31 @@
-use std::io;
-use std::sync::mpsc::SendError;
 use std::sync::Mutex;
 
 use lazy_static::lazy_static;
 use thiserror::Error;
 
-use crate::clients::application_thread::ApplicationThreadClient;
+use crate::clients::client::{Client, ClientError as ClientApiError};
 use crate::clients::registry::{ClientId, Registry};
-use crate::clients::ClientRequest;
+use cheetah_common::room::field::{FieldId, FieldType};
+use cheetah_common::room::object::GameObjectId;
 
 pub mod channel;
 pub mod client;
 pub mod command;
 pub mod logs;
+pub mod room_state;
 
 #[derive(Error, Debug)]
-#[allow(clippy::large_enum_variant)]
 pub enum ClientError {
-	#[error("Create client error {0}")]
-	CreateClientError(#[from] io::Error),
 	#[error("Registry mutex error {0}")]
 	RegistryMutex(String),
 	#[error("Client not found {0}")]
 	ClientNotFound(ClientId),
-	#[error("Connection status mutex error {0}")]
-	ConnectionStatusMutexError(String),
-	#[error("Send task error {0}")]
-	SendTaskError(#[from] SendError<ClientRequest>),
+	#[error(transparent)]
+	Client(#[from] ClientApiError),
+	#[error("Object not found in room state {0:?}")]
+	RoomStateObjectNotFound(GameObjectId),
+	#[error("Field not found in room state {0:?} {1} {2}")]
+	RoomStateFieldNotFound(GameObjectId, FieldId, FieldType),
 }
 
 impl ClientError {
@@ -38,9 +37,16 @@ impl ClientError {
 		match self {
 			ClientError::RegistryMutex(_) => 1,
 			ClientError::ClientNotFound(_) => 2,
-			ClientError::ConnectionStatusMutexError { .. } => 3,
-			ClientError::SendTaskError { .. } => 4,
-			ClientError::CreateClientError(_) => 5,
+			ClientError::Client(error) => match error {
+				ClientApiError::ConnectionStatusMutex(_) => 3,
+				ClientApiError::NetworkThreadStopped => 4,
+				ClientApiError::Io(_) | ClientApiError::WrongMemberToken(_) => 5,
+				ClientApiError::RoomStateNotEnabled => 6,
+				ClientApiError::NotSingleThreaded => 9,
+				ClientApiError::WrongEmulationParameters(_) => 10,
+			},
+			ClientError::RoomStateObjectNotFound(_) => 7,
+			ClientError::RoomStateFieldNotFound(..) => 8,
 		}
 	}
 }
@@ -69,7 +75,7 @@ where
 
 pub fn execute_with_client<F, R>(client_id: ClientId, action: F) -> u8
 where
-	F: FnOnce(&mut ApplicationThreadClient) -> Result<R, ClientError>,
+	F: FnOnce(&mut Client) -> Result<R, ClientError>,
 {
 	execute(|registry| match registry.clients.get_mut(&client_id) {
 		None => Err(ClientError::ClientNotFound(client_id)),
@@ -0,0 +1,148 @@
+use std::slice;
+
+use cheetah_common::room::field::{FieldId, FieldType};
+use cheetah_common::room::object::GameObjectId;
+
+use crate::clients::registry::ClientId;
+use crate::clients::room_state::{GameObjectState, RoomState, RoomStateChange};
+use crate::ffi::command::BufferFFI;
+use crate::ffi::{execute_with_client, ClientError};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoomStateObjectFFI {
+	pub template: u16,
+	pub access_groups: u64,
+}
+
+#[no_mangle]
+pub extern "C" fn enable_room_state(client_id: ClientId) -> u8 {
+	execute_with_client(client_id, |client| {
+		client.enable_room_state();
+		Ok(())
+	})
+}
+
+#[no_mangle]
+pub extern "C" fn update_room_state(client_id: ClientId) -> u8 {
+	execute_with_client(client_id, |client| {
+		client.update_room_state();
+		Ok(())
+	})
+}
+
+///
+/// Получить идентификаторы загруженных объектов, не более capacity
+///
+/// # Safety
+///
+/// `out_objects` должен указывать на массив из `capacity` элементов
+///
+#[no_mangle]
+#[allow(unsafe_op_in_unsafe_fn)]
+pub unsafe extern "C" fn get_room_state_objects(client_id: ClientId, out_objects: *mut GameObjectId, capacity: u16, count: &mut u16) -> u8 {
+	*count = 0;
+	let out_objects = slice::from_raw_parts_mut(out_objects, capacity as usize);
+	execute_with_client(client_id, |client| {
+		for ((object_id, _), out) in client.room_state()?.get_created_objects().zip(out_objects.iter_mut()) {
+			*out = *object_id;
+			*count += 1;
+		}
+		Ok(())
+	})
+}
+
+#[no_mangle]
+pub extern "C" fn get_room_state_object(client_id: ClientId, object_id: &GameObjectId, result: &mut RoomStateObjectFFI) -> u8 {
+	execute_with_client(client_id, |client| {
+		let object = get_object(client.room_state()?, object_id)?;
+		result.template = object.template;
+		result.access_groups = object.access_groups.0;
+		Ok(())
+	})
+}
+
+#[no_mangle]
+pub extern "C" fn get_room_state_long(client_id: ClientId, object_id: &GameObjectId, field_id: FieldId, result: &mut i64) -> u8 {
+	execute_with_client(client_id, |client| {
+		let object = get_object(client.room_state()?, object_id)?;
+		*result = *object.longs.get(&field_id).ok_or(ClientError::RoomStateFieldNotFound(*object_id, field_id, FieldType::Long))?;
+		Ok(())
+	})
+}
+
+#[no_mangle]
+pub extern "C" fn get_room_state_double(client_id: ClientId, object_id: &GameObjectId, field_id: FieldId, result: &mut f64) -> u8 {
+	execute_with_client(client_id, |client| {
+		let object = get_object(client.room_state()?, object_id)?;
+		*result = *object.doubles.get(&field_id).ok_or(ClientError::RoomStateFieldNotFound(*object_id, field_id, FieldType::Double))?;
+		Ok(())
+	})
+}
+
+#[no_mangle]
+pub extern "C" fn get_room_state_structure(client_id: ClientId, object_id: &GameObjectId, field_id: FieldId, result: &mut BufferFFI) -> u8 {
+	execute_with_client(client_id, |client| {
+		let object = get_object(client.room_state()?, object_id)?;
+		let structure = object
+			.structures
+			.get(&field_id)
+			.ok_or(ClientError::RoomStateFieldNotFound(*object_id, field_id, FieldType::Structure))?;
+		*result = structure.buffer.as_slice().into();
+		Ok(())
+	})
+}
+
+#[no_mangle]
+pub extern "C" fn get_room_state_items_count(client_id: ClientId, object_id: &GameObjectId, field_id: FieldId, result: &mut u16) -> u8 {
+	execute_with_client(client_id, |client| {
+		let object = get_object(client.room_state()?, object_id)?;
+		*result = object.items.get(&field_id).map(|items| items.len() as u16).unwrap_or_default();
+		Ok(())
+	})
+}
+
+#[no_mangle]
+pub extern "C" fn get_room_state_item(client_id: ClientId, object_id: &GameObjectId, field_id: FieldId, index: u16, result: &mut BufferFFI) -> u8 {
+	execute_with_client(client_id, |client| {
+		let object = get_object(client.room_state()?, object_id)?;
+		let item = object
+			.items
+			.get(&field_id)
+			.and_then(|items| items.get(index as usize))
+			.ok_or(ClientError::RoomStateFieldNotFound(*object_id, field_id, FieldType::Items))?;
+		*result = item.buffer.as_slice().into();
+		Ok(())
+	})
+}
+
+///
+/// Забрать накопленные изменения состояния комнаты, не более capacity
+///
+/// # Safety
+///
+/// `out_changes` должен указывать на массив из `capacity` элементов
+///
+#[no_mangle]
+#[allow(unsafe_op_in_unsafe_fn)]
+pub unsafe extern "C" fn pop_room_state_changes(client_id: ClientId, out_changes: *mut RoomStateChange, capacity: u16, count: &mut u16) -> u8 {
+	*count = 0;
+	let out_changes = slice::from_raw_parts_mut(out_changes, capacity as usize);
+	execute_with_client(client_id, |client| {
+		let room_state = client.room_state()?;
+		for out in out_changes.iter_mut() {
+			match room_state.pop_change() {
+				None => break,
+				Some(change) => {
+					*out = change;
+					*count += 1;
+				}
+			}
+		}
+		Ok(())
+	})
+}
+
+fn get_object<'a>(room_state: &'a RoomState, object_id: &GameObjectId) -> Result<&'a GameObjectState, ClientError> {
+	room_state.get_object(object_id).ok_or(ClientError::RoomStateObjectNotFound(*object_id))
+}
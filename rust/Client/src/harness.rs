@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use cheetah_common::network::memory::MemoryNetwork;
+use cheetah_common::network::socket::Transport;
+use cheetah_common::room::access::AccessGroups;
+use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
+use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
+use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use cheetah_server::server::manager::{ManagementTask, ManagementTaskExecutionError, ManagementTaskResult};
+use cheetah_server::server::network::GameSocket;
+use cheetah_server::server::room::config::member::MemberCreateParams;
+use cheetah_server::server::room::config::room::RoomCreateParams;
+use cheetah_server::server::room::Room;
+use cheetah_server::server::Server;
+use thiserror::Error;
+
+use crate::clients::client::{Client, ClientError, ClientMode, ConnectOptions};
+
+pub type HarnessClientId = usize;
+
+///
+/// Виртуальные часы, время меняется только явным вызовом [`VirtualClock::advance`]
+///
+#[derive(Debug, Clone, Copy)]
+pub struct VirtualClock {
+	now: Instant,
+}
+
+impl Default for VirtualClock {
+	fn default() -> Self {
+		Self { now: Instant::now() }
+	}
+}
+
+impl VirtualClock {
+	#[must_use]
+	pub fn now(&self) -> Instant {
+		self.now
+	}
+
+	pub fn advance(&mut self, duration: Duration) {
+		self.now += duration;
+	}
+}
+
+///
+/// Окружение для быстрых детерминированных тестов: сервер и клиенты в одном потоке без реальной сети и ожиданий
+///
+/// - пакеты передаются через [`MemoryNetwork`], клиенты работают в однопоточном режиме
+/// - время задается [`VirtualClock`], каждый шаг выполняет цикл всех клиентов, затем цикл сервера
+/// - задержки и потери пакетов задаются эмулятором клиента и зависят только от виртуального времени
+///   и начального значения генератора случайных чисел ([`TestHarness::set_emulation_seed`])
+/// - клиенты принадлежат окружению и доступны по идентификатору через [`TestHarness::client`]
+///
+pub struct TestHarness {
+	clock: VirtualClock,
+	step: Duration,
+	disconnect_timeout: Duration,
+	network: MemoryNetwork,
+	server: Server,
+	server_address: SocketAddr,
+	clients: BTreeMap<HarnessClientId, Client>,
+	members: BTreeMap<HarnessClientId, MemberAndRoomId>,
+	next_client_id: HarnessClientId,
+	emulation_seed: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum TestHarnessError {
+	#[error("Io {0}")]
+	Io(#[from] io::Error),
+	#[error("Client {0}")]
+	Client(#[from] ClientError),
+	#[error("ManagementTask {0}")]
+	ManagementTask(#[from] ManagementTaskExecutionError),
+	#[error("Unexpected management task result {0:?}")]
+	UnexpectedResult(Box<ManagementTaskResult>),
+}
+
+impl TestHarness {
+	///
+	/// Шаг виртуального времени по-умолчанию
+	///
+	pub const DEFAULT_STEP: Duration = Duration::from_millis(10);
+	///
+	/// Начальное значение генератора случайных чисел эмуляторов по-умолчанию
+	///
+	pub const DEFAULT_EMULATION_SEED: u64 = 0;
+
+	pub fn new(disconnect_timeout: Duration) -> Result<Self, TestHarnessError> {
+		let network = MemoryNetwork::new();
+		let server = Server::new_caller_driven(GameSocket::Memory(network.bind()?), ProtocolConfiguration { disconnect_timeout })?;
+		let server_address = server.local_addr()?;
+		Ok(Self {
+			clock: Default::default(),
+			step: Self::DEFAULT_STEP,
+			disconnect_timeout,
+			network,
+			server,
+			server_address,
+			clients: Default::default(),
+			members: Default::default(),
+			next_client_id: 0,
+			emulation_seed: Self::DEFAULT_EMULATION_SEED,
+		})
+	}
+
+	#[must_use]
+	pub fn now(&self) -> Instant {
+		self.clock.now()
+	}
+
+	pub fn set_step(&mut self, step: Duration) {
+		self.step = step;
+	}
+
+	///
+	/// Начальное значение генератора случайных чисел эмуляторов, каждый клиент получает значение seed + идентификатор клиента
+	///
+	pub fn set_emulation_seed(&mut self, seed: u64) -> Result<(), TestHarnessError> {
+		self.emulation_seed = seed;
+		for (id, client) in &mut self.clients {
+			client.set_emulation_seed(Self::client_emulation_seed(seed, *id))?;
+		}
+		Ok(())
+	}
+
+	fn client_emulation_seed(seed: u64, id: HarnessClientId) -> u64 {
+		seed.wrapping_add(id as u64)
+	}
+
+	pub fn create_room(&mut self, template: RoomCreateParams) -> Result<RoomId, TestHarnessError> {
+		match self.execute_task(ManagementTask::CreateRoom(template))? {
+			ManagementTaskResult::CreateRoom(room_id) => Ok(room_id),
+			result => Err(TestHarnessError::UnexpectedResult(Box::new(result))),
+		}
+	}
+
+	pub fn create_member(&mut self, room_id: RoomId, template: MemberCreateParams) -> Result<RoomMemberId, TestHarnessError> {
+		match self.execute_task(ManagementTask::CreateMember(room_id, template))? {
+			ManagementTaskResult::CreateMember(member_id) => Ok(member_id),
+			result => Err(TestHarnessError::UnexpectedResult(Box::new(result))),
+		}
+	}
+
+	pub fn dump(&mut self, room_id: RoomId) -> Result<Option<Room>, TestHarnessError> {
+		match self.execute_task(ManagementTask::Dump(room_id))? {
+			ManagementTaskResult::Dump(room) => Ok(room),
+			result => Err(TestHarnessError::UnexpectedResult(Box::new(result))),
+		}
+	}
+
+	///
+	/// Выполнить задачу управления сервером в текущем виртуальном времени
+	///
+	pub fn execute_task(&mut self, task: ManagementTask) -> Result<ManagementTaskResult, ManagementTaskExecutionError> {
+		self.server.execute_task(task, self.clock.now())
+	}
+
+	///
+	/// Подключить клиента для ранее созданного пользователя
+	///
+	pub fn connect(&mut self, room_id: RoomId, member_id: RoomMemberId, private_key: MemberPrivateKey) -> Result<HarnessClientId, TestHarnessError> {
+		let mut client = Client::connect(ConnectOptions {
+			mode: ClientMode::SingleThreaded,
+			transport: Transport::Memory(self.network.clone()),
+			..ConnectOptions::new(self.server_address, member_id, room_id, private_key, self.disconnect_timeout)
+		})?;
+		let id = self.next_client_id;
+		client.set_emulation_seed(Self::client_emulation_seed(self.emulation_seed, id))?;
+		self.next_client_id += 1;
+		self.clients.insert(id, client);
+		self.members.insert(id, MemberAndRoomId { member_id, room_id });
+		Ok(id)
+	}
+
+	///
+	/// Создать пользователя и подключить для него клиента
+	///
+	pub fn create_client(&mut self, room_id: RoomId, groups: AccessGroups) -> Result<HarnessClientId, TestHarnessError> {
+		let template = MemberCreateParams::new_member(groups, Default::default());
+		let private_key = template.private_key.clone();
+		let member_id = self.create_member(room_id, template)?;
+		self.connect(room_id, member_id, private_key)
+	}
+
+	///
+	/// Клиент окружения, паникует если клиент не найден
+	///
+	pub fn client(&mut self, id: HarnessClientId) -> &mut Client {
+		self.clients.get_mut(&id).unwrap_or_else(|| panic!("client {id} not found"))
+	}
+
+	///
+	/// Пользователь клиента, паникует если клиент не найден
+	///
+	#[must_use]
+	pub fn member(&self, id: HarnessClientId) -> MemberAndRoomId {
+		*self.members.get(&id).unwrap_or_else(|| panic!("client {id} not found"))
+	}
+
+	///
+	/// Забрать клиента из окружения, его циклы больше не выполняются
+	///
+	pub fn remove_client(&mut self, id: HarnessClientId) -> Option<Client> {
+		self.members.remove(&id);
+		self.clients.remove(&id)
+	}
+
+	///
+	/// Один шаг виртуального времени
+	///
+	pub fn cycle(&mut self) {
+		self.clock.advance(self.step);
+		let now = self.clock.now();
+		for (id, client) in &mut self.clients {
+			if let Err(e) = client.cycle(now) {
+				tracing::error!("[harness] client {} cycle error {:?}", id, e);
+			}
+		}
+		self.server.cycle(now);
+	}
+
+	///
+	/// Выполнять шаги пока не пройдет заданное виртуальное время
+	///
+	pub fn advance(&mut self, duration: Duration) {
+		let deadline = self.clock.now() + duration;
+		while self.clock.now() < deadline {
+			self.cycle();
+		}
+	}
+
+	///
+	/// Выполнять шаги до выполнения условия, но не дольше заданного виртуального времени
+	///
+	pub fn run_until<F>(&mut self, timeout: Duration, mut condition: F) -> bool
+	where
+		F: FnMut(&mut Self) -> bool,
+	{
+		let deadline = self.clock.now() + timeout;
+		while self.clock.now() < deadline {
+			self.cycle();
+			if condition(self) {
+				return true;
+			}
+		}
+		false
+	}
+}
@@ -1,2 +1,4 @@
 pub mod clients;
 pub mod ffi;
+#[cfg(feature = "test-harness")]
+pub mod harness;
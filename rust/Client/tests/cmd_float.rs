@@ -1,7 +1,5 @@
-use cheetah_client::ffi;
-use cheetah_client::ffi::command::{S2CCommandFFI, S2CommandUnionFFI};
+use cheetah_common::commands::s2c::S2CCommand;
 use cheetah_common::commands::types::float::DoubleField;
-use cheetah_common::commands::CommandTypeId;
 
 use crate::helpers::helper::setup;
 
@@ -9,66 +7,31 @@ pub mod helpers;
 
 #[test]
 fn should_inc() {
-	let (helper, [client1, client2]) = setup(Default::default());
-	let object_id = helper.create_member_object(client1);
-	ffi::command::room::attach_to_room(client2);
-	helper.receive(client2);
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
 
-	ffi::command::float_value::inc_double_value(client1, &object_id, 1, 100.0);
-	ffi::command::float_value::inc_double_value(client1, &object_id, 1, 100.0);
+	client1.increment_double(object_id, 1, 100.0).unwrap();
+	client1.increment_double(object_id, 1, 100.0).unwrap();
 
-	let commands = helper.receive(client2);
-
-	assert_eq!(
-		commands[0],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetDouble,
-			command: S2CommandUnionFFI {
-				set_double: DoubleField { object_id, field_id: 1, value: 100.0 }
-			}
-		}
-	);
-
-	assert_eq!(
-		commands[1],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetDouble,
-			command: S2CommandUnionFFI {
-				set_double: DoubleField { object_id, field_id: 1, value: 200.0 }
-			}
-		}
-	);
+	let commands = helper.receive(&mut client2);
+	assert_eq!(commands[0], S2CCommand::SetDouble(DoubleField { object_id, field_id: 1, value: 100.0 }));
+	assert_eq!(commands[1], S2CCommand::SetDouble(DoubleField { object_id, field_id: 1, value: 200.0 }));
 }
 
 #[test]
 fn should_set() {
-	let (helper, [client1, client2]) = setup(Default::default());
-
-	let object_id = helper.create_member_object(client1);
-	ffi::command::room::attach_to_room(client2);
-	helper.receive(client2);
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
 
-	ffi::command::float_value::set_double_value(client1, &object_id, 1, 100.0);
-	ffi::command::float_value::set_double_value(client1, &object_id, 1, 200.0);
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
 
-	let commands = helper.receive(client2);
-	assert_eq!(
-		commands[0],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetDouble,
-			command: S2CommandUnionFFI {
-				set_double: DoubleField { object_id, field_id: 1, value: 100.0 }
-			}
-		}
-	);
+	client1.set_double(object_id, 1, 100.0).unwrap();
+	client1.set_double(object_id, 1, 200.0).unwrap();
 
-	assert_eq!(
-		commands[1],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetDouble,
-			command: S2CommandUnionFFI {
-				set_double: DoubleField { object_id, field_id: 1, value: 200.0 }
-			}
-		}
-	);
+	let commands = helper.receive(&mut client2);
+	assert_eq!(commands[0], S2CCommand::SetDouble(DoubleField { object_id, field_id: 1, value: 100.0 }));
+	assert_eq!(commands[1], S2CCommand::SetDouble(DoubleField { object_id, field_id: 1, value: 200.0 }));
 }
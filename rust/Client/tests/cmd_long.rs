@@ -1,7 +1,6 @@
-use cheetah_client::ffi;
-use cheetah_client::ffi::command::{S2CCommandFFI, S2CommandUnionFFI};
+use cheetah_common::commands::guarantees::ReliabilityGuarantees;
+use cheetah_common::commands::s2c::S2CCommand;
 use cheetah_common::commands::types::long::LongField;
-use cheetah_common::commands::CommandTypeId;
 
 use crate::helpers::helper::setup;
 
@@ -9,68 +8,49 @@ pub mod helpers;
 
 #[test]
 fn should_inc() {
-	let (helper, [client1, client2]) = setup(Default::default());
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
 
-	let object_id = helper.create_member_object(client1);
-	ffi::command::room::attach_to_room(client2);
-	helper.receive(client2);
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
 
-	ffi::command::long_value::inc_long_value(client1, &object_id, 1, 100);
-	ffi::command::long_value::inc_long_value(client1, &object_id, 1, 200);
+	client1.increment_long(object_id, 1, 100).unwrap();
+	client1.increment_long(object_id, 1, 200).unwrap();
 
-	let commands = helper.receive(client2);
-
-	assert_eq!(
-		commands[0],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetLong,
-			command: S2CommandUnionFFI {
-				set_long: LongField { object_id, field_id: 1, value: 100 }
-			}
-		}
-	);
-
-	assert_eq!(
-		commands[1],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetLong,
-			command: S2CommandUnionFFI {
-				set_long: LongField { object_id, field_id: 1, value: 300 }
-			}
-		}
-	);
+	let commands = helper.receive(&mut client2);
+	assert_eq!(commands[0], S2CCommand::SetLong(LongField { object_id, field_id: 1, value: 100 }));
+	assert_eq!(commands[1], S2CCommand::SetLong(LongField { object_id, field_id: 1, value: 300 }));
 }
 
 #[test]
 fn should_set() {
-	let (helper, [client1, client2]) = setup(Default::default());
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
+
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
 
-	let object_id = helper.create_member_object(client1);
-	ffi::command::room::attach_to_room(client2);
-	helper.receive(client2);
+	client1.set_long(object_id, 1, 100).unwrap();
+	client1.set_long(object_id, 1, 200).unwrap();
 
-	ffi::command::long_value::set_long_value(client1, &object_id, 1, 100);
-	ffi::command::long_value::set_long_value(client1, &object_id, 1, 200);
+	let commands = helper.receive(&mut client2);
+	assert_eq!(commands[0], S2CCommand::SetLong(LongField { object_id, field_id: 1, value: 100 }));
+	assert_eq!(commands[1], S2CCommand::SetLong(LongField { object_id, field_id: 1, value: 200 }));
+}
+
+#[test]
+fn should_set_in_latest_channel() {
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
 
-	let commands = helper.receive(client2);
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
 
-	assert_eq!(
-		commands[0],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetLong,
-			command: S2CommandUnionFFI {
-				set_long: LongField { object_id, field_id: 1, value: 100 }
-			}
-		}
-	);
+	client1.set_channel(ReliabilityGuarantees::ReliableLatest);
+	for value in 0..10 {
+		client1.set_long(object_id, 1, value).unwrap();
+	}
 
-	assert_eq!(
-		commands[1],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetLong,
-			command: S2CommandUnionFFI {
-				set_long: LongField { object_id, field_id: 1, value: 200 }
-			}
-		}
-	);
+	let commands = helper.receive(&mut client2);
+	assert_eq!(commands.last().unwrap(), &S2CCommand::SetLong(LongField { object_id, field_id: 1, value: 9 }));
 }
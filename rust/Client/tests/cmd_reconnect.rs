@@ -1,7 +1,12 @@
-use cheetah_client::ffi;
-use cheetah_client::ffi::command::{S2CCommandFFI, S2CommandUnionFFI};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cheetah_client::clients::client::Client;
+use cheetah_client::clients::reconnect::ReconnectPolicy;
+use cheetah_client::clients::ClientEvent;
+use cheetah_common::commands::s2c::S2CCommand;
 use cheetah_common::commands::types::create::CreateGameObject;
-use cheetah_common::commands::CommandTypeId;
+use cheetah_common::network::ConnectionStatus;
 
 use crate::helpers::helper::IntegrationTestHelper;
 use crate::helpers::server::IntegrationTestServerBuilder;
@@ -12,28 +17,73 @@ pub mod helpers;
 fn should_reconnect() {
 	let mut helper = IntegrationTestHelper::new(Default::default());
 	let (member_id, private_key) = helper.create_member();
-	let client = helper.create_client(member_id, &private_key, 0);
-	let object_id = helper.create_member_object(client);
-	ffi::command::room::attach_to_room(client);
+	let mut client = helper.create_client(member_id, &private_key, 0);
+	let object_id = helper.create_member_object(&mut client);
+	client.attach_to_room().unwrap();
 	helper.wait_udp();
 
-	let reconnected_client = helper.create_client(member_id, &private_key, 5);
-	ffi::command::room::attach_to_room(reconnected_client);
+	let mut reconnected_client = helper.create_client(member_id, &private_key, 5);
+	reconnected_client.attach_to_room().unwrap();
 	helper.wait_udp();
-	let commands = helper.receive(reconnected_client);
+	let commands = helper.receive(&mut reconnected_client);
 
-	assert!(commands.len() > 0);
+	assert!(!commands.is_empty());
 	assert_eq!(
 		commands[0],
-		S2CCommandFFI {
-			command_type: CommandTypeId::CreateGameObject,
-			command: S2CommandUnionFFI {
-				create: CreateGameObject {
-					object_id: object_id,
-					template: IntegrationTestServerBuilder::DEFAULT_TEMPLATE,
-					access_groups: IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP,
-				}
-			},
-		}
+		S2CCommand::Create(CreateGameObject {
+			object_id,
+			template: IntegrationTestServerBuilder::DEFAULT_TEMPLATE,
+			access_groups: IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP,
+		})
 	);
 }
+
+///
+/// Клиент обнаруживает разрыв раньше сервера, переподключается и заново получает состояние комнаты
+///
+#[test]
+fn should_reconnect_automatically() {
+	let mut helper = IntegrationTestHelper::new(Default::default());
+	let (member_id, private_key) = helper.create_member();
+	let mut client = helper.create_client_with_disconnect_timeout(member_id, &private_key, Duration::from_secs(1));
+	client
+		.set_reconnect_policy(Some(ReconnectPolicy {
+			initial_delay: Duration::from_millis(100),
+			..Default::default()
+		}))
+		.unwrap();
+	let object_id = helper.create_member_object(&mut client);
+	client.attach_to_room().unwrap();
+	helper.wait_udp();
+	helper.receive(&mut client);
+
+	client.set_drop_emulation(1.0, Duration::from_secs(60)).unwrap();
+	wait_status(&client, ConnectionStatus::Reconnecting);
+	// эмуляция сети сохраняется при переподключении
+	client.reset_emulation().unwrap();
+	wait_status(&client, ConnectionStatus::Connected);
+
+	let mut events = Vec::new();
+	let deadline = Instant::now() + Duration::from_secs(5);
+	while !events.contains(&ClientEvent::ResyncEnd) && Instant::now() < deadline {
+		events.extend(client.receive_events());
+		thread::sleep(Duration::from_millis(10));
+	}
+	let create = ClientEvent::Command(S2CCommand::Create(CreateGameObject {
+		object_id,
+		template: IntegrationTestServerBuilder::DEFAULT_TEMPLATE,
+		access_groups: IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP,
+	}));
+	let position = |event: &ClientEvent| events.iter().position(|current| current == event).unwrap();
+	assert!(position(&ClientEvent::ResyncBegin) < position(&create));
+	assert!(position(&create) < position(&ClientEvent::ResyncEnd));
+	assert!(!client.is_resynchronizing());
+}
+
+fn wait_status(client: &Client, status: ConnectionStatus) {
+	let deadline = Instant::now() + Duration::from_secs(5);
+	while client.connection_status().unwrap() != status {
+		assert!(Instant::now() < deadline, "status {status:?} not reached, current {:?}", client.connection_status().unwrap());
+		thread::sleep(Duration::from_millis(10));
+	}
+}
@@ -1,6 +1,7 @@
-use cheetah_client::ffi;
-use cheetah_client::ffi::command::{BinaryFieldFFI, BufferFFI, S2CCommandFFI, S2CommandUnionFFI};
-use cheetah_common::commands::CommandTypeId;
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::structure::BinaryField;
+use cheetah_common::room::buffer::{Buffer, MAX_BUFFER_SIZE};
+use cheetah_common::room::field::FieldId;
 
 use crate::helpers::helper::setup;
 
@@ -8,28 +9,86 @@ pub mod helpers;
 
 #[test]
 fn should_set() {
-	let (helper, [client1, client2]) = setup(Default::default());
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
 
-	let object_id = helper.create_member_object(client1);
-	ffi::command::room::attach_to_room(client2);
-	helper.receive(client2);
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
 
-	let structure_buffer = BufferFFI::from(vec![100].as_slice());
+	let structure = Buffer::from([100].as_slice());
 	let structure_field_id = 10;
-	ffi::command::structure::set_structure(client1, &object_id, structure_field_id, &structure_buffer);
+	client1.set_structure(object_id, structure_field_id, structure.clone()).unwrap();
 
-	let commands = helper.receive(client2);
+	let commands = helper.receive(&mut client2);
 	assert_eq!(
 		commands[0],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SetStructure,
-			command: S2CommandUnionFFI {
-				buffer_field: BinaryFieldFFI {
-					object_id,
-					field_id: structure_field_id,
-					value: structure_buffer.into(),
-				}
-			}
-		}
+		S2CCommand::SetStructure(BinaryField {
+			object_id,
+			field_id: structure_field_id,
+			value: structure,
+		})
 	);
 }
+
+///
+/// Структура максимального размера (случайные данные не сжимаются) не помещается в один UDP пакет
+///
+#[test]
+fn should_set_max_size_structure() {
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
+
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
+
+	let data: Vec<u8> = (0..MAX_BUFFER_SIZE).map(|_| rand::random()).collect();
+	let structure = Buffer::from(data.as_slice());
+	let structure_field_id = 10;
+	client1.set_structure(object_id, structure_field_id, structure.clone()).unwrap();
+
+	let commands = helper.receive(&mut client2);
+	assert_eq!(
+		commands[0],
+		S2CCommand::SetStructure(BinaryField {
+			object_id,
+			field_id: structure_field_id,
+			value: structure,
+		})
+	);
+}
+
+///
+/// Несколько структур максимального размера не помещаются в один пакет протокола
+///
+#[test]
+fn should_set_many_max_size_structures() {
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
+
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
+
+	let structures: Vec<Buffer> = (0..8)
+		.map(|_| {
+			let data: Vec<u8> = (0..MAX_BUFFER_SIZE).map(|_| rand::random()).collect();
+			Buffer::from(data.as_slice())
+		})
+		.collect();
+	for (field_id, structure) in structures.iter().enumerate() {
+		client1.set_structure(object_id, field_id as FieldId, structure.clone()).unwrap();
+	}
+
+	helper.wait_udp();
+	let commands = helper.receive(&mut client2);
+	assert_eq!(commands.len(), structures.len());
+	for (field_id, structure) in structures.into_iter().enumerate() {
+		assert_eq!(
+			commands[field_id],
+			S2CCommand::SetStructure(BinaryField {
+				object_id,
+				field_id: field_id as FieldId,
+				value: structure,
+			})
+		);
+	}
+}
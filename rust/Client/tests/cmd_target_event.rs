@@ -1,7 +1,6 @@
-use cheetah_client::ffi;
-use cheetah_client::ffi::command::{BinaryFieldFFI, BufferFFI, S2CCommandFFI, S2CommandUnionFFI};
-use cheetah_common::commands::CommandTypeId;
-use cheetah_common::room::object::GameObjectId;
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::structure::BinaryField;
+use cheetah_common::room::buffer::Buffer;
 
 use crate::helpers::helper::IntegrationTestHelper;
 use crate::helpers::server::IntegrationTestServerBuilder;
@@ -15,40 +14,33 @@ fn test() {
 	let (member1, member1_key) = helper.create_member();
 	let (member2, member2_key) = helper.create_member();
 	let (member3, member3_key) = helper.create_member();
-	let client1 = helper.create_client(member1, &member1_key, 0);
-	let client2 = helper.create_client(member2, &member2_key, 0);
-	let client3 = helper.create_client(member3, &member3_key, 0);
+	let mut client1 = helper.create_client(member1, &member1_key, 0);
+	let mut client2 = helper.create_client(member2, &member2_key, 0);
+	let mut client3 = helper.create_client(member3, &member3_key, 0);
 
-	ffi::command::room::attach_to_room(client2);
-	ffi::command::room::attach_to_room(client3);
+	client2.attach_to_room().unwrap();
+	client3.attach_to_room().unwrap();
 
-	let mut object_id = GameObjectId::default();
-	ffi::command::object::create_object(client1, 1, IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP.0, &mut object_id);
-	ffi::command::object::created_object(client1, &object_id, false, &Default::default());
-	helper.receive(client2);
-	helper.receive(client3);
+	let object_id = client1.create_object(1, IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP).unwrap();
+	client1.created_object(object_id, false, None).unwrap();
+	helper.receive(&mut client2);
+	helper.receive(&mut client3);
 
-	let mut event_buffer = BufferFFI { len: 1, ..Default::default() };
-	event_buffer.buffer[0] = 100;
+	let event = Buffer::from([100].as_slice());
 	let event_field_id = 10;
 
-	ffi::command::event::send_target_event(client1, member2, &object_id, event_field_id, &event_buffer);
+	client1.send_target_event(member2, object_id, event_field_id, event.clone()).unwrap();
 
-	let commands = helper.receive(client3);
+	let commands = helper.receive(&mut client3);
 	assert_eq!(commands, vec![]);
 
-	let commands = helper.receive(client2);
+	let commands = helper.receive(&mut client2);
 	assert_eq!(
 		commands[0],
-		S2CCommandFFI {
-			command_type: CommandTypeId::SendEvent,
-			command: S2CommandUnionFFI {
-				buffer_field: BinaryFieldFFI {
-					object_id,
-					field_id: event_field_id,
-					value: event_buffer,
-				}
-			}
-		}
+		S2CCommand::Event(BinaryField {
+			object_id,
+			field_id: event_field_id,
+			value: event,
+		})
 	);
 }
@@ -0,0 +1,55 @@
+use std::sync::atomic::Ordering;
+
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::structure::BinaryField;
+use cheetah_common::room::buffer::Buffer;
+
+use crate::helpers::helper::setup;
+use crate::helpers::server::IntegrationTestServerBuilder;
+
+pub mod helpers;
+
+#[test]
+fn should_compress_payload() {
+	let (helper, [mut client1, mut client2]) = setup(IntegrationTestServerBuilder::default().set_compression(true));
+	client1.set_compression(true).unwrap();
+
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
+
+	let structure = Buffer::from(vec![7; 4096].as_slice());
+	client1.set_structure(object_id, 1, structure.clone()).unwrap();
+
+	let commands = helper.receive(&mut client2);
+	assert_eq!(
+		commands[0],
+		S2CCommand::SetStructure(BinaryField {
+			object_id,
+			field_id: 1,
+			value: structure,
+		})
+	);
+
+	let statistics = client1.statistics();
+	assert!(statistics.send_payload_size.load(Ordering::Relaxed) * 10 < statistics.send_payload_raw_size.load(Ordering::Relaxed));
+	let statistics = client2.statistics();
+	assert!(statistics.recv_payload_size.load(Ordering::Relaxed) * 10 < statistics.recv_payload_raw_size.load(Ordering::Relaxed));
+}
+
+#[test]
+fn should_not_compress_by_default() {
+	let (helper, [mut client1, mut client2]) = setup(Default::default());
+
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	helper.receive(&mut client2);
+
+	client1.set_structure(object_id, 1, Buffer::from(vec![7; 4096].as_slice())).unwrap();
+	helper.receive(&mut client2);
+
+	let statistics = client1.statistics();
+	assert_eq!(statistics.send_payload_size.load(Ordering::Relaxed), statistics.send_payload_raw_size.load(Ordering::Relaxed));
+	let statistics = client2.statistics();
+	assert_eq!(statistics.recv_payload_size.load(Ordering::Relaxed), statistics.recv_payload_raw_size.load(Ordering::Relaxed));
+}
@@ -4,7 +4,6 @@ use std::time::Duration;
 
 use crate::helpers::helper::IntegrationTestHelper;
 use crate::helpers::server::IntegrationTestServerBuilder;
-use cheetah_client::ffi::execute_with_client;
 use cheetah_common::network::ConnectionStatus;
 
 pub mod helpers;
@@ -16,11 +15,17 @@ fn should_connect_to_server() {
 	let (member_id, private_key) = helper.create_member();
 	let client = helper.create_client(member_id, &private_key, 0);
 	helper.wait_udp();
-	execute_with_client(client, |api| {
-		let status = api.get_connection_status().unwrap();
-		assert_eq!(status, ConnectionStatus::Connected);
-		Ok(())
-	});
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connected);
+}
+
+#[test]
+fn should_connect_to_server_via_ipv6() {
+	let builder = IntegrationTestServerBuilder::default().use_ipv6();
+	let mut helper = IntegrationTestHelper::new(builder);
+	let (member_id, private_key) = helper.create_member();
+	let client = helper.create_client(member_id, &private_key, 0);
+	helper.wait_udp();
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connected);
 }
 
 #[test]
@@ -29,26 +34,13 @@ fn should_disconnect_when_server_closed() {
 
 	let mut helper = IntegrationTestHelper::new(builder);
 	let (member_id, private_key) = helper.create_member();
-	let client = helper.create_client(member_id, &private_key, 0);
+	let mut client = helper.create_client(member_id, &private_key, 0);
 	helper.wait_udp();
-
-	execute_with_client(client, |api| {
-		let status = api.get_connection_status().unwrap();
-		assert_eq!(status, ConnectionStatus::Connected);
-		Ok(())
-	});
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connected);
 
 	drop(helper);
 
-	execute_with_client(client, |api| {
-		api.set_protocol_time_offset(IntegrationTestServerBuilder::DISCONNECT_DURATION).unwrap();
-		Ok(())
-	});
+	client.set_protocol_time_offset(IntegrationTestServerBuilder::DISCONNECT_DURATION).unwrap();
 	thread::sleep(Duration::from_millis(100));
-
-	execute_with_client(client, |api| {
-		let status = api.get_connection_status().unwrap();
-		assert!(matches!(status, ConnectionStatus::Disconnected(DisconnectedReason::Timeout)));
-		Ok(())
-	});
+	assert!(matches!(client.connection_status().unwrap(), ConnectionStatus::Disconnected(DisconnectedReason::Timeout)));
 }
@@ -6,7 +6,6 @@ use std::time::Duration;
 
 use crate::helpers::helper::IntegrationTestHelper;
 use crate::helpers::server::IntegrationTestServerBuilder;
-use cheetah_client::ffi::execute_with_client;
 use cheetah_common::network::ConnectionStatus;
 
 pub mod helpers;
@@ -19,12 +18,7 @@ fn should_disconnect_on_delete_member() {
 	let (member_id, private_key) = helper.create_member();
 	let client = helper.create_client(member_id, &private_key, 0);
 	helper.wait_udp();
-
-	execute_with_client(client, |api| {
-		let status = api.get_connection_status().unwrap();
-		assert_eq!(status, ConnectionStatus::Connected);
-		Ok(())
-	});
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connected);
 
 	assert!(
 		helper.server.delete_member(MemberAndRoomId { room_id: helper.room_id, member_id }).is_ok(),
@@ -33,9 +27,8 @@ fn should_disconnect_on_delete_member() {
 
 	thread::sleep(Duration::from_millis(100));
 
-	execute_with_client(client, |api| {
-		let status = api.get_connection_status().unwrap();
-		assert!(matches!(status, ConnectionStatus::Disconnected(DisconnectedReason::Command(DisconnectByCommandReason::MemberDeleted))));
-		Ok(())
-	});
+	assert!(matches!(
+		client.connection_status().unwrap(),
+		ConnectionStatus::Disconnected(DisconnectedReason::Command(DisconnectByCommandReason::MemberDeleted))
+	));
 }
@@ -0,0 +1,49 @@
+use std::thread;
+use std::time::Duration;
+
+use crate::helpers::helper::IntegrationTestHelper;
+use crate::helpers::server::IntegrationTestServerBuilder;
+use cheetah_client::ffi::client::{get_connection_status, ConnectionStatusFFI};
+use cheetah_common::network::ConnectionStatus;
+
+pub mod helpers;
+
+#[test]
+fn should_disconnect_with_server_shutdown_reason_after_drain_timeout() {
+	let builder = IntegrationTestServerBuilder::default();
+
+	let mut helper = IntegrationTestHelper::new(builder);
+	let (member_id, private_key) = helper.create_member();
+	let client = helper.create_client(member_id, &private_key, 0);
+	helper.wait_udp();
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connected);
+
+	helper.server.drain(Duration::from_millis(200)).unwrap();
+	assert!(helper.server.create_room(Default::default()).is_err(), "want rejected create_room while draining");
+
+	thread::sleep(Duration::from_millis(100));
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connected);
+
+	thread::sleep(Duration::from_millis(300));
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::DisconnectedByServerShutdown);
+}
+
+#[test]
+fn should_disconnect_with_server_shutdown_reason_on_shutdown() {
+	let builder = IntegrationTestServerBuilder::default();
+
+	let mut helper = IntegrationTestHelper::new(builder);
+	let (member_id, private_key) = helper.create_member();
+	let client = helper.create_client(member_id, &private_key, 0);
+	let (ffi_member_id, ffi_private_key) = helper.create_member();
+	let ffi_client = helper.create_ffi_client(ffi_member_id, &ffi_private_key);
+	helper.wait_udp();
+
+	helper.server.shutdown();
+	thread::sleep(Duration::from_millis(100));
+
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::DisconnectedByServerShutdown);
+	let mut status = ConnectionStatusFFI::Connecting;
+	get_connection_status(ffi_client, &mut status);
+	assert!(matches!(status, ConnectionStatusFFI::DisconnectedByServerShutdown));
+}
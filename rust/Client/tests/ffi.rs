@@ -0,0 +1,202 @@
+use cheetah_client::clients::client::ClientMode;
+use cheetah_client::clients::registry::ClientId;
+use cheetah_client::ffi;
+use cheetah_client::ffi::channel::Channel;
+use cheetah_client::ffi::client::{ConnectionStatusFFI, Statistics};
+use cheetah_client::ffi::command::{BinaryFieldFFI, BinaryFieldRefFFI, BufferFFI, S2CCommandCallbacksFFI, S2CCommandFFI, S2CommandUnionFFI};
+use cheetah_common::commands::types::long::LongField;
+use cheetah_common::commands::types::member::MemberDisconnected;
+use cheetah_common::commands::CommandTypeId;
+use cheetah_common::network::quality::ConnectionQuality;
+use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
+use std::ffi::CString;
+use std::slice;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::Mutex;
+
+use crate::helpers::helper::setup_ffi;
+
+pub mod helpers;
+
+///
+/// FFI функции построены на [`cheetah_client::clients::client::Client`], проверяем преобразование команд и статусов
+///
+#[test]
+fn should_send_and_receive_commands_via_ffi() {
+	let (helper, [client1, client2]) = setup_ffi(Default::default());
+	helper.wait_udp();
+	let mut status = ConnectionStatusFFI::Connecting;
+	assert_eq!(ffi::client::get_connection_status(client1, &mut status), 0);
+	assert!(matches!(status, ConnectionStatusFFI::Connected));
+
+	let object_id = helper.create_ffi_member_object(client1);
+	ffi::command::room::attach_to_room(client2);
+	ffi::command::room::attach_to_room(client1);
+	helper.ffi_receive(client2);
+
+	ffi::channel::set_channel(client1, Channel::ReliableOrdered, 1);
+	ffi::command::long_value::set_long_value(client1, &object_id, 1, 100);
+	let event = BufferFFI::from([1, 2, 3].as_slice());
+	ffi::command::event::send_event(client1, &object_id, 2, &event);
+
+	let commands = helper.ffi_receive(client2);
+	assert_eq!(
+		commands,
+		vec![
+			S2CCommandFFI {
+				command_type: CommandTypeId::SetLong,
+				command: S2CommandUnionFFI {
+					set_long: LongField { object_id, field_id: 1, value: 100 }
+				}
+			},
+			S2CCommandFFI {
+				command_type: CommandTypeId::SendEvent,
+				command: S2CommandUnionFFI {
+					buffer_field: BinaryFieldFFI { object_id, field_id: 2, value: event }
+				}
+			}
+		]
+	);
+
+	let mut statistics = Statistics::default();
+	ffi::client::get_statistics(client2, &mut statistics);
+	assert!(statistics.recv_packet_count > 0);
+
+	helper.ffi_receive(client1);
+	assert_eq!(ffi::client::destroy_client(client2), 0);
+	let commands = helper.ffi_receive(client1);
+	assert_eq!(
+		commands[0],
+		S2CCommandFFI {
+			command_type: CommandTypeId::MemberDisconnected,
+			command: S2CommandUnionFFI {
+				member_disconnect: MemberDisconnected { member_id: 2 }
+			}
+		}
+	);
+}
+
+#[test]
+fn should_fail_create_client_with_wrong_token() {
+	let mut client = 0;
+	let code = ffi::client::do_create_client_with_token(0, "127.0.0.1:5555", &[1, 2, 3], &MemberPrivateKey::new_random(), 10, ClientMode::Thread, &mut client);
+	assert_eq!(code, 5);
+}
+
+#[test]
+fn should_fail_create_client_with_wrong_token_buffer_size() {
+	let mut client = 0;
+	let member_token = BufferFFI { len: u16::MAX, ..Default::default() };
+	let address = CString::new("127.0.0.1:5555").unwrap();
+	let private_key = BufferFFI::from(vec![0; 32].as_slice());
+	let code = unsafe { ffi::client::create_client_with_token(0, address.as_ptr(), &member_token, &private_key, 10, &mut client) };
+	assert_eq!(code, 5);
+}
+
+#[test]
+fn should_fail_set_wrong_emulation_parameters() {
+	let (_helper, [client, _]) = setup_ffi(Default::default());
+	assert_eq!(ffi::client::set_emulation_preset(client, 3), 0);
+	assert_eq!(ffi::client::set_emulation_preset(client, 200), 10);
+	assert_eq!(ffi::client::set_duplicate_emulation(client, 1.5), 10);
+	assert_eq!(ffi::client::set_reorder_emulation(client, -0.5, 10), 10);
+	assert_eq!(ffi::client::set_burst_loss_emulation(client, 0.1, 0.1, f64::NAN, 0.1), 10);
+	assert_eq!(ffi::client::set_burst_loss_emulation(client, 0.1, 0.1, 0.0, 1.0), 0);
+}
+
+static RECEIVED_LONGS: Mutex<Vec<LongField>> = Mutex::new(Vec::new());
+static RECEIVED_EVENTS: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+
+#[test]
+fn should_receive_commands_via_callbacks() {
+	let (helper, [client1, client2]) = setup_ffi(Default::default());
+	let object_id = helper.create_ffi_member_object(client1);
+	ffi::command::room::attach_to_room(client2);
+	helper.ffi_receive(client2);
+
+	ffi::channel::set_channel(client1, Channel::ReliableOrdered, 1);
+	// больше чем помещается в массив для receive
+	for value in 0..1500 {
+		ffi::command::long_value::set_long_value(client1, &object_id, 1, value);
+	}
+	ffi::command::event::send_event(client1, &object_id, 2, &BufferFFI::from([1, 2, 3].as_slice()));
+	helper.wait_udp();
+	helper.wait_udp();
+
+	let callbacks = S2CCommandCallbacksFFI {
+		on_set_long: Some(on_set_long),
+		on_event: Some(on_event),
+		..Default::default()
+	};
+	assert_eq!(ffi::client::receive_with_callbacks(client2, &callbacks), 0);
+
+	let longs = RECEIVED_LONGS.lock().unwrap();
+	assert_eq!(longs.len(), 1500);
+	assert_eq!(longs[1499], LongField { object_id, field_id: 1, value: 1499 });
+	assert_eq!(*RECEIVED_EVENTS.lock().unwrap(), vec![vec![1, 2, 3]]);
+}
+
+static CALLBACK_CLIENT: AtomicU16 = AtomicU16::new(0);
+static CALLBACK_STATUS_CODES: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+///
+/// Обработчики вызываются без блокировки реестра клиентов, поэтому из них можно вызывать FFI функции
+///
+#[test]
+fn should_call_ffi_from_callbacks() {
+	let (helper, [client1, client2]) = setup_ffi(Default::default());
+	let object_id = helper.create_ffi_member_object(client1);
+	ffi::command::room::attach_to_room(client2);
+	helper.ffi_receive(client2);
+
+	ffi::command::event::send_event(client1, &object_id, 1, &BufferFFI::from([1].as_slice()));
+	helper.wait_udp();
+
+	CALLBACK_CLIENT.store(client2, Ordering::Relaxed);
+	let callbacks = S2CCommandCallbacksFFI {
+		on_event: Some(on_event_get_status),
+		..Default::default()
+	};
+	assert_eq!(ffi::client::receive_with_callbacks(client2, &callbacks), 0);
+	assert_eq!(*CALLBACK_STATUS_CODES.lock().unwrap(), vec![0]);
+}
+
+static QUALITY_STATUS_CODES: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+///
+/// Обработчик качества соединения вызывается без блокировки реестра клиентов
+///
+#[test]
+fn should_call_ffi_from_connection_quality_listener() {
+	let (helper, [client1, _]) = setup_ffi(Default::default());
+	assert_eq!(ffi::client::set_connection_quality_listener(client1, Some(on_connection_quality_get_status)), 0);
+	let mut attempts = 0;
+	while QUALITY_STATUS_CODES.lock().unwrap().is_empty() && attempts < 50 {
+		helper.ffi_receive(client1);
+		attempts += 1;
+	}
+	let codes = QUALITY_STATUS_CODES.lock().unwrap();
+	assert!(!codes.is_empty());
+	assert!(codes.iter().all(|code| *code == 0));
+}
+
+extern "C" fn on_connection_quality_get_status(client_id: ClientId, _: ConnectionQuality) {
+	let mut status = ConnectionStatusFFI::Connecting;
+	let code = ffi::client::get_connection_status(client_id, &mut status);
+	QUALITY_STATUS_CODES.lock().unwrap().push(code);
+}
+
+extern "C" fn on_event_get_status(_: &BinaryFieldRefFFI) {
+	let mut status = ConnectionStatusFFI::Connecting;
+	let code = ffi::client::get_connection_status(CALLBACK_CLIENT.load(Ordering::Relaxed), &mut status);
+	CALLBACK_STATUS_CODES.lock().unwrap().push(code);
+}
+
+extern "C" fn on_set_long(command: &LongField) {
+	RECEIVED_LONGS.lock().unwrap().push(*command);
+}
+
+extern "C" fn on_event(command: &BinaryFieldRefFFI) {
+	let value = unsafe { slice::from_raw_parts(command.value, command.len as usize) };
+	RECEIVED_EVENTS.lock().unwrap().push(value.to_vec());
+}
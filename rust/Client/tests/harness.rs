@@ -0,0 +1,231 @@
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use cheetah_client::clients::reconnect::ReconnectPolicy;
+use cheetah_client::harness::{HarnessClientId, TestHarness};
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::long::LongField;
+use cheetah_common::network::emulator::NetworkConditionsPreset;
+use cheetah_common::network::quality::ConnectionQuality;
+use cheetah_common::network::ConnectionStatus;
+use cheetah_common::room::access::AccessGroups;
+use cheetah_game_realtime_protocol::frame::disconnected_reason::DisconnectedReason;
+use cheetah_game_realtime_protocol::RoomId;
+use cheetah_server::server::manager::ManagementTask;
+use cheetah_server::server::network::MemberNetworkEmulation;
+
+const ACCESS_GROUPS: AccessGroups = AccessGroups(0b111);
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+///
+/// Обмен командами между клиентами без реальной сети, время виртуальное
+///
+#[test]
+fn should_exchange_commands() {
+	let (mut harness, _, client1, client2) = setup();
+	let object_id = harness.client(client1).create_object(1, ACCESS_GROUPS).unwrap();
+	harness.client(client1).created_object(object_id, false, None).unwrap();
+	harness.client(client1).set_long(object_id, 1, 100).unwrap();
+
+	let expected = S2CCommand::SetLong(LongField { object_id, field_id: 1, value: 100 });
+	let mut received = Vec::new();
+	assert!(harness.run_until(Duration::from_secs(1), |harness| {
+		received.extend(harness.client(client2).receive());
+		received.contains(&expected)
+	}));
+}
+
+///
+/// Сервер отключает пользователя по таймауту без реального ожидания
+///
+#[test]
+fn should_disconnect_member_by_timeout() {
+	let (mut harness, room_id, client1, _) = setup();
+	harness.remove_client(client1).unwrap();
+
+	harness.advance(DISCONNECT_TIMEOUT / 2);
+	assert_eq!(count_online_members(&mut harness, room_id), 2);
+	harness.advance(DISCONNECT_TIMEOUT);
+	assert_eq!(count_online_members(&mut harness, room_id), 1);
+}
+
+///
+/// Эмуляция задержки зависит только от виртуального времени, поэтому результат повторяется от запуска к запуску
+///
+#[test]
+fn should_emulate_rtt_deterministically() {
+	let first = measure_delivery_time_with_rtt(Duration::from_millis(200));
+	let second = measure_delivery_time_with_rtt(Duration::from_millis(200));
+	assert_eq!(first, second);
+	assert!(first >= Duration::from_millis(100), "{first:?}");
+	assert!(measure_delivery_time_with_rtt(Duration::ZERO) < first);
+}
+
+///
+/// Случайные параметры эмуляции повторяются при одинаковом начальном значении генератора случайных чисел
+///
+#[test]
+fn should_repeat_random_emulation_with_same_seed() {
+	let measure = || {
+		measure_delivery_time(|harness, client1, _| {
+			harness.client(client1).set_rtt_emulation(Duration::from_millis(200), 0.8).unwrap();
+			harness.client(client1).set_drop_emulation(0.3, Duration::ZERO).unwrap();
+		})
+	};
+	assert_eq!(measure(), measure());
+}
+
+///
+/// Надежные команды доставляются один раз и в итоговом состоянии, несмотря на дублирование, перестановку и потерю пакетов
+///
+#[test]
+fn should_deliver_commands_over_emulated_3g() {
+	let (mut harness, _, client1, client2) = setup();
+	// потери и перестановки пакетов повторяются от запуска к запуску
+	harness.set_emulation_seed(3).unwrap();
+	harness.client(client1).set_emulation_preset(NetworkConditionsPreset::ThreeG).unwrap();
+	harness.client(client2).set_emulation_preset(NetworkConditionsPreset::ThreeG).unwrap();
+	harness.client(client1).set_duplicate_emulation(0.2).unwrap();
+	harness.client(client1).set_reorder_emulation(0.2, Duration::from_millis(100)).unwrap();
+	let object_id = harness.client(client1).create_object(1, ACCESS_GROUPS).unwrap();
+	harness.client(client1).created_object(object_id, false, None).unwrap();
+	for i in 0..10_u8 {
+		harness.client(client1).send_event(object_id, 1, [i].as_slice().into()).unwrap();
+	}
+
+	let mut events = Vec::new();
+	assert!(harness.run_until(Duration::from_secs(30), |harness| {
+		events.extend(harness.client(client2).receive().filter(|command| matches!(command, S2CCommand::Event(_))));
+		events.len() >= 10
+	}));
+	harness.advance(Duration::from_secs(2));
+	events.extend(harness.client(client2).receive().filter(|command| matches!(command, S2CCommand::Event(_))));
+	assert_eq!(events.len(), 10);
+}
+
+///
+/// Оценка качества соединения ухудшается при задержках и потерях, обработчик получает изменения оценки
+///
+#[test]
+fn should_rate_connection_quality() {
+	let (mut harness, _, client1, _) = setup();
+	let changes = Arc::new(Mutex::new(Vec::new()));
+	let listener_changes = Arc::clone(&changes);
+	harness
+		.client(client1)
+		.set_connection_quality_listener(Some(Box::new(move |quality| listener_changes.lock().unwrap().push(quality))));
+	assert!(harness.run_until(Duration::from_secs(20), |harness| harness.client(client1).connection_quality() == ConnectionQuality::Excellent));
+	harness.client(client1).receive().for_each(drop);
+
+	harness.client(client1).set_rtt_emulation(Duration::from_millis(300), 0.0).unwrap();
+	harness.client(client1).set_drop_emulation(0.3, Duration::ZERO).unwrap();
+	let object_id = harness.client(client1).create_object(1, ACCESS_GROUPS).unwrap();
+	for value in 0..20 {
+		harness.client(client1).set_long(object_id, 1, value).unwrap();
+		harness.advance(Duration::from_millis(100));
+	}
+	assert!(harness.run_until(Duration::from_secs(20), |harness| harness.client(client1).connection_quality() == ConnectionQuality::Poor));
+	harness.client(client1).receive().for_each(drop);
+
+	let statistics = harness.client(client1).statistics();
+	assert!(statistics.rtt_max_in_ms.load(Ordering::Relaxed) >= 300);
+	assert!(statistics.rtt_min_in_ms.load(Ordering::Relaxed) <= statistics.rtt_avg_in_ms.load(Ordering::Relaxed));
+	assert!(statistics.retransmit_count.load(Ordering::Relaxed) > 0);
+	assert!(f64::from_bits(statistics.packet_loss.load(Ordering::Relaxed)) > 0.0);
+	let changes = changes.lock().unwrap();
+	assert_eq!(changes.first(), Some(&ConnectionQuality::Excellent));
+	assert_eq!(changes.last(), Some(&ConnectionQuality::Poor));
+}
+
+///
+/// Эмуляция сети сохраняется при переподключении, после исчерпания попыток клиент остается отключенным
+///
+#[test]
+fn should_stop_reconnect_after_max_attempts() {
+	let (mut harness, _, client1, _) = setup();
+	let policy = ReconnectPolicy {
+		initial_delay: Duration::from_millis(100),
+		max_delay: Duration::from_millis(100),
+		max_attempts: Some(2),
+	};
+	harness.client(client1).set_reconnect_policy(Some(policy)).unwrap();
+	harness.client(client1).set_drop_emulation(1.0, Duration::from_secs(600)).unwrap();
+
+	let mut statuses = Vec::new();
+	harness.run_until(DISCONNECT_TIMEOUT * 6, |harness| {
+		let status = harness.client(client1).connection_status().unwrap();
+		if statuses.last() != Some(&status) {
+			statuses.push(status);
+		}
+		false
+	});
+	assert_eq!(
+		statuses,
+		vec![ConnectionStatus::Connected, ConnectionStatus::Reconnecting, ConnectionStatus::Disconnected(DisconnectedReason::Timeout)]
+	);
+}
+
+fn measure_delivery_time_with_rtt(rtt: Duration) -> Duration {
+	measure_delivery_time(|harness, client1, _| harness.client(client1).set_rtt_emulation(rtt, 0.0).unwrap())
+}
+
+///
+/// Эмуляция сети пользователя на сервере задерживает доставку без настройки клиента
+///
+#[test]
+fn should_emulate_member_network_on_server() {
+	let without_emulation = measure_delivery_time(|_, _, _| {});
+	let with_emulation = measure_delivery_time(|harness, _, client2| {
+		let emulation = MemberNetworkEmulation {
+			rtt: Duration::from_millis(400),
+			..Default::default()
+		};
+		harness.execute_task(ManagementTask::SetMemberNetworkEmulation(harness.member(client2), Some(emulation))).unwrap();
+	});
+	assert!(with_emulation >= without_emulation + Duration::from_millis(200), "{with_emulation:?} {without_emulation:?}");
+}
+
+///
+/// Время доставки команды от первого клиента второму
+///
+fn measure_delivery_time<F>(configure: F) -> Duration
+where
+	F: FnOnce(&mut TestHarness, HarnessClientId, HarnessClientId),
+{
+	let (mut harness, _, client1, client2) = setup();
+	configure(&mut harness, client1, client2);
+	let object_id = harness.client(client1).create_object(1, ACCESS_GROUPS).unwrap();
+	harness.client(client1).created_object(object_id, false, None).unwrap();
+	harness.client(client1).set_long(object_id, 1, 100).unwrap();
+
+	let start = harness.now();
+	let expected = S2CCommand::SetLong(LongField { object_id, field_id: 1, value: 100 });
+	let mut received = Vec::new();
+	assert!(harness.run_until(Duration::from_secs(5), |harness| {
+		received.extend(harness.client(client2).receive());
+		received.contains(&expected)
+	}));
+	harness.now() - start
+}
+
+///
+/// Комната с двумя подключенными клиентами, второй клиент загрузил объекты комнаты
+///
+fn setup() -> (TestHarness, RoomId, HarnessClientId, HarnessClientId) {
+	let mut harness = TestHarness::new(DISCONNECT_TIMEOUT).unwrap();
+	let room_id = harness.create_room(Default::default()).unwrap();
+	let client1 = harness.create_client(room_id, ACCESS_GROUPS).unwrap();
+	let client2 = harness.create_client(room_id, ACCESS_GROUPS).unwrap();
+	assert!(harness.run_until(Duration::from_secs(1), |harness| {
+		[client1, client2].iter().all(|id| harness.client(*id).connection_status().unwrap() == ConnectionStatus::Connected)
+	}));
+	harness.client(client2).attach_to_room().unwrap();
+	harness.advance(Duration::from_millis(100));
+	(harness, room_id, client1, client2)
+}
+
+fn count_online_members(harness: &mut TestHarness, room_id: RoomId) -> usize {
+	let room = harness.dump(room_id).unwrap().unwrap();
+	room.members.values().filter(|member| member.status.is_online()).count()
+}
@@ -4,11 +4,14 @@ use std::net::SocketAddr;
 use std::thread;
 use std::time::Duration;
 
+use cheetah_client::clients::client::{Client, ClientMode, ConnectOptions};
 use cheetah_client::clients::registry::ClientId;
 use cheetah_client::ffi;
-use cheetah_client::ffi::client::do_create_client;
+use cheetah_client::ffi::client::{do_create_client, MAX_RECEIVE_COMMANDS};
 use cheetah_client::ffi::command::{S2CCommandFFI, S2CommandUnionFFI};
+use cheetah_common::commands::s2c::S2CCommand;
 use cheetah_common::commands::CommandTypeId;
+use cheetah_common::network::socket::Transport;
 use cheetah_common::room::object::GameObjectId;
 use cheetah_game_realtime_protocol::frame::ConnectionId;
 use cheetah_server::server::manager::ServerManager;
@@ -18,53 +21,87 @@ use crate::helpers::server::IntegrationTestServerBuilder;
 
 pub struct IntegrationTestHelper {
 	socket_addr: SocketAddr,
+	websocket_addr: Option<SocketAddr>,
 	pub room_id: RoomId,
 	pub server: ServerManager,
 }
 
 impl IntegrationTestHelper {
-	pub fn receive(&self, client: ClientId) -> Vec<S2CCommandFFI> {
-		let mut commands = vec![
-			S2CCommandFFI {
-				command_type: CommandTypeId::CreateGameObject,
-				command: S2CommandUnionFFI { empty: () },
-			};
-			1024
-		];
-		self.wait_udp();
-
-		let mut count = 0;
-		ffi::client::receive(client, commands.as_mut_ptr(), &mut count);
-		commands[0..count as usize].to_vec()
-	}
-}
+	pub const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 
-impl IntegrationTestHelper {
 	#[must_use]
 	pub fn new(builder: IntegrationTestServerBuilder) -> Self {
-		let (socket_addr, server, room_id) = builder.build();
-		Self { socket_addr, room_id, server }
+		let (socket_addr, websocket_addr, server, room_id) = builder.build();
+		Self {
+			socket_addr,
+			websocket_addr,
+			room_id,
+			server,
+		}
 	}
 
-	pub fn create_client(&self, member_id: RoomMemberId, private_key: &MemberPrivateKey, connection_id: ConnectionId) -> ClientId {
-		let mut client: ClientId = 0;
-		do_create_client(connection_id, &self.socket_addr.to_string(), member_id, self.room_id, private_key, 10, &mut client);
-		client
+	pub fn create_client(&self, member_id: RoomMemberId, private_key: &MemberPrivateKey, connection_id: ConnectionId) -> Client {
+		Client::connect(ConnectOptions {
+			connection_id,
+			..ConnectOptions::new(self.socket_addr, member_id, self.room_id, private_key.clone(), Self::DISCONNECT_TIMEOUT)
+		})
+		.unwrap()
+	}
+
+	pub fn create_client_with_disconnect_timeout(&self, member_id: RoomMemberId, private_key: &MemberPrivateKey, disconnect_timeout: Duration) -> Client {
+		Client::connect(ConnectOptions::new(self.socket_addr, member_id, self.room_id, private_key.clone(), disconnect_timeout)).unwrap()
+	}
+
+	pub fn create_single_threaded_client(&self, member_id: RoomMemberId, private_key: &MemberPrivateKey) -> Client {
+		Client::connect(ConnectOptions {
+			mode: ClientMode::SingleThreaded,
+			..ConnectOptions::new(self.socket_addr, member_id, self.room_id, private_key.clone(), Self::DISCONNECT_TIMEOUT)
+		})
+		.unwrap()
+	}
+
+	pub fn create_client_with_token(&self, member_token: &[u8], session_key: &MemberPrivateKey) -> Client {
+		Client::connect(ConnectOptions::with_token(self.socket_addr, member_token, session_key.clone(), Self::DISCONNECT_TIMEOUT)).unwrap()
+	}
+
+	///
+	/// Клиент с подключением через WebSocket, сервер должен быть создан с [`IntegrationTestServerBuilder::enable_websocket`]
+	///
+	pub fn create_websocket_client(&self, member_id: RoomMemberId, private_key: &MemberPrivateKey, mode: ClientMode) -> Client {
+		Client::connect(ConnectOptions {
+			mode,
+			transport: Transport::WebSocket,
+			..ConnectOptions::new(self.websocket_addr(), member_id, self.room_id, private_key.clone(), Self::DISCONNECT_TIMEOUT)
+		})
+		.unwrap()
+	}
+
+	pub fn create_websocket_client_with_token(&self, member_token: &[u8], session_key: &MemberPrivateKey) -> Client {
+		Client::connect(ConnectOptions {
+			transport: Transport::WebSocket,
+			..ConnectOptions::with_token(self.websocket_addr(), member_token, session_key.clone(), Self::DISCONNECT_TIMEOUT)
+		})
+		.unwrap()
+	}
+
+	pub fn websocket_addr(&self) -> SocketAddr {
+		self.websocket_addr.expect("websocket not enabled")
+	}
+
+	pub fn receive(&self, client: &mut Client) -> Vec<S2CCommand> {
+		self.wait_udp();
+		client.receive().collect()
 	}
 
 	pub fn wait_udp(&self) {
 		thread::sleep(Duration::from_millis(200));
 	}
 
-	pub fn create_member_object(&self, client_id: ClientId) -> GameObjectId {
-		let mut object_id = GameObjectId::default();
-		ffi::command::object::create_object(
-			client_id,
-			IntegrationTestServerBuilder::DEFAULT_TEMPLATE,
-			IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP.0,
-			&mut object_id,
-		);
-		ffi::command::object::created_object(client_id, &object_id, false, &Default::default());
+	pub fn create_member_object(&self, client: &mut Client) -> GameObjectId {
+		let object_id = client
+			.create_object(IntegrationTestServerBuilder::DEFAULT_TEMPLATE, IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP)
+			.unwrap();
+		client.created_object(object_id, false, None).unwrap();
 		object_id
 	}
 
@@ -79,15 +116,71 @@ impl IntegrationTestHelper {
 		let member_id = self.server.create_member(self.room_id, member_template).ok().unwrap();
 		(member_id, private_key)
 	}
+
+	///
+	/// Клиент в реестре FFI, для проверки FFI функций
+	///
+	pub fn create_ffi_client(&self, member_id: RoomMemberId, private_key: &MemberPrivateKey) -> ClientId {
+		self.create_ffi_client_with_mode(member_id, private_key, ClientMode::Thread)
+	}
+
+	pub fn create_ffi_client_with_mode(&self, member_id: RoomMemberId, private_key: &MemberPrivateKey, mode: ClientMode) -> ClientId {
+		self.create_ffi_client_with_address(&self.socket_addr.to_string(), member_id, private_key, mode)
+	}
+
+	pub fn create_ffi_client_with_address(&self, server_address: &str, member_id: RoomMemberId, private_key: &MemberPrivateKey, mode: ClientMode) -> ClientId {
+		let mut client: ClientId = 0;
+		assert_eq!(
+			do_create_client(0, server_address, member_id, self.room_id, private_key, Self::DISCONNECT_TIMEOUT.as_secs(), mode, &mut client),
+			0
+		);
+		client
+	}
+
+	pub fn ffi_receive(&self, client: ClientId) -> Vec<S2CCommandFFI> {
+		let mut commands = vec![
+			S2CCommandFFI {
+				command_type: CommandTypeId::CreateGameObject,
+				command: S2CommandUnionFFI { empty: () },
+			};
+			MAX_RECEIVE_COMMANDS
+		];
+		self.wait_udp();
+
+		let mut count = 0;
+		ffi::client::receive(client, commands.as_mut_ptr(), &mut count);
+		commands[0..count as usize].to_vec()
+	}
+
+	pub fn create_ffi_member_object(&self, client_id: ClientId) -> GameObjectId {
+		let mut object_id = GameObjectId::default();
+		ffi::command::object::create_object(
+			client_id,
+			IntegrationTestServerBuilder::DEFAULT_TEMPLATE,
+			IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP.0,
+			&mut object_id,
+		);
+		ffi::command::object::created_object(client_id, &object_id, false, &Default::default());
+		object_id
+	}
 }
 
 #[must_use]
-pub fn setup<const N: usize>(builder: IntegrationTestServerBuilder) -> (IntegrationTestHelper, [ClientId; N]) {
+pub fn setup<const N: usize>(builder: IntegrationTestServerBuilder) -> (IntegrationTestHelper, [Client; N]) {
 	let mut helper = IntegrationTestHelper::new(builder);
-	let mut members = [0; N];
-	for member in members.iter_mut() {
+	let members = std::array::from_fn(|_| {
 		let (member_id, private_key) = helper.create_member();
-		*member = helper.create_client(member_id, &private_key, 0);
-	}
+		helper.create_client(member_id, &private_key, 0)
+	});
+	(helper, members)
+}
+
+#[must_use]
+pub fn setup_ffi<const N: usize>(builder: IntegrationTestServerBuilder) -> (IntegrationTestHelper, [ClientId; N]) {
+	let mut helper = IntegrationTestHelper::new(builder);
+	let members = std::array::from_fn(|_| {
+		let (member_id, private_key) = helper.create_member();
+		helper.create_ffi_client(member_id, &private_key)
+	});
 	(helper, members)
 }
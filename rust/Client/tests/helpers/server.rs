@@ -5,7 +5,8 @@ use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
 use cheetah_game_realtime_protocol::RoomId;
 use cheetah_server::server::manager::ServerManager;
 use cheetah_server::server::room::config::room::RoomCreateParams;
-use std::net::SocketAddr;
+use cheetah_server::server::websocket::WebSocketListener;
+use std::net::{SocketAddr, TcpListener, UdpSocket};
 use std::time::Duration;
 
 ///
@@ -14,6 +15,9 @@ use std::time::Duration;
 #[derive(Debug, Default)]
 pub struct IntegrationTestServerBuilder {
 	template: RoomCreateParams,
+	ipv6: bool,
+	member_token_key: Option<Vec<u8>>,
+	websocket: bool,
 }
 
 impl IntegrationTestServerBuilder {
@@ -22,8 +26,35 @@ impl IntegrationTestServerBuilder {
 	pub const DISCONNECT_DURATION: Duration = Duration::from_secs(30);
 
 	#[must_use]
-	pub fn build(self) -> (SocketAddr, ServerManager, RoomId) {
-		let socket = bind_to_free_socket().unwrap();
+	pub fn set_compression(mut self, compression: bool) -> Self {
+		self.template.compression = compression;
+		self
+	}
+
+	#[must_use]
+	pub fn set_member_token_key(mut self, key: &[u8]) -> Self {
+		self.member_token_key = Some(key.to_vec());
+		self
+	}
+
+	///
+	/// Дополнительно к UDP принимать подключения через WebSocket
+	///
+	#[must_use]
+	pub fn enable_websocket(mut self) -> Self {
+		self.websocket = true;
+		self
+	}
+
+	#[must_use]
+	pub fn use_ipv6(mut self) -> Self {
+		self.ipv6 = true;
+		self
+	}
+
+	#[must_use]
+	pub fn build(self) -> (SocketAddr, Option<SocketAddr>, ServerManager, RoomId) {
+		let socket = if self.ipv6 { UdpSocket::bind("[::1]:0").unwrap() } else { bind_to_free_socket().unwrap() };
 		let addr = socket.local_addr().unwrap();
 		let mut server = ServerManager::new(
 			socket,
@@ -32,7 +63,18 @@ impl IntegrationTestServerBuilder {
 			},
 		)
 		.unwrap();
+		if let Some(key) = self.member_token_key {
+			server.set_member_token_key(key).unwrap();
+		}
+		let websocket_addr = if self.websocket {
+			let listener = WebSocketListener::new(TcpListener::bind(if self.ipv6 { "[::1]:0" } else { "127.0.0.1:0" }).unwrap()).unwrap();
+			let websocket_addr = listener.local_addr().unwrap();
+			server.enable_websocket(listener).unwrap();
+			Some(websocket_addr)
+		} else {
+			None
+		};
 		let room_id = server.create_room(self.template).ok().unwrap();
-		(addr, server, room_id)
+		(addr, websocket_addr, server, room_id)
 	}
 }
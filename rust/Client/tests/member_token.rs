@@ -0,0 +1,84 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::network::token::{MemberToken, MemberTokenClaims};
+use cheetah_common::network::ConnectionStatus;
+
+use crate::helpers::helper::IntegrationTestHelper;
+use crate::helpers::server::IntegrationTestServerBuilder;
+
+pub mod helpers;
+
+const TOKEN_KEY: &[u8] = b"member-token-key";
+
+#[test]
+fn should_connect_by_token() {
+	let helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default().set_member_token_key(TOKEN_KEY));
+	let token_1 = MemberToken::sign(TOKEN_KEY, claims(&helper, 1, 60));
+	let token_2 = MemberToken::sign(TOKEN_KEY, claims(&helper, 2, 60));
+	let mut client_1 = helper.create_client_with_token(&token_1.encode(), &token_1.session_key(TOKEN_KEY));
+	let mut client_2 = helper.create_client_with_token(&token_2.encode(), &token_2.session_key(TOKEN_KEY));
+	helper.wait_udp();
+	assert_eq!(client_1.connection_status().unwrap(), ConnectionStatus::Connected);
+	assert_eq!(client_2.connection_status().unwrap(), ConnectionStatus::Connected);
+
+	client_2.attach_to_room().unwrap();
+	helper.create_member_object(&mut client_1);
+	let commands = helper.receive(&mut client_2);
+	assert!(commands.iter().any(|command| matches!(command, S2CCommand::Create(_))));
+}
+
+///
+/// Повторное подключение по новому токену до разрыва предыдущего соединения продолжает сессию пользователя
+///
+#[test]
+fn should_resume_member_with_new_token() {
+	let helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default().set_member_token_key(TOKEN_KEY));
+	let token = MemberToken::sign(TOKEN_KEY, claims(&helper, 1, 60));
+	let client = helper.create_client_with_token(&token.encode(), &token.session_key(TOKEN_KEY));
+	helper.wait_udp();
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connected);
+
+	let new_token = MemberToken::sign(TOKEN_KEY, claims(&helper, 1, 60));
+	let new_client = helper.create_client_with_token(&new_token.encode(), &new_token.session_key(TOKEN_KEY));
+	helper.wait_udp();
+	assert_eq!(new_client.connection_status().unwrap(), ConnectionStatus::Connected);
+}
+
+#[test]
+fn should_not_connect_with_expired_token() {
+	let helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default().set_member_token_key(TOKEN_KEY));
+	let token = MemberToken::sign(TOKEN_KEY, claims(&helper, 1, 0));
+	let client = helper.create_client_with_token(&token.encode(), &token.session_key(TOKEN_KEY));
+	helper.wait_udp();
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connecting);
+}
+
+#[test]
+fn should_not_connect_with_token_signed_by_other_key() {
+	let helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default().set_member_token_key(TOKEN_KEY));
+	let token = MemberToken::sign(b"other-key", claims(&helper, 1, 60));
+	let client = helper.create_client_with_token(&token.encode(), &token.session_key(b"other-key"));
+	helper.wait_udp();
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connecting);
+}
+
+#[test]
+fn should_not_connect_by_token_when_key_not_configured() {
+	let helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default());
+	let token = MemberToken::sign(TOKEN_KEY, claims(&helper, 1, 60));
+	let client = helper.create_client_with_token(&token.encode(), &token.session_key(TOKEN_KEY));
+	helper.wait_udp();
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connecting);
+}
+
+fn claims(helper: &IntegrationTestHelper, user_id: u64, expires_in_sec: u64) -> MemberTokenClaims {
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+	MemberTokenClaims {
+		room_id: helper.room_id,
+		user_id,
+		groups: IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP,
+		expires_at: now + expires_in_sec,
+		nonce: rand::random(),
+	}
+}
@@ -0,0 +1,185 @@
+use cheetah_client::clients::registry::ClientId;
+use cheetah_client::clients::room_state::{RoomState, RoomStateChange, RoomStateChangeType};
+use cheetah_client::ffi;
+use cheetah_client::ffi::command::BufferFFI;
+use cheetah_client::ffi::room_state::RoomStateObjectFFI;
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::create::{CreateGameObject, GameObjectCreated};
+use cheetah_common::commands::types::long::LongField;
+use cheetah_common::room::field::FieldType;
+use cheetah_common::room::object::GameObjectId;
+use cheetah_common::room::owner::GameObjectOwner;
+
+use crate::helpers::helper::setup_ffi;
+use crate::helpers::server::IntegrationTestServerBuilder;
+
+pub mod helpers;
+
+#[test]
+fn should_mirror_room_state() {
+	let (helper, [client1, client2]) = setup_ffi(Default::default());
+	ffi::room_state::enable_room_state(client2);
+	ffi::command::room::attach_to_room(client2);
+
+	let object_id = helper.create_ffi_member_object(client1);
+	ffi::command::long_value::set_long_value(client1, &object_id, 1, 100);
+	ffi::command::float_value::set_double_value(client1, &object_id, 2, 3.5);
+	let structure: BufferFFI = vec![1, 2, 3].as_slice().into();
+	ffi::command::structure::set_structure(client1, &object_id, 3, &structure);
+	let item: BufferFFI = vec![4, 5].as_slice().into();
+	ffi::command::items::add_item(client1, &object_id, 4, &item);
+	helper.wait_udp();
+	ffi::room_state::update_room_state(client2);
+
+	let mut objects = [GameObjectId::default(); 16];
+	let mut count = 0;
+	unsafe {
+		ffi::room_state::get_room_state_objects(client2, objects.as_mut_ptr(), objects.len() as u16, &mut count);
+	}
+	assert_eq!(&objects[0..count as usize], &[object_id]);
+
+	let mut object = RoomStateObjectFFI::default();
+	ffi::room_state::get_room_state_object(client2, &object_id, &mut object);
+	assert_eq!(object.template, IntegrationTestServerBuilder::DEFAULT_TEMPLATE);
+
+	let mut long_value = 0;
+	ffi::room_state::get_room_state_long(client2, &object_id, 1, &mut long_value);
+	assert_eq!(long_value, 100);
+
+	let mut double_value = 0.0;
+	ffi::room_state::get_room_state_double(client2, &object_id, 2, &mut double_value);
+	assert!((double_value - 3.5).abs() < f64::EPSILON);
+
+	let mut structure_value = BufferFFI::default();
+	ffi::room_state::get_room_state_structure(client2, &object_id, 3, &mut structure_value);
+	assert_eq!(structure_value, structure);
+
+	let mut items_count = 0;
+	ffi::room_state::get_room_state_items_count(client2, &object_id, 4, &mut items_count);
+	assert_eq!(items_count, 1);
+	let mut item_value = BufferFFI::default();
+	ffi::room_state::get_room_state_item(client2, &object_id, 4, 0, &mut item_value);
+	assert_eq!(item_value, item);
+}
+
+#[test]
+fn should_collect_delete_object_change() {
+	let (helper, [client1, client2]) = setup_ffi(Default::default());
+	ffi::room_state::enable_room_state(client2);
+	ffi::command::room::attach_to_room(client2);
+
+	let object_id = helper.create_ffi_member_object(client1);
+	helper.ffi_receive(client2);
+	assert_eq!(pop_changes(client2).len(), 1);
+
+	ffi::command::long_value::set_long_value(client1, &object_id, 1, 100);
+	ffi::command::object::delete_object(client1, &object_id);
+	helper.ffi_receive(client2);
+
+	let changes = pop_changes(client2);
+	assert_eq!(changes.len(), 1);
+	assert_eq!(changes[0].change_type, RoomStateChangeType::ObjectDeleted);
+	assert_eq!(changes[0].object_id, object_id);
+
+	let mut object = RoomStateObjectFFI::default();
+	assert_ne!(ffi::room_state::get_room_state_object(client2, &object_id, &mut object), 0);
+}
+
+///
+/// Объект созданный и удаленный между двумя опросами изменений движку не интересен
+///
+#[test]
+fn should_skip_changes_of_created_and_deleted_object() {
+	let (helper, [client1, client2]) = setup_ffi(Default::default());
+	ffi::room_state::enable_room_state(client2);
+	ffi::command::room::attach_to_room(client2);
+
+	let object_id = helper.create_ffi_member_object(client1);
+	ffi::command::long_value::set_long_value(client1, &object_id, 1, 100);
+	ffi::command::object::delete_object(client1, &object_id);
+	helper.ffi_receive(client2);
+
+	assert!(pop_changes(client2).is_empty());
+}
+
+#[test]
+fn should_merge_field_changes() {
+	let (helper, [client1, client2]) = setup_ffi(Default::default());
+	ffi::room_state::enable_room_state(client2);
+	ffi::command::room::attach_to_room(client2);
+
+	let object_id = helper.create_ffi_member_object(client1);
+	helper.ffi_receive(client2);
+	let changes = pop_changes(client2);
+	assert_eq!(changes.len(), 1);
+	assert_eq!(changes[0].change_type, RoomStateChangeType::ObjectCreated);
+
+	ffi::command::long_value::set_long_value(client1, &object_id, 1, 100);
+	ffi::command::long_value::set_long_value(client1, &object_id, 1, 200);
+	helper.ffi_receive(client2);
+
+	let changes = pop_changes(client2);
+	assert_eq!(changes.len(), 1);
+	assert_eq!(changes[0].change_type, RoomStateChangeType::FieldChanged);
+	assert_eq!(changes[0].field_id, 1);
+	assert_eq!(changes[0].field_type, FieldType::Long);
+}
+
+///
+/// Повторное изменение поля заменяет ожидающее изменение на месте, в том числе после удаления других объектов
+///
+#[test]
+fn should_replace_pending_field_changes_in_place() {
+	let mut state = RoomState::default();
+	let object_a = GameObjectId::new(1, GameObjectOwner::Room);
+	let object_b = GameObjectId::new(2, GameObjectOwner::Room);
+	for object_id in [object_a, object_b] {
+		state.apply(&S2CCommand::Create(CreateGameObject {
+			object_id,
+			template: 1,
+			access_groups: Default::default(),
+		}));
+		state.apply(&S2CCommand::Created(GameObjectCreated { object_id }));
+	}
+	let set_long = |object_id, field_id, value| S2CCommand::SetLong(LongField { object_id, field_id, value });
+
+	state.apply(&set_long(object_b, 1, 1));
+	state.apply(&set_long(object_a, 1, 1));
+	state.apply(&set_long(object_a, 2, 1));
+	assert_eq!(state.pop_change().unwrap().change_type, RoomStateChangeType::ObjectCreated);
+
+	state.apply(&S2CCommand::Delete(object_b));
+	for value in 0..1000 {
+		state.apply(&set_long(object_a, 1, value));
+	}
+
+	let changes: Vec<_> = std::iter::from_fn(|| state.pop_change())
+		.map(|change| (change.change_type, change.object_id, change.field_id))
+		.collect();
+	assert_eq!(changes, vec![(RoomStateChangeType::FieldChanged, object_a, 1), (RoomStateChangeType::FieldChanged, object_a, 2),]);
+
+	state.apply(&set_long(object_a, 1, 5));
+	assert_eq!(state.pop_change().unwrap().field_id, 1);
+	assert!(state.pop_change().is_none());
+}
+
+#[test]
+fn should_fail_when_room_state_not_enabled() {
+	let (_helper, [client]) = setup_ffi(Default::default());
+	let mut long_value = 0;
+	assert_eq!(ffi::room_state::get_room_state_long(client, &GameObjectId::default(), 1, &mut long_value), 6);
+}
+
+fn pop_changes(client_id: ClientId) -> Vec<RoomStateChange> {
+	let mut changes = [RoomStateChange {
+		change_type: RoomStateChangeType::ObjectCreated,
+		object_id: Default::default(),
+		field_id: 0,
+		field_type: FieldType::Long,
+	}; 16];
+	let mut count = 0;
+	unsafe {
+		ffi::room_state::pop_room_state_changes(client_id, changes.as_mut_ptr(), changes.len() as u16, &mut count);
+	}
+	changes[0..count as usize].to_vec()
+}
@@ -0,0 +1,76 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cheetah_client::clients::client::{Client, ClientMode};
+use cheetah_client::ffi;
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::long::LongField;
+use cheetah_common::network::ConnectionStatus;
+
+use crate::helpers::helper::IntegrationTestHelper;
+
+pub mod helpers;
+
+///
+/// Клиент без сетевого потока работает только при вызове cycle
+///
+#[test]
+fn should_exchange_commands_in_single_threaded_mode() {
+	let mut helper = IntegrationTestHelper::new(Default::default());
+	let (member1, key1) = helper.create_member();
+	let (member2, key2) = helper.create_member();
+	let mut client1 = helper.create_single_threaded_client(member1, &key1);
+	let mut client2 = helper.create_single_threaded_client(member2, &key2);
+
+	thread::sleep(Duration::from_millis(100));
+	assert_eq!(client1.connection_status().unwrap(), ConnectionStatus::Connecting);
+
+	cycle_until(&mut [&mut client1, &mut client2], |clients| {
+		clients.iter().all(|client| client.connection_status().unwrap() == ConnectionStatus::Connected)
+	});
+
+	let object_id = helper.create_member_object(&mut client1);
+	client2.attach_to_room().unwrap();
+	client1.set_long(object_id, 1, 100).unwrap();
+
+	let mut received = Vec::new();
+	cycle_until(&mut [&mut client1, &mut client2], |clients| {
+		received.extend(clients[1].receive());
+		received.contains(&S2CCommand::SetLong(LongField { object_id, field_id: 1, value: 100 }))
+	});
+}
+
+#[test]
+fn should_cycle_only_single_threaded_client_via_ffi() {
+	let mut helper = IntegrationTestHelper::new(Default::default());
+	let (member_id, private_key) = helper.create_member();
+	let single_threaded_client = helper.create_ffi_client_with_mode(member_id, &private_key, ClientMode::SingleThreaded);
+	let (member_id, private_key) = helper.create_member();
+	let client = helper.create_ffi_client_with_mode(member_id, &private_key, ClientMode::Thread);
+
+	let mut status = ffi::client::ConnectionStatusFFI::Connecting;
+	for now in 0..100 {
+		assert_eq!(ffi::client::cycle(single_threaded_client, now * 10), 0);
+		ffi::client::get_connection_status(single_threaded_client, &mut status);
+		if matches!(status, ffi::client::ConnectionStatusFFI::Connected) {
+			break;
+		}
+		thread::sleep(Duration::from_millis(10));
+	}
+	assert!(matches!(status, ffi::client::ConnectionStatusFFI::Connected));
+	assert_eq!(ffi::client::cycle(client, 0), 9);
+}
+
+fn cycle_until<const N: usize>(clients: &mut [&mut Client; N], mut condition: impl FnMut(&mut [&mut Client; N]) -> bool) {
+	let deadline = Instant::now() + Duration::from_secs(5);
+	while Instant::now() < deadline {
+		for client in clients.iter_mut() {
+			client.cycle(Instant::now()).unwrap();
+		}
+		if condition(clients) {
+			return;
+		}
+		thread::sleep(Duration::from_millis(1));
+	}
+	panic!("Condition not reached");
+}
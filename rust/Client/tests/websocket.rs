@@ -0,0 +1,113 @@
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cheetah_client::clients::client::ClientMode;
+use cheetah_client::ffi;
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::types::long::LongField;
+use cheetah_common::commands::types::structure::BinaryField;
+use cheetah_common::network::token::{MemberToken, MemberTokenClaims};
+use cheetah_common::network::ConnectionStatus;
+use cheetah_common::room::buffer::Buffer;
+
+use crate::helpers::helper::IntegrationTestHelper;
+use crate::helpers::server::IntegrationTestServerBuilder;
+
+pub mod helpers;
+
+const TOKEN_KEY: &[u8] = b"member-token-key";
+
+///
+/// Пользователи с разными транспортами в одной комнате обмениваются командами
+///
+#[test]
+fn should_exchange_commands_between_udp_and_websocket_members() {
+	let mut helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default().enable_websocket());
+	let (udp_member, udp_key) = helper.create_member();
+	let (websocket_member, websocket_key) = helper.create_member();
+	let mut udp_client = helper.create_client(udp_member, &udp_key, 0);
+	let mut websocket_client = helper.create_websocket_client(websocket_member, &websocket_key, ClientMode::Thread);
+	helper.wait_udp();
+	assert_eq!(websocket_client.connection_status().unwrap(), ConnectionStatus::Connected);
+
+	udp_client.attach_to_room().unwrap();
+	websocket_client.attach_to_room().unwrap();
+	let udp_object_id = helper.create_member_object(&mut udp_client);
+	udp_client.set_long(udp_object_id, 1, 100).unwrap();
+	let commands = helper.receive(&mut websocket_client);
+	assert!(commands.iter().any(|command| matches!(command, S2CCommand::Create(created) if created.object_id == udp_object_id)));
+	assert!(commands.contains(&S2CCommand::SetLong(LongField {
+		object_id: udp_object_id,
+		field_id: 1,
+		value: 100
+	})));
+
+	let websocket_object_id = helper.create_member_object(&mut websocket_client);
+	let event = Buffer::from([1, 2, 3].as_slice());
+	websocket_client.send_target_event(udp_member, websocket_object_id, 5, event.clone()).unwrap();
+	let commands = helper.receive(&mut udp_client);
+	assert!(commands
+		.iter()
+		.any(|command| matches!(command, S2CCommand::Create(created) if created.object_id == websocket_object_id)));
+	assert!(commands.contains(&S2CCommand::Event(BinaryField {
+		object_id: websocket_object_id,
+		field_id: 5,
+		value: event,
+	})));
+	assert_ne!(websocket_client.statistics().recv_packet_count.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn should_connect_by_token_via_websocket() {
+	let helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default().set_member_token_key(TOKEN_KEY).enable_websocket());
+	let token = MemberToken::sign(TOKEN_KEY, claims(&helper, 1));
+	let client = helper.create_websocket_client_with_token(&token.encode(), &token.session_key(TOKEN_KEY));
+	helper.wait_udp();
+	assert_eq!(client.connection_status().unwrap(), ConnectionStatus::Connected);
+}
+
+///
+/// В FFI транспорт выбирается по префиксу адреса сервера
+///
+#[test]
+fn should_connect_via_websocket_by_ffi_address() {
+	let mut helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default().enable_websocket());
+	let (member_id, private_key) = helper.create_member();
+	let client = helper.create_ffi_client_with_address(&format!("ws://{}", helper.websocket_addr()), member_id, &private_key, ClientMode::Thread);
+	helper.wait_udp();
+	let mut status = ffi::client::ConnectionStatusFFI::Connecting;
+	ffi::client::get_connection_status(client, &mut status);
+	assert!(matches!(status, ffi::client::ConnectionStatusFFI::Connected));
+}
+
+///
+/// WebSocket с TLS не поддерживается, клиент не создается
+///
+#[test]
+fn should_not_create_client_for_secure_websocket_address() {
+	let mut helper = IntegrationTestHelper::new(IntegrationTestServerBuilder::default().enable_websocket());
+	let (member_id, private_key) = helper.create_member();
+	let mut client = 0;
+	let result = ffi::client::do_create_client(
+		0,
+		&format!("wss://{}", helper.websocket_addr()),
+		member_id,
+		helper.room_id,
+		&private_key,
+		1,
+		ClientMode::Thread,
+		&mut client,
+	);
+	assert_ne!(result, 0);
+}
+
+fn claims(helper: &IntegrationTestHelper, user_id: u64) -> MemberTokenClaims {
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+	MemberTokenClaims {
+		room_id: helper.room_id,
+		user_id,
+		groups: IntegrationTestServerBuilder::DEFAULT_ACCESS_GROUP,
+		expires_at: now + 60,
+		nonce: rand::random(),
+	}
+}
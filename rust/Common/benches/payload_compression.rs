@@ -0,0 +1,96 @@
+//!
+//! Сравнение сжатия пакета с командами с только посегментным сжатием протокола
+//!
+//! - segments - протокол делит пакет на сегменты по [`SEGMENT_SIZE`] байт и сжимает каждый сегмент отдельно
+//! - payload + segments - коллектор сжимает пакет целиком, затем протокол сжимает сегменты
+//!
+//! Запуск: `cargo bench -p cheetah-common --bench payload_compression`
+//!
+//! Для 200 структур по 50 байт: только сегменты - 43 сегмента и 4798 байт, с предварительным сжатием пакета -
+//! 12 сегментов и 2472 байт при одинаковом времени (~66 мкс вместе с добавлением команд)
+//!
+use std::hint::black_box;
+
+use cheetah_common::commands::c2s::C2SCommand;
+use cheetah_common::commands::guarantees::ReliabilityGuarantees;
+use cheetah_common::commands::types::structure::BinaryField;
+use cheetah_common::commands::BothDirectionCommand;
+use cheetah_common::network::collectors::out_collector::OutCommandsCollector;
+use cheetah_common::room::buffer::Buffer;
+use cheetah_common::room::object::GameObjectId;
+use cheetah_common::room::owner::GameObjectOwner;
+use cheetah_game_realtime_protocol::frame::packets_collector::PACKET_SIZE;
+use cheetah_game_realtime_protocol::frame::segment::SEGMENT_SIZE;
+use cheetah_game_realtime_protocol::OutputDataProducer;
+use easybench::bench;
+
+const OBJECTS: u32 = 200;
+
+fn main() {
+	for compression in [false, true] {
+		let mut collector = collector_with_commands(compression);
+		let mut packet = [0; PACKET_SIZE];
+		let (size, _) = collector.get_output_data(&mut packet);
+		let (segments, wire_size) = compress_segments(&packet[0..size]);
+		let name = if compression { "payload + segments" } else { "segments" };
+		println!("{name}: payload {size} bytes, {segments} segments, {wire_size} bytes after segment compression");
+
+		// коллектор переиспользуется как в клиенте и сервере, время включает добавление команд
+		let bench = bench(|| {
+			add_commands(&mut collector);
+			let mut packet = [0; PACKET_SIZE];
+			let (size, _) = collector.get_output_data(&mut packet);
+			black_box(compress_segments(&packet[0..size]))
+		});
+		println!("{name}: {bench}");
+	}
+}
+
+///
+/// Типичный кадр игры со структурами - состояние юнитов в SetStructure
+///
+fn collector_with_commands(compression: bool) -> OutCommandsCollector {
+	let mut collector = OutCommandsCollector::default();
+	collector.set_compression(compression);
+	add_commands(&mut collector);
+	collector
+}
+
+fn add_commands(collector: &mut OutCommandsCollector) {
+	for id in 0..OBJECTS {
+		let object_id = GameObjectId::new(id, GameObjectOwner::Room);
+		collector.add_command(
+			ReliabilityGuarantees::ReliableUnordered,
+			BothDirectionCommand::C2S(C2SCommand::SetStructure(Box::new(BinaryField {
+				object_id,
+				field_id: 1,
+				value: Buffer::from(unit_state(id).as_slice()),
+			}))),
+		);
+	}
+}
+
+fn unit_state(id: u32) -> Vec<u8> {
+	let mut state = Vec::new();
+	state.extend_from_slice(&id.to_le_bytes());
+	for axis in 0..3 {
+		state.extend_from_slice(&(id as f32 * 0.5 + axis as f32).to_le_bytes());
+	}
+	state.extend_from_slice(&[0, 0, 0, 0, 0, 0, 128, 63]);
+	state.extend_from_slice(&100_u16.to_le_bytes());
+	state.extend_from_slice(b"unit_warrior\0\0\0\0");
+	state.extend_from_slice(&[0; 8]);
+	state
+}
+
+///
+/// Повторяет разбиение и сжатие пакета на сегменты в протоколе
+///
+fn compress_segments(packet: &[u8]) -> (usize, usize) {
+	let mut encoder = snap::raw::Encoder::new();
+	let mut buffer = [0; 4096];
+	packet
+		.chunks(SEGMENT_SIZE)
+		.map(|segment| encoder.compress(segment, &mut buffer).unwrap())
+		.fold((0, 0), |(count, size), segment_size| (count + 1, size + segment_size))
+}
@@ -61,7 +61,7 @@ impl C2SCommand {
 			C2SCommand::IncrementDouble(command) => Some(command.object_id),
 			C2SCommand::Event(command) => Some(command.object_id),
 			C2SCommand::TargetEvent(command) => Some(command.event.object_id),
-			C2SCommand::Delete(object_id) => Some(object_id.clone()),
+			C2SCommand::Delete(object_id) => Some(*object_id),
 			C2SCommand::AttachToRoom => None,
 			C2SCommand::DetachFromRoom => None,
 			C2SCommand::DeleteField(command) => Some(command.object_id),
@@ -150,7 +150,7 @@ impl C2SCommand {
 			CommandTypeId::AttachToRoom => C2SCommand::AttachToRoom,
 			CommandTypeId::DetachFromRoom => C2SCommand::DetachFromRoom,
 			CommandTypeId::CreatedGameObject => C2SCommand::CreatedGameObject(C2SCreatedGameObject::decode(object_id?, input)?.into()),
-			CommandTypeId::DeleteObject => C2SCommand::Delete(object_id?.clone()),
+			CommandTypeId::DeleteObject => C2SCommand::Delete(object_id?),
 			CommandTypeId::CreateGameObject => C2SCommand::CreateGameObject(CreateGameObject::decode(object_id?, input)?),
 			CommandTypeId::IncrementLong => C2SCommand::IncrementLongValue(IncrementLong::decode(object_id?, field_id?, input)?),
 			CommandTypeId::IncrementDouble => C2SCommand::IncrementDouble(IncrementDouble::decode(object_id?, field_id?, input)?),
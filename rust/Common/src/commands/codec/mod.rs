@@ -14,7 +14,9 @@ mod tests {
 	use crate::commands::s2c::S2CCommand;
 	use crate::commands::types::float::DoubleField;
 	use crate::commands::types::long::LongField;
+	use crate::commands::types::structure::BinaryField;
 	use crate::commands::{BothDirectionCommand, CommandWithReliabilityGuarantees};
+	use crate::room::buffer::{Buffer, MAX_BUFFER_SIZE};
 	use crate::room::object::GameObjectId;
 	use crate::room::owner::GameObjectOwner;
 	use cheetah_game_realtime_protocol::frame::packets_collector::PACKET_SIZE;
@@ -39,8 +41,7 @@ mod tests {
 					value: 16,
 				})),
 			},
-		]
-		.into();
+		];
 
 		check(true, commands);
 	}
@@ -85,6 +86,28 @@ mod tests {
 		check(false, commands);
 	}
 
+	///
+	/// Команды максимального размера кодируются в пакет протокола, фрагментация пакета на сегменты выполняется протоколом
+	///
+	#[test]
+	fn test_max_size_structures() {
+		let mut commands = vec![];
+		for i in 0..10 {
+			let command = CommandWithReliabilityGuarantees {
+				reliability_guarantees: ReliabilityGuaranteesChannel::ReliableUnordered,
+				command: BothDirectionCommand::S2C(S2CCommand::SetStructure(BinaryField {
+					object_id: Default::default(),
+					field_id: i,
+					value: Buffer {
+						buffer: vec![i as u8; MAX_BUFFER_SIZE],
+					},
+				})),
+			};
+			commands.push(command);
+		}
+		check(false, commands);
+	}
+
 	#[allow(clippy::cast_possible_truncation)]
 	fn check(server_side: bool, original_commands: Vec<CommandWithReliabilityGuarantees>) {
 		let mut cloned_original_commands: VecDeque<_> = original_commands.clone().into();
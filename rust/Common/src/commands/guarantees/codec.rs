@@ -19,12 +19,12 @@ impl ChannelType {
 	const UNRELIABLE_UNORDERED: Self = ChannelType(2);
 	const UNRELIABLE_ORDERED: Self = ChannelType(3);
 	const RELIABLE_SEQUENCE: Self = ChannelType(4);
+	const RELIABLE_LATEST: Self = ChannelType(5);
 }
 
 ///
 /// Кодирование/декодирование канала
 ///
-
 impl ReliabilityGuaranteesChannel {
 	///
 	/// Получить идентификатор типа
@@ -37,6 +37,7 @@ impl ReliabilityGuaranteesChannel {
 			ReliabilityGuaranteesChannel::UnreliableUnordered => ChannelType::UNRELIABLE_UNORDERED,
 			ReliabilityGuaranteesChannel::UnreliableOrdered(_, _) => ChannelType::UNRELIABLE_ORDERED,
 			ReliabilityGuaranteesChannel::ReliableSequence(_, _) => ChannelType::RELIABLE_SEQUENCE,
+			ReliabilityGuaranteesChannel::ReliableLatest(_) => ChannelType::RELIABLE_LATEST,
 		};
 		assert!(id.0 < 8); // если больше 7 то надо переделывать формат передачи фреймов
 		id
@@ -46,7 +47,10 @@ impl ReliabilityGuaranteesChannel {
 		match self {
 			ReliabilityGuaranteesChannel::ReliableUnordered => {}
 			ReliabilityGuaranteesChannel::UnreliableUnordered => {}
-			ReliabilityGuaranteesChannel::ReliableOrdered(_, sequence) | ReliabilityGuaranteesChannel::UnreliableOrdered(_, sequence) | ReliabilityGuaranteesChannel::ReliableSequence(_, sequence) => {
+			ReliabilityGuaranteesChannel::ReliableOrdered(_, sequence)
+			| ReliabilityGuaranteesChannel::UnreliableOrdered(_, sequence)
+			| ReliabilityGuaranteesChannel::ReliableSequence(_, sequence)
+			| ReliabilityGuaranteesChannel::ReliableLatest(sequence) => {
 				out.write_variable_u64(u64::from(sequence.0))?;
 			}
 		}
@@ -60,6 +64,7 @@ impl ReliabilityGuaranteesChannel {
 			ChannelType::RELIABLE_ORDERED => ReliabilityGuaranteesChannel::ReliableOrdered(channel_group?, ChannelSequence(input.read_variable_u64()?.try_into()?)),
 			ChannelType::UNRELIABLE_ORDERED => ReliabilityGuaranteesChannel::UnreliableOrdered(channel_group?, ChannelSequence(input.read_variable_u64()?.try_into()?)),
 			ChannelType::RELIABLE_SEQUENCE => ReliabilityGuaranteesChannel::ReliableSequence(channel_group?, ChannelSequence(input.read_variable_u64()?.try_into()?)),
+			ChannelType::RELIABLE_LATEST => ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(input.read_variable_u64()?.try_into()?)),
 			_ => return Err(CommandChannelDecodeError::UnknownType(*channel_type)),
 		})
 	}
@@ -130,6 +135,15 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_reliable_latest() {
+		check(
+			ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(255)),
+			ChannelType::RELIABLE_LATEST,
+			Err(CommandContextError::ContextNotContainsChannelGroupId),
+		);
+	}
+
 	fn check(original: ReliabilityGuaranteesChannel, channel_type: ChannelType, channel_group_id: Result<ChannelGroup, CommandContextError>) {
 		let mut buffer = [0_u8; 100];
 		let mut cursor = Cursor::new(buffer.as_mut());
@@ -35,6 +35,11 @@ pub enum ReliabilityGuarantees {
 	/// Выполняем команды строго по-порядку по группе
 	///
 	ReliableSequence(ChannelGroup),
+	///
+	/// Для команд установки значения поля доставляем только последнее значение,
+	/// новая команда для того же поля объекта заменяет еще не отправленную старую
+	///
+	ReliableLatest,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
@@ -59,6 +64,10 @@ pub enum ReliabilityGuaranteesChannel {
 	/// Выполняем команды строго по-порядку по группе, гарантированная доставка
 	///
 	ReliableSequence(ChannelGroup, ChannelSequence),
+	///
+	/// Отбрасываем устаревшие значения поля объекта, гарантированная доставка
+	///
+	ReliableLatest(ChannelSequence),
 }
 
 impl From<&ReliabilityGuaranteesChannel> for ReliabilityGuarantees {
@@ -69,6 +78,7 @@ impl From<&ReliabilityGuaranteesChannel> for ReliabilityGuarantees {
 			ReliabilityGuaranteesChannel::UnreliableUnordered => ReliabilityGuarantees::UnreliableUnordered,
 			ReliabilityGuaranteesChannel::UnreliableOrdered(channel, _) => ReliabilityGuarantees::UnreliableOrdered(*channel),
 			ReliabilityGuaranteesChannel::ReliableSequence(channel, _) => ReliabilityGuarantees::ReliableSequence(*channel),
+			ReliabilityGuaranteesChannel::ReliableLatest(_) => ReliabilityGuarantees::ReliableLatest,
 		}
 	}
 }
@@ -80,6 +90,7 @@ impl ReliabilityGuaranteesChannel {
 			ReliabilityGuaranteesChannel::ReliableUnordered => true,
 			ReliabilityGuaranteesChannel::ReliableOrdered(_, _) => true,
 			ReliabilityGuaranteesChannel::ReliableSequence(_, _) => true,
+			ReliabilityGuaranteesChannel::ReliableLatest(_) => true,
 			ReliabilityGuaranteesChannel::UnreliableUnordered => false,
 			ReliabilityGuaranteesChannel::UnreliableOrdered(_, _) => false,
 		}
@@ -92,6 +103,7 @@ impl ReliabilityGuaranteesChannel {
 			ReliabilityGuaranteesChannel::UnreliableUnordered => None,
 			ReliabilityGuaranteesChannel::UnreliableOrdered(group, _) => Some(*group),
 			ReliabilityGuaranteesChannel::ReliableSequence(group, _) => Some(*group),
+			ReliabilityGuaranteesChannel::ReliableLatest(_) => None,
 		}
 	}
 }
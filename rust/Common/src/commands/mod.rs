@@ -1,11 +1,13 @@
 use num_derive::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
+use strum_macros::AsRefStr;
 use thiserror::Error;
 
 use crate::commands::c2s::C2SCommand;
 use crate::commands::context::CommandContextError;
 use crate::commands::guarantees::{ChannelSequence, ReliabilityGuarantees, ReliabilityGuaranteesChannel};
 use crate::commands::s2c::S2CCommand;
+use crate::room::field::Field;
 use crate::room::object::GameObjectId;
 
 pub mod c2s;
@@ -51,13 +53,49 @@ impl BothDirectionCommand {
 			BothDirectionCommand::C2S(command) => command.get_object_id(),
 		}
 	}
+
+	///
+	/// Поле объекта, значение которого полностью заменяется командой,
+	/// для таких команд важно только последнее значение
+	///
+	#[must_use]
+	pub fn get_replaced_field(&self) -> Option<(GameObjectId, Field)> {
+		match &self {
+			BothDirectionCommand::S2C(command @ (S2CCommand::SetLong(_) | S2CCommand::SetDouble(_) | S2CCommand::SetStructure(_) | S2CCommand::DeleteField(_))) => Some((
+				command.get_object_id()?,
+				Field {
+					id: command.get_field_id()?,
+					field_type: command.get_field_type()?,
+				},
+			)),
+			BothDirectionCommand::C2S(command @ (C2SCommand::SetLong(_) | C2SCommand::SetDouble(_) | C2SCommand::SetStructure(_) | C2SCommand::DeleteField(_))) => Some((
+				command.get_object_id()?,
+				Field {
+					id: command.get_field_id()?,
+					field_type: command.get_field_type()?,
+				},
+			)),
+			_ => None,
+		}
+	}
+
+	///
+	/// Объект, удаляемый командой
+	///
+	#[must_use]
+	pub fn get_deleted_object_id(&self) -> Option<GameObjectId> {
+		match &self {
+			BothDirectionCommand::S2C(S2CCommand::Delete(object_id)) | BothDirectionCommand::C2S(C2SCommand::Delete(object_id)) => Some(*object_id),
+			_ => None,
+		}
+	}
 }
 
 ///
 /// Идентификатор типа команды
 ///
 #[repr(u8)]
-#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, FromPrimitive, ToPrimitive, Hash, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, FromPrimitive, ToPrimitive, Hash, AsRefStr, Serialize, Deserialize)]
 pub enum CommandTypeId {
 	CreateGameObject = 0,
 	CreatedGameObject,
@@ -155,14 +193,11 @@ mod tests {
 		let object_id = GameObjectId::new(100, GameObjectOwner::Room);
 		let field_id = 77;
 		check(
-			&S2CCommand::SetStructure(
-				BinaryField {
-					object_id,
-					field_id,
-					value: Buffer::from([1, 2, 3, 4].as_ref()),
-				}
-				.into(),
-			),
+			&S2CCommand::SetStructure(BinaryField {
+				object_id,
+				field_id,
+				value: Buffer::from([1, 2, 3, 4].as_ref()),
+			}),
 			CommandTypeId::SetStructure,
 			Some(object_id),
 			Some(field_id),
@@ -174,14 +209,11 @@ mod tests {
 		let object_id = GameObjectId::new(100, GameObjectOwner::Room);
 		let field_id = 77;
 		check(
-			&S2CCommand::Event(
-				BinaryField {
-					object_id,
-					field_id,
-					value: Buffer::from(vec![1, 2, 3, 4].as_slice()),
-				}
-				.into(),
-			),
+			&S2CCommand::Event(BinaryField {
+				object_id,
+				field_id,
+				value: Buffer::from(vec![1, 2, 3, 4].as_slice()),
+			}),
 			CommandTypeId::SendEvent,
 			Some(object_id),
 			Some(field_id),
@@ -193,14 +225,11 @@ mod tests {
 		let object_id = GameObjectId::new(100, GameObjectOwner::Room);
 		let field_id = 77;
 		check(
-			&S2CCommand::AddItem(
-				BinaryField {
-					object_id,
-					field_id,
-					value: Buffer::from(vec![1, 2, 3, 4].as_slice()),
-				}
-				.into(),
-			),
+			&S2CCommand::AddItem(BinaryField {
+				object_id,
+				field_id,
+				value: Buffer::from(vec![1, 2, 3, 4].as_slice()),
+			}),
 			CommandTypeId::AddItem,
 			Some(object_id),
 			Some(field_id),
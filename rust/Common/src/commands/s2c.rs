@@ -53,7 +53,7 @@ impl S2CCommand {
 			S2CCommand::Create(command) => Some(command.object_id),
 			S2CCommand::Created(command) => Some(command.object_id),
 			S2CCommand::Event(command) => Some(command.object_id),
-			S2CCommand::Delete(object_id) => Some(object_id.clone()),
+			S2CCommand::Delete(object_id) => Some(*object_id),
 			S2CCommand::DeleteField(command) => Some(command.object_id),
 			S2CCommand::SetLong(command) => command.object_id.into(),
 			S2CCommand::SetDouble(command) => command.object_id.into(),
@@ -126,12 +126,12 @@ impl S2CCommand {
 			CommandTypeId::DeleteObject => S2CCommand::Delete(object_id?),
 			CommandTypeId::SetLong => S2CCommand::SetLong(LongField::decode(object_id?, field_id?, input)?),
 			CommandTypeId::SetDouble => S2CCommand::SetDouble(DoubleField::decode(object_id?, field_id?, input)?),
-			CommandTypeId::SetStructure => S2CCommand::SetStructure(BinaryField::decode(object_id?, field_id?, input)?.into()),
-			CommandTypeId::SendEvent => S2CCommand::Event(BinaryField::decode(object_id?, field_id?, input)?.into()),
+			CommandTypeId::SetStructure => S2CCommand::SetStructure(BinaryField::decode(object_id?, field_id?, input)?),
+			CommandTypeId::SendEvent => S2CCommand::Event(BinaryField::decode(object_id?, field_id?, input)?),
 			CommandTypeId::DeleteField => S2CCommand::DeleteField(DeleteField::decode(object_id?, field_id?, input)?),
 			CommandTypeId::MemberConnected => S2CCommand::MemberConnected(MemberConnected::decode(input)?),
 			CommandTypeId::MemberDisconnected => S2CCommand::MemberDisconnected(MemberDisconnected::decode(input)?),
-			CommandTypeId::AddItem => S2CCommand::AddItem(BinaryField::decode(object_id?, field_id?, input)?.into()),
+			CommandTypeId::AddItem => S2CCommand::AddItem(BinaryField::decode(object_id?, field_id?, input)?),
 			_ => return Err(CommandDecodeError::UnknownTypeId(*command_type_id)),
 		})
 	}
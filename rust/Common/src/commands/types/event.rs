@@ -16,6 +16,7 @@ pub struct TargetEvent {
 	pub event: BinaryField,
 }
 
+#[allow(clippy::useless_conversion)]
 impl TargetEvent {
 	pub fn encode(&self, out: &mut Cursor<&mut [u8]>) -> std::io::Result<()> {
 		out.write_variable_u64(u64::from(self.target))?;
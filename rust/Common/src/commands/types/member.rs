@@ -16,6 +16,7 @@ pub struct MemberDisconnected {
 	pub member_id: RoomMemberId,
 }
 
+#[allow(clippy::useless_conversion)]
 impl MemberConnected {
 	pub fn encode(&self, out: &mut Cursor<&mut [u8]>) -> std::io::Result<()> {
 		out.write_variable_u64(self.member_id.into())
@@ -30,6 +31,7 @@ impl MemberConnected {
 	}
 }
 
+#[allow(clippy::useless_conversion)]
 impl MemberDisconnected {
 	pub fn encode(&self, out: &mut Cursor<&mut [u8]>) -> std::io::Result<()> {
 		out.write_variable_u64(self.member_id.into())
@@ -1,9 +1,14 @@
 use crate::commands::codec::decoder::decode_commands;
 use crate::commands::guarantees::{ChannelGroup, ChannelSequence, ReliabilityGuaranteesChannel};
 use crate::commands::CommandWithReliabilityGuarantees;
+use crate::network::collectors::payload::{decode_latest_acks, decompress_payload, PayloadStatistics, PAYLOAD_LATEST_ACKS, PAYLOAD_SERVER_SHUTDOWN};
+use crate::room::field::Field;
+use crate::room::object::GameObjectId;
 use cheetah_game_realtime_protocol::InputDataHandler;
+use fnv::FnvHashMap;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
+use std::io::Cursor;
 
 ///
 /// Коллектор входящих команд
@@ -14,9 +19,27 @@ pub struct InCommandsCollector {
 	last_sequence_by_group: [ChannelSequence; 256],
 	sequences: [ChannelSequence; 256],
 	sequence_commands: Box<[Option<BinaryHeap<SequenceApplicationCommand>>; 256]>,
+	///
+	/// Последняя примененная sequence для полей объектов из канала ReliableLatest
+	///
+	latest_sequence_by_field: FnvHashMap<(GameObjectId, Field), ChannelSequence>,
+	///
+	/// Полученные команды ReliableLatest, получение которых нужно подтвердить удаленной стороне
+	///
+	pub(crate) latest_acks: Vec<ChannelSequence>,
+	///
+	/// Подтверждения удаленной стороны для отправленных команд ReliableLatest
+	///
+	pub(crate) acked_latest: Vec<ChannelSequence>,
 	ready_commands: Vec<CommandWithReliabilityGuarantees>,
 	is_get_ready_commands: bool,
 	pub server_side: bool,
+	decompress_buffer: Vec<u8>,
+	pub statistics: PayloadStatistics,
+	///
+	/// Сервер закрывает соединение из-за остановки
+	///
+	pub server_shutdown: bool,
 }
 
 ///
@@ -26,9 +49,35 @@ pub struct InCommandsCollector {
 ///
 const SEQUENCE_COMMANDS_LIMIT: usize = 4096;
 
+///
+/// Лимит подтверждений ReliableLatest, ожидающих передачи в [`crate::network::collectors::out_collector::OutCommandsCollector`],
+/// неподтвержденные команды будут отправлены повторно
+///
+const LATEST_ACKS_LIMIT: usize = 4096;
+
 impl InputDataHandler for InCommandsCollector {
 	fn on_input_data(&mut self, data: &[u8]) {
-		match decode_commands(self.server_side, data) {
+		if data.first().is_some_and(|flags| flags & PAYLOAD_SERVER_SHUTDOWN != 0) {
+			self.server_shutdown = true;
+		}
+		let payload = match decompress_payload(data, &mut self.decompress_buffer) {
+			Ok(payload) => payload,
+			Err(e) => {
+				tracing::error!("Error decompress commands {:?}", e);
+				return;
+			}
+		};
+		self.statistics.raw_size += payload.len() as u64 + 1;
+		self.statistics.size += data.len() as u64;
+		let mut cursor = Cursor::new(payload);
+		if data[0] & PAYLOAD_LATEST_ACKS != 0 {
+			if let Err(e) = decode_latest_acks(&mut cursor, &mut self.acked_latest) {
+				tracing::error!("Error decode latest acks {:?}", e);
+				return;
+			}
+			self.acked_latest.truncate(LATEST_ACKS_LIMIT);
+		}
+		match decode_commands(self.server_side, &payload[cursor.position() as usize..]) {
 			Ok(commands) => {
 				tracing::debug!("c2s: {:?}", commands);
 				self.collect(commands.as_slice());
@@ -40,7 +89,10 @@ impl InputDataHandler for InCommandsCollector {
 	}
 
 	fn reset(&mut self) {
-		*self = InCommandsCollector::new(self.server_side)
+		*self = InCommandsCollector {
+			statistics: self.statistics,
+			..InCommandsCollector::new(self.server_side)
+		}
 	}
 }
 
@@ -51,9 +103,15 @@ impl InCommandsCollector {
 			last_sequence_by_group: [ChannelSequence(0); 256],
 			sequences: [ChannelSequence(0); 256],
 			sequence_commands: Box::new([INIT; 256]),
+			latest_sequence_by_field: Default::default(),
+			latest_acks: Default::default(),
+			acked_latest: Default::default(),
 			ready_commands: Default::default(),
 			is_get_ready_commands: false,
 			server_side,
+			decompress_buffer: Default::default(),
+			statistics: Default::default(),
+			server_shutdown: false,
 		}
 	}
 
@@ -72,12 +130,16 @@ impl InCommandsCollector {
 		}
 
 		commands.iter().cloned().for_each(|c| {
+			if let Some(object_id) = c.command.get_deleted_object_id() {
+				self.latest_sequence_by_field.retain(|(field_object_id, _), _| *field_object_id != object_id);
+			}
 			match c.reliability_guarantees {
 				ReliabilityGuaranteesChannel::ReliableUnordered | ReliabilityGuaranteesChannel::UnreliableUnordered => self.ready_commands.push(c),
 				ReliabilityGuaranteesChannel::ReliableOrdered(group, sequence) | ReliabilityGuaranteesChannel::UnreliableOrdered(group, sequence) => {
 					self.process_ordered(group, sequence, c);
 				}
 				ReliabilityGuaranteesChannel::ReliableSequence(channel_id, sequence) => self.process_sequence(channel_id, sequence, c),
+				ReliabilityGuaranteesChannel::ReliableLatest(sequence) => self.process_latest(sequence, c),
 			};
 		});
 	}
@@ -128,6 +190,28 @@ impl InCommandsCollector {
 	}
 }
 
+impl InCommandsCollector {
+	///
+	/// Отбрасываем значение поля, если уже применено более новое (например при повторной отправке неподтвержденного значения),
+	/// получение значения подтверждается в любом случае
+	///
+	fn process_latest(&mut self, sequence: ChannelSequence, command: CommandWithReliabilityGuarantees) {
+		match command.command.get_replaced_field() {
+			None => self.ready_commands.push(command),
+			Some(key) => {
+				if self.latest_acks.len() < LATEST_ACKS_LIMIT {
+					self.latest_acks.push(sequence);
+				}
+				let last_sequence = self.latest_sequence_by_field.get(&key);
+				if last_sequence.map(|last| sequence.0 > last.0).unwrap_or(true) {
+					self.latest_sequence_by_field.insert(key, sequence);
+					self.ready_commands.push(command);
+				}
+			}
+		}
+	}
+}
+
 #[derive(Debug)]
 struct SequenceApplicationCommand {
 	sequence: ChannelSequence,
@@ -173,17 +257,28 @@ mod tests {
 	use crate::commands::types::long::LongField;
 	use crate::commands::{BothDirectionCommand, CommandWithReliabilityGuarantees};
 	use crate::network::collectors::in_collector::InCommandsCollector;
+	use crate::network::collectors::payload::{PayloadCompressor, PAYLOAD_SERVER_SHUTDOWN};
 	use crate::room::object::GameObjectId;
 	use crate::room::owner::GameObjectOwner;
 	use cheetah_game_realtime_protocol::frame::packets_collector::PACKET_SIZE;
 	use cheetah_game_realtime_protocol::InputDataHandler;
+	use std::slice;
+
+	#[test]
+	pub(crate) fn should_detect_server_shutdown() {
+		let mut in_commands = InCommandsCollector::new(false);
+		in_commands.on_input_data(&[0]);
+		assert!(!in_commands.server_shutdown);
+		in_commands.on_input_data(&[PAYLOAD_SERVER_SHUTDOWN]);
+		assert!(in_commands.server_shutdown);
+	}
 
 	#[test]
 	pub(crate) fn test_clear_after_get_ready_commands() {
 		let mut in_commands = InCommandsCollector::new(true);
 		let cmd_1 = create_test_command(ReliabilityGuaranteesChannel::ReliableUnordered, 1);
 		let mut packet = [0; PACKET_SIZE];
-		let (size, _) = encode_commands(&mut vec![cmd_1.clone()].into(), &mut packet);
+		let size = encode(slice::from_ref(&cmd_1), &mut packet);
 		in_commands.on_input_data(&packet[0..size]);
 		assert_eq!(in_commands.get_ready_commands(), [cmd_1]);
 		assert_eq!(in_commands.get_ready_commands(), []);
@@ -194,7 +289,7 @@ mod tests {
 		let mut in_commands = InCommandsCollector::new(true);
 		let cmd_1 = create_test_command(ReliabilityGuaranteesChannel::ReliableUnordered, 1);
 		let mut packet = [0; PACKET_SIZE];
-		let (size, _) = encode_commands(&mut vec![cmd_1.clone()].into(), &mut packet);
+		let size = encode(slice::from_ref(&cmd_1), &mut packet);
 		in_commands.on_input_data(&packet[0..size]);
 		in_commands.on_input_data(&packet[0..size]);
 		assert_eq!(in_commands.get_ready_commands(), [cmd_1.clone(), cmd_1]);
@@ -206,8 +301,8 @@ mod tests {
 		let mut in_commands = InCommandsCollector::new(true);
 		let cmd_1 = create_test_command(ReliabilityGuaranteesChannel::ReliableUnordered, 1);
 		let cmd_2 = create_test_command(ReliabilityGuaranteesChannel::ReliableUnordered, 2);
-		assert(&mut in_commands, &[cmd_2.clone()], &[cmd_2]);
-		assert(&mut in_commands, &[cmd_1.clone()], &[cmd_1]);
+		assert(&mut in_commands, slice::from_ref(&cmd_2), slice::from_ref(&cmd_2));
+		assert(&mut in_commands, slice::from_ref(&cmd_1), slice::from_ref(&cmd_1));
 	}
 
 	#[test]
@@ -218,9 +313,9 @@ mod tests {
 		let cmd_2 = create_test_command(ReliabilityGuaranteesChannel::ReliableOrdered(ChannelGroup(1), ChannelSequence(2)), 2);
 		let cmd_3 = create_test_command(ReliabilityGuaranteesChannel::ReliableOrdered(ChannelGroup(1), ChannelSequence(3)), 3);
 
-		assert(&mut in_commands, &[cmd_1.clone()], &[cmd_1]);
-		assert(&mut in_commands, &[cmd_3.clone()], &[cmd_3]);
-		assert(&mut in_commands, &[cmd_2.clone()], &[]);
+		assert(&mut in_commands, slice::from_ref(&cmd_1), slice::from_ref(&cmd_1));
+		assert(&mut in_commands, slice::from_ref(&cmd_3), slice::from_ref(&cmd_3));
+		assert(&mut in_commands, slice::from_ref(&cmd_2), &[]);
 	}
 
 	#[test]
@@ -230,8 +325,8 @@ mod tests {
 		let cmd_1 = create_test_command(ReliabilityGuaranteesChannel::ReliableOrdered(ChannelGroup(1), ChannelSequence(1)), 1);
 		let cmd_2 = create_test_command(ReliabilityGuaranteesChannel::ReliableOrdered(ChannelGroup(2), ChannelSequence(1)), 2);
 
-		assert(&mut in_commands, &[cmd_2.clone()], &[cmd_2]);
-		assert(&mut in_commands, &[cmd_1.clone()], &[cmd_1]);
+		assert(&mut in_commands, slice::from_ref(&cmd_2), slice::from_ref(&cmd_2));
+		assert(&mut in_commands, slice::from_ref(&cmd_1), slice::from_ref(&cmd_1));
 	}
 
 	#[test]
@@ -245,12 +340,12 @@ mod tests {
 		let cmd_5 = create_test_command(ReliabilityGuaranteesChannel::ReliableSequence(ChannelGroup(1), ChannelSequence(4)), 5);
 		let cmd_6 = create_test_command(ReliabilityGuaranteesChannel::ReliableSequence(ChannelGroup(1), ChannelSequence(5)), 5);
 
-		assert(&mut in_commands, &[cmd_3.clone()], &[]);
-		assert(&mut in_commands, &[cmd_1.clone()], &[cmd_1]);
-		assert(&mut in_commands, &[cmd_5.clone()], &[]);
-		assert(&mut in_commands, &[cmd_2.clone()], &[cmd_2, cmd_3]);
-		assert(&mut in_commands, &[cmd_4.clone()], &[cmd_4, cmd_5]);
-		assert(&mut in_commands, &[cmd_6.clone()], &[cmd_6]);
+		assert(&mut in_commands, slice::from_ref(&cmd_3), &[]);
+		assert(&mut in_commands, slice::from_ref(&cmd_1), slice::from_ref(&cmd_1));
+		assert(&mut in_commands, slice::from_ref(&cmd_5), &[]);
+		assert(&mut in_commands, slice::from_ref(&cmd_2), &[cmd_2.clone(), cmd_3.clone()]);
+		assert(&mut in_commands, slice::from_ref(&cmd_4), &[cmd_4.clone(), cmd_5.clone()]);
+		assert(&mut in_commands, slice::from_ref(&cmd_6), slice::from_ref(&cmd_6));
 	}
 
 	#[test]
@@ -264,21 +359,72 @@ mod tests {
 		let cmd_2_b = create_test_command(ReliabilityGuaranteesChannel::ReliableSequence(ChannelGroup(2), ChannelSequence(1)), 5);
 		let cmd_2_c = create_test_command(ReliabilityGuaranteesChannel::ReliableSequence(ChannelGroup(2), ChannelSequence(2)), 6);
 
-		assert(&mut in_commands, &[cmd_1_a.clone()], &[cmd_1_a]);
-		assert(&mut in_commands, &[cmd_2_b.clone()], &[]);
-		assert(&mut in_commands, &[cmd_1_c.clone()], &[]);
-		assert(&mut in_commands, &[cmd_2_a.clone()], &[cmd_2_a, cmd_2_b]);
-		assert(&mut in_commands, &[cmd_1_b.clone()], &[cmd_1_b, cmd_1_c]);
-		assert(&mut in_commands, &[cmd_2_c.clone()], &[cmd_2_c]);
+		assert(&mut in_commands, slice::from_ref(&cmd_1_a), slice::from_ref(&cmd_1_a));
+		assert(&mut in_commands, slice::from_ref(&cmd_2_b), &[]);
+		assert(&mut in_commands, slice::from_ref(&cmd_1_c), &[]);
+		assert(&mut in_commands, slice::from_ref(&cmd_2_a), &[cmd_2_a.clone(), cmd_2_b.clone()]);
+		assert(&mut in_commands, slice::from_ref(&cmd_1_b), &[cmd_1_b.clone(), cmd_1_c.clone()]);
+		assert(&mut in_commands, slice::from_ref(&cmd_2_c), slice::from_ref(&cmd_2_c));
+	}
+
+	#[test]
+	pub(crate) fn test_latest() {
+		let mut in_commands = InCommandsCollector::new(true);
+
+		let cmd_1 = create_test_command(ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(1)), 1);
+		let cmd_2 = create_test_command(ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(2)), 2);
+		let cmd_3 = create_test_object_command(ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(3)), 1, 3);
+
+		assert(&mut in_commands, slice::from_ref(&cmd_2), slice::from_ref(&cmd_2));
+		// устаревшее значение того же поля
+		assert(&mut in_commands, slice::from_ref(&cmd_1), &[]);
+		// повторная доставка
+		assert(&mut in_commands, slice::from_ref(&cmd_2), &[]);
+		// другой объект
+		assert(&mut in_commands, slice::from_ref(&cmd_3), slice::from_ref(&cmd_3));
+		assert_eq!(in_commands.latest_acks, vec![ChannelSequence(2), ChannelSequence(1), ChannelSequence(2), ChannelSequence(3)]);
+	}
+
+	#[test]
+	pub(crate) fn should_remove_latest_sequences_of_deleted_object() {
+		let mut in_commands = InCommandsCollector::new(true);
+		let cmd_1 = create_test_object_command(ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(1)), 1, 1);
+		let cmd_2 = create_test_object_command(ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(2)), 2, 2);
+		let delete = CommandWithReliabilityGuarantees {
+			reliability_guarantees: ReliabilityGuaranteesChannel::ReliableUnordered,
+			command: BothDirectionCommand::C2S(C2SCommand::Delete(GameObjectId::new(1, GameObjectOwner::Room))),
+		};
+		assert(&mut in_commands, &[cmd_1.clone(), cmd_2.clone()], &[cmd_1, cmd_2]);
+		assert_eq!(in_commands.latest_sequence_by_field.len(), 2);
+		assert(&mut in_commands, slice::from_ref(&delete), slice::from_ref(&delete));
+		assert_eq!(in_commands.latest_sequence_by_field.len(), 1);
 	}
 
 	fn assert(in_commands: &mut InCommandsCollector, commands: &[CommandWithReliabilityGuarantees], expect: &[CommandWithReliabilityGuarantees]) {
 		let mut data = [0; PACKET_SIZE];
-		let (size, _) = encode_commands(&mut commands.to_vec().into(), &mut data);
+		let size = encode(commands, &mut data);
 		in_commands.on_input_data(&data[0..size]);
 		assert_eq!(in_commands.get_ready_commands(), expect);
 	}
 
+	fn encode(commands: &[CommandWithReliabilityGuarantees], packet: &mut [u8]) -> usize {
+		packet[0] = 0;
+		let (size, _) = encode_commands(&mut commands.to_vec().into(), &mut packet[1..]);
+		size + 1
+	}
+
+	#[test]
+	pub(crate) fn test_compressed() {
+		let mut in_commands = InCommandsCollector::new(true);
+		let commands = vec![create_test_command(ReliabilityGuaranteesChannel::ReliableUnordered, 1); 100];
+		let mut packet = [0; PACKET_SIZE];
+		let size = encode(commands.as_slice(), &mut packet);
+		let size = PayloadCompressor::default().compress(&mut packet, size);
+		in_commands.on_input_data(&packet[0..size]);
+		assert_eq!(in_commands.get_ready_commands(), commands.as_slice());
+		assert!(in_commands.statistics.size < in_commands.statistics.raw_size);
+	}
+
 	fn create_test_command(channel: ReliabilityGuaranteesChannel, content: i64) -> CommandWithReliabilityGuarantees {
 		create_test_object_command(channel, 0, content)
 	}
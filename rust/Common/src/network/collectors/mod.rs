@@ -1,2 +1,3 @@
 pub mod in_collector;
 pub mod out_collector;
+pub mod payload;
@@ -1,42 +1,155 @@
+use cheetah_game_realtime_protocol::reliable::retransmit::RETRANSMIT_DEFAULT_ACK_TIMEOUT;
 use cheetah_game_realtime_protocol::OutputDataProducer;
 use std::collections::VecDeque;
+use std::time::Instant;
 
 use fnv::FnvHashMap;
 
 use crate::commands::codec::encoder::encode_commands;
 use crate::commands::guarantees::{ChannelGroup, ChannelSequence, ReliabilityGuarantees, ReliabilityGuaranteesChannel};
 use crate::commands::{BothDirectionCommand, CommandWithReliabilityGuarantees};
+use crate::network::collectors::in_collector::InCommandsCollector;
+use crate::network::collectors::payload::{encode_latest_acks, PayloadCompressor, PayloadStatistics, PAYLOAD_LATEST_ACKS, PAYLOAD_SERVER_SHUTDOWN};
 
 ///
 /// Коллектор команд для отправки
 ///
-/// - удаление дубликатов команд
+/// - удаление дубликатов команд для [`ReliabilityGuarantees::ReliableLatest`]
 /// - sequence команды
+/// - сжатие пакета с командами
+///
+/// Значения полей из канала [`ReliabilityGuarantees::ReliableLatest`] отправляются в ненадежных фреймах,
+/// коллектор сам повторяет отправку неподтвержденных значений, поэтому замененное значение не отправляется повторно.
+/// При наличии обеих очередей пакеты с обычными командами и со значениями ReliableLatest чередуются,
+/// чтобы постоянный поток обычных команд не задерживал отправку значений
 ///
 #[derive(Debug, Default)]
 pub struct OutCommandsCollector {
 	sequences: FnvHashMap<SequenceKey, ChannelSequence>,
 	commands: VecDeque<CommandWithReliabilityGuarantees>,
+	latest_commands: VecDeque<CommandWithReliabilityGuarantees>,
+	unacked_latest_commands: FnvHashMap<ChannelSequence, UnackedLatestCommand>,
+	///
+	/// Следующий пакет отдается значениям ReliableLatest, если они есть
+	///
+	latest_turn: bool,
+	latest_acks: Vec<ChannelSequence>,
+	now: Option<Instant>,
+	compression: bool,
+	compressor: PayloadCompressor,
+	server_shutdown: bool,
+	pub statistics: PayloadStatistics,
 }
 
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 pub struct SequenceKey(pub ReliabilityGuarantees, pub ChannelGroup);
 
+#[derive(Debug)]
+struct UnackedLatestCommand {
+	command: CommandWithReliabilityGuarantees,
+	sent_time: Option<Instant>,
+}
+
 impl OutputDataProducer for OutCommandsCollector {
 	fn contains_output_data(&self) -> bool {
-		!self.commands.is_empty()
+		!self.commands.is_empty() || !self.latest_commands.is_empty() || !self.latest_acks.is_empty()
 	}
 
 	fn get_output_data(&mut self, packet: &mut [u8]) -> (usize, bool) {
-		encode_commands(&mut self.commands, packet)
+		packet[0] = if self.server_shutdown { PAYLOAD_SERVER_SHUTDOWN } else { 0 };
+		let mut offset = 1;
+		if !self.latest_acks.is_empty() {
+			match encode_latest_acks(&mut self.latest_acks, &mut packet[1..]) {
+				Ok(size) => {
+					packet[0] |= PAYLOAD_LATEST_ACKS;
+					offset += size;
+				}
+				Err(e) => {
+					tracing::error!("Error encode latest acks {:?}", e);
+				}
+			}
+		}
+		let latest = !self.latest_commands.is_empty() && (self.latest_turn || self.commands.is_empty());
+		let (size, contains_reliability_command) = if latest {
+			// фрейм только со значениями ReliableLatest - ненадежный, повтор отправки выполняет коллектор
+			(self.encode_latest_commands(&mut packet[offset..]), false)
+		} else {
+			encode_commands(&mut self.commands, &mut packet[offset..])
+		};
+		self.latest_turn = !latest;
+		let raw_size = size + offset;
+		let size = if self.compression { self.compressor.compress(packet, raw_size) } else { raw_size };
+		self.statistics.raw_size += raw_size as u64;
+		self.statistics.size += size as u64;
+		(size, contains_reliability_command)
 	}
 
 	fn reset(&mut self) {
-		*self = Default::default();
+		*self = Self {
+			compression: self.compression,
+			compressor: std::mem::take(&mut self.compressor),
+			server_shutdown: self.server_shutdown,
+			statistics: self.statistics,
+			..Default::default()
+		};
 	}
 }
 
 impl OutCommandsCollector {
+	///
+	/// Сжимать пакеты с командами, принимающая сторона распаковывает их по флагу в заголовке пакета
+	///
+	pub fn set_compression(&mut self, compression: bool) {
+		self.compression = compression;
+	}
+
+	///
+	/// Отметить все следующие пакеты флагом остановки сервера
+	///
+	pub fn set_server_shutdown(&mut self) {
+		self.server_shutdown = true;
+	}
+
+	///
+	/// Количество команд, ожидающих отправки
+	///
+	#[must_use]
+	pub fn pending_commands_count(&self) -> usize {
+		self.commands.len() + self.latest_commands.len()
+	}
+
+	///
+	/// Повторить отправку значений ReliableLatest, для которых не получено подтверждение,
+	/// необходимо вызывать перед сбором исходящих фреймов протокола
+	///
+	pub fn cycle(&mut self, now: Instant) {
+		self.now = Some(now);
+		let mut expired = Vec::new();
+		for (sequence, unacked) in &mut self.unacked_latest_commands {
+			match unacked.sent_time {
+				None => unacked.sent_time = Some(now),
+				Some(sent_time) if now.duration_since(sent_time) >= RETRANSMIT_DEFAULT_ACK_TIMEOUT => expired.push(*sequence),
+				Some(_) => {}
+			}
+		}
+		for sequence in expired {
+			if let Some(unacked) = self.unacked_latest_commands.remove(&sequence) {
+				self.latest_commands.push_back(unacked.command);
+			}
+		}
+	}
+
+	///
+	/// Забрать подтверждения ReliableLatest из входящих пакетов: подтвердить удаленной стороне полученные значения
+	/// и перестать повторять отправку подтвержденных
+	///
+	pub fn on_input_collected(&mut self, in_commands: &mut InCommandsCollector) {
+		self.latest_acks.append(&mut in_commands.latest_acks);
+		for sequence in in_commands.acked_latest.drain(..) {
+			self.unacked_latest_commands.remove(&sequence);
+		}
+	}
+
 	pub fn add_command(&mut self, channel_type: ReliabilityGuarantees, command: BothDirectionCommand) {
 		tracing::debug!("OutCommandsCollector: {:?}", command);
 		match self.create_channel(channel_type) {
@@ -44,11 +157,19 @@ impl OutCommandsCollector {
 				tracing::error!("can not create channel for {:?} {:?}", channel_type, command);
 			}
 			Some(channel) => {
+				let latest = channel_type == ReliabilityGuarantees::ReliableLatest && command.get_replaced_field().is_some();
+				if latest {
+					self.remove_replaced_command(&command);
+				}
 				let command = CommandWithReliabilityGuarantees {
 					reliability_guarantees: channel,
 					command,
 				};
-				self.commands.push_back(command);
+				if latest {
+					self.latest_commands.push_back(command);
+				} else {
+					self.commands.push_back(command);
+				}
 			}
 		}
 	}
@@ -60,9 +181,37 @@ impl OutCommandsCollector {
 			ReliabilityGuarantees::UnreliableUnordered => Some(ReliabilityGuaranteesChannel::UnreliableUnordered),
 			ReliabilityGuarantees::UnreliableOrdered(group) => Some(ReliabilityGuaranteesChannel::UnreliableOrdered(group, self.next_sequence(channel_type, group))),
 			ReliabilityGuarantees::ReliableSequence(group) => Some(ReliabilityGuaranteesChannel::ReliableSequence(group, self.next_sequence(channel_type, group))),
+			ReliabilityGuarantees::ReliableLatest => Some(ReliabilityGuaranteesChannel::ReliableLatest(self.next_sequence(channel_type, ChannelGroup::default()))),
+		}
+	}
+
+	///
+	/// Удаляем ожидающие отправки и неподтвержденные значения поля, которые заменяются новой командой
+	///
+	fn remove_replaced_command(&mut self, command: &BothDirectionCommand) {
+		if let Some(field) = command.get_replaced_field() {
+			self.latest_commands.retain(|pending| pending.command.get_replaced_field() != Some(field));
+			self.unacked_latest_commands.retain(|_, unacked| unacked.command.command.get_replaced_field() != Some(field));
 		}
 	}
 
+	///
+	/// Отправленные значения ожидают подтверждения до повторной отправки в [`Self::cycle`]
+	///
+	fn encode_latest_commands(&mut self, packet: &mut [u8]) -> usize {
+		// encode_commands забирает команды из начала очереди, в пакет помещается не более 255 команд
+		let candidates: Vec<_> = self.latest_commands.iter().take(u8::MAX as usize).cloned().collect();
+		let pending_count = self.latest_commands.len();
+		let (size, _) = encode_commands(&mut self.latest_commands, packet);
+		let sent_count = pending_count - self.latest_commands.len();
+		for command in candidates.into_iter().take(sent_count) {
+			if let ReliabilityGuaranteesChannel::ReliableLatest(sequence) = command.reliability_guarantees {
+				self.unacked_latest_commands.insert(sequence, UnackedLatestCommand { command, sent_time: self.now });
+			}
+		}
+		size
+	}
+
 	fn next_sequence(&mut self, guarantees: ReliabilityGuarantees, group: ChannelGroup) -> ChannelSequence {
 		let key = SequenceKey(guarantees, group);
 		let mut sequence = self.sequences.get_mut(&key);
@@ -83,11 +232,16 @@ mod tests {
 	use crate::commands::c2s::C2SCommand::IncrementLongValue;
 	use crate::commands::codec::decoder::decode_commands;
 	use crate::commands::guarantees::{ChannelGroup, ChannelSequence, ReliabilityGuarantees};
-	use crate::commands::types::long::IncrementLong;
+	use crate::commands::types::long::{IncrementLong, LongField};
 	use crate::commands::BothDirectionCommand;
+	use crate::network::collectors::in_collector::InCommandsCollector;
 	use crate::network::collectors::out_collector::*;
+	use crate::network::collectors::payload::decompress_payload;
 	use crate::room::field::FieldId;
 	use cheetah_game_realtime_protocol::frame::packets_collector::PACKET_SIZE;
+	use cheetah_game_realtime_protocol::reliable::retransmit::RETRANSMIT_DEFAULT_ACK_TIMEOUT;
+	use cheetah_game_realtime_protocol::InputDataHandler;
+	use std::time::Instant;
 
 	#[test]
 	fn test_group_sequence() {
@@ -129,7 +283,7 @@ mod tests {
 		collector.add_command(ReliabilityGuarantees::UnreliableUnordered, BothDirectionCommand::C2S(C2SCommand::AttachToRoom));
 		let mut packet = [0; PACKET_SIZE];
 		let (size, _) = collector.get_output_data(&mut packet);
-		let commands = decode_commands(true, &packet[0..size]).unwrap();
+		let commands = decode_commands(true, &packet[1..size]).unwrap();
 		assert_eq!(
 			commands,
 			vec![CommandWithReliabilityGuarantees {
@@ -139,6 +293,15 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn should_mark_packet_with_server_shutdown() {
+		let mut collector = OutCommandsCollector::default();
+		collector.set_server_shutdown();
+		let mut packet = [0; PACKET_SIZE];
+		collector.get_output_data(&mut packet);
+		assert_eq!(packet[0], PAYLOAD_SERVER_SHUTDOWN);
+	}
+
 	#[test]
 	fn should_ordered_commands() {
 		let mut collector = OutCommandsCollector::default();
@@ -172,9 +335,9 @@ mod tests {
 		}
 		let commands = send_and_receive(&mut collector);
 
-		for i in 0..COUNT {
+		for (i, command) in commands.iter().enumerate() {
 			assert_eq!(
-				commands[i],
+				*command,
 				CommandWithReliabilityGuarantees {
 					reliability_guarantees: ReliabilityGuaranteesChannel::UnreliableUnordered,
 					command: create_command(i),
@@ -183,18 +346,193 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn should_replace_latest_value() {
+		let mut collector = OutCommandsCollector::default();
+		for value in 0..10 {
+			collector.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(1, value));
+		}
+		collector.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(2, 100));
+
+		let commands = send_and_receive(&mut collector);
+		assert_eq!(
+			commands,
+			vec![
+				CommandWithReliabilityGuarantees {
+					reliability_guarantees: ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(9)),
+					command: create_set_long_command(1, 9),
+				},
+				CommandWithReliabilityGuarantees {
+					reliability_guarantees: ReliabilityGuaranteesChannel::ReliableLatest(ChannelSequence(10)),
+					command: create_set_long_command(2, 100),
+				}
+			]
+		);
+	}
+
+	///
+	/// Команды не заменяющие значение поля (например инкремент) не удаляются
+	///
+	#[test]
+	fn should_not_replace_increment_in_latest_channel() {
+		let mut collector = OutCommandsCollector::default();
+		collector.add_command(ReliabilityGuarantees::ReliableLatest, create_command(1));
+		collector.add_command(ReliabilityGuarantees::ReliableLatest, create_command(1));
+		assert_eq!(send_and_receive(&mut collector).len(), 2);
+	}
+
+	///
+	/// Команды из других каналов не заменяются
+	///
+	#[test]
+	fn should_not_replace_command_from_other_channel() {
+		let mut collector = OutCommandsCollector::default();
+		collector.add_command(ReliabilityGuarantees::ReliableSequence(ChannelGroup(0)), create_set_long_command(1, 1));
+		collector.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(1, 2));
+		assert_eq!(send_and_receive(&mut collector).len(), 2);
+	}
+
+	///
+	/// Пакет только со значениями ReliableLatest отправляется ненадежным фреймом
+	///
+	#[test]
+	fn should_send_latest_values_in_unreliable_frame() {
+		let mut collector = OutCommandsCollector::default();
+		collector.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(1, 1));
+		let mut packet = [0; PACKET_SIZE];
+		let (_, reliability) = collector.get_output_data(&mut packet);
+		assert!(!reliability);
+	}
+
+	///
+	/// Неподтвержденное значение отправляется повторно, если оно не было заменено новым значением
+	///
+	#[test]
+	fn should_resend_only_latest_unacked_value() {
+		let mut collector = OutCommandsCollector::default();
+		let now = Instant::now();
+		collector.cycle(now);
+		collector.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(1, 1));
+		collector.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(2, 1));
+		assert_eq!(send_and_receive(&mut collector).len(), 2);
+		collector.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(1, 2));
+		assert_eq!(send_and_receive(&mut collector).len(), 1);
+
+		collector.cycle(now + RETRANSMIT_DEFAULT_ACK_TIMEOUT);
+		let commands: Vec<_> = send_and_receive(&mut collector).into_iter().map(|command| command.command).collect();
+		assert_eq!(commands.len(), 2);
+		assert!(commands.contains(&create_set_long_command(1, 2)));
+		assert!(commands.contains(&create_set_long_command(2, 1)));
+	}
+
+	#[test]
+	fn should_not_resend_acked_latest_value() {
+		let mut sender = OutCommandsCollector::default();
+		let mut sender_in = InCommandsCollector::new(false);
+		let mut receiver = OutCommandsCollector::default();
+		let mut receiver_in = InCommandsCollector::new(true);
+		let now = Instant::now();
+		sender.cycle(now);
+		sender.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(1, 1));
+
+		let mut packet = [0; PACKET_SIZE];
+		let (size, _) = sender.get_output_data(&mut packet);
+		receiver_in.on_input_data(&packet[0..size]);
+		assert_eq!(receiver_in.get_ready_commands().len(), 1);
+		receiver.on_input_collected(&mut receiver_in);
+
+		assert!(receiver.contains_output_data());
+		let (size, _) = receiver.get_output_data(&mut packet);
+		sender_in.on_input_data(&packet[0..size]);
+		sender.on_input_collected(&mut sender_in);
+
+		sender.cycle(now + RETRANSMIT_DEFAULT_ACK_TIMEOUT);
+		assert!(!sender.contains_output_data());
+	}
+
+	///
+	/// Значения ReliableLatest доставляются и подтверждаются при постоянном потоке обычных команд
+	///
+	#[test]
+	fn should_not_starve_latest_values_with_regular_commands_every_cycle() {
+		let mut sender = OutCommandsCollector::default();
+		let mut sender_in = InCommandsCollector::new(false);
+		let mut receiver = OutCommandsCollector::default();
+		let mut receiver_in = InCommandsCollector::new(true);
+		let now = Instant::now();
+		sender.cycle(now);
+		sender.add_command(ReliabilityGuarantees::ReliableLatest, create_set_long_command(1, 1));
+
+		let mut packet = [0; PACKET_SIZE];
+		let mut received_latest = Vec::new();
+		for i in 0..4 {
+			sender.add_command(ReliabilityGuarantees::ReliableOrdered(ChannelGroup(0)), create_command(i));
+			let (size, _) = sender.get_output_data(&mut packet);
+			receiver_in.on_input_data(&packet[0..size]);
+			received_latest.extend(
+				receiver_in
+					.get_ready_commands()
+					.iter()
+					.filter(|command| matches!(command.reliability_guarantees, ReliabilityGuaranteesChannel::ReliableLatest(_)))
+					.map(|command| command.command.clone()),
+			);
+			receiver.on_input_collected(&mut receiver_in);
+			while receiver.contains_output_data() {
+				let (size, _) = receiver.get_output_data(&mut packet);
+				sender_in.on_input_data(&packet[0..size]);
+			}
+			sender.on_input_collected(&mut sender_in);
+		}
+		assert_eq!(received_latest, vec![create_set_long_command(1, 1)]);
+
+		sender.cycle(now + RETRANSMIT_DEFAULT_ACK_TIMEOUT);
+		assert!(sender.latest_commands.is_empty());
+		assert!(sender.unacked_latest_commands.is_empty());
+	}
+
+	#[test]
+	fn should_compress() {
+		let mut collector = OutCommandsCollector::default();
+		collector.set_compression(true);
+		for _ in 0..100 {
+			collector.add_command(ReliabilityGuarantees::UnreliableUnordered, create_command(1));
+		}
+		let commands = send_and_receive(&mut collector);
+		assert_eq!(commands.len(), 100);
+		assert!(collector.statistics.size < collector.statistics.raw_size);
+	}
+
+	#[test]
+	fn should_not_compress_when_disabled() {
+		let mut collector = OutCommandsCollector::default();
+		for _ in 0..100 {
+			collector.add_command(ReliabilityGuarantees::UnreliableUnordered, create_command(1));
+		}
+		send_and_receive(&mut collector);
+		assert_eq!(collector.statistics.size, collector.statistics.raw_size);
+	}
+
 	fn send_and_receive(collector: &mut OutCommandsCollector) -> Vec<CommandWithReliabilityGuarantees> {
 		let mut commands = Vec::new();
 		while collector.contains_output_data() {
 			let mut packet = [0; PACKET_SIZE];
 			let (size, _) = collector.get_output_data(&mut packet);
-			for command in decode_commands(true, &packet[0..size]).unwrap() {
+			let mut buffer = Vec::new();
+			for command in decode_commands(true, decompress_payload(&packet[0..size], &mut buffer).unwrap()).unwrap() {
 				commands.push(command);
 			}
 		}
 		commands
 	}
 
+	fn create_set_long_command(field_id: FieldId, value: i64) -> BothDirectionCommand {
+		BothDirectionCommand::C2S(C2SCommand::SetLong(LongField {
+			object_id: Default::default(),
+			field_id,
+			value,
+		}))
+	}
+
 	fn create_command(i: usize) -> BothDirectionCommand {
 		BothDirectionCommand::C2S(IncrementLongValue(IncrementLong {
 			object_id: Default::default(),
@@ -0,0 +1,197 @@
+//!
+//! Формат пакета с командами: первый байт - флаги, далее подтверждения ReliableLatest (по флагу) и команды.
+//!
+//! Байт флагов несовместим с предыдущим форматом (пакет начинался сразу с команд), клиенты и сервер
+//! с разными форматами не понимают друг друга и должны обновляться одновременно.
+//!
+//! Пакет сжимается целиком до разбиения протоколом на сегменты, посегментное сжатие протокола
+//! видит только [`cheetah_game_realtime_protocol::frame::segment::SEGMENT_SIZE`] байт и для структур почти не работает,
+//! см. `benches/payload_compression.rs`.
+//!
+use cheetah_game_realtime_protocol::codec::variable_int::{VariableIntReader, VariableIntWriter};
+use cheetah_game_realtime_protocol::frame::packets_collector::PACKET_SIZE;
+use std::io::{Cursor, Error, ErrorKind};
+use thiserror::Error;
+
+use crate::commands::guarantees::ChannelSequence;
+
+///
+/// Флаги заголовка пакета с командами (первый байт пакета), команды сжаты
+///
+pub(crate) const PAYLOAD_COMPRESSED: u8 = 1;
+///
+/// Сервер закрывает соединение из-за остановки, протокол не содержит такой причины разрыва соединения,
+/// поэтому она передается флагом вместе с заголовком разрыва соединения
+///
+pub(crate) const PAYLOAD_SERVER_SHUTDOWN: u8 = 2;
+///
+/// Перед командами передаются подтверждения получения команд канала ReliableLatest
+///
+pub(crate) const PAYLOAD_LATEST_ACKS: u8 = 4;
+
+///
+/// Максимальное количество подтверждений ReliableLatest в одном пакете, остальные отправляются в следующих пакетах
+///
+const MAX_LATEST_ACKS_IN_PAYLOAD: usize = 64;
+
+///
+/// Статистика размера пакетов с командами до и после сжатия
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadStatistics {
+	///
+	/// Размер команд без сжатия
+	///
+	pub raw_size: u64,
+	///
+	/// Фактический размер переданных данных
+	///
+	pub size: u64,
+}
+
+#[derive(Error, Debug)]
+pub enum PayloadDecodeError {
+	#[error("Empty payload")]
+	Empty,
+	#[error("Decompress error {0}")]
+	Decompress(#[from] snap::Error),
+	#[error("Decompressed payload is too large {0}")]
+	TooLarge(usize),
+}
+
+///
+/// Сжатие команд пакета, кодировщик и буфер переиспользуются между пакетами
+///
+pub(crate) struct PayloadCompressor {
+	encoder: snap::raw::Encoder,
+	buffer: Vec<u8>,
+}
+
+impl Default for PayloadCompressor {
+	fn default() -> Self {
+		Self {
+			encoder: snap::raw::Encoder::new(),
+			buffer: Vec::new(),
+		}
+	}
+}
+
+impl std::fmt::Debug for PayloadCompressor {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("PayloadCompressor").finish_non_exhaustive()
+	}
+}
+
+impl PayloadCompressor {
+	///
+	/// Сжать команды в packet[1..size], если сжатие уменьшает размер
+	///
+	/// - packet[0] - флаги
+	/// - возвращает итоговый размер пакета
+	///
+	pub(crate) fn compress(&mut self, packet: &mut [u8], size: usize) -> usize {
+		self.buffer.resize(snap::raw::max_compress_len(PACKET_SIZE), 0);
+		match self.encoder.compress(&packet[1..size], &mut self.buffer) {
+			Ok(compressed_size) if compressed_size + 1 < size => {
+				packet[0] |= PAYLOAD_COMPRESSED;
+				packet[1..compressed_size + 1].copy_from_slice(&self.buffer[0..compressed_size]);
+				compressed_size + 1
+			}
+			Ok(_) => size,
+			Err(e) => {
+				tracing::error!("Compress payload error {:?}", e);
+				size
+			}
+		}
+	}
+}
+
+///
+/// Получить команды из пакета, при необходимости распаковать
+///
+pub(crate) fn decompress_payload<'a>(packet: &'a [u8], buffer: &'a mut Vec<u8>) -> Result<&'a [u8], PayloadDecodeError> {
+	let flags = *packet.first().ok_or(PayloadDecodeError::Empty)?;
+	if flags & PAYLOAD_COMPRESSED == 0 {
+		return Ok(&packet[1..]);
+	}
+	let size = snap::raw::decompress_len(&packet[1..])?;
+	if size > PACKET_SIZE {
+		return Err(PayloadDecodeError::TooLarge(size));
+	}
+	buffer.resize(size, 0);
+	let size = snap::raw::Decoder::new().decompress(&packet[1..], buffer.as_mut_slice())?;
+	Ok(&buffer[0..size])
+}
+
+///
+/// Записать подтверждения ReliableLatest (не более [`MAX_LATEST_ACKS_IN_PAYLOAD`]), записанные подтверждения удаляются из acks
+///
+/// - возвращает размер записанных данных
+///
+pub(crate) fn encode_latest_acks(acks: &mut Vec<ChannelSequence>, out: &mut [u8]) -> std::io::Result<usize> {
+	let mut cursor = Cursor::new(out);
+	let count = acks.len().min(MAX_LATEST_ACKS_IN_PAYLOAD);
+	cursor.write_variable_u64(count as u64)?;
+	for sequence in acks.drain(0..count) {
+		cursor.write_variable_u64(u64::from(sequence.0))?;
+	}
+	Ok(cursor.position() as usize)
+}
+
+pub(crate) fn decode_latest_acks(input: &mut Cursor<&[u8]>, out: &mut Vec<ChannelSequence>) -> std::io::Result<()> {
+	let count = input.read_variable_u64()?;
+	if count > MAX_LATEST_ACKS_IN_PAYLOAD as u64 {
+		return Err(Error::new(ErrorKind::InvalidData, format!("Too many latest acks {count}")));
+	}
+	for _ in 0..count {
+		let sequence = input.read_variable_u64()?.try_into().map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+		out.push(ChannelSequence(sequence));
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use crate::commands::guarantees::ChannelSequence;
+	use crate::network::collectors::payload::{decode_latest_acks, decompress_payload, encode_latest_acks, PayloadCompressor, MAX_LATEST_ACKS_IN_PAYLOAD, PAYLOAD_COMPRESSED};
+
+	#[test]
+	fn should_compress_payload() {
+		let mut packet = vec![0; 1000];
+		packet[1..].fill(7);
+		let original = packet.clone();
+		let size = PayloadCompressor::default().compress(&mut packet, original.len());
+		assert!(size < original.len());
+		assert_eq!(packet[0], PAYLOAD_COMPRESSED);
+
+		let mut buffer = Vec::new();
+		assert_eq!(decompress_payload(&packet[0..size], &mut buffer).unwrap(), &original[1..]);
+	}
+
+	#[test]
+	fn should_not_compress_when_not_effective() {
+		let mut packet: Vec<u8> = (0..100).map(|_| rand::random()).collect();
+		packet[0] = 0;
+		let original = packet.clone();
+		let size = PayloadCompressor::default().compress(&mut packet, original.len());
+		assert_eq!(size, original.len());
+		assert_eq!(packet, original);
+
+		let mut buffer = Vec::new();
+		assert_eq!(decompress_payload(&packet[0..size], &mut buffer).unwrap(), &original[1..]);
+	}
+
+	#[test]
+	fn should_encode_limited_latest_acks() {
+		let mut acks: Vec<ChannelSequence> = (0..MAX_LATEST_ACKS_IN_PAYLOAD as u32 + 10).map(ChannelSequence).collect();
+		let mut buffer = [0; 1024];
+		let size = encode_latest_acks(&mut acks, &mut buffer).unwrap();
+		assert_eq!(acks.len(), 10);
+
+		let mut decoded = Vec::new();
+		decode_latest_acks(&mut Cursor::new(&buffer[0..size]), &mut decoded).unwrap();
+		assert_eq!(decoded, (0..MAX_LATEST_ACKS_IN_PAYLOAD as u32).map(ChannelSequence).collect::<Vec<_>>());
+	}
+}
@@ -1,9 +1,12 @@
-use rand::random;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 use std::net::SocketAddr;
 use std::ops::{Add, Div, Sub};
+use std::str::FromStr;
 use std::time::{Duration, Instant};
+use thiserror::Error;
 
 ///
 /// Эмуляция характеристик сети
@@ -26,57 +29,166 @@ pub struct NetworkLatencyEmulator {
 	/// Процент случайности в RTT - 0..1
 	///
 	rtt_dispersion: Option<f64>,
+	///
+	/// Ограничение пропускной способности канала
+	///
+	bandwidth: Option<Bandwidth>,
+	///
+	/// Вероятность дублирования пакета
+	///
+	duplicate_probability: Option<f64>,
+	///
+	/// Вероятность задержки пакета на заданное время, пакеты отправленные позже обгоняют задержанный пакет
+	///
+	reorder: Option<(f64, Duration)>,
+	///
+	/// Потеря пакетов сериями
+	///
+	burst_loss: Option<BurstLoss>,
 
 	///
 	/// Время начала потери пакетов (отказа сети)
 	///
 	drop_start: Option<Instant>,
-	out_queue: BinaryHeap<BinaryFrame>,
-	in_queue: BinaryHeap<BinaryFrame>,
+	out_direction: EmulatedDirection,
+	in_direction: EmulatedDirection,
+	frame_sequence: u64,
+	rng: EmulatorRng,
+}
+
+///
+/// Генератор случайных чисел эмулятора, по-умолчанию инициализируется случайным значением
+///
+#[derive(Debug)]
+struct EmulatorRng(StdRng);
+
+///
+/// Пропускная способность канала, пакеты передаются последовательно,
+/// пакеты не помещающиеся в очередь на передачу отбрасываются
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bandwidth {
+	pub bytes_per_second: u64,
+	pub queue_size_in_bytes: u64,
+}
+
+///
+/// Модель Гилберта-Эллиота: канал переходит между хорошим и плохим состоянием,
+/// в каждом состоянии своя вероятность потери пакета, что дает потери сериями
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurstLoss {
+	///
+	/// Вероятность перехода из хорошего состояния в плохое (для каждого пакета)
+	///
+	pub good_to_bad_probability: f64,
+	///
+	/// Вероятность перехода из плохого состояния в хорошее (для каждого пакета)
+	///
+	pub bad_to_good_probability: f64,
+	pub good_loss_probability: f64,
+	pub bad_loss_probability: f64,
+}
+
+///
+/// Типичные характеристики сетей
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkConditionsPreset {
+	Wifi,
+	///
+	/// Перегруженный Wi-Fi - большой разброс RTT, потери сериями, дублирование и перестановка пакетов
+	///
+	CongestedWifi,
+	Lte,
+	ThreeG,
+	Edge,
+}
+
+#[derive(Error, Debug)]
+#[error("Unknown network conditions preset {0}")]
+pub struct UnknownNetworkConditionsPreset(String);
+
+#[derive(Error, Debug)]
+#[error("Wrong emulation probability {0}, expected value from 0 to 1")]
+pub struct WrongEmulationProbability(pub f64);
+
+///
+/// Вероятность для параметров эмуляции, допустимы значения от 0 до 1
+///
+pub fn check_probability(probability: f64) -> Result<(), WrongEmulationProbability> {
+	if (0.0..=1.0).contains(&probability) {
+		Ok(())
+	} else {
+		Err(WrongEmulationProbability(probability))
+	}
+}
+
+///
+/// Состояние одного направления передачи
+///
+#[derive(Debug, Default)]
+struct EmulatedDirection {
+	queue: BinaryHeap<BinaryFrame>,
+	///
+	/// Время окончания передачи последнего пакета при ограничении пропускной способности
+	///
+	link_busy_until: Option<Instant>,
+	burst_loss_bad_state: bool,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct BinaryFrame {
 	time: Instant,
+	///
+	/// Порядковый номер, для сохранения порядка пакетов с одинаковым временем
+	///
+	sequence: u64,
 	addr: Option<SocketAddr>,
 	buffer: Vec<u8>,
 }
 
 impl Ord for BinaryFrame {
 	fn cmp(&self, other: &Self) -> Ordering {
-		other.time.cmp(&self.time)
+		other.time.cmp(&self.time).then_with(|| other.sequence.cmp(&self.sequence))
 	}
 }
 
 impl PartialOrd for BinaryFrame {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-		other.time.partial_cmp(&self.time)
+		Some(self.cmp(other))
+	}
+}
+
+impl Default for EmulatorRng {
+	fn default() -> Self {
+		Self(StdRng::from_entropy())
 	}
 }
 
 impl NetworkLatencyEmulator {
+	///
+	/// Задать начальное значение генератора случайных чисел, для повторяемых тестов
+	///
+	pub fn set_seed(&mut self, seed: u64) {
+		self.rng = EmulatorRng(StdRng::seed_from_u64(seed));
+	}
+
 	///
 	/// Получаем данные из сокета и решаем отдавать ли их или использовать очередь для эмуляции характеристик сети
 	///
 	pub fn schedule_in(&mut self, now: Instant, buffer: &[u8]) {
-		if !self.check_drop_time(now) {
-			let time = self.get_schedule_time(now);
-			self.in_queue.push(BinaryFrame {
-				time,
-				addr: None,
-				buffer: buffer.to_vec(),
-			});
-		}
+		self.schedule(now, buffer, None);
 	}
 
 	///
 	/// Получить данные для клиента с учетом всех параметров эмуляции
 	///
 	pub fn get_in(&mut self, now: Instant) -> Option<Vec<u8>> {
-		match self.in_queue.peek() {
+		match self.in_direction.queue.peek() {
 			None => None,
 			Some(frame) => (now >= frame.time).then(|| {
-				let frame = self.in_queue.pop().unwrap();
+				let frame = self.in_direction.queue.pop().unwrap();
 				frame.buffer
 			}),
 		}
@@ -86,36 +198,72 @@ impl NetworkLatencyEmulator {
 	/// Сохраняем данные для отправки, реальная отправка происходит с учетом всех характеристик эмулируемой сети
 	///
 	pub fn schedule_out(&mut self, now: Instant, buffer: &[u8], addr: SocketAddr) {
-		if !self.check_drop_time(now) {
-			let time = self.get_schedule_time(now);
-			self.out_queue.push(BinaryFrame {
-				time,
-				addr: Some(addr),
-				buffer: buffer.to_vec(),
-			});
-		}
+		self.schedule(now, buffer, Some(addr));
 	}
 
 	///
 	/// Получаем данные для отправки в реальный сокет
 	///
 	pub fn get_out(&mut self, now: Instant) -> Option<(Vec<u8>, SocketAddr)> {
-		match self.out_queue.peek() {
+		match self.out_direction.queue.peek() {
 			None => None,
 			Some(data) => (now >= data.time).then(|| {
-				let data = self.out_queue.pop().unwrap();
-				(data.buffer.clone(), data.addr.unwrap())
+				let data = self.out_direction.queue.pop().unwrap();
+				(data.buffer, data.addr.unwrap())
 			}),
 		}
 	}
 
+	///
+	/// addr задан только для исходящих пакетов
+	///
+	fn schedule(&mut self, now: Instant, buffer: &[u8], addr: Option<SocketAddr>) {
+		if self.check_drop_time(now) {
+			return;
+		}
+		let mut time = self.get_schedule_time(now);
+		let direction = match addr {
+			None => &mut self.in_direction,
+			Some(_) => &mut self.out_direction,
+		};
+		if let Some(burst_loss) = &self.burst_loss {
+			if direction.check_burst_loss(burst_loss, &mut self.rng.0) {
+				return;
+			}
+		}
+		if let Some(bandwidth) = &self.bandwidth {
+			match direction.transmit(now, buffer.len(), bandwidth) {
+				None => return,
+				Some(transmit_time) => time = time.add(transmit_time),
+			}
+		}
+		if let Some((reorder_probability, reorder_delay)) = self.reorder {
+			if reorder_probability > self.rng.0.gen() {
+				time = time.add(reorder_delay);
+			}
+		}
+		let copies = match self.duplicate_probability {
+			Some(duplicate_probability) if duplicate_probability > self.rng.0.gen() => 2,
+			_ => 1,
+		};
+		for _ in 0..copies {
+			self.frame_sequence += 1;
+			direction.queue.push(BinaryFrame {
+				time,
+				sequence: self.frame_sequence,
+				addr,
+				buffer: buffer.to_vec(),
+			});
+		}
+	}
+
 	fn get_schedule_time(&mut self, now: Instant) -> Instant {
 		let rtt = *self.rtt.as_ref().unwrap_or(&Duration::from_millis(0));
 		let half_rtt = rtt.div(2);
 		let mut time = now.add(half_rtt);
-		let rand: f64 = random();
+		let rand: f64 = self.rng.0.gen();
 		let delta = half_rtt.mul_f64(self.rtt_dispersion.unwrap_or(0.0) * rand);
-		if random() {
+		if self.rng.0.gen() {
 			time = time.add(delta);
 		} else {
 			time = time.sub(delta);
@@ -132,15 +280,11 @@ impl NetworkLatencyEmulator {
 		};
 
 		match self.drop_probability {
-			None => false,
-			Some(drop_probability) => {
-				if drop_probability > random() {
-					self.drop_start = Some(now);
-					true
-				} else {
-					false
-				}
+			Some(drop_probability) if drop_probability > self.rng.0.gen() => {
+				self.drop_start = Some(now);
+				true
 			}
+			_ => false,
 		}
 	}
 
@@ -153,6 +297,170 @@ impl NetworkLatencyEmulator {
 		self.drop_probability = Some(drop_probability);
 		self.drop_time = Some(drop_time);
 	}
+
+	pub fn configure_bandwidth(&mut self, bandwidth: Bandwidth) {
+		self.bandwidth = Some(bandwidth);
+	}
+
+	pub fn configure_duplicate(&mut self, duplicate_probability: f64) {
+		self.duplicate_probability = Some(duplicate_probability);
+	}
+
+	pub fn configure_reorder(&mut self, reorder_probability: f64, reorder_delay: Duration) {
+		self.reorder = Some((reorder_probability, reorder_delay));
+	}
+
+	pub fn configure_burst_loss(&mut self, burst_loss: BurstLoss) {
+		self.burst_loss = Some(burst_loss);
+	}
+
+	///
+	/// Заменить все параметры эмуляции параметрами типичной сети, пакеты в очередях сохраняются
+	///
+	pub fn configure_preset(&mut self, preset: NetworkConditionsPreset) {
+		self.drop_probability = None;
+		self.drop_time = None;
+		self.drop_start = None;
+		self.bandwidth = None;
+		self.duplicate_probability = None;
+		self.reorder = None;
+		self.burst_loss = None;
+		match preset {
+			NetworkConditionsPreset::Wifi => {
+				self.configure_rtt(Duration::from_millis(10), 0.2);
+			}
+			NetworkConditionsPreset::CongestedWifi => {
+				self.configure_rtt(Duration::from_millis(80), 0.6);
+				self.configure_bandwidth(Bandwidth::from_kbits(2_000, 16 * 1024));
+				self.configure_burst_loss(BurstLoss {
+					good_to_bad_probability: 0.02,
+					bad_to_good_probability: 0.25,
+					good_loss_probability: 0.005,
+					bad_loss_probability: 0.4,
+				});
+				self.configure_duplicate(0.01);
+				self.configure_reorder(0.05, Duration::from_millis(30));
+			}
+			NetworkConditionsPreset::Lte => {
+				self.configure_rtt(Duration::from_millis(50), 0.2);
+				self.configure_bandwidth(Bandwidth::from_kbits(10_000, 64 * 1024));
+				self.configure_burst_loss(BurstLoss {
+					good_to_bad_probability: 0.005,
+					bad_to_good_probability: 0.5,
+					good_loss_probability: 0.0,
+					bad_loss_probability: 0.5,
+				});
+				self.configure_reorder(0.001, Duration::from_millis(20));
+			}
+			NetworkConditionsPreset::ThreeG => {
+				self.configure_rtt(Duration::from_millis(200), 0.3);
+				self.configure_bandwidth(Bandwidth::from_kbits(750, 32 * 1024));
+				self.configure_burst_loss(BurstLoss {
+					good_to_bad_probability: 0.01,
+					bad_to_good_probability: 0.3,
+					good_loss_probability: 0.0,
+					bad_loss_probability: 0.5,
+				});
+				self.configure_duplicate(0.001);
+				self.configure_reorder(0.01, Duration::from_millis(50));
+			}
+			NetworkConditionsPreset::Edge => {
+				self.configure_rtt(Duration::from_millis(500), 0.3);
+				self.configure_bandwidth(Bandwidth::from_kbits(240, 16 * 1024));
+				self.configure_burst_loss(BurstLoss {
+					good_to_bad_probability: 0.02,
+					bad_to_good_probability: 0.2,
+					good_loss_probability: 0.005,
+					bad_loss_probability: 0.7,
+				});
+				self.configure_reorder(0.02, Duration::from_millis(100));
+			}
+		}
+	}
+}
+
+impl EmulatedDirection {
+	///
+	/// Переход между состояниями и решение о потере пакета
+	///
+	fn check_burst_loss(&mut self, burst_loss: &BurstLoss, rng: &mut StdRng) -> bool {
+		let transition_probability = if self.burst_loss_bad_state {
+			burst_loss.bad_to_good_probability
+		} else {
+			burst_loss.good_to_bad_probability
+		};
+		if transition_probability > rng.gen() {
+			self.burst_loss_bad_state = !self.burst_loss_bad_state;
+		}
+		let loss_probability = if self.burst_loss_bad_state {
+			burst_loss.bad_loss_probability
+		} else {
+			burst_loss.good_loss_probability
+		};
+		loss_probability > rng.gen()
+	}
+
+	///
+	/// Поставить пакет в очередь на передачу, возвращает задержку до окончания передачи или None если очередь переполнена
+	///
+	fn transmit(&mut self, now: Instant, size: usize, bandwidth: &Bandwidth) -> Option<Duration> {
+		let bytes_per_second = bandwidth.bytes_per_second.max(1) as f64;
+		let busy_until = self.link_busy_until.filter(|time| *time > now).unwrap_or(now);
+		let backlog = busy_until.duration_since(now).as_secs_f64() * bytes_per_second;
+		if backlog + size as f64 > bandwidth.queue_size_in_bytes as f64 {
+			return None;
+		}
+		let busy_until = busy_until.add(Duration::from_secs_f64(size as f64 / bytes_per_second));
+		self.link_busy_until = Some(busy_until);
+		Some(busy_until.duration_since(now))
+	}
+}
+
+impl Bandwidth {
+	#[must_use]
+	pub fn from_kbits(kbits_per_second: u64, queue_size_in_bytes: u64) -> Self {
+		Self {
+			bytes_per_second: kbits_per_second * 1000 / 8,
+			queue_size_in_bytes,
+		}
+	}
+}
+
+impl NetworkConditionsPreset {
+	pub const ALL: [NetworkConditionsPreset; 5] = [Self::Wifi, Self::CongestedWifi, Self::Lte, Self::ThreeG, Self::Edge];
+
+	#[must_use]
+	pub fn name(&self) -> &'static str {
+		match self {
+			NetworkConditionsPreset::Wifi => "wifi",
+			NetworkConditionsPreset::CongestedWifi => "congested-wifi",
+			NetworkConditionsPreset::Lte => "lte",
+			NetworkConditionsPreset::ThreeG => "3g",
+			NetworkConditionsPreset::Edge => "edge",
+		}
+	}
+}
+
+///
+/// Преобразование из номера пресета, порядок совпадает с [`NetworkConditionsPreset::ALL`]
+///
+impl TryFrom<u8> for NetworkConditionsPreset {
+	type Error = UnknownNetworkConditionsPreset;
+
+	fn try_from(value: u8) -> Result<Self, Self::Error> {
+		Self::ALL.get(usize::from(value)).copied().ok_or_else(|| UnknownNetworkConditionsPreset(value.to_string()))
+	}
+}
+
+impl FromStr for NetworkConditionsPreset {
+	type Err = UnknownNetworkConditionsPreset;
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		Self::ALL
+			.into_iter()
+			.find(|preset| preset.name().eq_ignore_ascii_case(name))
+			.ok_or_else(|| UnknownNetworkConditionsPreset(name.to_owned()))
+	}
 }
 
 #[cfg(test)]
@@ -162,7 +470,7 @@ mod tests {
 	use std::str::FromStr;
 	use std::time::{Duration, Instant};
 
-	use crate::network::emulator::NetworkLatencyEmulator;
+	use crate::network::emulator::{check_probability, Bandwidth, BurstLoss, NetworkConditionsPreset, NetworkLatencyEmulator};
 
 	///
 	/// Если не заданы ограничения - все должно работать
@@ -191,14 +499,14 @@ mod tests {
 		emulator.rtt = Some(rtt);
 		emulator.schedule_out(now, send_data.as_slice(), SocketAddr::from_str("127.0.0.1:5050").unwrap());
 
-		assert!(matches!(emulator.get_out(now), None));
+		assert!(emulator.get_out(now).is_none());
 
 		// время задержки прошло - данные доступны для отправки
 		now = now.add(rtt.div(2));
-		assert!(matches!(emulator.get_out(now), Some(_)));
+		assert!(emulator.get_out(now).is_some());
 
 		// больше данных нет
-		assert!(matches!(emulator.get_out(now), None));
+		assert!(emulator.get_out(now).is_none());
 	}
 
 	#[test]
@@ -209,14 +517,14 @@ mod tests {
 		let rtt = Duration::from_millis(1000);
 		emulator.rtt = Some(rtt);
 		emulator.schedule_in(now, send_data.as_slice());
-		assert!(matches!(emulator.get_in(now), None));
+		assert!(emulator.get_in(now).is_none());
 
 		// время задержки прошло - данные доступны для отправки
 		now = now.add(rtt.div(2));
-		assert!(matches!(emulator.get_in(now), Some(_)));
+		assert!(emulator.get_in(now).is_some());
 
 		// больше данных нет
-		assert!(matches!(emulator.get_in(now), None));
+		assert!(emulator.get_in(now).is_none());
 	}
 
 	#[test]
@@ -313,8 +621,8 @@ mod tests {
 		emulator.schedule_out(now, frame_2.as_slice(), SocketAddr::from_str("127.0.0.1:5050").unwrap());
 
 		let now = now.add(half_rtt.sub(Duration::from_millis(1)));
-		assert!(matches!(emulator.get_in(now), None));
-		assert!(matches!(emulator.get_out(now), None));
+		assert!(emulator.get_in(now).is_none());
+		assert!(emulator.get_out(now).is_none());
 
 		let now = now.add(Duration::from_millis(1));
 		assert!(matches!(emulator.get_in(now),Some(frame) if frame==frame_1 ));
@@ -323,4 +631,156 @@ mod tests {
 		assert!(matches!(emulator.get_out(now),Some((frame,_)) if frame==frame_1));
 		assert!(matches!(emulator.get_out(now),Some((frame,_)) if frame==frame_2));
 	}
+
+	///
+	/// Пакеты передаются последовательно со скоростью канала, не поместившиеся в очередь отбрасываются
+	///
+	#[test]
+	fn should_limit_bandwidth() {
+		let mut emulator = NetworkLatencyEmulator::default();
+		emulator.configure_bandwidth(Bandwidth {
+			bytes_per_second: 1000,
+			queue_size_in_bytes: 250,
+		});
+		let addr = SocketAddr::from_str("127.0.0.1:5050").unwrap();
+		let now = Instant::now();
+		let buffer = vec![0; 100];
+		for _ in 0..3 {
+			emulator.schedule_out(now, buffer.as_slice(), addr);
+		}
+
+		assert!(emulator.get_out(now.add(Duration::from_millis(99))).is_none());
+		assert!(emulator.get_out(now.add(Duration::from_millis(100))).is_some());
+		assert!(emulator.get_out(now.add(Duration::from_millis(199))).is_none());
+		assert!(emulator.get_out(now.add(Duration::from_millis(200))).is_some());
+		// третий пакет не поместился в очередь
+		assert!(emulator.get_out(now.add(Duration::from_secs(1))).is_none());
+
+		// очередь освободилась
+		let now = now.add(Duration::from_secs(1));
+		emulator.schedule_out(now, buffer.as_slice(), addr);
+		assert!(emulator.get_out(now.add(Duration::from_millis(100))).is_some());
+	}
+
+	#[test]
+	fn should_duplicate_packet() {
+		let mut emulator = NetworkLatencyEmulator::default();
+		emulator.configure_duplicate(1.0);
+		let now = Instant::now();
+		emulator.schedule_in(now, &[1, 2, 3]);
+		assert!(matches!(emulator.get_in(now), Some(buffer) if buffer==vec![1, 2, 3]));
+		assert!(matches!(emulator.get_in(now), Some(buffer) if buffer==vec![1, 2, 3]));
+		assert!(emulator.get_in(now).is_none());
+	}
+
+	///
+	/// Задержанный пакет обгоняют пакеты, отправленные позже
+	///
+	#[test]
+	fn should_reorder_packets() {
+		let mut emulator = NetworkLatencyEmulator::default();
+		let reorder_delay = Duration::from_millis(50);
+		emulator.configure_reorder(1.0, reorder_delay);
+		let now = Instant::now();
+		emulator.schedule_in(now, &[1]);
+		emulator.configure_reorder(0.0, reorder_delay);
+		emulator.schedule_in(now.add(Duration::from_millis(10)), &[2]);
+
+		let now = now.add(reorder_delay);
+		assert!(matches!(emulator.get_in(now), Some(frame) if frame==vec![2]));
+		assert!(matches!(emulator.get_in(now), Some(frame) if frame==vec![1]));
+	}
+
+	///
+	/// В модели Гилберта-Эллиота потери идут сериями - средняя длина серии 1/bad_to_good_probability
+	///
+	#[test]
+	fn should_lose_packets_in_bursts() {
+		let mut emulator = NetworkLatencyEmulator::default();
+		emulator.configure_burst_loss(BurstLoss {
+			good_to_bad_probability: 0.01,
+			bad_to_good_probability: 0.1,
+			good_loss_probability: 0.0,
+			bad_loss_probability: 1.0,
+		});
+		let now = Instant::now();
+		let mut lost = 0;
+		let mut bursts = 0;
+		let mut previous_lost = false;
+		for _ in 0..100_000 {
+			emulator.schedule_in(now, &[1]);
+			let is_lost = emulator.get_in(now).is_none();
+			if is_lost {
+				lost += 1;
+				if !previous_lost {
+					bursts += 1;
+				}
+			}
+			previous_lost = is_lost;
+		}
+		assert!(bursts > 0);
+		let average_burst = f64::from(lost) / f64::from(bursts);
+		assert!(average_burst > 5.0, "{average_burst}");
+	}
+
+	#[test]
+	fn should_configure_preset() {
+		let mut emulator = NetworkLatencyEmulator::default();
+		emulator.configure_drop(1.0, Duration::from_secs(1));
+		emulator.configure_preset(NetworkConditionsPreset::ThreeG);
+		assert_eq!(emulator.rtt, Some(Duration::from_millis(200)));
+		assert!(emulator.drop_probability.is_none());
+		assert!(emulator.bandwidth.is_some());
+		assert!(emulator.burst_loss.is_some());
+
+		emulator.configure_preset(NetworkConditionsPreset::Wifi);
+		assert!(emulator.bandwidth.is_none());
+		assert!(emulator.burst_loss.is_none());
+	}
+
+	#[test]
+	fn should_parse_preset_name() {
+		for preset in NetworkConditionsPreset::ALL {
+			assert_eq!(NetworkConditionsPreset::from_str(preset.name()).unwrap(), preset);
+		}
+		assert_eq!(NetworkConditionsPreset::from_str("3G").unwrap(), NetworkConditionsPreset::ThreeG);
+		assert!(NetworkConditionsPreset::from_str("5g").is_err());
+	}
+
+	#[test]
+	fn should_convert_preset_from_number() {
+		assert_eq!(NetworkConditionsPreset::try_from(0).unwrap(), NetworkConditionsPreset::Wifi);
+		assert_eq!(NetworkConditionsPreset::try_from(4).unwrap(), NetworkConditionsPreset::Edge);
+		assert!(NetworkConditionsPreset::try_from(5).is_err());
+	}
+
+	#[test]
+	fn should_check_probability() {
+		assert!(check_probability(0.0).is_ok());
+		assert!(check_probability(1.0).is_ok());
+		assert!(check_probability(-0.1).is_err());
+		assert!(check_probability(1.1).is_err());
+		assert!(check_probability(f64::NAN).is_err());
+	}
+
+	#[test]
+	fn should_repeat_emulation_with_same_seed() {
+		let delivered = |seed: u64| {
+			let mut emulator = NetworkLatencyEmulator::default();
+			emulator.set_seed(seed);
+			emulator.configure_preset(NetworkConditionsPreset::CongestedWifi);
+			emulator.configure_drop(0.3, Duration::ZERO);
+			let now = Instant::now();
+			for i in 0..200_u8 {
+				emulator.schedule_in(now, &[i]);
+			}
+			let mut delivered = Vec::new();
+			while let Some(buffer) = emulator.get_in(now.add(Duration::from_secs(10))) {
+				delivered.push(buffer[0]);
+			}
+			delivered
+		};
+		assert_eq!(delivered(7), delivered(7));
+		assert_ne!(delivered(7), delivered(8));
+	}
 }
@@ -0,0 +1,174 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Debug, Formatter};
+use std::io;
+use std::io::{Error, ErrorKind};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+///
+/// Максимальное количество пакетов в очереди сокета, при переполнении пакеты отбрасываются как в UDP
+///
+const MAX_QUEUE_SIZE: usize = 4096;
+
+///
+/// Сеть в памяти процесса для детерминированных тестов
+///
+/// - пакеты доставляются мгновенно и без потерь, в порядке отправки
+/// - пакеты на несуществующий адрес отбрасываются
+/// - задержки и потери задаются эмулятором [`crate::network::emulator::NetworkLatencyEmulator`], как и для UDP
+///
+#[derive(Clone, Default)]
+pub struct MemoryNetwork {
+	inner: Arc<Mutex<MemoryNetworkInner>>,
+}
+
+#[derive(Default)]
+struct MemoryNetworkInner {
+	sockets: HashMap<SocketAddr, VecDeque<(SocketAddr, Vec<u8>)>>,
+	last_port: u16,
+}
+
+///
+/// Сокет в [`MemoryNetwork`], освобождает адрес при удалении
+///
+#[derive(Debug)]
+pub struct MemorySocket {
+	network: MemoryNetwork,
+	address: SocketAddr,
+}
+
+impl MemoryNetwork {
+	#[must_use]
+	pub fn new() -> Self {
+		Default::default()
+	}
+
+	///
+	/// Открыть сокет на свободном адресе
+	///
+	pub fn bind(&self) -> io::Result<MemorySocket> {
+		let mut inner = self.inner.lock().unwrap();
+		for _ in 0..u16::MAX {
+			inner.last_port = inner.last_port.checked_add(1).unwrap_or(1);
+			let address = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), inner.last_port);
+			if let Entry::Vacant(entry) = inner.sockets.entry(address) {
+				entry.insert(Default::default());
+				return Ok(MemorySocket { network: self.clone(), address });
+			}
+		}
+		Err(Error::new(ErrorKind::AddrNotAvailable, "no free address in memory network"))
+	}
+
+	fn send_to(&self, source: SocketAddr, buf: &[u8], target: SocketAddr) {
+		if let Some(queue) = self.inner.lock().unwrap().sockets.get_mut(&target) {
+			if queue.len() < MAX_QUEUE_SIZE {
+				queue.push_back((source, buf.to_vec()));
+			}
+		}
+	}
+
+	fn recv_from(&self, address: SocketAddr) -> Option<(SocketAddr, Vec<u8>)> {
+		self.inner.lock().unwrap().sockets.get_mut(&address).and_then(VecDeque::pop_front)
+	}
+}
+
+impl PartialEq for MemoryNetwork {
+	fn eq(&self, other: &Self) -> bool {
+		Arc::ptr_eq(&self.inner, &other.inner)
+	}
+}
+
+impl Eq for MemoryNetwork {}
+
+impl Debug for MemoryNetwork {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("MemoryNetwork").field("sockets", &self.inner.lock().unwrap().sockets.len()).finish()
+	}
+}
+
+impl MemorySocket {
+	#[must_use]
+	pub fn local_addr(&self) -> SocketAddr {
+		self.address
+	}
+
+	pub fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+		self.network.send_to(self.address, buf, addr);
+		Ok(buf.len())
+	}
+
+	///
+	/// Получить пакет, если пакет больше буфера - он обрезается, как в UDP
+	///
+	pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+		match self.network.recv_from(self.address) {
+			None => Err(Error::new(ErrorKind::WouldBlock, "")),
+			Some((source, data)) => {
+				let size = data.len().min(buf.len());
+				buf[0..size].copy_from_slice(&data[0..size]);
+				Ok((size, source))
+			}
+		}
+	}
+}
+
+impl Drop for MemorySocket {
+	fn drop(&mut self) {
+		if let Ok(mut inner) = self.network.inner.lock() {
+			inner.sockets.remove(&self.address);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::ErrorKind;
+
+	use crate::network::memory::MemoryNetwork;
+
+	#[test]
+	fn should_send_and_receive() {
+		let network = MemoryNetwork::new();
+		let socket_a = network.bind().unwrap();
+		let socket_b = network.bind().unwrap();
+		assert_ne!(socket_a.local_addr(), socket_b.local_addr());
+
+		socket_a.send_to(&[1, 2, 3], socket_b.local_addr()).unwrap();
+		socket_a.send_to(&[4], socket_b.local_addr()).unwrap();
+		let mut buf = [0; 16];
+		assert_eq!(socket_b.recv_from(&mut buf).unwrap(), (3, socket_a.local_addr()));
+		assert_eq!(&buf[0..3], &[1, 2, 3]);
+		assert_eq!(socket_b.recv_from(&mut buf).unwrap(), (1, socket_a.local_addr()));
+		assert_eq!(socket_b.recv_from(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+		assert_eq!(socket_a.recv_from(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+	}
+
+	#[test]
+	fn should_drop_packets_to_closed_socket() {
+		let network = MemoryNetwork::new();
+		let socket_a = network.bind().unwrap();
+		let socket_b = network.bind().unwrap();
+		let address_b = socket_b.local_addr();
+		drop(socket_b);
+		socket_a.send_to(&[1], address_b).unwrap();
+		let socket_c = network.bind().unwrap();
+		assert_ne!(socket_c.local_addr(), address_b);
+		let mut buf = [0; 16];
+		assert_eq!(socket_c.recv_from(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+	}
+
+	#[test]
+	fn should_isolate_networks() {
+		let network_a = MemoryNetwork::new();
+		let network_b = MemoryNetwork::new();
+		let socket_a = network_a.bind().unwrap();
+		let socket_b = network_b.bind().unwrap();
+		assert_eq!(socket_a.local_addr(), socket_b.local_addr());
+		socket_a.send_to(&[1], socket_b.local_addr()).unwrap();
+		let mut buf = [0; 16];
+		assert_eq!(socket_b.recv_from(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+		assert_ne!(network_a, network_b);
+		assert_eq!(network_a, network_a.clone());
+	}
+}
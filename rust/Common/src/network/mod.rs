@@ -1,6 +1,8 @@
 use crate::network::collectors::in_collector::InCommandsCollector;
 use crate::network::collectors::out_collector::OutCommandsCollector;
-use crate::network::socket::UdpSocketWrapper;
+use crate::network::quality::ConnectionQualityStatistics;
+use crate::network::socket::{SocketWrapper, Transport};
+use crate::network::token::{MemberToken, MEMBER_TOKEN_PACKET_PREFIX, MEMBER_TOKEN_SIZE};
 use cheetah_game_realtime_protocol::codec::cipher::Cipher;
 use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
 use cheetah_game_realtime_protocol::frame::disconnected_reason::DisconnectedReason;
@@ -13,18 +15,37 @@ use std::collections::VecDeque;
 use std::io::ErrorKind;
 use std::net::SocketAddr;
 use std::net::UdpSocket;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 pub mod collectors;
 pub mod emulator;
+pub mod memory;
+pub mod quality;
 pub mod socket;
+pub mod token;
+pub mod websocket;
 
 pub fn bind_to_free_socket() -> std::io::Result<UdpSocket> {
 	UdpSocket::bind("0.0.0.0:0")
 }
 
+///
+/// Открыть сокет на свободном порту с тем же семейством адресов, что и у адреса сервера
+///
+pub fn bind_to_free_socket_for(server_address: &SocketAddr) -> std::io::Result<UdpSocket> {
+	match server_address {
+		SocketAddr::V4(_) => bind_to_free_socket(),
+		SocketAddr::V6(_) => UdpSocket::bind("[::]:0"),
+	}
+}
+
 pub type CheetahProtocol = Protocol<InCommandsCollector, OutCommandsCollector>;
 
+///
+/// Период повторной отправки токена пользователя до установки соединения
+///
+const MEMBER_TOKEN_RESEND_PERIOD: Duration = Duration::from_millis(100);
+
 #[derive(Debug)]
 pub struct NetworkChannel {
 	pub state: ConnectionStatus,
@@ -32,9 +53,12 @@ pub struct NetworkChannel {
 	pub protocol: CheetahProtocol,
 	private_key: MemberPrivateKey,
 	server_address: SocketAddr,
-	pub socket_wrapper: UdpSocketWrapper,
+	pub socket_wrapper: SocketWrapper,
+	pub quality_statistics: ConnectionQualityStatistics,
 	out_frames: VecDeque<Frame>,
 	member_and_room_id: MemberAndRoomId,
+	member_token: Option<[u8; MEMBER_TOKEN_SIZE]>,
+	member_token_send_time: Option<Instant>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -48,10 +72,26 @@ pub enum ConnectionStatus {
 	/// Соединение закрыто
 	///
 	Disconnected(DisconnectedReason),
+	///
+	/// Соединение закрыто сервером при остановке
+	///
+	DisconnectedByServerShutdown,
+	///
+	/// Соединение разорвано, клиент выполняет автоматическое переподключение
+	///
+	Reconnecting,
+}
+
+impl ConnectionStatus {
+	#[must_use]
+	pub fn is_disconnected(&self) -> bool {
+		matches!(self, ConnectionStatus::Disconnected(_) | ConnectionStatus::DisconnectedByServerShutdown)
+	}
 }
 
 impl NetworkChannel {
 	#[allow(clippy::unwrap_in_result)]
+	#[allow(clippy::too_many_arguments)]
 	pub fn new(
 		connection_id: ConnectionId,
 		server_side: bool,
@@ -59,6 +99,7 @@ impl NetworkChannel {
 		member_id: RoomMemberId,
 		room_id: RoomId,
 		server_address: SocketAddr,
+		transport: Transport,
 		start_application_time: Instant,
 		configuration: ProtocolConfiguration,
 	) -> std::io::Result<NetworkChannel> {
@@ -70,7 +111,7 @@ impl NetworkChannel {
 			start_application_time,
 			configuration,
 		);
-		let channel = UdpSocketWrapper::new()?;
+		let channel = SocketWrapper::new_with_transport(&server_address, transport)?;
 
 		Ok(NetworkChannel {
 			current_connection_id: 0,
@@ -79,18 +120,29 @@ impl NetworkChannel {
 			private_key,
 			server_address,
 			socket_wrapper: channel,
+			quality_statistics: Default::default(),
 			out_frames: Default::default(),
 			member_and_room_id: MemberAndRoomId { member_id, room_id },
+			member_token: None,
+			member_token_send_time: None,
 		})
 	}
 
+	///
+	/// Подключаться по токену, токен отправляется на сервер до установки соединения
+	///
+	pub fn set_member_token(&mut self, token: &MemberToken) {
+		self.member_token = Some(token.encode());
+	}
+
 	pub fn cycle(&mut self, now: Instant) {
-		if let ConnectionStatus::Disconnected(_) = self.state {
+		if self.state.is_disconnected() {
 			return;
 		}
 
 		self.socket_wrapper.cycle(now);
 		self.do_read(now);
+		self.send_member_token(now);
 		self.do_write(now);
 
 		if self.protocol.is_connected(now) {
@@ -98,11 +150,30 @@ impl NetworkChannel {
 		}
 
 		if let Some(reason) = self.protocol.is_disconnected(now) {
-			self.state = ConnectionStatus::Disconnected(reason);
+			self.state = match reason {
+				DisconnectedReason::Command(_) if self.protocol.input_data_handler.server_shutdown => ConnectionStatus::DisconnectedByServerShutdown,
+				reason => ConnectionStatus::Disconnected(reason),
+			};
+		}
+	}
+
+	fn send_member_token(&mut self, now: Instant) {
+		if self.protocol.is_connected(now) || self.member_token_send_time.is_some_and(|time| now.duration_since(time) < MEMBER_TOKEN_RESEND_PERIOD) {
+			return;
+		}
+		if let Some(token) = self.member_token {
+			let mut buffer = [0; MEMBER_TOKEN_PACKET_PREFIX.len() + MEMBER_TOKEN_SIZE];
+			buffer[0..MEMBER_TOKEN_PACKET_PREFIX.len()].copy_from_slice(&MEMBER_TOKEN_PACKET_PREFIX);
+			buffer[MEMBER_TOKEN_PACKET_PREFIX.len()..].copy_from_slice(&token);
+			if let Err(e) = self.socket_wrapper.send_to(now, &buffer, self.server_address) {
+				tracing::error!("error send member token {:?}", e);
+			}
+			self.member_token_send_time = Some(now);
 		}
 	}
 
 	fn do_write(&mut self, now: Instant) {
+		self.protocol.output_data_producer.cycle(now);
 		self.protocol.collect_out_frames(now, &mut self.out_frames);
 
 		let mut buffer = [0; 2048];
@@ -113,6 +184,7 @@ impl NetworkChannel {
 			match self.socket_wrapper.send_to(now, &buffer[0..frame_buffer_size], self.server_address) {
 				Ok(size) => {
 					if size == frame_buffer_size {
+						self.quality_statistics.on_frame_sent(frame);
 						self.out_frames.pop_back();
 					} else {
 						tracing::error!("error send frame size mismatch send {:?}, frame {:?}", size, frame_buffer_size);
@@ -158,6 +230,15 @@ impl NetworkChannel {
 	}
 
 	fn on_frame_received(&mut self, now: Instant, frame: &Frame) {
+		let connection_id = self.protocol.connection_id;
 		self.protocol.on_frame_received(frame, now);
+		self.protocol.output_data_producer.on_input_collected(&mut self.protocol.input_data_handler);
+		// протокол сбросил состояние при смене идентификатора соединения удаленной стороной
+		if self.protocol.connection_id != connection_id {
+			self.quality_statistics = Default::default();
+		}
+		if frame.connection_id == self.protocol.connection_id {
+			self.quality_statistics.on_frame_received(frame);
+		}
 	}
 }
@@ -0,0 +1,259 @@
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+
+use cheetah_game_realtime_protocol::frame::headers::Header;
+use cheetah_game_realtime_protocol::frame::{Frame, FrameId};
+use fnv::FnvBuildHasher;
+
+///
+/// Количество последних входящих фреймов для расчета доли потерь
+///
+const PACKET_LOSS_WINDOW_SIZE: usize = 128;
+
+///
+/// Время без входящих фреймов, после которого соединение считается плохим (сервер отправляет keep alive раз в секунду)
+///
+const MAX_SILENCE_DURATION: Duration = Duration::from_secs(2);
+
+///
+/// Статистика качества соединения, собирается по входящим и исходящим фреймам канала
+///
+/// - потери считаются по пропускам в идентификаторах последних входящих фреймов
+/// - повторно отправленные фреймы определяются по заголовку `Retransmit`
+/// - надежные фреймы ожидают подтверждения до получения `Ack` от удаленной стороны
+///
+#[derive(Debug, Default)]
+pub struct ConnectionQualityStatistics {
+	received_frames: VecDeque<FrameId>,
+	wait_ack_frames: HashSet<FrameId, FnvBuildHasher>,
+	pub retransmit_count: u64,
+}
+
+///
+/// Статистика rtt по последним замерам
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RttStatistics {
+	pub min: Duration,
+	pub avg: Duration,
+	pub max: Duration,
+	///
+	/// Среднее отклонение между соседними замерами
+	///
+	pub jitter: Duration,
+}
+
+///
+/// Оценка качества соединения для индикатора в интерфейсе игры
+///
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionQuality {
+	///
+	/// Недостаточно данных для оценки или соединение не установлено
+	///
+	#[default]
+	Unknown,
+	Excellent,
+	Good,
+	Fair,
+	Poor,
+}
+
+///
+/// Предельные значения параметров соединения для оценки
+///
+struct QualityThresholds {
+	quality: ConnectionQuality,
+	rtt: Duration,
+	jitter: Duration,
+	packet_loss: f64,
+}
+
+const QUALITY_THRESHOLDS: [QualityThresholds; 3] = [
+	QualityThresholds {
+		quality: ConnectionQuality::Excellent,
+		rtt: Duration::from_millis(60),
+		jitter: Duration::from_millis(10),
+		packet_loss: 0.01,
+	},
+	QualityThresholds {
+		quality: ConnectionQuality::Good,
+		rtt: Duration::from_millis(120),
+		jitter: Duration::from_millis(30),
+		packet_loss: 0.03,
+	},
+	QualityThresholds {
+		quality: ConnectionQuality::Fair,
+		rtt: Duration::from_millis(250),
+		jitter: Duration::from_millis(60),
+		packet_loss: 0.08,
+	},
+];
+
+impl ConnectionQualityStatistics {
+	pub fn on_frame_sent(&mut self, frame: &Frame) {
+		if frame.headers.first(|header| if let Header::Retransmit(header) = header { Some(header) } else { None }).is_some() {
+			self.retransmit_count += 1;
+		} else if frame.reliability {
+			self.wait_ack_frames.insert(frame.frame_id);
+		}
+	}
+
+	pub fn on_frame_received(&mut self, frame: &Frame) {
+		let ack_headers = frame.headers.find(|header| if let Header::Ack(header) = header { Some(header) } else { None });
+		for ack_header in ack_headers {
+			for frame_id in ack_header.get_frames() {
+				self.wait_ack_frames.remove(frame_id);
+			}
+		}
+
+		if !self.received_frames.contains(&frame.frame_id) {
+			self.received_frames.push_back(frame.frame_id);
+			if self.received_frames.len() > PACKET_LOSS_WINDOW_SIZE {
+				self.received_frames.pop_front();
+			}
+		}
+	}
+
+	///
+	/// Доля потерянных входящих фреймов (от 0 до 1) среди последних [`PACKET_LOSS_WINDOW_SIZE`]
+	///
+	#[must_use]
+	#[allow(clippy::cast_precision_loss)]
+	pub fn packet_loss(&self) -> f64 {
+		let (min, max) = match (self.received_frames.iter().min(), self.received_frames.iter().max()) {
+			(Some(min), Some(max)) => (*min, *max),
+			_ => return 0.0,
+		};
+		let expected = max - min + 1;
+		1.0 - self.received_frames.len() as f64 / expected as f64
+	}
+
+	///
+	/// Количество отправленных надежных фреймов, для которых еще не получено подтверждение
+	///
+	#[must_use]
+	pub fn unacked_reliable_frames(&self) -> usize {
+		self.wait_ack_frames.len()
+	}
+}
+
+impl RttStatistics {
+	#[must_use]
+	#[allow(clippy::cast_possible_truncation)]
+	pub fn from_samples<'a>(samples: impl Iterator<Item = &'a Duration>) -> Option<Self> {
+		let samples: Vec<Duration> = samples.copied().collect();
+		let min = *samples.iter().min()?;
+		let max = *samples.iter().max()?;
+		let avg = samples.iter().sum::<Duration>() / samples.len() as u32;
+		let jitter = if samples.len() > 1 {
+			samples.windows(2).map(|pair| pair[0].abs_diff(pair[1])).sum::<Duration>() / (samples.len() - 1) as u32
+		} else {
+			Duration::ZERO
+		};
+		Some(Self { min, avg, max, jitter })
+	}
+}
+
+impl ConnectionQuality {
+	///
+	/// Оценка по rtt, потерям и времени с последнего входящего фрейма, без замеров rtt - [`ConnectionQuality::Unknown`]
+	///
+	#[must_use]
+	pub fn rate(rtt: Option<RttStatistics>, packet_loss: f64, since_last_frame: Duration) -> Self {
+		let rtt = match rtt {
+			None => return ConnectionQuality::Unknown,
+			Some(rtt) => rtt,
+		};
+		if since_last_frame >= MAX_SILENCE_DURATION {
+			return ConnectionQuality::Poor;
+		}
+		QUALITY_THRESHOLDS
+			.iter()
+			.find(|thresholds| rtt.avg <= thresholds.rtt && rtt.jitter <= thresholds.jitter && packet_loss <= thresholds.packet_loss)
+			.map_or(ConnectionQuality::Poor, |thresholds| thresholds.quality)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use cheetah_game_realtime_protocol::frame::headers::Header;
+	use cheetah_game_realtime_protocol::frame::segment::Segment;
+	use cheetah_game_realtime_protocol::frame::{Frame, FrameId};
+	use cheetah_game_realtime_protocol::reliable::ack::header::AckHeader;
+	use cheetah_game_realtime_protocol::reliable::retransmit::header::RetransmitHeader;
+
+	use crate::network::quality::{ConnectionQuality, ConnectionQualityStatistics, RttStatistics};
+
+	#[test]
+	fn should_calculate_packet_loss_by_frame_id_gaps() {
+		let mut statistics = ConnectionQualityStatistics::default();
+		assert!(statistics.packet_loss().abs() < f64::EPSILON);
+		for frame_id in [1, 2, 4, 5, 5, 8] {
+			statistics.on_frame_received(&create_frame(frame_id, false));
+		}
+		assert!((statistics.packet_loss() - 0.375).abs() < f64::EPSILON);
+	}
+
+	#[test]
+	fn should_count_unacked_and_retransmitted_frames() {
+		let mut statistics = ConnectionQualityStatistics::default();
+		statistics.on_frame_sent(&create_frame(1, true));
+		statistics.on_frame_sent(&create_frame(2, true));
+		statistics.on_frame_sent(&create_frame(3, false));
+		assert_eq!(statistics.unacked_reliable_frames(), 2);
+
+		let mut retransmit_frame = create_frame(4, true);
+		retransmit_frame.headers.add(Header::Retransmit(RetransmitHeader::new(1)));
+		statistics.on_frame_sent(&retransmit_frame);
+		assert_eq!(statistics.retransmit_count, 1);
+		assert_eq!(statistics.unacked_reliable_frames(), 2);
+
+		let mut ack_frame = create_frame(1, false);
+		let mut ack_header = AckHeader::default();
+		ack_header.add_frame_id(1);
+		ack_frame.headers.add(Header::Ack(ack_header));
+		statistics.on_frame_received(&ack_frame);
+		assert_eq!(statistics.unacked_reliable_frames(), 1);
+	}
+
+	#[test]
+	fn should_calculate_rtt_statistics() {
+		assert_eq!(RttStatistics::from_samples([].iter()), None);
+		let samples = [Duration::from_millis(10), Duration::from_millis(30), Duration::from_millis(20)];
+		let rtt = RttStatistics::from_samples(samples.iter()).unwrap();
+		assert_eq!(
+			rtt,
+			RttStatistics {
+				min: Duration::from_millis(10),
+				avg: Duration::from_millis(20),
+				max: Duration::from_millis(30),
+				jitter: Duration::from_millis(15),
+			}
+		);
+	}
+
+	#[test]
+	fn should_rate_connection_quality() {
+		let rtt = |avg: u64, jitter: u64| {
+			Some(RttStatistics {
+				avg: Duration::from_millis(avg),
+				jitter: Duration::from_millis(jitter),
+				..Default::default()
+			})
+		};
+		assert_eq!(ConnectionQuality::rate(None, 0.0, Duration::ZERO), ConnectionQuality::Unknown);
+		assert_eq!(ConnectionQuality::rate(rtt(30, 5), 0.0, Duration::ZERO), ConnectionQuality::Excellent);
+		assert_eq!(ConnectionQuality::rate(rtt(30, 5), 0.02, Duration::ZERO), ConnectionQuality::Good);
+		assert_eq!(ConnectionQuality::rate(rtt(200, 5), 0.0, Duration::ZERO), ConnectionQuality::Fair);
+		assert_eq!(ConnectionQuality::rate(rtt(30, 100), 0.0, Duration::ZERO), ConnectionQuality::Poor);
+		assert_eq!(ConnectionQuality::rate(rtt(30, 5), 0.0, Duration::from_secs(3)), ConnectionQuality::Poor);
+	}
+
+	fn create_frame(frame_id: FrameId, reliability: bool) -> Frame {
+		Frame::new(0, frame_id, reliability, Segment::default())
+	}
+}
@@ -3,26 +3,97 @@ use std::io::{Error, ErrorKind};
 use std::net::{SocketAddr, UdpSocket};
 use std::time::Instant;
 
-use crate::network::bind_to_free_socket;
+use crate::network::bind_to_free_socket_for;
 use crate::network::emulator::NetworkLatencyEmulator;
+use crate::network::memory::{MemoryNetwork, MemorySocket};
+use crate::network::websocket::WebSocketClient;
+
+///
+/// Транспорт для обмена фреймами с сервером
+///
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Transport {
+	#[default]
+	Udp,
+	///
+	/// Для сетей без UDP, сервер должен быть запущен с WebSocket listener
+	///
+	/// - только нативные платформы: соединение устанавливается через tcp сокет, подключение блокирует поток до [`crate::network::websocket::CONNECT_TIMEOUT`]
+	/// - только ws://, TLS (wss://) не поддерживается
+	/// - в браузере (WebGL) tcp сокеты недоступны, для него нужен отдельный транспорт через WebSocket API браузера, он не реализован
+	///
+	WebSocket,
+	///
+	/// Сеть в памяти процесса, для детерминированных тестов
+	///
+	Memory(MemoryNetwork),
+}
 
 #[derive(Debug)]
-pub struct UdpSocketWrapper {
-	socket: UdpSocket,
+pub struct SocketWrapper {
+	socket: Socket,
 	emulator: Option<NetworkLatencyEmulator>,
+	emulator_seed: Option<u64>,
 	pub recv_packet_count: u64,
 	pub send_packet_count: u64,
 	pub recv_size: u64, // размер всех принятых данных
 	pub send_size: u64, //размер всех отправленных данных
 }
 
-impl UdpSocketWrapper {
-	pub fn new() -> io::Result<Self> {
-		let socket = bind_to_free_socket()?;
-		socket.set_nonblocking(true)?;
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+enum Socket {
+	Udp(UdpSocket),
+	WebSocket(Box<WebSocketClient>),
+	Memory(MemorySocket),
+}
+
+impl Socket {
+	fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		match self {
+			Socket::Udp(socket) => socket.recv(buf),
+			Socket::WebSocket(socket) => socket.recv(buf),
+			Socket::Memory(socket) => socket.recv_from(buf).map(|(size, _)| size),
+		}
+	}
+
+	///
+	/// WebSocket соединение установлено с сервером, поэтому адрес используется только для UDP
+	///
+	fn send_to(&mut self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+		match self {
+			Socket::Udp(socket) => socket.send_to(buf, addr),
+			Socket::WebSocket(socket) => socket.send(buf),
+			Socket::Memory(socket) => socket.send_to(buf, addr),
+		}
+	}
+}
+
+impl SocketWrapper {
+	///
+	/// Создать сокет для обмена данными с сервером, семейство адресов сокета выбирается по адресу сервера
+	///
+	pub fn new(server_address: &SocketAddr) -> io::Result<Self> {
+		Self::new_with_transport(server_address, Transport::Udp)
+	}
+
+	///
+	/// Создать сокет с заданным транспортом, для WebSocket устанавливается tcp соединение с сервером
+	///
+	pub fn new_with_transport(server_address: &SocketAddr, transport: Transport) -> io::Result<Self> {
+		let socket = match transport {
+			Transport::Udp => {
+				let socket = bind_to_free_socket_for(server_address)?;
+				socket.set_nonblocking(true)?;
+				Socket::Udp(socket)
+			}
+			Transport::WebSocket => Socket::WebSocket(Box::new(WebSocketClient::connect(server_address)?)),
+			Transport::Memory(network) => Socket::Memory(network.bind()?),
+		};
 		Ok(Self {
 			socket,
 			emulator: None,
+			emulator_seed: None,
 			recv_packet_count: 0,
 			send_packet_count: 0,
 			recv_size: 0,
@@ -30,6 +101,14 @@ impl UdpSocketWrapper {
 		})
 	}
 
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		match &self.socket {
+			Socket::Udp(socket) => socket.local_addr(),
+			Socket::WebSocket(socket) => socket.local_addr(),
+			Socket::Memory(socket) => Ok(socket.local_addr()),
+		}
+	}
+
 	pub fn recv(&mut self, now: Instant, buf: &mut [u8]) -> io::Result<usize> {
 		let result = self.socket.recv(buf);
 		if let Ok(read_bytes) = result {
@@ -68,9 +147,15 @@ impl UdpSocketWrapper {
 	}
 
 	///
-	/// Если в эмуляторе есть данные для отправки в реальный сокет - отправляем
+	/// Если в эмуляторе есть данные для отправки в реальный сокет - отправляем,
+	/// для WebSocket дополнительно отправляются сообщения из очереди
 	///
 	pub fn cycle(&mut self, now: Instant) {
+		if let Socket::WebSocket(socket) = &mut self.socket {
+			if let Err(e) = socket.flush() {
+				tracing::error!("[NetworkChannel] websocket flush error {:?}", e);
+			}
+		}
 		if let Some(emulator) = self.emulator.as_mut() {
 			while let Some((buffer, addr)) = emulator.get_out(now) {
 				match self.socket.send_to(buffer.as_slice(), addr) {
@@ -91,7 +176,11 @@ impl UdpSocketWrapper {
 		T: FnOnce(&mut NetworkLatencyEmulator),
 	{
 		if self.emulator.is_none() {
-			self.emulator.replace(NetworkLatencyEmulator::default());
+			let mut emulator = NetworkLatencyEmulator::default();
+			if let Some(seed) = self.emulator_seed {
+				emulator.set_seed(seed);
+			}
+			self.emulator.replace(emulator);
 		}
 
 		if let Some(emulator) = self.emulator.as_mut() {
@@ -99,6 +188,16 @@ impl UdpSocketWrapper {
 		}
 	}
 
+	///
+	/// Начальное значение генератора случайных чисел эмулятора, сохраняется при отключении эмуляции
+	///
+	pub fn set_emulator_seed(&mut self, seed: u64) {
+		self.emulator_seed = Some(seed);
+		if let Some(emulator) = self.emulator.as_mut() {
+			emulator.set_seed(seed);
+		}
+	}
+
 	///
 	/// Отключить эмуляцию характеристик сети
 	/// - все не отправленные и не принятые пакеты будут потеряны
@@ -111,22 +210,26 @@ impl UdpSocketWrapper {
 
 #[cfg(test)]
 pub mod tests {
+	use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
 	use std::ops::Add;
 	use std::time::{Duration, Instant};
 
-	use crate::network::socket::UdpSocketWrapper;
+	use tungstenite::Message;
+
+	use crate::network::memory::MemoryNetwork;
+	use crate::network::socket::{SocketWrapper, Transport};
 
 	///
 	/// Проверяем работу канала в обычном режиме
 	///
 	#[test]
 	fn should_receive_and_send() {
-		let mut channel_a = UdpSocketWrapper::new().unwrap();
-		let mut channel_b = UdpSocketWrapper::new().unwrap();
+		let mut channel_a = SocketWrapper::new(&ipv4()).unwrap();
+		let mut channel_b = SocketWrapper::new(&ipv4()).unwrap();
 
 		let now = Instant::now();
 		let send_data = vec![1, 2, 3];
-		channel_a.send_to(now, send_data.as_slice(), channel_b.socket.local_addr().unwrap()).unwrap();
+		channel_a.send_to(now, send_data.as_slice(), local_addr(&channel_b)).unwrap();
 		std::thread::sleep(Duration::from_millis(10));
 		let mut recv_data = [0; 1024];
 		assert!(matches!(channel_b.recv(now, &mut recv_data), Ok(size) if send_data.len()==size));
@@ -139,31 +242,31 @@ pub mod tests {
 	///
 	#[test]
 	fn should_receive_and_send_with_emulator() {
-		let mut channel_a = UdpSocketWrapper::new().unwrap();
+		let mut channel_a = SocketWrapper::new(&ipv4()).unwrap();
 		let rtt = Duration::from_millis(100);
 		let half_rtt = rtt.div_f64(2.0);
 		channel_a.config_emulator(|emulator| {
 			emulator.configure_rtt(rtt, 0.0);
 		});
-		let mut channel_b = UdpSocketWrapper::new().unwrap();
+		let mut channel_b = SocketWrapper::new(&ipv4()).unwrap();
 		channel_b.config_emulator(|emulator| {
 			emulator.configure_rtt(rtt, 0.0);
 		});
 
 		let now = Instant::now();
 		let send_data = vec![1, 2, 3];
-		channel_a.send_to(now, send_data.as_slice(), channel_b.socket.local_addr().unwrap()).unwrap();
+		channel_a.send_to(now, send_data.as_slice(), local_addr(&channel_b)).unwrap();
 
 		// данных нет - так как включен эмулятор лага
 		std::thread::sleep(Duration::from_millis(10));
 		let mut recv_data = [0; 1024];
-		assert!(matches!(channel_b.recv(now, &mut recv_data), Err(_)));
+		assert!(channel_b.recv(now, &mut recv_data).is_err());
 		// время окончания эмуляции rtt
 		let after_rtt_time = now.add(half_rtt).add(Duration::from_millis(10));
 		// данные должны быть отправлены, но их еще не будет на принимающей стороне
 		channel_a.cycle(after_rtt_time);
 		std::thread::sleep(Duration::from_millis(10));
-		assert!(matches!(channel_b.recv(now, &mut recv_data), Err(_)));
+		assert!(channel_b.recv(now, &mut recv_data).is_err());
 
 		// а теперь будут, так как прошло время эмуляции rtt
 		assert!(matches!(channel_b.recv(after_rtt_time, &mut recv_data), Ok(size) if send_data.len()==size));
@@ -174,8 +277,8 @@ pub mod tests {
 	///
 	#[test]
 	fn should_statistics() {
-		let mut channel_a = UdpSocketWrapper::new().unwrap();
-		let mut channel_b = UdpSocketWrapper::new().unwrap();
+		let mut channel_a = SocketWrapper::new(&ipv4()).unwrap();
+		let mut channel_b = SocketWrapper::new(&ipv4()).unwrap();
 
 		assert_eq!(channel_a.recv_size, 0);
 		assert_eq!(channel_a.send_size, 0);
@@ -184,7 +287,7 @@ pub mod tests {
 
 		let now = Instant::now();
 		let send_data = vec![1, 2, 3];
-		channel_a.send_to(now, send_data.as_slice(), channel_b.socket.local_addr().unwrap()).unwrap();
+		channel_a.send_to(now, send_data.as_slice(), local_addr(&channel_b)).unwrap();
 		std::thread::sleep(Duration::from_millis(10));
 		let mut recv_data = [0; 1024];
 		channel_b.recv(now, &mut recv_data).unwrap();
@@ -199,4 +302,89 @@ pub mod tests {
 		assert_eq!(channel_b.recv_packet_count, 1);
 		assert_eq!(channel_b.send_packet_count, 0);
 	}
+
+	///
+	/// Проверяем работу канала через IPv6
+	///
+	#[test]
+	fn should_receive_and_send_ipv6() {
+		let mut channel_a = SocketWrapper::new(&ipv6()).unwrap();
+		let mut channel_b = SocketWrapper::new(&ipv6()).unwrap();
+		assert!(channel_a.local_addr().unwrap().is_ipv6());
+
+		let now = Instant::now();
+		let send_data = vec![1, 2, 3];
+		let addr = SocketAddr::new(Ipv6Addr::LOCALHOST.into(), channel_b.local_addr().unwrap().port());
+		channel_a.send_to(now, send_data.as_slice(), addr).unwrap();
+		std::thread::sleep(Duration::from_millis(10));
+		let mut recv_data = [0; 1024];
+		assert!(matches!(channel_b.recv(now, &mut recv_data), Ok(size) if send_data.len()==size));
+	}
+
+	///
+	/// Проверяем обмен данными через WebSocket, сервер отправляет полученные сообщения обратно
+	///
+	#[test]
+	fn should_receive_and_send_websocket() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let server_address = listener.local_addr().unwrap();
+		let server = std::thread::spawn(move || {
+			let (stream, _) = listener.accept().unwrap();
+			let mut socket = tungstenite::accept(stream).unwrap();
+			let message = socket.read_message().unwrap();
+			socket.write_message(message).unwrap();
+			socket.write_message(Message::Text("ignored".to_owned())).unwrap();
+			socket.write_message(Message::Binary(vec![4, 5])).unwrap();
+			socket.read_message().ok();
+		});
+
+		let mut channel = SocketWrapper::new_with_transport(&server_address, Transport::WebSocket).unwrap();
+		let send_data = vec![1, 2, 3];
+		// отправляем до завершения handshake - данные будут отправлены после установки соединения
+		channel.send_to(Instant::now(), send_data.as_slice(), server_address).unwrap();
+		let mut recv_data = [0; 1024];
+		let mut received = Vec::new();
+		let start = Instant::now();
+		while received.len() < 2 && start.elapsed() < Duration::from_secs(5) {
+			let now = Instant::now();
+			channel.cycle(now);
+			if let Ok(size) = channel.recv(now, &mut recv_data) {
+				received.push(recv_data[0..size].to_vec());
+			}
+			std::thread::sleep(Duration::from_millis(1));
+		}
+		drop(channel);
+		server.join().unwrap();
+
+		assert_eq!(received, vec![send_data, vec![4, 5]]);
+	}
+
+	///
+	/// Проверяем обмен данными через сеть в памяти, пакеты доступны сразу после отправки
+	///
+	#[test]
+	fn should_receive_and_send_memory() {
+		let network = MemoryNetwork::new();
+		let mut channel_a = SocketWrapper::new_with_transport(&ipv4(), Transport::Memory(network.clone())).unwrap();
+		let mut channel_b = SocketWrapper::new_with_transport(&ipv4(), Transport::Memory(network)).unwrap();
+
+		let now = Instant::now();
+		let send_data = vec![1, 2, 3];
+		channel_a.send_to(now, send_data.as_slice(), channel_b.local_addr().unwrap()).unwrap();
+		let mut recv_data = [0; 1024];
+		assert!(matches!(channel_b.recv(now, &mut recv_data), Ok(size) if send_data.len()==size));
+		assert!(channel_b.recv(now, &mut recv_data).is_err());
+	}
+
+	fn ipv4() -> SocketAddr {
+		SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 5555)
+	}
+
+	fn ipv6() -> SocketAddr {
+		SocketAddr::new(Ipv6Addr::LOCALHOST.into(), 5555)
+	}
+
+	fn local_addr(channel: &SocketWrapper) -> SocketAddr {
+		SocketAddr::new(Ipv4Addr::LOCALHOST.into(), channel.local_addr().unwrap().port())
+	}
 }
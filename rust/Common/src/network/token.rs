@@ -0,0 +1,223 @@
+use byteorder::{BigEndian, ByteOrder};
+use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::room::access::AccessGroups;
+
+///
+/// Префикс UDP пакета с токеном пользователя, пакет с токеном отправляется клиентом до установки соединения
+///
+pub const MEMBER_TOKEN_PACKET_PREFIX: [u8; 8] = [0xFF, b'C', b'H', b'T', b'O', b'K', b'E', b'N'];
+
+///
+/// Идентификаторы пользователей, подключенных по токену, выделяются из отдельного диапазона,
+/// чтобы не пересекаться с идентификаторами пользователей созданных через `CreateMember`
+///
+pub const TOKEN_MEMBER_ID_FLAG: RoomMemberId = 1 << 63;
+
+const CLAIMS_SIZE: usize = 40;
+const SIGNATURE_SIZE: usize = 32;
+pub const MEMBER_TOKEN_SIZE: usize = CLAIMS_SIZE + SIGNATURE_SIZE;
+const SESSION_KEY_CONTEXT: &[u8] = b"cheetah-member-session-key";
+
+type HmacSha256 = Hmac<Sha256>;
+
+///
+/// Данные о пользователе, подписанные backend-ом
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemberTokenClaims {
+	pub room_id: RoomId,
+	pub user_id: u64,
+	pub groups: AccessGroups,
+	///
+	/// Время окончания действия токена в секундах от UNIX_EPOCH
+	///
+	pub expires_at: u64,
+	///
+	/// Случайное число, делает токены одного пользователя уникальными
+	///
+	pub nonce: u64,
+}
+
+///
+/// Токен для подключения пользователя к комнате без предварительного создания через `CreateMember`
+///
+/// - подписывается HMAC-SHA256 ключом, общим для backend и сервера
+/// - backend передает клиенту токен и сессионный ключ ([`MemberToken::session_key`])
+/// - сервер по токену регистрирует пользователя и вычисляет тот же сессионный ключ
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberToken {
+	pub claims: MemberTokenClaims,
+	signature: [u8; SIGNATURE_SIZE],
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MemberTokenError {
+	#[error("Wrong token size {0}")]
+	WrongSize(usize),
+	#[error("Wrong token signature")]
+	WrongSignature,
+	#[error("Token expired at {0}")]
+	Expired(u64),
+	#[error("Wrong user id {0}")]
+	WrongUserId(u64),
+}
+
+impl MemberTokenClaims {
+	#[must_use]
+	pub fn member_id(&self) -> RoomMemberId {
+		self.user_id | TOKEN_MEMBER_ID_FLAG
+	}
+
+	fn encode(&self) -> [u8; CLAIMS_SIZE] {
+		let mut out = [0; CLAIMS_SIZE];
+		BigEndian::write_u64(&mut out[0..8], self.room_id);
+		BigEndian::write_u64(&mut out[8..16], self.user_id);
+		BigEndian::write_u64(&mut out[16..24], self.groups.0);
+		BigEndian::write_u64(&mut out[24..32], self.expires_at);
+		BigEndian::write_u64(&mut out[32..40], self.nonce);
+		out
+	}
+
+	fn decode(data: &[u8]) -> Self {
+		Self {
+			room_id: BigEndian::read_u64(&data[0..8]),
+			user_id: BigEndian::read_u64(&data[8..16]),
+			groups: AccessGroups(BigEndian::read_u64(&data[16..24])),
+			expires_at: BigEndian::read_u64(&data[24..32]),
+			nonce: BigEndian::read_u64(&data[32..40]),
+		}
+	}
+}
+
+impl MemberToken {
+	#[must_use]
+	pub fn sign(key: &[u8], claims: MemberTokenClaims) -> Self {
+		let mut mac = Self::new_mac(key);
+		mac.update(&claims.encode());
+		Self {
+			claims,
+			signature: mac.finalize().into_bytes().into(),
+		}
+	}
+
+	///
+	/// Декодировать токен без проверки подписи, используется клиентом для получения идентификаторов комнаты и пользователя
+	///
+	pub fn decode(data: &[u8]) -> Result<Self, MemberTokenError> {
+		if data.len() != MEMBER_TOKEN_SIZE {
+			return Err(MemberTokenError::WrongSize(data.len()));
+		}
+		let mut signature = [0; SIGNATURE_SIZE];
+		signature.copy_from_slice(&data[CLAIMS_SIZE..]);
+		Ok(Self {
+			claims: MemberTokenClaims::decode(&data[0..CLAIMS_SIZE]),
+			signature,
+		})
+	}
+
+	#[must_use]
+	pub fn encode(&self) -> [u8; MEMBER_TOKEN_SIZE] {
+		let mut out = [0; MEMBER_TOKEN_SIZE];
+		out[0..CLAIMS_SIZE].copy_from_slice(&self.claims.encode());
+		out[CLAIMS_SIZE..].copy_from_slice(&self.signature);
+		out
+	}
+
+	///
+	/// Проверить подпись и время действия токена
+	///
+	pub fn verify(&self, key: &[u8], now_in_sec: u64) -> Result<(), MemberTokenError> {
+		let mut mac = Self::new_mac(key);
+		mac.update(&self.claims.encode());
+		mac.verify_slice(&self.signature).map_err(|_| MemberTokenError::WrongSignature)?;
+		if self.claims.expires_at <= now_in_sec {
+			return Err(MemberTokenError::Expired(self.claims.expires_at));
+		}
+		if self.claims.user_id & TOKEN_MEMBER_ID_FLAG != 0 {
+			return Err(MemberTokenError::WrongUserId(self.claims.user_id));
+		}
+		Ok(())
+	}
+
+	///
+	/// Сессионный ключ для шифрования фреймов, знать его могут только backend и сервер
+	///
+	#[must_use]
+	pub fn session_key(&self, key: &[u8]) -> MemberPrivateKey {
+		let mut mac = Self::new_mac(key);
+		mac.update(SESSION_KEY_CONTEXT);
+		mac.update(&self.signature);
+		MemberPrivateKey(mac.finalize().into_bytes().into())
+	}
+
+	#[must_use]
+	pub fn signature(&self) -> &[u8; SIGNATURE_SIZE] {
+		&self.signature
+	}
+
+	fn new_mac(key: &[u8]) -> HmacSha256 {
+		// HMAC принимает ключ любой длины
+		HmacSha256::new_from_slice(key).unwrap()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::network::token::{MemberToken, MemberTokenClaims, MemberTokenError, TOKEN_MEMBER_ID_FLAG};
+	use crate::room::access::AccessGroups;
+
+	const KEY: &[u8] = b"secret";
+
+	#[test]
+	fn should_sign_and_verify() {
+		let token = MemberToken::sign(KEY, claims());
+		let decoded = MemberToken::decode(&token.encode()).unwrap();
+		assert_eq!(decoded, token);
+		assert_eq!(decoded.verify(KEY, 50), Ok(()));
+		assert_eq!(decoded.claims.member_id(), 7 | TOKEN_MEMBER_ID_FLAG);
+	}
+
+	#[test]
+	fn should_fail_verify_with_wrong_key() {
+		let token = MemberToken::sign(KEY, claims());
+		assert_eq!(token.verify(b"other", 50), Err(MemberTokenError::WrongSignature));
+	}
+
+	#[test]
+	fn should_fail_verify_modified_claims() {
+		let mut data = MemberToken::sign(KEY, claims()).encode();
+		data[15] = 8;
+		assert_eq!(MemberToken::decode(&data).unwrap().verify(KEY, 50), Err(MemberTokenError::WrongSignature));
+	}
+
+	#[test]
+	fn should_fail_verify_expired() {
+		let token = MemberToken::sign(KEY, claims());
+		assert_eq!(token.verify(KEY, 100), Err(MemberTokenError::Expired(100)));
+	}
+
+	#[test]
+	fn should_derive_different_session_keys() {
+		let token_a = MemberToken::sign(KEY, claims());
+		let token_b = MemberToken::sign(KEY, MemberTokenClaims { nonce: 2, ..claims() });
+		assert_eq!(token_a.session_key(KEY).0, token_a.session_key(KEY).0);
+		assert_ne!(token_a.session_key(KEY).0, token_b.session_key(KEY).0);
+		assert_ne!(token_a.session_key(KEY).0, token_a.session_key(b"other").0);
+	}
+
+	fn claims() -> MemberTokenClaims {
+		MemberTokenClaims {
+			room_id: 1,
+			user_id: 7,
+			groups: AccessGroups(0b11),
+			expires_at: 100,
+			nonce: 1,
+		}
+	}
+}
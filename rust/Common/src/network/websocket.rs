@@ -0,0 +1,187 @@
+use std::io;
+use std::io::{Error, ErrorKind};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use tungstenite::handshake::client::ClientHandshake;
+use tungstenite::handshake::MidHandshake;
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::{HandshakeError, Message, WebSocket};
+
+///
+/// Максимальное время установки tcp соединения с сервером
+///
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+///
+/// Максимальное количество сообщений в очереди на отправку, при переполнении сообщения отбрасываются
+/// так же как потерянные UDP пакеты, восстановление данных возлагается на Relay протокол
+///
+pub const WEBSOCKET_MAX_SEND_QUEUE: usize = 1024;
+
+///
+/// Максимальный размер сообщения и WebSocket фрейма - размер буфера приема UDP пакетов на сервере,
+/// фрейм протокола больше этого размера не может быть принят и через UDP
+///
+pub const WEBSOCKET_MAX_MESSAGE_SIZE: usize = 512;
+
+///
+/// WebSocket соединение клиента с сервером
+///
+/// - каждый фрейм передается отдельным binary сообщением, кодирование фреймов такое же как и для UDP
+/// - соединение неблокирующее, handshake завершается в процессе обмена данными
+/// - до завершения handshake отправляемые фреймы накапливаются и отправляются после установки соединения
+/// - только ws:// поверх tcp сокета, поэтому не работает в браузере и не поддерживает TLS
+///
+#[derive(Debug)]
+pub struct WebSocketClient {
+	state: WebSocketClientState,
+	handshake_pending: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+enum WebSocketClientState {
+	Handshake(MidHandshake<ClientHandshake<TcpStream>>),
+	Connected(WebSocket<TcpStream>),
+	Closed,
+}
+
+impl WebSocketClient {
+	pub fn connect(server_address: &SocketAddr) -> io::Result<Self> {
+		let stream = TcpStream::connect_timeout(server_address, CONNECT_TIMEOUT)?;
+		stream.set_nodelay(true)?;
+		stream.set_nonblocking(true)?;
+		let config = WebSocketConfig {
+			max_send_queue: Some(WEBSOCKET_MAX_SEND_QUEUE),
+			max_message_size: Some(WEBSOCKET_MAX_MESSAGE_SIZE),
+			max_frame_size: Some(WEBSOCKET_MAX_MESSAGE_SIZE),
+			..Default::default()
+		};
+		let state = match tungstenite::client::client_with_config(format!("ws://{server_address}/"), stream, Some(config)) {
+			Ok((socket, _)) => WebSocketClientState::Connected(socket),
+			Err(HandshakeError::Interrupted(handshake)) => WebSocketClientState::Handshake(handshake),
+			Err(HandshakeError::Failure(e)) => return Err(Error::new(ErrorKind::ConnectionRefused, format!("websocket handshake error {e:?}"))),
+		};
+		Ok(Self {
+			state,
+			handshake_pending: Default::default(),
+		})
+	}
+
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		match &self.state {
+			WebSocketClientState::Handshake(handshake) => handshake.get_ref().get_ref().local_addr(),
+			WebSocketClientState::Connected(socket) => socket.get_ref().local_addr(),
+			WebSocketClientState::Closed => Err(Error::new(ErrorKind::NotConnected, "websocket closed")),
+		}
+	}
+
+	pub fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.continue_handshake()?;
+		let socket = match &mut self.state {
+			WebSocketClientState::Handshake(_) => return Err(Error::new(ErrorKind::WouldBlock, "")),
+			WebSocketClientState::Connected(socket) => socket,
+			WebSocketClientState::Closed => return Err(Error::new(ErrorKind::NotConnected, "websocket closed")),
+		};
+		loop {
+			match socket.read_message() {
+				Ok(Message::Binary(data)) => {
+					if data.len() > buf.len() {
+						tracing::error!("[websocket] message too large {:?}", data.len());
+						continue;
+					}
+					buf[0..data.len()].copy_from_slice(&data);
+					return Ok(data.len());
+				}
+				Ok(Message::Close(_)) => {
+					self.state = WebSocketClientState::Closed;
+					return Err(Error::new(ErrorKind::ConnectionAborted, "websocket closed by server"));
+				}
+				Ok(_) => {}
+				Err(e) => return Err(self.on_error(e)),
+			}
+		}
+	}
+
+	pub fn send(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.continue_handshake()?;
+		match &self.state {
+			WebSocketClientState::Handshake(_) => {
+				if self.handshake_pending.len() < WEBSOCKET_MAX_SEND_QUEUE {
+					self.handshake_pending.push(buf.to_vec());
+				}
+				Ok(buf.len())
+			}
+			WebSocketClientState::Connected(_) => self.write(buf.to_vec()).map(|_| buf.len()),
+			WebSocketClientState::Closed => Err(Error::new(ErrorKind::NotConnected, "websocket closed")),
+		}
+	}
+
+	fn write(&mut self, data: Vec<u8>) -> io::Result<()> {
+		let socket = match &mut self.state {
+			WebSocketClientState::Connected(socket) => socket,
+			_ => return Err(Error::new(ErrorKind::NotConnected, "websocket not connected")),
+		};
+		match socket.write_message(Message::Binary(data)) {
+			Ok(_) | Err(tungstenite::Error::SendQueueFull(_)) => Ok(()),
+			// сообщение уже в очереди, будет отправлено при следующем flush
+			Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+			Err(e) => Err(self.on_error(e)),
+		}
+	}
+
+	///
+	/// Отправить сообщения из очереди
+	///
+	pub fn flush(&mut self) -> io::Result<()> {
+		self.continue_handshake()?;
+		if let WebSocketClientState::Connected(socket) = &mut self.state {
+			match socket.write_pending() {
+				Ok(_) => {}
+				Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => {}
+				Err(e) => return Err(self.on_error(e)),
+			}
+		}
+		Ok(())
+	}
+
+	fn continue_handshake(&mut self) -> io::Result<()> {
+		if !matches!(self.state, WebSocketClientState::Handshake(_)) {
+			return Ok(());
+		}
+		match std::mem::replace(&mut self.state, WebSocketClientState::Closed) {
+			WebSocketClientState::Handshake(handshake) => match handshake.handshake() {
+				Ok((socket, _)) => {
+					self.state = WebSocketClientState::Connected(socket);
+					for data in std::mem::take(&mut self.handshake_pending) {
+						self.write(data)?;
+					}
+					Ok(())
+				}
+				Err(HandshakeError::Interrupted(handshake)) => {
+					self.state = WebSocketClientState::Handshake(handshake);
+					Ok(())
+				}
+				Err(HandshakeError::Failure(e)) => Err(Error::new(ErrorKind::ConnectionRefused, format!("websocket handshake error {e:?}"))),
+			},
+			state => {
+				self.state = state;
+				Ok(())
+			}
+		}
+	}
+
+	fn on_error(&mut self, e: tungstenite::Error) -> Error {
+		match e {
+			tungstenite::Error::Io(e) if e.kind() == ErrorKind::WouldBlock => e,
+			tungstenite::Error::Io(e) => {
+				self.state = WebSocketClientState::Closed;
+				e
+			}
+			e => {
+				self.state = WebSocketClientState::Closed;
+				Error::new(ErrorKind::ConnectionAborted, format!("websocket error {e:?}"))
+			}
+		}
+	}
+}
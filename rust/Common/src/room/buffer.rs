@@ -36,10 +36,10 @@ impl Buffer {
 
 	pub(crate) fn encode(&self, out: &mut Cursor<&mut [u8]>) -> std::io::Result<()> {
 		out.write_variable_u64(self.buffer.len() as u64)?;
-		let res = out.write(&self.buffer.as_slice());
+		let res = out.write(self.buffer.as_slice());
 		match res {
 			Ok(size) => {
-				if size == self.buffer.len() as usize {
+				if size == self.buffer.len() {
 					Ok(())
 				} else {
 					Err(Error::new(ErrorKind::Interrupted, "not fully saved"))
@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::io::Cursor;
 use std::io::ErrorKind;
 
@@ -18,16 +19,15 @@ pub enum FieldType {
 	Items,
 }
 
-impl ToString for FieldType {
-	fn to_string(&self) -> String {
-		match self {
+impl Display for FieldType {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
 			FieldType::Long => "long",
 			FieldType::Double => "double",
 			FieldType::Structure => "structure",
 			FieldType::Event => "event",
 			FieldType::Items => "items",
-		}
-		.into()
+		})
 	}
 }
 
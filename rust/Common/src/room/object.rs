@@ -59,6 +59,7 @@ impl GameObjectId {
 			out.write_variable_u64(self.member_id)
 		}
 	}
+	#[allow(clippy::useless_conversion)]
 	pub fn decode(input: &mut Cursor<&[u8]>) -> std::io::Result<Self> {
 		let id = input.read_variable_u64()?.try_into().map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
 		let owner = input.read_variable_u64()?;
@@ -5,6 +5,7 @@ use lazy_static::lazy_static;
 use crate::EmbeddedServerWrapper;
 
 pub(crate) mod logs;
+pub(crate) mod room;
 pub(crate) mod server;
 
 lazy_static! {
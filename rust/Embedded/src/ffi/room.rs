@@ -0,0 +1,276 @@
+use std::sync::Arc;
+
+use cheetah_common::room::access::AccessGroups;
+use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use cheetah_server::server::manager::ServerManager;
+use cheetah_server::server::room::config::member::MemberCreateParams;
+use cheetah_server::server::room::config::room::RoomCreateParams;
+use cheetah_server::server::room::member::RoomMemberStatus;
+use widestring::{U16CStr, U16CString};
+
+use crate::ffi::{ServerId, REGISTRY};
+
+#[repr(C)]
+#[derive(Debug, Default)]
+pub(crate) struct MemberFFI {
+	id: RoomMemberId,
+	private_key: [u8; 32],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MemberStatusFFI {
+	Created,
+	CreatedNotConnectedAndDeleted,
+	Connected,
+	Attached,
+	Detached,
+	Disconnected,
+}
+
+///
+/// Создать комнату, template - шаблон комнаты в формате RON (как в файлах шаблонов сервера), null - пустая комната
+///
+/// # Safety
+///
+/// `template` должен быть null или указывать на строку UTF-16, завершенную нулем
+///
+#[no_mangle]
+#[allow(unsafe_op_in_unsafe_fn)]
+pub(crate) unsafe extern "C" fn create_room(server_id: ServerId, result: &mut RoomId, on_error: extern "C" fn(*const u16), template: *const u16) -> bool {
+	let template = if template.is_null() {
+		Ok(RoomCreateParams::default())
+	} else {
+		let template = U16CStr::from_ptr_str(template).to_string_lossy();
+		ron::from_str(&template).map_err(|e| format!("Wrong room template {e}"))
+	};
+	let room_id = execute(server_id, on_error, |server| {
+		let template = template?;
+		template.validate().map_err(|e| format!("Wrong room template {e}"))?;
+		server.create_room(template).map_err(|e| format!("{e:?}"))
+	});
+	match room_id {
+		None => false,
+		Some(room_id) => {
+			*result = room_id;
+			true
+		}
+	}
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn delete_room(server_id: ServerId, room_id: RoomId, on_error: extern "C" fn(*const u16)) -> bool {
+	execute(server_id, on_error, |server| server.delete_room(room_id).map_err(|e| format!("{e:?}"))).is_some()
+}
+
+///
+/// Создать пользователя, в результат записывается идентификатор и ключ для подключения клиента
+///
+#[no_mangle]
+pub(crate) extern "C" fn create_member(server_id: ServerId, room_id: RoomId, groups: u64, result: &mut MemberFFI, on_error: extern "C" fn(*const u16)) -> bool {
+	register_member(server_id, room_id, MemberCreateParams::new_member(AccessGroups(groups), Default::default()), result, on_error)
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn create_super_member(server_id: ServerId, room_id: RoomId, result: &mut MemberFFI, on_error: extern "C" fn(*const u16)) -> bool {
+	register_member(server_id, room_id, MemberCreateParams::new_super_member(), result, on_error)
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn delete_member(server_id: ServerId, room_id: RoomId, member_id: RoomMemberId, on_error: extern "C" fn(*const u16)) -> bool {
+	execute(server_id, on_error, |server| server.delete_member(MemberAndRoomId { member_id, room_id }).map_err(|e| format!("{e:?}"))).is_some()
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn get_rooms(server_id: ServerId, on_room: extern "C" fn(RoomId), on_error: extern "C" fn(*const u16)) -> bool {
+	match execute(server_id, on_error, |server| server.get_rooms().map_err(|e| format!("{e:?}"))) {
+		None => false,
+		Some(rooms) => {
+			for room_id in rooms {
+				on_room(room_id);
+			}
+			true
+		}
+	}
+}
+
+#[no_mangle]
+pub(crate) extern "C" fn get_room_members(server_id: ServerId, room_id: RoomId, on_member: extern "C" fn(RoomMemberId, MemberStatusFFI), on_error: extern "C" fn(*const u16)) -> bool {
+	let members = execute(server_id, on_error, |server| {
+		server
+			.get_room_members_status(room_id)
+			.map_err(|e| format!("{e:?}"))?
+			.ok_or_else(|| format!("Room {room_id} not found"))
+	});
+	match members {
+		None => false,
+		Some(members) => {
+			for (member_id, status) in members {
+				on_member(member_id, MemberStatusFFI::from(status));
+			}
+			true
+		}
+	}
+}
+
+///
+/// Состояние комнаты в формате RON
+///
+#[no_mangle]
+pub(crate) extern "C" fn dump_room(server_id: ServerId, room_id: RoomId, on_dump: extern "C" fn(*const u16), on_error: extern "C" fn(*const u16)) -> bool {
+	let dump = execute(server_id, on_error, |server| {
+		let room = server.dump(room_id).map_err(|e| format!("{e:?}"))?.ok_or_else(|| format!("Room {room_id} not found"))?;
+		let dump = ron::to_string(&room).map_err(|e| format!("{e:?}"))?;
+		U16CString::from_str(dump).map_err(|e| format!("{e:?}"))
+	});
+	match dump {
+		None => false,
+		Some(dump) => {
+			on_dump(dump.as_ptr());
+			true
+		}
+	}
+}
+
+fn register_member(server_id: ServerId, room_id: RoomId, template: MemberCreateParams, result: &mut MemberFFI, on_error: extern "C" fn(*const u16)) -> bool {
+	let private_key = template.private_key.0;
+	match execute(server_id, on_error, |server| server.create_member(room_id, template).map_err(|e| format!("{e:?}"))) {
+		None => false,
+		Some(member_id) => {
+			result.id = member_id;
+			result.private_key = private_key;
+			true
+		}
+	}
+}
+
+///
+/// Выполнить действие с сервером, None - при ошибке, об ошибке сообщается через on_error
+///
+/// Реестр блокируется только на время получения сервера, а сервер - на время действия,
+/// поэтому обработчики хоста вызываются без блокировок и могут снова обращаться к серверу
+///
+fn execute<T, F>(server_id: ServerId, on_error: extern "C" fn(*const u16), action: F) -> Option<T>
+where
+	F: FnOnce(&mut ServerManager) -> Result<T, String>,
+{
+	let manager = REGISTRY.lock().unwrap().servers.get(&server_id).map(|server| Arc::clone(&server.manager));
+	let result = match manager {
+		None => Err(format!("Server {server_id} not found")),
+		Some(manager) => action(&mut manager.blocking_lock()),
+	};
+	match result {
+		Ok(result) => Some(result),
+		Err(e) => {
+			let string = U16CString::from_str(e).unwrap();
+			on_error(string.as_ptr());
+			None
+		}
+	}
+}
+
+impl From<RoomMemberStatus> for MemberStatusFFI {
+	fn from(value: RoomMemberStatus) -> Self {
+		match value {
+			RoomMemberStatus::Created => MemberStatusFFI::Created,
+			RoomMemberStatus::CreatedNotConnectedAndDeleted => MemberStatusFFI::CreatedNotConnectedAndDeleted,
+			RoomMemberStatus::Connected => MemberStatusFFI::Connected,
+			RoomMemberStatus::Attached => MemberStatusFFI::Attached,
+			RoomMemberStatus::Detached => MemberStatusFFI::Detached,
+			RoomMemberStatus::Disconnected => MemberStatusFFI::Disconnected,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::net::Ipv4Addr;
+	use std::sync::Mutex;
+
+	use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+	use lazy_static::lazy_static;
+	use widestring::{U16CStr, U16CString};
+
+	use crate::ffi::room::{create_member, create_room, create_super_member, delete_member, delete_room, dump_room, get_room_members, get_rooms, MemberFFI, MemberStatusFFI};
+	use crate::ffi::server::{destroy_server, run_new_server_without_management_services, BindSocket, EmbeddedServerDescription};
+	use crate::ffi::ServerId;
+
+	lazy_static! {
+		static ref ROOMS: Mutex<Vec<RoomId>> = Mutex::new(Default::default());
+		static ref MEMBERS: Mutex<Vec<(RoomMemberId, MemberStatusFFI)>> = Mutex::new(Default::default());
+		static ref DUMP: Mutex<String> = Mutex::new(Default::default());
+		static ref ERROR: Mutex<String> = Mutex::new(Default::default());
+	}
+
+	#[test]
+	fn should_manage_room_without_grpc() {
+		let server_id = run_server();
+
+		let template = U16CString::from_str("(objects: [(id: 1, template: 5)])").unwrap();
+		let mut room_id = 0;
+		assert!(unsafe { create_room(server_id, &mut room_id, on_error, template.as_ptr()) });
+		assert!(get_rooms(server_id, on_room, on_error));
+		assert!(ROOMS.lock().unwrap().contains(&room_id));
+
+		let mut member = MemberFFI::default();
+		assert!(create_member(server_id, room_id, 0b11, &mut member, on_error));
+		assert_ne!(member.private_key, [0; 32]);
+		let mut super_member = MemberFFI::default();
+		assert!(create_super_member(server_id, room_id, &mut super_member, on_error));
+
+		assert!(get_room_members(server_id, room_id, on_member, on_error));
+		let mut members = MEMBERS.lock().unwrap().clone();
+		members.sort_by_key(|(id, _)| *id);
+		assert_eq!(members, vec![(member.id, MemberStatusFFI::Created), (super_member.id, MemberStatusFFI::Created)]);
+
+		assert!(dump_room(server_id, room_id, on_dump, on_error));
+		assert!(DUMP.lock().unwrap().contains("template_id:5"));
+
+		assert!(delete_member(server_id, room_id, member.id, on_error));
+		assert!(delete_room(server_id, room_id, on_error));
+		assert!(!delete_room(server_id, room_id, on_expected_error));
+		assert!(destroy_server(server_id));
+	}
+
+	#[test]
+	fn should_fail_create_room_with_wrong_template() {
+		let server_id = run_server();
+		let template = U16CString::from_str("(objects: [(id: 0)])").unwrap();
+		let mut room_id = 0;
+		assert!(!unsafe { create_room(server_id, &mut room_id, on_expected_error, template.as_ptr()) });
+		assert!(ERROR.lock().unwrap().contains("Object id 0"));
+		assert!(!unsafe { create_room(ServerId::MAX, &mut room_id, on_expected_error, std::ptr::null()) });
+		assert!(destroy_server(server_id));
+	}
+
+	fn run_server() -> ServerId {
+		let mut result = EmbeddedServerDescription::default();
+		let socket = BindSocket {
+			ip: Ipv4Addr::LOCALHOST.to_ipv6_mapped().octets(),
+			port: 0,
+		};
+		assert!(run_new_server_without_management_services(&mut result, on_error, &socket));
+		result.id
+	}
+
+	extern "C" fn on_room(room_id: RoomId) {
+		ROOMS.lock().unwrap().push(room_id);
+	}
+
+	extern "C" fn on_member(member_id: RoomMemberId, status: MemberStatusFFI) {
+		MEMBERS.lock().unwrap().push((member_id, status));
+	}
+
+	extern "C" fn on_dump(dump: *const u16) {
+		*DUMP.lock().unwrap() = unsafe { U16CStr::from_ptr_str(dump) }.to_string_lossy();
+	}
+
+	extern "C" fn on_error(message: *const u16) {
+		panic!("{}", unsafe { U16CStr::from_ptr_str(message) }.to_string_lossy())
+	}
+
+	extern "C" fn on_expected_error(message: *const u16) {
+		*ERROR.lock().unwrap() = unsafe { U16CStr::from_ptr_str(message) }.to_string_lossy();
+	}
+}
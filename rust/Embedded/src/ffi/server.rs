@@ -1,27 +1,32 @@
-use std::net::{IpAddr, SocketAddr};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 
 use crate::ffi::{ServerId, REGISTRY};
 use crate::EmbeddedServerWrapper;
 
+///
+/// IP адрес в формате IPv6, IPv4 адреса передаются как IPv4-mapped IPv6 (::ffff:a.b.c.d)
+///
+pub(crate) type IpAddressFFI = [u8; 16];
+
 #[derive(Debug, Copy, Clone, Default)]
 #[repr(C)]
 pub(crate) struct EmbeddedServerDescription {
 	pub(crate) id: ServerId,
-	game_ip: [u8; 4],
+	game_ip: IpAddressFFI,
 	game_port: u16,
-	internal_grpc_ip: [u8; 4],
+	internal_grpc_ip: IpAddressFFI,
 	internal_grpc_port: u16,
-	internal_webgrpc_ip: [u8; 4],
+	internal_webgrpc_ip: IpAddressFFI,
 	internal_webgrpc_port: u16,
-	admin_webgrpc_ip: [u8; 4],
+	admin_webgrpc_ip: IpAddressFFI,
 	admin_webgrpc_port: u16,
 }
 
 #[repr(C)]
 #[derive(Default, Debug)]
 pub(crate) struct BindSocket {
-	ip: [u8; 4],
-	port: u16,
+	pub(crate) ip: IpAddressFFI,
+	pub(crate) port: u16,
 }
 
 #[no_mangle]
@@ -33,31 +38,38 @@ pub(crate) extern "C" fn run_new_server(
 	dump_rest_service_socket: &BindSocket,
 	game_udp_socket: &BindSocket,
 ) -> bool {
-	let mut registry = REGISTRY.lock().unwrap();
-	registry.next_server_id += 1;
-	let server_id = registry.next_server_id;
+	let internal_grpc_address = internal_grpc_socket.into();
+	let internal_webgrpc_address = internal_webgrpc_socket.into();
+	let dump_rest_service_address = dump_rest_service_socket.into();
+	let game_udp_address = game_udp_socket.into();
+
+	register_server(
+		EmbeddedServerWrapper::run_new_server(internal_grpc_address, internal_webgrpc_address, dump_rest_service_address, game_udp_address),
+		result,
+		on_error,
+	)
+}
 
-	let internal_grpc_address = SocketAddr::new(IpAddr::from(internal_grpc_socket.ip), internal_grpc_socket.port);
-	let internal_webgrpc_address = SocketAddr::new(IpAddr::from(internal_webgrpc_socket.ip), internal_webgrpc_socket.port);
-	let dump_rest_service_address = SocketAddr::new(IpAddr::from(dump_rest_service_socket.ip), dump_rest_service_socket.port);
-	let game_udp_address = SocketAddr::new(IpAddr::from(game_udp_socket.ip), game_udp_socket.port);
+///
+/// Запуск сервера без grpc, grpc-web и debug rest сервисов, адреса и порты сервисов в описании сервера - нулевые
+///
+#[no_mangle]
+pub(crate) extern "C" fn run_new_server_without_management_services(result: &mut EmbeddedServerDescription, on_error: extern "C" fn(*const u16), game_udp_socket: &BindSocket) -> bool {
+	register_server(EmbeddedServerWrapper::run_new_server_without_management_services(game_udp_socket.into()), result, on_error)
+}
 
-	match EmbeddedServerWrapper::run_new_server(internal_grpc_address, internal_webgrpc_address, dump_rest_service_address, game_udp_address) {
+fn register_server(server: anyhow::Result<EmbeddedServerWrapper>, result: &mut EmbeddedServerDescription, on_error: extern "C" fn(*const u16)) -> bool {
+	match server {
 		Ok(server) => {
+			let mut registry = REGISTRY.lock().unwrap();
+			registry.next_server_id += 1;
+			let server_id = registry.next_server_id;
 			result.id = server_id;
 
-			if !set_addr(&mut result.game_ip, on_error, &server.game_socket_addr.ip())
-				|| !set_addr(&mut result.internal_grpc_ip, on_error, &server.internal_grpc_socket_addr.ip())
-				|| !set_addr(&mut result.internal_webgrpc_ip, on_error, &server.internal_webgrpc_socket_addr.ip())
-				|| !set_addr(&mut result.admin_webgrpc_ip, on_error, &server.admin_webgrpc_socket_addr.ip())
-			{
-				return false;
-			}
-
-			result.game_port = server.game_socket_addr.port();
-			result.internal_grpc_port = server.internal_grpc_socket_addr.port();
-			result.internal_webgrpc_port = server.internal_webgrpc_socket_addr.port();
-			result.admin_webgrpc_port = server.admin_webgrpc_socket_addr.port();
+			(result.game_ip, result.game_port) = to_ffi_socket(Some(server.game_socket_addr));
+			(result.internal_grpc_ip, result.internal_grpc_port) = to_ffi_socket(server.internal_grpc_socket_addr);
+			(result.internal_webgrpc_ip, result.internal_webgrpc_port) = to_ffi_socket(server.internal_webgrpc_socket_addr);
+			(result.admin_webgrpc_ip, result.admin_webgrpc_port) = to_ffi_socket(server.admin_webgrpc_socket_addr);
 			registry.servers.insert(server_id, server);
 			true
 		}
@@ -69,16 +81,20 @@ pub(crate) extern "C" fn run_new_server(
 	}
 }
 
-fn set_addr(out: &mut [u8; 4], on_error: extern "C" fn(*const u16), ip_addr: &IpAddr) -> bool {
+impl From<&BindSocket> for SocketAddr {
+	fn from(socket: &BindSocket) -> Self {
+		SocketAddr::new(Ipv6Addr::from(socket.ip).to_canonical(), socket.port)
+	}
+}
+
+fn to_ffi_socket(addr: Option<SocketAddr>) -> (IpAddressFFI, u16) {
+	addr.map(|addr| (to_ffi(&addr.ip()), addr.port())).unwrap_or_default()
+}
+
+fn to_ffi(ip_addr: &IpAddr) -> IpAddressFFI {
 	match ip_addr {
-		IpAddr::V4(v4) => {
-			*out = v4.octets();
-			true
-		}
-		IpAddr::V6(_) => {
-			on_error(widestring::U16CString::from_str("IPv6 not supported").unwrap().as_ptr());
-			false
-		}
+		IpAddr::V4(v4) => v4.to_ipv6_mapped().octets(),
+		IpAddr::V6(v6) => v6.octets(),
 	}
 }
 
@@ -95,7 +111,8 @@ pub(crate) extern "C" fn destroy_server(server_id: ServerId) -> bool {
 
 #[cfg(test)]
 mod test {
-	use crate::ffi::server::{destroy_server, run_new_server, EmbeddedServerDescription};
+	use crate::ffi::server::{destroy_server, run_new_server, run_new_server_without_management_services, BindSocket, EmbeddedServerDescription};
+	use std::net::{Ipv4Addr, Ipv6Addr};
 
 	#[test]
 	pub(crate) fn should_run_new_server() {
@@ -113,11 +130,53 @@ mod test {
 		assert!(!destroy_server(result.id));
 	}
 
+	#[test]
+	pub(crate) fn should_run_new_server_on_ipv4() {
+		let mut result = EmbeddedServerDescription::default();
+		let socket = BindSocket {
+			ip: Ipv4Addr::LOCALHOST.to_ipv6_mapped().octets(),
+			port: 0,
+		};
+		assert!(run_new_server(&mut result, on_error, &socket, &socket, &socket, &socket));
+		assert_eq!(result.game_ip, Ipv4Addr::LOCALHOST.to_ipv6_mapped().octets());
+		assert_ne!(result.game_port, 0);
+		assert!(destroy_server(result.id));
+	}
+
+	#[test]
+	pub(crate) fn should_run_new_server_on_ipv6() {
+		let mut result = EmbeddedServerDescription::default();
+		let socket = BindSocket {
+			ip: Ipv6Addr::LOCALHOST.octets(),
+			port: 0,
+		};
+		assert!(run_new_server(&mut result, on_error, &socket, &socket, &socket, &socket));
+		assert_eq!(result.game_ip, Ipv6Addr::LOCALHOST.octets());
+		assert_eq!(result.internal_grpc_ip, Ipv6Addr::LOCALHOST.octets());
+		assert_ne!(result.game_port, 0);
+		assert!(destroy_server(result.id));
+	}
+
+	#[test]
+	pub(crate) fn should_run_new_server_without_management_services() {
+		let mut result = EmbeddedServerDescription::default();
+		let socket = BindSocket {
+			ip: Ipv4Addr::LOCALHOST.to_ipv6_mapped().octets(),
+			port: 0,
+		};
+		assert!(run_new_server_without_management_services(&mut result, on_error, &socket));
+		assert_ne!(result.game_port, 0);
+		assert_eq!(result.internal_grpc_port, 0);
+		assert_eq!(result.internal_webgrpc_port, 0);
+		assert_eq!(result.admin_webgrpc_port, 0);
+		assert!(destroy_server(result.id));
+	}
+
 	pub(crate) extern "C" fn on_error(message: *const u16) {
 		panic!("Fail create server with message {message:?}")
 	}
 
-	fn setup_server(mut result: &mut EmbeddedServerDescription) -> bool {
-		run_new_server(&mut result, on_error, &Default::default(), &Default::default(), &Default::default(), &Default::default())
+	fn setup_server(result: &mut EmbeddedServerDescription) -> bool {
+		run_new_server(result, on_error, &Default::default(), &Default::default(), &Default::default(), &Default::default())
 	}
 }
@@ -3,25 +3,36 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use thiserror::Error;
+use tokio::net::TcpListener;
 use tokio::runtime::Runtime;
 use tokio::sync::Mutex;
 
+use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
 use cheetah_server::builder::ServerBuilder;
-use cheetah_server::server::manager::ServerManager;
+use cheetah_server::server::manager::{ManagementTaskError, ServerManager};
+use cheetah_server::server::room::config::member::MemberCreateParams;
+use cheetah_server::server::room::config::room::RoomCreateParams;
+use cheetah_server::server::room::member::RoomMemberStatus;
+use cheetah_server::server::room::Room;
 
 mod ffi;
 
 ///
 /// Обертка для запуска сервера из so/dll.
 /// - методы не могут быть async так как они будут вызываться как методы so/dll
+/// - комнатами и пользователями можно управлять напрямую, без grpc, в этом случае tcp порты можно не открывать
 ///
 pub struct EmbeddedServerWrapper {
 	runtime: Runtime,
 	pub manager: Arc<Mutex<ServerManager>>,
 	pub game_socket_addr: SocketAddr,
-	pub internal_grpc_socket_addr: SocketAddr,
-	pub internal_webgrpc_socket_addr: SocketAddr,
-	pub admin_webgrpc_socket_addr: SocketAddr,
+	///
+	/// Адреса внутренних сервисов, None - если сервисы отключены
+	///
+	pub internal_grpc_socket_addr: Option<SocketAddr>,
+	pub internal_webgrpc_socket_addr: Option<SocketAddr>,
+	pub admin_webgrpc_socket_addr: Option<SocketAddr>,
 }
 
 #[derive(Error, Debug)]
@@ -32,29 +43,38 @@ pub enum EmbeddedServerWrapperError {
 
 impl EmbeddedServerWrapper {
 	pub fn run_new_server(internal_grpc_address: SocketAddr, internal_webgrpc_address: SocketAddr, dump_rest_service_address: SocketAddr, game_udp_address: SocketAddr) -> anyhow::Result<Self> {
-		let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(2).enable_io().enable_time().build()?;
-
-		let server = runtime.block_on(async move {
+		Self::run(
 			ServerBuilder::default()
 				.set_internal_grpc_service_bind_address(internal_grpc_address)
 				.set_internal_webgrpc_service_bind_address(internal_webgrpc_address)
 				.set_debug_rest_service_bind_address(dump_rest_service_address)
-				.set_games_service_bind_address(game_udp_address)
-				.build()
-				.await
-		})?;
+				.set_games_service_bind_address(game_udp_address),
+		)
+	}
+
+	///
+	/// Запуск сервера только с игровым udp сокетом, для локальной игры и LAN режима
+	///
+	pub fn run_new_server_without_management_services(game_udp_address: SocketAddr) -> anyhow::Result<Self> {
+		Self::run(ServerBuilder::default().set_games_service_bind_address(game_udp_address).disable_management_services())
+	}
+
+	fn run(builder: ServerBuilder) -> anyhow::Result<Self> {
+		let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(2).enable_io().enable_time().build()?;
+
+		let server = runtime.block_on(async move { builder.enable_dual_stack().build().await })?;
 		let manager = Arc::clone(&server.manager);
 		let game_socket_addr = server.game_socket_addr;
-		let internal_grpc_socket_addr = server.internal_grpc_listener.local_addr()?;
-		let admin_webgrpc_socket_addr = server.debug_rest_service_listener.local_addr()?;
-		let internal_webgrpc_socket_addr = server.internal_webgrpc_listener.local_addr()?;
+		let internal_grpc_socket_addr = server.internal_grpc_listener.as_ref().map(TcpListener::local_addr).transpose()?;
+		let admin_webgrpc_socket_addr = server.debug_rest_service_listener.as_ref().map(TcpListener::local_addr).transpose()?;
+		let internal_webgrpc_socket_addr = server.internal_webgrpc_listener.as_ref().map(TcpListener::local_addr).transpose()?;
 		runtime.spawn(async move {
 			server.run().await;
 		});
 
-		Self::assert_open_port(internal_grpc_socket_addr)?;
-		Self::assert_open_port(internal_webgrpc_socket_addr)?;
-		Self::assert_open_port(admin_webgrpc_socket_addr)?;
+		for addr in [internal_grpc_socket_addr, internal_webgrpc_socket_addr, admin_webgrpc_socket_addr].into_iter().flatten() {
+			Self::assert_open_port(addr)?;
+		}
 
 		Ok(EmbeddedServerWrapper {
 			runtime,
@@ -66,6 +86,37 @@ impl EmbeddedServerWrapper {
 		})
 	}
 
+	pub fn create_room(&self, template: RoomCreateParams) -> Result<RoomId, ManagementTaskError> {
+		self.manager.blocking_lock().create_room(template)
+	}
+
+	pub fn delete_room(&self, room_id: RoomId) -> Result<(), ManagementTaskError> {
+		self.manager.blocking_lock().delete_room(room_id)
+	}
+
+	pub fn create_member(&self, room_id: RoomId, template: MemberCreateParams) -> Result<RoomMemberId, ManagementTaskError> {
+		self.manager.blocking_lock().create_member(room_id, template)
+	}
+
+	pub fn delete_member(&self, id: MemberAndRoomId) -> Result<(), ManagementTaskError> {
+		self.manager.blocking_lock().delete_member(id)
+	}
+
+	pub fn get_rooms(&self) -> Result<Vec<RoomId>, ManagementTaskError> {
+		self.manager.blocking_lock().get_rooms()
+	}
+
+	///
+	/// Пользователи комнаты, None - если комнаты нет
+	///
+	pub fn get_members(&self, room_id: RoomId) -> Result<Option<Vec<(RoomMemberId, RoomMemberStatus)>>, ManagementTaskError> {
+		self.manager.blocking_lock().get_room_members_status(room_id)
+	}
+
+	pub fn dump(&self, room_id: RoomId) -> Result<Option<Room>, ManagementTaskError> {
+		self.manager.blocking_lock().dump(room_id)
+	}
+
 	fn assert_open_port(socket: SocketAddr) -> Result<(), EmbeddedServerWrapperError> {
 		let mut counter = 0;
 		while !port_scanner::scan_port_addr(socket) {
@@ -90,13 +141,20 @@ mod test {
 	use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 	use std::time::Duration;
 
+	use cheetah_common::room::access::AccessGroups;
+	use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+	use cheetah_server::server::room::config::member::MemberCreateParams;
+	use cheetah_server::server::room::config::object::GameObjectCreateParams;
+	use cheetah_server::server::room::config::room::RoomCreateParams;
+	use cheetah_server::server::room::member::RoomMemberStatus;
+
 	use crate::EmbeddedServerWrapper;
 
 	#[test]
 	fn should_open_tcp_ports_after_start() {
 		let server = create_server();
-		let admin_grpc_port = server.admin_webgrpc_socket_addr.port();
-		let internal_grpc_port = server.internal_grpc_socket_addr.port();
+		let admin_grpc_port = server.admin_webgrpc_socket_addr.unwrap().port();
+		let internal_grpc_port = server.internal_grpc_socket_addr.unwrap().port();
 		assert!(port_scanner::scan_port(admin_grpc_port));
 		assert!(port_scanner::scan_port(internal_grpc_port));
 	}
@@ -110,11 +168,47 @@ mod test {
 		assert_ne!(server_a.internal_grpc_socket_addr, server_b.internal_grpc_socket_addr);
 	}
 
+	#[test]
+	fn should_run_without_management_services() {
+		let server = EmbeddedServerWrapper::run_new_server_without_management_services(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)).unwrap();
+		assert_ne!(server.game_socket_addr.port(), 0);
+		assert!(server.internal_grpc_socket_addr.is_none());
+		assert!(server.internal_webgrpc_socket_addr.is_none());
+		assert!(server.admin_webgrpc_socket_addr.is_none());
+		server.shutdown();
+	}
+
+	#[test]
+	fn should_manage_rooms_and_members_directly() {
+		let server = EmbeddedServerWrapper::run_new_server_without_management_services(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)).unwrap();
+		let template = RoomCreateParams {
+			objects: vec![GameObjectCreateParams {
+				id: 1,
+				template: 5,
+				..Default::default()
+			}],
+			..Default::default()
+		};
+		let room_id = server.create_room(template).unwrap();
+		assert_eq!(server.get_rooms().unwrap(), vec![room_id]);
+		assert_eq!(server.dump(room_id).unwrap().unwrap().objects.len(), 1);
+
+		let member_id = server.create_member(room_id, MemberCreateParams::new_member(AccessGroups(1), Default::default())).unwrap();
+		let members = server.get_members(room_id).unwrap().unwrap();
+		assert_eq!(members, vec![(member_id, RoomMemberStatus::Created)]);
+
+		server.delete_member(MemberAndRoomId { member_id, room_id }).unwrap();
+		server.delete_room(room_id).unwrap();
+		assert!(server.get_rooms().unwrap().is_empty());
+		assert!(server.get_members(room_id).unwrap().is_none());
+		server.shutdown();
+	}
+
 	#[test]
 	fn should_shutdown_server() {
 		let server = create_server();
-		let admin_grpc_port = server.admin_webgrpc_socket_addr.port();
-		let internal_grpc_port = server.internal_grpc_socket_addr.port();
+		let admin_grpc_port = server.admin_webgrpc_socket_addr.unwrap().port();
+		let internal_grpc_port = server.internal_grpc_socket_addr.unwrap().port();
 		server.shutdown();
 		std::thread::sleep(Duration::from_millis(100));
 		assert!(!port_scanner::scan_port(admin_grpc_port));
@@ -122,13 +216,12 @@ mod test {
 	}
 
 	fn create_server() -> EmbeddedServerWrapper {
-		let server = EmbeddedServerWrapper::run_new_server(
+		EmbeddedServerWrapper::run_new_server(
 			SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
 			SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
 			SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
 			SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
 		)
-		.unwrap();
-		server
+		.unwrap()
 	}
 }
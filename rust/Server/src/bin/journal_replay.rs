@@ -0,0 +1,36 @@
+use cheetah_server::server::journal::replay::JournalReplay;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: cheetah-journal-replay <path.journal> [--to <record>] [--verbose]";
+
+///
+/// Воспроизведение журнала комнаты: выполняет записи до заданной (по-умолчанию - все, с проверкой итогового состояния)
+/// и выводит состояние комнаты в формате RON
+///
+#[allow(clippy::print_stdout)]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+	let mut path: Option<PathBuf> = None;
+	let mut to: Option<usize> = None;
+	let mut verbose = false;
+	let mut args = std::env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--to" => to = Some(args.next().ok_or(USAGE)?.parse()?),
+			"--verbose" => verbose = true,
+			_ if path.is_none() => path = Some(arg.into()),
+			_ => return Err(format!("Unknown argument {arg}\n{USAGE}").into()),
+		}
+	}
+	let mut replay = JournalReplay::load(&path.ok_or(USAGE)?)?;
+	let to = to.unwrap_or(replay.records().len());
+	while replay.position() < to {
+		let position = replay.position();
+		match replay.step()? {
+			None => break,
+			Some(record) if verbose => println!("{} {} {:?}", position, record.time_in_ms, record.event),
+			Some(_) => {}
+		}
+	}
+	println!("{}", ron::ser::to_string_pretty(replay.room(), Default::default())?);
+	Ok(())
+}
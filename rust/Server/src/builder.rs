@@ -1,41 +1,88 @@
 use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
-use std::io;
-use std::net::{SocketAddr, UdpSocket};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
+use std::{fs, io};
 
-use crate::server::manager::RoomsServerManagerError;
+use crate::server::manager::{ManagementTaskError, RoomsServerManagerError};
+use crate::server::room::config::templates::{RoomTemplates, RoomTemplatesError};
+use crate::server::security::{TlsSettings, TlsSettingsError};
+use crate::server::websocket::WebSocketListener;
 use crate::{Server, ServerManager};
 use thiserror::Error;
 use tokio::net::TcpListener;
 use tokio::sync::Mutex;
 
+///
+/// Минимальный интервал перечитывания шаблонов комнат
+///
+pub const MIN_ROOM_TEMPLATES_RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
 ///
 /// Паттерн Создатель для игрового сервера
 /// - если адреса для udp/grpc не заданы - то в качестве адреса выбирается 127.0.0.1, в качестве
-/// порта - свободный порт
+///   порта - свободный порт
+/// - при включенном dual stack сокеты с адресом 0.0.0.0 открываются на [::] и принимают как IPv4 так и IPv6 соединения,
+///   если IPv6 недоступен - используется исходный IPv4 адрес
+/// - при остановке сервер ожидает завершения комнат не дольше drain timeout, затем отключает пользователей
+/// - TLS и bearer токен применяются ко всем внутренним сервисам (grpc, grpc-web, debug rest)
+/// - WebSocket listener для клиентов без UDP открывается только если задан его адрес
 ///
 pub struct ServerBuilder {
 	game_bind_addr: SocketAddr,
+	websocket_bind_address: Option<SocketAddr>,
 	debug_rest_service_bind_address: SocketAddr,
 	internal_grpc_service_bind_address: SocketAddr,
 	internal_webgrpc_service_bind_address: SocketAddr,
 	is_agones_enabled: bool,
+	registry_var_names: Vec<String>,
+	is_dual_stack_enabled: bool,
+	is_management_services_enabled: bool,
+	member_token_key: Option<Vec<u8>>,
+	management_tls: Option<TlsSettings>,
+	management_token: Option<String>,
+	drain_timeout: Duration,
+	is_drain_on_terminate_signal_enabled: bool,
 	protocol_configuration: ProtocolConfiguration,
+	super_member_key: Option<Vec<u8>>,
+	max_alive_rooms: usize,
+	max_created_rooms: usize,
+	room_templates_dir: Option<PathBuf>,
+	room_templates_reload_interval: Duration,
+	room_journal_dir: Option<PathBuf>,
+	member_metrics_limit: Option<usize>,
 }
 
 impl Default for ServerBuilder {
 	fn default() -> Self {
 		Self {
 			game_bind_addr: SocketAddr::from_str("127.0.0.1:0").unwrap(),
+			websocket_bind_address: None,
 			debug_rest_service_bind_address: SocketAddr::from_str("127.0.0.1:0").unwrap(),
 			internal_grpc_service_bind_address: SocketAddr::from_str("127.0.0.1:0").unwrap(),
 			internal_webgrpc_service_bind_address: SocketAddr::from_str("127.0.0.1:0").unwrap(),
 			is_agones_enabled: false,
+			registry_var_names: Vec::new(),
+			is_dual_stack_enabled: false,
+			is_management_services_enabled: true,
+			member_token_key: None,
+			management_tls: None,
+			management_token: None,
+			drain_timeout: Duration::from_secs(60),
+			is_drain_on_terminate_signal_enabled: false,
 			protocol_configuration: ProtocolConfiguration {
 				disconnect_timeout: Duration::from_secs(180),
 			},
+			super_member_key: None,
+			max_alive_rooms: 20,
+			max_created_rooms: 80,
+			room_templates_dir: None,
+			room_templates_reload_interval: Duration::from_secs(5),
+			room_journal_dir: None,
+			member_metrics_limit: None,
 		}
 	}
 }
@@ -50,6 +97,18 @@ pub enum ServerBuilderError {
 	ErrorBindUdpSocket(io::Error),
 	#[error("ErrorGetLocalAddrFromUdpSocket {0}")]
 	ErrorGetLocalAddrFromUdpSocket(io::Error),
+	#[error("ErrorOpenWebSocketSocket {0}")]
+	ErrorOpenWebSocketSocket(io::Error),
+	#[error("ManagementTask {0}")]
+	ManagementTask(ManagementTaskError),
+	#[error("ManagementTls {0}")]
+	ManagementTls(TlsSettingsError),
+	#[error("RoomTemplates {0}")]
+	RoomTemplates(RoomTemplatesError),
+	#[error("RoomTemplatesReloadInterval {0:?} less than {MIN_ROOM_TEMPLATES_RELOAD_INTERVAL:?}")]
+	RoomTemplatesReloadInterval(Duration),
+	#[error("ErrorCreateRoomJournalDir {0}")]
+	ErrorCreateRoomJournalDir(io::Error),
 }
 
 impl ServerBuilder {
@@ -59,6 +118,15 @@ impl ServerBuilder {
 		self
 	}
 
+	///
+	/// Адрес WebSocket listener, фреймы передаются так же как через UDP, комнаты не различают транспорт пользователей
+	///
+	#[must_use]
+	pub fn set_websocket_bind_address(mut self, addr: SocketAddr) -> Self {
+		self.websocket_bind_address = Some(addr);
+		self
+	}
+
 	#[must_use]
 	pub fn set_debug_rest_service_bind_address(mut self, addr: SocketAddr) -> Self {
 		self.debug_rest_service_bind_address = addr;
@@ -83,6 +151,122 @@ impl ServerBuilder {
 		self
 	}
 
+	///
+	/// Имена переменных окружения, значения которых передаются в registry вместе со статусом сервера
+	///
+	#[must_use]
+	pub fn set_registry_var_names(mut self, names: Vec<String>) -> Self {
+		self.registry_var_names = names;
+		self
+	}
+
+	#[must_use]
+	pub fn enable_dual_stack(mut self) -> Self {
+		self.is_dual_stack_enabled = true;
+		self
+	}
+
+	///
+	/// Не открывать grpc, grpc-web и debug rest сервисы, сервер управляется только через [`ServerManager`],
+	/// используется при запуске сервера внутри приложения
+	///
+	#[must_use]
+	pub fn disable_management_services(mut self) -> Self {
+		self.is_management_services_enabled = false;
+		self
+	}
+
+	///
+	/// Останавливать сервер по сигналу SIGTERM (на Windows - Ctrl-C) с ожиданием завершения комнат
+	///
+	#[must_use]
+	pub fn enable_drain_on_terminate_signal(mut self) -> Self {
+		self.is_drain_on_terminate_signal_enabled = true;
+		self
+	}
+
+	#[must_use]
+	pub fn set_drain_timeout(mut self, drain_timeout: Duration) -> Self {
+		self.drain_timeout = drain_timeout;
+		self
+	}
+
+	///
+	/// Ключ для проверки токенов пользователей, без ключа подключение по токенам отключено
+	///
+	#[must_use]
+	pub fn set_member_token_key(mut self, key: Vec<u8>) -> Self {
+		self.member_token_key = Some(key);
+		self
+	}
+
+	///
+	/// TLS для внутренних сервисов, при заданном CA клиентов - mTLS
+	///
+	#[must_use]
+	pub fn set_management_tls(mut self, tls: TlsSettings) -> Self {
+		self.management_tls = Some(tls);
+		self
+	}
+
+	///
+	/// Bearer токен, обязательный для всех запросов к внутренним сервисам
+	///
+	#[must_use]
+	pub fn set_management_token(mut self, token: String) -> Self {
+		self.management_token = Some(token);
+		self
+	}
+
+	///
+	/// Ключ супер пользователя, создаваемого в каждой новой комнате
+	///
+	#[must_use]
+	pub fn set_super_member_key(mut self, key: Vec<u8>) -> Self {
+		self.super_member_key = Some(key);
+		self
+	}
+
+	///
+	/// Ограничения для agones: количество одновременно живущих комнат и общее количество созданных комнат до перезапуска
+	///
+	#[must_use]
+	pub fn set_rooms_limits(mut self, max_alive_rooms: usize, max_created_rooms: usize) -> Self {
+		self.max_alive_rooms = max_alive_rooms;
+		self.max_created_rooms = max_created_rooms;
+		self
+	}
+
+	///
+	/// Каталог RON/JSON шаблонов комнат, шаблоны проверяются при запуске и перечитываются с заданным интервалом,
+	/// интервал не может быть меньше [`MIN_ROOM_TEMPLATES_RELOAD_INTERVAL`]
+	///
+	#[must_use]
+	pub fn set_room_templates_dir(mut self, dir: PathBuf, reload_interval: Duration) -> Self {
+		self.room_templates_dir = Some(dir);
+		self.room_templates_reload_interval = reload_interval;
+		self
+	}
+
+	///
+	/// Каталог журналов комнат, журнал записывается только для комнат с включенным `journal`, каталог создается при запуске
+	///
+	#[must_use]
+	pub fn set_room_journal_dir(mut self, dir: PathBuf) -> Self {
+		self.room_journal_dir = Some(dir);
+		self
+	}
+
+	///
+	/// Экспортировать в prometheus сетевую статистику пользователей с метками комнаты и пользователя,
+	/// не более max_members пользователей
+	///
+	#[must_use]
+	pub fn enable_member_metrics(mut self, max_members: usize) -> Self {
+		self.member_metrics_limit = Some(max_members);
+		self
+	}
+
 	#[must_use]
 	pub fn set_disconnect_duration(mut self, disconnect_timeout: Duration) -> Self {
 		self.protocol_configuration.disconnect_timeout = disconnect_timeout;
@@ -90,22 +274,186 @@ impl ServerBuilder {
 	}
 
 	pub async fn build(self) -> Result<Server, ServerBuilderError> {
-		let game_socket = UdpSocket::bind(self.game_bind_addr).map_err(ServerBuilderError::ErrorBindUdpSocket)?;
+		if let Some(tls) = &self.management_tls {
+			tls.rustls_config().map_err(ServerBuilderError::ManagementTls)?;
+		}
+		if self.room_templates_dir.is_some() && self.room_templates_reload_interval < MIN_ROOM_TEMPLATES_RELOAD_INTERVAL {
+			return Err(ServerBuilderError::RoomTemplatesReloadInterval(self.room_templates_reload_interval));
+		}
+		let room_templates = match &self.room_templates_dir {
+			None => None,
+			Some(dir) => Some(Arc::new(RoomTemplates::load(dir).map_err(ServerBuilderError::RoomTemplates)?)),
+		};
+		let game_socket = bind_udp_socket(self.game_bind_addr, self.is_dual_stack_enabled).map_err(ServerBuilderError::ErrorBindUdpSocket)?;
 		let game_socket_addr = game_socket.local_addr().map_err(ServerBuilderError::ErrorGetLocalAddrFromUdpSocket)?;
-		let server_manager = ServerManager::new(game_socket, self.protocol_configuration).map_err(ServerBuilderError::RoomsServerManager)?;
+		let mut server_manager = ServerManager::new(game_socket, self.protocol_configuration).map_err(ServerBuilderError::RoomsServerManager)?;
+		if let Some(key) = self.member_token_key {
+			server_manager.set_member_token_key(key).map_err(ServerBuilderError::ManagementTask)?;
+		}
+		if let Some(dir) = self.room_journal_dir {
+			fs::create_dir_all(&dir).map_err(ServerBuilderError::ErrorCreateRoomJournalDir)?;
+			server_manager.set_room_journal_dir(dir).map_err(ServerBuilderError::ManagementTask)?;
+		}
+		if let Some(room_templates) = &room_templates {
+			server_manager.set_room_templates(Arc::clone(room_templates)).map_err(ServerBuilderError::ManagementTask)?;
+		}
+		if let Some(max_members) = self.member_metrics_limit {
+			server_manager.enable_member_metrics(max_members).map_err(ServerBuilderError::ManagementTask)?;
+		}
+		let websocket_addr = match self.websocket_bind_address {
+			None => None,
+			Some(addr) => {
+				let listener = bind_std_tcp_listener(addr, self.is_dual_stack_enabled)
+					.and_then(WebSocketListener::new)
+					.map_err(ServerBuilderError::ErrorOpenWebSocketSocket)?;
+				let websocket_addr = listener.local_addr().map_err(ServerBuilderError::ErrorOpenWebSocketSocket)?;
+				server_manager.enable_websocket(listener).map_err(ServerBuilderError::ManagementTask)?;
+				Some(websocket_addr)
+			}
+		};
 		let manager = Arc::new(Mutex::new(server_manager));
 
-		let internal_grpc_listener = TcpListener::bind(self.internal_grpc_service_bind_address).await.map_err(ServerBuilderError::ErrorOpenGrpcSocket)?;
-		let internal_webgrpc_listener = TcpListener::bind(self.internal_webgrpc_service_bind_address).await.map_err(ServerBuilderError::ErrorOpenGrpcSocket)?;
-		let debug_rest_service_listener = TcpListener::bind(self.debug_rest_service_bind_address).await.map_err(ServerBuilderError::ErrorOpenGrpcSocket)?;
+		let bind_management_listener = |addr| -> Result<Option<TcpListener>, ServerBuilderError> {
+			if self.is_management_services_enabled {
+				bind_tcp_listener(addr, self.is_dual_stack_enabled).map(Some).map_err(ServerBuilderError::ErrorOpenGrpcSocket)
+			} else {
+				Ok(None)
+			}
+		};
+		let internal_grpc_listener = bind_management_listener(self.internal_grpc_service_bind_address)?;
+		let internal_webgrpc_listener = bind_management_listener(self.internal_webgrpc_service_bind_address)?;
+		let debug_rest_service_listener = bind_management_listener(self.debug_rest_service_bind_address)?;
 
 		Ok(Server {
 			game_socket_addr,
+			websocket_addr,
 			internal_webgrpc_listener,
 			internal_grpc_listener,
 			debug_rest_service_listener,
 			is_agones_enabled: self.is_agones_enabled,
+			registry_var_names: self.registry_var_names,
 			manager,
+			management_tls: self.management_tls,
+			management_token: self.management_token.map(Arc::from),
+			drain_timeout: self.drain_timeout,
+			is_drain_on_terminate_signal_enabled: self.is_drain_on_terminate_signal_enabled,
+			super_member_key: self.super_member_key,
+			max_alive_rooms: self.max_alive_rooms,
+			max_created_rooms: self.max_created_rooms,
+			room_templates,
+			room_templates_reload_interval: self.room_templates_reload_interval,
 		})
 	}
 }
+
+fn bind_udp_socket(addr: SocketAddr, dual_stack: bool) -> io::Result<UdpSocket> {
+	match dual_stack_addr(addr, dual_stack) {
+		None => UdpSocket::bind(addr),
+		Some(dual_stack_addr) => match bind_dual_stack_socket(dual_stack_addr, Type::DGRAM, Protocol::UDP) {
+			Ok(socket) => Ok(socket.into()),
+			Err(e) => {
+				tracing::warn!("Dual stack udp socket {:?} not available, use {:?}: {:?}", dual_stack_addr, addr, e);
+				UdpSocket::bind(addr)
+			}
+		},
+	}
+}
+
+fn bind_tcp_listener(addr: SocketAddr, dual_stack: bool) -> io::Result<TcpListener> {
+	let listener = bind_std_tcp_listener(addr, dual_stack)?;
+	listener.set_nonblocking(true)?;
+	TcpListener::from_std(listener)
+}
+
+fn bind_std_tcp_listener(addr: SocketAddr, dual_stack: bool) -> io::Result<std::net::TcpListener> {
+	let listener = match dual_stack_addr(addr, dual_stack) {
+		None => std::net::TcpListener::bind(addr)?,
+		Some(dual_stack_addr) => match bind_dual_stack_socket(dual_stack_addr, Type::STREAM, Protocol::TCP).and_then(|socket| socket.listen(1024).map(|_| socket)) {
+			Ok(socket) => socket.into(),
+			Err(e) => {
+				tracing::warn!("Dual stack tcp socket {:?} not available, use {:?}: {:?}", dual_stack_addr, addr, e);
+				std::net::TcpListener::bind(addr)?
+			}
+		},
+	};
+	Ok(listener)
+}
+
+///
+/// Адрес для dual stack сокета, None - если адрес необходимо использовать как есть
+///
+fn dual_stack_addr(addr: SocketAddr, dual_stack: bool) -> Option<SocketAddr> {
+	if !dual_stack {
+		return None;
+	}
+	match addr.ip() {
+		IpAddr::V4(ip) if ip == Ipv4Addr::UNSPECIFIED => Some(SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), addr.port())),
+		IpAddr::V6(ip) if ip == Ipv6Addr::UNSPECIFIED => Some(addr),
+		_ => None,
+	}
+}
+
+fn bind_dual_stack_socket(addr: SocketAddr, socket_type: Type, protocol: Protocol) -> io::Result<Socket> {
+	let socket = Socket::new(Domain::IPV6, socket_type, Some(protocol))?;
+	socket.set_only_v6(false)?;
+	if socket_type == Type::STREAM {
+		socket.set_reuse_address(true)?;
+	}
+	socket.bind(&addr.into())?;
+	Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::builder::{bind_tcp_listener, bind_udp_socket, ServerBuilder, ServerBuilderError};
+	use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+	use std::str::FromStr;
+	use std::time::Duration;
+
+	#[test]
+	fn should_receive_ipv4_and_ipv6_on_dual_stack_socket() {
+		let socket = bind_udp_socket(SocketAddr::from_str("0.0.0.0:0").unwrap(), true).unwrap();
+		socket.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+		let port = socket.local_addr().unwrap().port();
+		assert!(socket.local_addr().unwrap().is_ipv6());
+
+		UdpSocket::bind("127.0.0.1:0").unwrap().send_to(&[4], SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port)).unwrap();
+		UdpSocket::bind("[::1]:0").unwrap().send_to(&[6], SocketAddr::new(Ipv6Addr::LOCALHOST.into(), port)).unwrap();
+
+		let mut received = [0; 2];
+		let mut buffer = [0; 16];
+		for value in &mut received {
+			socket.recv(&mut buffer).unwrap();
+			*value = buffer[0];
+		}
+		received.sort_unstable();
+		assert_eq!(received, [4, 6]);
+	}
+
+	#[test]
+	fn should_use_address_as_is_without_dual_stack() {
+		let socket = bind_udp_socket(SocketAddr::from_str("127.0.0.1:0").unwrap(), true).unwrap();
+		assert_eq!(socket.local_addr().unwrap().ip(), Ipv4Addr::LOCALHOST);
+		let socket = bind_udp_socket(SocketAddr::from_str("0.0.0.0:0").unwrap(), false).unwrap();
+		assert!(socket.local_addr().unwrap().is_ipv4());
+	}
+
+	#[tokio::test]
+	async fn should_accept_ipv4_and_ipv6_on_dual_stack_listener() {
+		let listener = bind_tcp_listener(SocketAddr::from_str("0.0.0.0:0").unwrap(), true).unwrap();
+		let port = listener.local_addr().unwrap().port();
+		let _ipv4 = tokio::net::TcpStream::connect(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port)).await.unwrap();
+		let (_, ipv4_peer) = listener.accept().await.unwrap();
+		assert_eq!(ipv4_peer.ip().to_canonical(), Ipv4Addr::LOCALHOST);
+
+		let _ipv6 = tokio::net::TcpStream::connect(SocketAddr::new(Ipv6Addr::LOCALHOST.into(), port)).await.unwrap();
+		let (_, ipv6_peer) = listener.accept().await.unwrap();
+		assert_eq!(ipv6_peer.ip(), Ipv6Addr::LOCALHOST);
+	}
+
+	#[tokio::test]
+	async fn should_fail_build_with_zero_room_templates_reload_interval() {
+		let builder = ServerBuilder::default().set_room_templates_dir("templates".into(), Duration::ZERO);
+		assert!(matches!(builder.build().await, Err(ServerBuilderError::RoomTemplatesReloadInterval(Duration::ZERO))));
+	}
+}
@@ -0,0 +1,562 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{fs, io};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::builder::ServerBuilder;
+use crate::env::{get_debug_rest_service_default_address, get_internal_grpc_service_default_address, get_internal_webgrpc_service_default_address};
+use crate::server::security::TlsSettings;
+
+///
+/// Конфигурация сервера, загружается из TOML/RON файла
+///
+/// - отсутствующие в файле ключи берутся из значений по-умолчанию
+/// - переменные окружения (`LOG_LEVEL`, `MAX_ALIVE_ROOMS`, ...) переопределяют значения из файла
+/// - ошибки загрузки и проверки содержат имя некорректного ключа
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+	pub log_level: String,
+	pub listeners: ListenersConfig,
+	pub protocol: ProtocolConfig,
+	pub shutdown: ShutdownConfig,
+	pub limits: LimitsConfig,
+	pub security: SecurityConfig,
+	pub integrations: IntegrationsConfig,
+	pub room_templates: RoomTemplatesConfig,
+	pub room_journal: RoomJournalConfig,
+	pub metrics: MetricsConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListenersConfig {
+	pub game: SocketAddr,
+	pub internal_grpc: SocketAddr,
+	pub internal_webgrpc: SocketAddr,
+	pub debug_rest: SocketAddr,
+	///
+	/// WebSocket listener для клиентов без UDP, не открывается если не задан
+	///
+	pub websocket: Option<SocketAddr>,
+	///
+	/// Открывать сокеты с адресом 0.0.0.0 на [::] для приема IPv4 и IPv6 соединений
+	///
+	pub dual_stack: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProtocolConfig {
+	///
+	/// Время без входящих пакетов, после которого пользователь считается отключенным
+	///
+	pub disconnect_timeout_in_sec: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+	pub drain_timeout_in_sec: u64,
+	pub drain_on_terminate_signal: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+	pub max_alive_rooms: usize,
+	pub max_created_rooms: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityConfig {
+	pub super_member_key: Option<String>,
+	pub member_token_key: Option<String>,
+	pub management_token: Option<String>,
+	pub management_tls: Option<ManagementTlsConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManagementTlsConfig {
+	pub cert: PathBuf,
+	pub key: PathBuf,
+	#[serde(default)]
+	pub client_ca: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IntegrationsConfig {
+	pub agones: bool,
+	///
+	/// Имена переменных окружения, значения которых передаются в registry вместе со статусом сервера
+	///
+	pub registry_var_names: Vec<String>,
+}
+
+///
+/// Каталог шаблонов комнат для `CreateRoomFromTemplate`
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoomTemplatesConfig {
+	pub dir: Option<PathBuf>,
+	pub reload_interval_in_sec: u64,
+}
+
+///
+/// Каталог журналов комнат, журнал ведется только для комнат созданных с `journal`
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoomJournalConfig {
+	pub dir: Option<PathBuf>,
+}
+
+///
+/// Метрики prometheus
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+	///
+	/// Максимальное количество пользователей с сетевой статистикой в метриках, 0 - статистика пользователей не экспортируется
+	///
+	pub member_metrics_limit: usize,
+}
+
+#[derive(Error, Debug)]
+pub enum ServerConfigError {
+	#[error("Read config {0:?}: {1}")]
+	Io(PathBuf, io::Error),
+	#[error("Unknown config format {0:?}, expected .toml or .ron")]
+	UnknownFormat(PathBuf),
+	#[error("Config key {key}: {message}")]
+	Parse { key: String, message: String },
+	#[error("Env {name}: {message}")]
+	Env { name: String, message: String },
+	#[error("Config key {key}: {message}")]
+	Invalid { key: String, message: String },
+	#[error("Config key security.management_tls: {0}")]
+	ManagementTls(io::Error),
+}
+
+impl Default for ServerConfig {
+	fn default() -> Self {
+		Self {
+			log_level: "info".to_owned(),
+			listeners: Default::default(),
+			protocol: Default::default(),
+			shutdown: Default::default(),
+			limits: Default::default(),
+			security: Default::default(),
+			integrations: Default::default(),
+			room_templates: Default::default(),
+			room_journal: Default::default(),
+			metrics: Default::default(),
+		}
+	}
+}
+
+impl Default for ListenersConfig {
+	fn default() -> Self {
+		Self {
+			game: "0.0.0.0:5555".parse().unwrap(),
+			internal_grpc: get_internal_grpc_service_default_address(),
+			internal_webgrpc: get_internal_webgrpc_service_default_address(),
+			debug_rest: get_debug_rest_service_default_address(),
+			websocket: None,
+			dual_stack: true,
+		}
+	}
+}
+
+impl Default for ProtocolConfig {
+	fn default() -> Self {
+		Self { disconnect_timeout_in_sec: 180 }
+	}
+}
+
+impl Default for ShutdownConfig {
+	fn default() -> Self {
+		Self {
+			drain_timeout_in_sec: 60,
+			drain_on_terminate_signal: true,
+		}
+	}
+}
+
+impl Default for RoomTemplatesConfig {
+	fn default() -> Self {
+		Self { dir: None, reload_interval_in_sec: 5 }
+	}
+}
+
+impl Default for LimitsConfig {
+	fn default() -> Self {
+		Self {
+			max_alive_rooms: 20,
+			max_created_rooms: 80,
+		}
+	}
+}
+
+impl ServerConfig {
+	///
+	/// Загрузить конфигурацию из файла (если задан), применить переменные окружения и проверить результат
+	///
+	pub fn load(path: Option<&Path>) -> Result<Self, ServerConfigError> {
+		let mut config = match path {
+			None => Self::default(),
+			Some(path) => Self::from_file(path)?,
+		};
+		config.apply_env(|name| std::env::var(name).ok())?;
+		config.validate()?;
+		Ok(config)
+	}
+
+	///
+	/// Формат файла определяется по расширению - .toml или .ron
+	///
+	pub fn from_file(path: &Path) -> Result<Self, ServerConfigError> {
+		let content = fs::read_to_string(path).map_err(|e| ServerConfigError::Io(path.to_path_buf(), e))?;
+		match path.extension().and_then(|extension| extension.to_str()) {
+			Some("toml") => Self::from_toml(&content),
+			Some("ron") => Self::from_ron(&content),
+			_ => Err(ServerConfigError::UnknownFormat(path.to_path_buf())),
+		}
+	}
+
+	pub fn from_toml(content: &str) -> Result<Self, ServerConfigError> {
+		Self::deserialize(toml::Deserializer::new(content))
+	}
+
+	pub fn from_ron(content: &str) -> Result<Self, ServerConfigError> {
+		let mut deserializer = ron::Deserializer::from_str(content).map_err(|e| ServerConfigError::Parse {
+			key: ".".to_owned(),
+			message: e.to_string(),
+		})?;
+		Self::deserialize(&mut deserializer)
+	}
+
+	fn deserialize<'de, D>(deserializer: D) -> Result<Self, ServerConfigError>
+	where
+		D: serde::Deserializer<'de>,
+		D::Error: std::fmt::Display,
+	{
+		serde_path_to_error::deserialize(deserializer).map_err(|e| ServerConfigError::Parse {
+			key: e.path().to_string(),
+			message: e.inner().to_string(),
+		})
+	}
+
+	#[must_use]
+	pub fn to_toml(&self) -> String {
+		toml::to_string_pretty(self).unwrap()
+	}
+
+	///
+	/// Переопределить значения переменными окружения, имена переменных совпадают с использовавшимися до появления файла конфигурации
+	///
+	pub fn apply_env<F>(&mut self, var: F) -> Result<(), ServerConfigError>
+	where
+		F: Fn(&str) -> Option<String>,
+	{
+		if let Some(value) = var("LOG_LEVEL") {
+			self.log_level = value;
+		}
+		apply_parsed_env(&var, "DISCONNECT_TIMEOUT_IN_SEC", &mut self.protocol.disconnect_timeout_in_sec)?;
+		apply_parsed_env(&var, "DRAIN_TIMEOUT_IN_SEC", &mut self.shutdown.drain_timeout_in_sec)?;
+		apply_parsed_env(&var, "MAX_ALIVE_ROOMS", &mut self.limits.max_alive_rooms)?;
+		apply_parsed_env(&var, "MAX_CREATED_ROOMS", &mut self.limits.max_created_rooms)?;
+		apply_parsed_env(&var, "MEMBER_METRICS_LIMIT", &mut self.metrics.member_metrics_limit)?;
+		if var("ENABLE_AGONES").is_some() {
+			self.integrations.agones = true;
+		}
+		if let Some(value) = var("REGISTRY_VAR_NAMES") {
+			self.integrations.registry_var_names = value.split(',').map(str::trim).filter(|name| !name.is_empty()).map(str::to_owned).collect();
+		}
+		if let Some(value) = var("SUPER_MEMBER_KEY") {
+			self.security.super_member_key = Some(value);
+		}
+		if let Some(value) = var("MEMBER_TOKEN_KEY") {
+			self.security.member_token_key = Some(value);
+		}
+		if let Some(value) = var("MANAGEMENT_TOKEN") {
+			self.security.management_token = Some(value);
+		}
+		if let Some(value) = var("ROOM_TEMPLATES_DIR") {
+			self.room_templates.dir = Some(value.into());
+		}
+		if let Some(value) = var("ROOM_JOURNAL_DIR") {
+			self.room_journal.dir = Some(value.into());
+		}
+		if let (Some(cert), Some(key)) = (var("MANAGEMENT_TLS_CERT"), var("MANAGEMENT_TLS_KEY")) {
+			self.security.management_tls = Some(ManagementTlsConfig {
+				cert: cert.into(),
+				key: key.into(),
+				client_ca: var("MANAGEMENT_TLS_CLIENT_CA").map(PathBuf::from),
+			});
+		}
+		Ok(())
+	}
+
+	pub fn validate(&self) -> Result<(), ServerConfigError> {
+		tracing::Level::from_str(&self.log_level).map_err(|e| invalid("log_level", e))?;
+		if self.protocol.disconnect_timeout_in_sec == 0 {
+			return Err(invalid("protocol.disconnect_timeout_in_sec", "must be greater than 0"));
+		}
+		if self.limits.max_alive_rooms == 0 {
+			return Err(invalid("limits.max_alive_rooms", "must be greater than 0"));
+		}
+		if self.limits.max_created_rooms < self.limits.max_alive_rooms {
+			return Err(invalid("limits.max_created_rooms", "must be greater than or equal to limits.max_alive_rooms"));
+		}
+		if self.room_templates.reload_interval_in_sec == 0 {
+			return Err(invalid("room_templates.reload_interval_in_sec", "must be greater than 0"));
+		}
+		let mut listeners = vec![
+			("listeners.game", self.listeners.game),
+			("listeners.internal_grpc", self.listeners.internal_grpc),
+			("listeners.internal_webgrpc", self.listeners.internal_webgrpc),
+			("listeners.debug_rest", self.listeners.debug_rest),
+		];
+		if let Some(websocket) = self.listeners.websocket {
+			listeners.push(("listeners.websocket", websocket));
+		}
+		// udp и tcp порты не пересекаются, поэтому игровой порт проверяется только на корректность
+		for (index, (key, addr)) in listeners.iter().enumerate().skip(1) {
+			if addr.port() == 0 {
+				continue;
+			}
+			if let Some((other, _)) = listeners[1..index].iter().find(|(_, other)| other.port() == addr.port()) {
+				return Err(invalid(key, format!("port {} already used by {}", addr.port(), other)));
+			}
+		}
+		for (key, value) in [
+			("security.super_member_key", &self.security.super_member_key),
+			("security.member_token_key", &self.security.member_token_key),
+			("security.management_token", &self.security.management_token),
+		] {
+			if value.as_ref().is_some_and(String::is_empty) {
+				return Err(invalid(key, "must not be empty"));
+			}
+		}
+		Ok(())
+	}
+
+	///
+	/// Создать [`ServerBuilder`], файлы TLS сертификатов читаются на этом шаге
+	///
+	pub fn into_builder(self) -> Result<ServerBuilder, ServerConfigError> {
+		let mut builder = ServerBuilder::default()
+			.set_games_service_bind_address(self.listeners.game)
+			.set_internal_grpc_service_bind_address(self.listeners.internal_grpc)
+			.set_internal_webgrpc_service_bind_address(self.listeners.internal_webgrpc)
+			.set_debug_rest_service_bind_address(self.listeners.debug_rest)
+			.set_disconnect_duration(Duration::from_secs(self.protocol.disconnect_timeout_in_sec))
+			.set_drain_timeout(Duration::from_secs(self.shutdown.drain_timeout_in_sec))
+			.set_rooms_limits(self.limits.max_alive_rooms, self.limits.max_created_rooms);
+		if let Some(addr) = self.listeners.websocket {
+			builder = builder.set_websocket_bind_address(addr);
+		}
+		if let Some(dir) = self.room_templates.dir {
+			builder = builder.set_room_templates_dir(dir, Duration::from_secs(self.room_templates.reload_interval_in_sec));
+		}
+		if let Some(dir) = self.room_journal.dir {
+			builder = builder.set_room_journal_dir(dir);
+		}
+		if self.metrics.member_metrics_limit > 0 {
+			builder = builder.enable_member_metrics(self.metrics.member_metrics_limit);
+		}
+		if self.listeners.dual_stack {
+			builder = builder.enable_dual_stack();
+		}
+		if self.shutdown.drain_on_terminate_signal {
+			builder = builder.enable_drain_on_terminate_signal();
+		}
+		if self.integrations.agones {
+			builder = builder.enable_agones();
+		}
+		builder = builder.set_registry_var_names(self.integrations.registry_var_names);
+		if let Some(key) = self.security.super_member_key {
+			builder = builder.set_super_member_key(key.into_bytes());
+		}
+		if let Some(key) = self.security.member_token_key {
+			builder = builder.set_member_token_key(key.into_bytes());
+		}
+		if let Some(token) = self.security.management_token {
+			builder = builder.set_management_token(token);
+		}
+		if let Some(tls) = self.security.management_tls {
+			let tls = TlsSettings::from_files(&tls.cert, &tls.key, tls.client_ca.as_deref()).map_err(ServerConfigError::ManagementTls)?;
+			builder = builder.set_management_tls(tls);
+		}
+		Ok(builder)
+	}
+}
+
+fn apply_parsed_env<F, T>(var: &F, name: &str, target: &mut T) -> Result<(), ServerConfigError>
+where
+	F: Fn(&str) -> Option<String>,
+	T: FromStr,
+	T::Err: std::fmt::Display,
+{
+	if let Some(value) = var(name) {
+		*target = value.trim().parse().map_err(|e: T::Err| ServerConfigError::Env {
+			name: name.to_owned(),
+			message: format!("{value:?} {e}"),
+		})?;
+	}
+	Ok(())
+}
+
+fn invalid(key: &str, message: impl ToString) -> ServerConfigError {
+	ServerConfigError::Invalid {
+		key: key.to_owned(),
+		message: message.to_string(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use crate::config::{ManagementTlsConfig, ServerConfig, ServerConfigError};
+
+	#[test]
+	fn should_load_toml() {
+		let config = ServerConfig::from_toml(
+			r#"
+			log_level = "debug"
+			[listeners]
+			game = "127.0.0.1:7777"
+			[limits]
+			max_alive_rooms = 5
+			"#,
+		)
+		.unwrap();
+		assert_eq!(config.log_level, "debug");
+		assert_eq!(config.listeners.game, "127.0.0.1:7777".parse().unwrap());
+		assert_eq!(config.limits.max_alive_rooms, 5);
+		assert_eq!(config.limits.max_created_rooms, ServerConfig::default().limits.max_created_rooms);
+	}
+
+	#[test]
+	fn should_load_ron() {
+		let config = ServerConfig::from_ron(r#"(protocol: (disconnect_timeout_in_sec: 10), integrations: (agones: true))"#).unwrap();
+		assert_eq!(config.protocol.disconnect_timeout_in_sec, 10);
+		assert!(config.integrations.agones);
+	}
+
+	#[test]
+	fn should_print_default_config_as_loadable_toml() {
+		let config = ServerConfig::default();
+		assert_eq!(ServerConfig::from_toml(&config.to_toml()).unwrap(), config);
+	}
+
+	#[test]
+	fn should_name_key_in_parse_error() {
+		match ServerConfig::from_toml("[limits]\nmax_alive_rooms = \"many\"").unwrap_err() {
+			ServerConfigError::Parse { key, .. } => assert_eq!(key, "limits.max_alive_rooms"),
+			e => panic!("{e:?}"),
+		}
+		match ServerConfig::from_ron("(listeners: (game: \"wrong\"))").unwrap_err() {
+			ServerConfigError::Parse { key, .. } => assert_eq!(key, "listeners.game"),
+			e => panic!("{e:?}"),
+		}
+	}
+
+	#[test]
+	fn should_fail_on_unknown_key() {
+		match ServerConfig::from_toml("[limits]\nmax_rooms = 1").unwrap_err() {
+			ServerConfigError::Parse { key, message } => {
+				assert_eq!(key, "limits.max_rooms");
+				assert!(message.contains("max_rooms"));
+			}
+			e => panic!("{e:?}"),
+		}
+	}
+
+	#[test]
+	fn should_override_by_env() {
+		let envs = HashMap::from([
+			("MAX_ALIVE_ROOMS", "3"),
+			("ENABLE_AGONES", ""),
+			("REGISTRY_VAR_NAMES", "BUILD_ID, VERSION"),
+			("MANAGEMENT_TOKEN", "token"),
+			("ROOM_TEMPLATES_DIR", "/templates"),
+			("ROOM_JOURNAL_DIR", "/journals"),
+			("MEMBER_METRICS_LIMIT", "50"),
+			("MANAGEMENT_TLS_CERT", "cert.pem"),
+			("MANAGEMENT_TLS_KEY", "key.pem"),
+		]);
+		let mut config = ServerConfig::default();
+		config.apply_env(|name| envs.get(name).map(|value| (*value).to_owned())).unwrap();
+		assert_eq!(config.limits.max_alive_rooms, 3);
+		assert!(config.integrations.agones);
+		assert_eq!(config.integrations.registry_var_names, vec!["BUILD_ID".to_owned(), "VERSION".to_owned()]);
+		assert_eq!(config.security.management_token.as_deref(), Some("token"));
+		assert_eq!(config.room_templates.dir, Some("/templates".into()));
+		assert_eq!(config.room_journal.dir, Some("/journals".into()));
+		assert_eq!(config.metrics.member_metrics_limit, 50);
+		assert_eq!(
+			config.security.management_tls,
+			Some(ManagementTlsConfig {
+				cert: "cert.pem".into(),
+				key: "key.pem".into(),
+				client_ca: None,
+			})
+		);
+	}
+
+	#[test]
+	fn should_name_env_in_error() {
+		let mut config = ServerConfig::default();
+		match config.apply_env(|name| (name == "DRAIN_TIMEOUT_IN_SEC").then(|| "soon".to_owned())).unwrap_err() {
+			ServerConfigError::Env { name, .. } => assert_eq!(name, "DRAIN_TIMEOUT_IN_SEC"),
+			e => panic!("{e:?}"),
+		}
+	}
+
+	#[test]
+	fn should_validate() {
+		assert!(ServerConfig::default().validate().is_ok());
+
+		let mut config = ServerConfig::default();
+		config.listeners.debug_rest = config.listeners.internal_grpc;
+		assert_invalid_key(&config, "listeners.debug_rest");
+
+		let mut config = ServerConfig::default();
+		config.listeners.websocket = Some(config.listeners.internal_grpc);
+		assert_invalid_key(&config, "listeners.websocket");
+
+		let config = ServerConfig {
+			log_level: "verbose".to_owned(),
+			..Default::default()
+		};
+		assert_invalid_key(&config, "log_level");
+
+		let mut config = ServerConfig::default();
+		config.limits.max_created_rooms = config.limits.max_alive_rooms - 1;
+		assert_invalid_key(&config, "limits.max_created_rooms");
+
+		let mut config = ServerConfig::default();
+		config.security.management_token = Some(String::new());
+		assert_invalid_key(&config, "security.management_token");
+	}
+
+	fn assert_invalid_key(config: &ServerConfig, expected: &str) {
+		match config.validate().unwrap_err() {
+			ServerConfigError::Invalid { key, .. } => assert_eq!(key, expected),
+			e => panic!("{e:?}"),
+		}
+	}
+}
@@ -12,7 +12,9 @@ use tokio::sync::Mutex;
 ///
 /// Цикл оповещения agones и NotifyService
 ///
-pub async fn agones_und_notifyservice_cycle(server_manager: Arc<Mutex<ServerManager>>, max_alive_rooms: usize, max_created_rooms: usize) {
+/// - при остановке сервера (drain) отправляется состояние NotReady, после остановки - shutdown в agones
+///
+pub async fn agones_und_notifyservice_cycle(server_manager: Arc<Mutex<ServerManager>>, max_alive_rooms: usize, max_created_rooms: usize, drain_timeout: Duration, registry_var_names: &[String]) {
 	match Sdk::connect(None, Some(Duration::from_secs(2)), Some(Duration::from_secs(2))).await {
 		Ok((mut sdk, gameserver)) => {
 			sdk.mark_ready().await.unwrap();
@@ -23,7 +25,14 @@ pub async fn agones_und_notifyservice_cycle(server_manager: Arc<Mutex<ServerMana
 			while is_server_running(&server_manager).await {
 				let server_manager = server_manager.lock().await;
 				let current_count_rooms = server_manager.get_rooms().unwrap_or_default().len();
-				let created_rooms_count = server_manager.get_created_rooms_count().unwrap();
+				let created_rooms_count = match server_manager.get_created_rooms_count() {
+					Ok(count) => count,
+					Err(e) => {
+						// поток сервера остановлен после завершения drain
+						tracing::info!("Agones: server stopped {:?}", e);
+						break;
+					}
+				};
 
 				if !allocated && current_count_rooms > 0 {
 					sdk.allocate().await.unwrap();
@@ -32,10 +41,15 @@ pub async fn agones_und_notifyservice_cycle(server_manager: Arc<Mutex<ServerMana
 
 				let need_to_restart = created_rooms_count >= max_created_rooms;
 				let is_allow_to_restart = need_to_restart && current_count_rooms == 0;
-				if is_allow_to_restart {
+				if is_allow_to_restart && !server_manager.is_draining() {
 					tracing::info!("Max created rooms limit reached - shutdown");
-					sdk.shutdown().await.unwrap();
-					notify_registry_with_tracing_error(&gameserver, State::NotReady).await;
+					if let Err(e) = server_manager.drain(drain_timeout) {
+						tracing::error!("Agones: error drain server {:?}", e);
+					}
+				}
+				if server_manager.is_draining() {
+					notify_registry_with_tracing_error(&gameserver, State::NotReady, registry_var_names).await;
+					health = send_agones_health(&mut sdk, health).await;
 				} else {
 					let server_is_full = current_count_rooms >= max_alive_rooms;
 					let state = if allocated {
@@ -47,12 +61,12 @@ pub async fn agones_und_notifyservice_cycle(server_manager: Arc<Mutex<ServerMana
 					} else {
 						State::Ready
 					};
-					notify_registry_with_tracing_error(&gameserver, state).await;
+					notify_registry_with_tracing_error(&gameserver, state, registry_var_names).await;
 					health = send_agones_health(&mut sdk, health).await;
 				}
 				tokio::time::sleep(Duration::from_secs(2)).await;
 			}
-			notify_registry_with_tracing_error(&gameserver, State::NotReady).await;
+			notify_registry_with_tracing_error(&gameserver, State::NotReady, registry_var_names).await;
 			sdk.shutdown().await.unwrap();
 		}
 		Err(e) => {
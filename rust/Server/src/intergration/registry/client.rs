@@ -17,31 +17,22 @@ impl RegistryClient {
 		Ok(Self { client })
 	}
 
-	pub async fn update_server_status(&self, game: Addr, grpc_internal: Addr, state: State) -> Result<(), Status> {
+	///
+	/// Вместе со статусом передаются значения переменных окружения с именами из `var_names`
+	///
+	pub async fn update_server_status(&self, game: Addr, grpc_internal: Addr, state: State, var_names: &[String]) -> Result<(), Status> {
 		let req = Request::new(status::ServerStatus {
 			game: Some(game),
 			grpc_internal: Some(grpc_internal),
 			state: state as i32,
-			envs: RegistryClient::get_envs(),
+			envs: RegistryClient::get_envs(var_names),
 		});
 
 		self.client.clone().set_status(req).await.map(|_| ())
 	}
 
-	fn get_envs() -> HashMap<String, String> {
-		match std::env::var("REGISTRY_VAR_NAMES") {
-			Ok(names) => {
-				return names
-					.split(",")
-					.map(|v| v.trim())
-					.map(|v| (v.into(), std::env::var(v)))
-					.filter(|(_, v)| v.is_ok())
-					.map(|(k, v)| (k, v.unwrap()))
-					.collect();
-			}
-			Err(_) => {}
-		}
-		Default::default()
+	fn get_envs(var_names: &[String]) -> HashMap<String, String> {
+		var_names.iter().filter_map(|name| std::env::var(name).ok().map(|value| (name.clone(), value))).collect()
 	}
 }
 
@@ -54,10 +45,10 @@ mod test {
 
 	#[test]
 	pub fn should_make_envs() {
-		env::set_var("REGISTRY_VAR_NAMES", "BUILD_ID,VERSION");
 		env::set_var("BUILD_ID", "1445");
 		env::set_var("VERSION", "7");
 		let map = HashMap::<String, String>::from([("BUILD_ID".into(), "1445".into()), ("VERSION".into(), "7".into())]);
-		assert_eq!(RegistryClient::get_envs(), map);
+		let var_names = ["BUILD_ID".to_owned(), "VERSION".to_owned(), "NOT_EXISTS_VAR".to_owned()];
+		assert_eq!(RegistryClient::get_envs(&var_names), map);
 	}
 }
@@ -17,8 +17,8 @@ pub enum RegistryError {
 	InvalidGameServerStatus(String),
 }
 
-pub async fn notify_registry_with_tracing_error(gs: &GameServer, state: State) -> () {
-	match notify_registry(gs, state).await {
+pub async fn notify_registry_with_tracing_error(gs: &GameServer, state: State, var_names: &[String]) -> () {
+	match notify_registry(gs, state, var_names).await {
 		Ok(_) => {}
 		Err(e) => {
 			tracing::error!("Error notify registry {:?}", e);
@@ -26,7 +26,7 @@ pub async fn notify_registry_with_tracing_error(gs: &GameServer, state: State) -
 	}
 }
 
-pub async fn notify_registry(gs: &GameServer, state: State) -> Result<(), RegistryError> {
+pub async fn notify_registry(gs: &GameServer, state: State, var_names: &[String]) -> Result<(), RegistryError> {
 	let registry_url = get_internal_srv_uri_from_env("CHEETAH_SERVER_STATUS_RECEIVER");
 	let client = RegistryClient::new(registry_url).await.map_err(RegistryError::from)?;
 
@@ -51,5 +51,5 @@ pub async fn notify_registry(gs: &GameServer, state: State) -> Result<(), Regist
 		port: u32::from(get_internal_grpc_service_default_port()),
 	};
 
-	client.update_server_status(game, grpc_internal, state).await.map_err(RegistryError::from)
+	client.update_server_status(game, grpc_internal, state, var_names).await.map_err(RegistryError::from)
 }
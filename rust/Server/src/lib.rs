@@ -1,12 +1,16 @@
+use std::future::Future;
 use std::net::SocketAddr;
-use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::intergration::agones::agones_und_notifyservice_cycle;
 use crate::server::debug::run_debug_server;
 use crate::server::manager::grpc::proto::realtime_server_management_service_server::RealtimeServerManagementServiceServer;
 use crate::server::manager::grpc::RealtimeServerManagementServiceImpl;
 use crate::server::manager::ServerManager;
+use crate::server::room::config::templates::RoomTemplates;
+use crate::server::security::{BearerTokenInterceptor, TlsSettings};
 use futures::join;
 use tokio::net::TcpListener;
 use tokio::sync::Mutex;
@@ -15,6 +19,7 @@ use tonic_health::ServingStatus;
 use tonic_web::GrpcWebLayer;
 
 pub mod builder;
+pub mod config;
 pub mod env;
 pub mod server;
 
@@ -25,35 +30,169 @@ pub mod intergration;
 ///
 pub struct Server {
 	pub game_socket_addr: SocketAddr,
-	pub internal_webgrpc_listener: TcpListener,
-	pub internal_grpc_listener: TcpListener,
-	pub debug_rest_service_listener: TcpListener,
+	///
+	/// Адрес WebSocket listener, None - если WebSocket не включен через [`builder::ServerBuilder::set_websocket_bind_address`]
+	///
+	pub websocket_addr: Option<SocketAddr>,
+	///
+	/// Внутренние сервисы, None - если отключены через [`builder::ServerBuilder::disable_management_services`]
+	///
+	pub internal_webgrpc_listener: Option<TcpListener>,
+	pub internal_grpc_listener: Option<TcpListener>,
+	pub debug_rest_service_listener: Option<TcpListener>,
 	pub is_agones_enabled: bool,
+	///
+	/// Имена переменных окружения, значения которых передаются в registry вместе со статусом сервера
+	///
+	pub registry_var_names: Vec<String>,
 	pub manager: Arc<Mutex<ServerManager>>,
+	///
+	/// TLS для grpc, grpc-web и debug rest сервисов, если не задан - соединения без шифрования
+	///
+	pub management_tls: Option<TlsSettings>,
+	///
+	/// Bearer токен для grpc, grpc-web и debug rest сервисов, если не задан - запросы не проверяются
+	///
+	pub management_token: Option<Arc<str>>,
+	///
+	/// Максимальное время ожидания завершения комнат при остановке сервера
+	///
+	pub drain_timeout: Duration,
+	///
+	/// Останавливать сервер с ожиданием завершения комнат по сигналу SIGTERM
+	///
+	pub is_drain_on_terminate_signal_enabled: bool,
+	///
+	/// Ключ супер пользователя, автоматически создаваемого в каждой комнате
+	///
+	pub super_member_key: Option<Vec<u8>>,
+	///
+	/// Ограничения на количество комнат, при превышении сервер сообщает agones о неготовности
+	///
+	pub max_alive_rooms: usize,
+	pub max_created_rooms: usize,
+	///
+	/// Шаблоны комнат для `CreateRoomFromTemplate`
+	///
+	pub room_templates: Option<Arc<RoomTemplates>>,
+	pub room_templates_reload_interval: Duration,
 }
 
 impl Server {
+	///
+	/// Сервер работает до остановки [`ServerManager`], после чего закрываются все внутренние сервисы
+	///
 	pub async fn run(self) {
-		let internal_grpc_future = Self::new_internal_grpc_service(self.internal_grpc_listener, Arc::clone(&self.manager));
-		let internal_webgrpc_future = Self::new_internal_webgrpc_service(self.internal_webgrpc_listener, Arc::clone(&self.manager));
-		let debug_rest_service = run_debug_server(Arc::clone(&self.manager), self.debug_rest_service_listener);
-		if self.is_agones_enabled {
-			let max_alive_rooms = usize::from_str(&env::get_env_or_default("MAX_ALIVE_ROOMS", "20")).unwrap();
-			let max_created_rooms = usize::from_str(&env::get_env_or_default("MAX_CREATED_ROOMS", "80")).unwrap();
-			let agones = agones_und_notifyservice_cycle(Arc::clone(&self.manager), max_alive_rooms, max_created_rooms);
-			join!(internal_grpc_future, internal_webgrpc_future, debug_rest_service, agones);
-		} else {
-			join!(internal_grpc_future, internal_webgrpc_future, debug_rest_service);
+		let interceptor = BearerTokenInterceptor::new(self.management_token.clone());
+		let service = RealtimeServerManagementServiceImpl::new(Arc::clone(&self.manager))
+			.with_super_member_key(self.super_member_key.clone())
+			.with_room_templates(self.room_templates.clone());
+		let internal_grpc_future = Self::run_if_enabled(self.internal_grpc_listener, |listener| {
+			Self::new_internal_grpc_service(listener, service.clone(), self.management_tls.clone(), interceptor.clone())
+		});
+		let internal_webgrpc_future = Self::run_if_enabled(self.internal_webgrpc_listener, |listener| {
+			Self::new_internal_webgrpc_service(listener, service.clone(), self.management_tls.clone(), interceptor.clone())
+		});
+		// настройки TLS проверены в ServerBuilder::build
+		let debug_rest_tls = self.management_tls.as_ref().map(|tls| tls.rustls_config().unwrap());
+		let debug_rest_service = Self::run_if_enabled(self.debug_rest_service_listener, |listener| {
+			run_debug_server(Arc::clone(&self.manager), listener, debug_rest_tls, self.management_token.clone())
+		});
+		let room_templates_reload = Self::reload_room_templates(self.room_templates.clone(), self.room_templates_reload_interval);
+		let services = async { join!(internal_grpc_future, internal_webgrpc_future, debug_rest_service, room_templates_reload) };
+		let lifecycle = async {
+			let stopped = Self::wait_stopped(Arc::clone(&self.manager), self.is_drain_on_terminate_signal_enabled, self.drain_timeout);
+			if self.is_agones_enabled {
+				let agones = agones_und_notifyservice_cycle(Arc::clone(&self.manager), self.max_alive_rooms, self.max_created_rooms, self.drain_timeout, &self.registry_var_names);
+				join!(stopped, agones);
+			} else {
+				stopped.await;
+			}
+		};
+		tokio::select! {
+			_ = services => {}
+			_ = lifecycle => {}
 		}
 	}
 
-	async fn new_internal_grpc_service(listener: TcpListener, manager: Arc<Mutex<ServerManager>>) {
-		let service = RealtimeServerManagementServiceServer::new(RealtimeServerManagementServiceImpl::new(manager));
+	async fn wait_stopped(manager: Arc<Mutex<ServerManager>>, drain_on_terminate_signal: bool, drain_timeout: Duration) {
+		let halt_signal = manager.lock().await.get_halt_signal();
+		if drain_on_terminate_signal {
+			tokio::select! {
+				_ = Self::wait_terminate_signal() => {
+					tracing::info!("Terminate signal received, drain server");
+					let manager = manager.lock().await;
+					if let Err(e) = manager.drain(drain_timeout) {
+						tracing::error!("Error drain server {:?}", e);
+					}
+				}
+				_ = Self::wait_halt(&halt_signal) => {}
+			}
+		}
+		Self::wait_halt(&halt_signal).await;
+	}
+
+	async fn run_if_enabled<F, Fut>(listener: Option<TcpListener>, service: F)
+	where
+		F: FnOnce(TcpListener) -> Fut,
+		Fut: Future<Output = ()>,
+	{
+		match listener {
+			None => std::future::pending::<()>().await,
+			Some(listener) => service(listener).await,
+		}
+	}
+
+	async fn reload_room_templates(room_templates: Option<Arc<RoomTemplates>>, interval: Duration) {
+		match room_templates {
+			None => std::future::pending::<()>().await,
+			Some(room_templates) => loop {
+				tokio::time::sleep(interval).await;
+				let room_templates = Arc::clone(&room_templates);
+				if let Err(e) = tokio::task::spawn_blocking(move || room_templates.reload()).await {
+					tracing::error!("Error reload room templates {:?}", e);
+				}
+			},
+		}
+	}
+
+	async fn wait_halt(halt_signal: &AtomicBool) {
+		while !halt_signal.load(Ordering::Relaxed) {
+			tokio::time::sleep(Duration::from_millis(100)).await;
+		}
+	}
+
+	#[cfg(unix)]
+	async fn wait_terminate_signal() {
+		match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+			Ok(mut signal) => {
+				signal.recv().await;
+			}
+			Err(e) => {
+				tracing::error!("Error listen terminate signal {:?}", e);
+				std::future::pending::<()>().await;
+			}
+		}
+	}
+
+	#[cfg(not(unix))]
+	async fn wait_terminate_signal() {
+		if let Err(e) = tokio::signal::ctrl_c().await {
+			tracing::error!("Error listen terminate signal {:?}", e);
+			std::future::pending::<()>().await;
+		}
+	}
+
+	///
+	/// health сервис доступен без токена, он используется для проверки готовности сервера
+	///
+	async fn new_internal_grpc_service(listener: TcpListener, service: RealtimeServerManagementServiceImpl, tls: Option<TlsSettings>, interceptor: BearerTokenInterceptor) {
+		let service = RealtimeServerManagementServiceServer::with_interceptor(service, interceptor);
 
 		let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
 		health_reporter.set_service_status("", ServingStatus::Serving).await;
 
-		tonic::transport::server::Server::builder()
+		Self::new_tonic_server_builder(tls)
 			.add_service(health_service.clone())
 			.add_service(service.clone())
 			.serve_with_incoming(TcpListenerStream::new(listener))
@@ -61,13 +200,13 @@ impl Server {
 			.unwrap();
 	}
 
-	async fn new_internal_webgrpc_service(listener: TcpListener, manager: Arc<Mutex<ServerManager>>) {
-		let service = RealtimeServerManagementServiceServer::new(RealtimeServerManagementServiceImpl::new(manager));
+	async fn new_internal_webgrpc_service(listener: TcpListener, service: RealtimeServerManagementServiceImpl, tls: Option<TlsSettings>, interceptor: BearerTokenInterceptor) {
+		let service = RealtimeServerManagementServiceServer::with_interceptor(service, interceptor);
 
 		let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
 		health_reporter.set_service_status("", ServingStatus::Serving).await;
 
-		tonic::transport::server::Server::builder()
+		Self::new_tonic_server_builder(tls)
 			.accept_http1(true)
 			.layer(GrpcWebLayer::new())
 			.add_service(health_service)
@@ -76,4 +215,13 @@ impl Server {
 			.await
 			.unwrap();
 	}
+
+	fn new_tonic_server_builder(tls: Option<TlsSettings>) -> tonic::transport::server::Server {
+		let builder = tonic::transport::server::Server::builder();
+		match tls {
+			None => builder,
+			// настройки TLS проверены в ServerBuilder::build
+			Some(tls) => builder.tls_config(tls.grpc_config()).unwrap(),
+		}
+	}
 }
@@ -1,78 +1,265 @@
 use std::sync::Arc;
 
-use crate::server::manager::ServerManager;
+use crate::server::debug::rooms::{ObjectsQuery, PageQuery, TraceQuery};
+use crate::server::manager::{ManagementTaskError, ServerManager};
+use crate::server::network::{MemberNetworkEmulation, MemberNetworkEmulationError};
 use crate::server::room::Room;
+use crate::server::security::{is_authorized, tls_incoming};
+use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use serde::Deserialize;
+use std::convert::Infallible;
 use tokio::net::TcpListener;
 use tokio::sync::Mutex;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
 use tokio_stream::wrappers::TcpListenerStream;
-use warp::Filter;
+use warp::http::StatusCode;
+use warp::reject::{InvalidQuery, MethodNotAllowed, Reject};
+use warp::{Filter, Rejection, Reply};
 
-pub async fn run_debug_server(server_manager: Arc<Mutex<ServerManager>>, listener: TcpListener) {
-	let cloned_server_manager = server_manager.clone();
+mod rooms;
 
-	let full_dump_rooms = warp::path("full_dump_rooms").map(move || full_dump_rooms(cloned_server_manager.clone()));
-	let cloned_server_manager = server_manager.clone();
-	let simple_dump_rooms = warp::path("simple_dump_rooms").map(move || simple_dump_rooms(cloned_server_manager.clone()));
-	let index = warp::any().map(get_help);
+#[derive(Debug)]
+struct Unauthorized;
 
-	let routes = full_dump_rooms.or(simple_dump_rooms).or(index);
-	let stream = TcpListenerStream::new(listener);
-	warp::serve(routes).run_incoming(stream).await
+impl Reject for Unauthorized {}
+
+///
+/// Запустить debug сервер
+/// - при заданной конфигурации TLS принимаются только TLS соединения
+/// - при заданном токене все запросы должны содержать заголовок authorization: Bearer <token>
+///
+pub async fn run_debug_server(server_manager: Arc<Mutex<ServerManager>>, listener: TcpListener, tls: Option<ServerConfig>, token: Option<Arc<str>>) {
+	let routes = routes(server_manager, token);
+	match tls {
+		None => warp::serve(routes).run_incoming(TcpListenerStream::new(listener)).await,
+		Some(tls) => warp::serve(routes).run_incoming(tls_incoming(listener, TlsAcceptor::from(Arc::new(tls)))).await,
+	}
+}
+
+fn routes(server_manager: Arc<Mutex<ServerManager>>, token: Option<Arc<str>>) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+	let full_dump_rooms_route = warp::path("full_dump_rooms").and(with_server_manager(server_manager.clone())).then(full_dump_rooms);
+	let simple_dump_rooms_route = warp::path("simple_dump_rooms").and(with_server_manager(server_manager.clone())).then(simple_dump_rooms);
+	let rooms_route = warp::path!("rooms")
+		.and(warp::get())
+		.and(with_server_manager(server_manager.clone()))
+		.and(warp::query::<PageQuery>())
+		.and_then(rooms::get_rooms);
+	let room_route = warp::path!("rooms" / RoomId)
+		.and(warp::get())
+		.and(with_server_manager(server_manager.clone()))
+		.and_then(|room_id, server_manager| rooms::get_room(server_manager, room_id));
+	let objects_route = warp::path!("rooms" / RoomId / "objects")
+		.and(warp::get())
+		.and(with_server_manager(server_manager.clone()))
+		.and(warp::query::<ObjectsQuery>())
+		.and_then(|room_id, server_manager, query| rooms::get_objects(server_manager, room_id, query));
+	// идентификатор объекта разбирается в обработчике, чтобы ошибка формата возвращалась как 400, а не 404
+	let object_route = warp::path!("rooms" / RoomId / "objects" / String)
+		.and(warp::get())
+		.and(with_server_manager(server_manager.clone()))
+		.and_then(|room_id, object_id, server_manager| rooms::get_object(server_manager, room_id, object_id));
+	let members_route = warp::path!("rooms" / RoomId / "members")
+		.and(warp::get())
+		.and(with_server_manager(server_manager.clone()))
+		.and(warp::query::<PageQuery>())
+		.and_then(|room_id, server_manager, query| rooms::get_members(server_manager, room_id, query));
+	let traffic_route = warp::path!("rooms" / RoomId / "traffic")
+		.and(warp::get())
+		.and(with_server_manager(server_manager.clone()))
+		.and_then(|room_id, server_manager| rooms::get_room_traffic(server_manager, room_id));
+	let enable_trace_route = warp::path!("rooms" / RoomId / "trace")
+		.and(warp::post())
+		.and(with_server_manager(server_manager.clone()))
+		.and(warp::query::<TraceQuery>())
+		.and_then(|room_id, server_manager, query| rooms::enable_command_trace(server_manager, room_id, query));
+	let disable_trace_route = warp::path!("rooms" / RoomId / "trace")
+		.and(warp::delete())
+		.and(with_server_manager(server_manager.clone()))
+		.and(warp::query::<TraceQuery>())
+		.and_then(|room_id, server_manager, query| rooms::disable_command_trace(server_manager, room_id, query));
+	let get_trace_route = warp::path!("rooms" / RoomId / "trace")
+		.and(warp::get())
+		.and(with_server_manager(server_manager.clone()))
+		.and(warp::query::<PageQuery>())
+		.and_then(|room_id, server_manager, query| rooms::get_command_trace(server_manager, room_id, query));
+	let set_member_emulation_route = warp::path!("members" / RoomId / RoomMemberId / "emulation")
+		.and(warp::post())
+		.and(with_server_manager(server_manager.clone()))
+		.and(warp::query::<EmulationQuery>())
+		.and_then(|room_id, member_id, server_manager, query| set_member_emulation(server_manager, MemberAndRoomId { member_id, room_id }, Some(query)));
+	let reset_member_emulation_route = warp::path!("members" / RoomId / RoomMemberId / "emulation")
+		.and(warp::delete())
+		.and(with_server_manager(server_manager))
+		.and_then(|room_id, member_id, server_manager| set_member_emulation(server_manager, MemberAndRoomId { member_id, room_id }, None));
+	let index = warp::path::end().map(get_help);
+
+	with_bearer_token(token)
+		.and(
+			full_dump_rooms_route
+				.or(simple_dump_rooms_route)
+				.or(rooms_route)
+				.or(room_route)
+				.or(objects_route)
+				.or(object_route)
+				.or(members_route)
+				.or(traffic_route)
+				.or(enable_trace_route)
+				.or(disable_trace_route)
+				.or(get_trace_route)
+				.or(set_member_emulation_route)
+				.or(reset_member_emulation_route)
+				.or(index),
+		)
+		.recover(handle_rejection)
+}
+
+fn with_server_manager(server_manager: Arc<Mutex<ServerManager>>) -> impl Filter<Extract = (Arc<Mutex<ServerManager>>,), Error = Infallible> + Clone {
+	warp::any().map(move || server_manager.clone())
+}
+
+fn with_bearer_token(token: Option<Arc<str>>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+	warp::header::optional::<String>("authorization")
+		.and_then(move |authorization: Option<String>| {
+			let authorized = is_authorized(token.as_deref(), authorization.as_deref());
+			async move {
+				if authorized {
+					Ok(())
+				} else {
+					Err(warp::reject::custom(Unauthorized))
+				}
+			}
+		})
+		.untuple_one()
+}
+
+///
+/// Ответ на отклоненный запрос, 404 - только если ни один маршрут не подошел по пути
+///
+async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Infallible> {
+	let (message, status) = if rejection.find::<Unauthorized>().is_some() {
+		("Unauthorized", StatusCode::UNAUTHORIZED)
+	} else if rejection.find::<InvalidQuery>().is_some() {
+		("Invalid query", StatusCode::BAD_REQUEST)
+	} else if rejection.find::<MethodNotAllowed>().is_some() {
+		("Method not allowed", StatusCode::METHOD_NOT_ALLOWED)
+	} else {
+		("Not found", StatusCode::NOT_FOUND)
+	};
+	Ok(warp::reply::with_status(message, status))
 }
 
 fn get_help() -> String {
 	let mut result = String::new();
 	result.push_str("/full_dump_rooms - full dump rooms\n");
 	result.push_str("/simple_dump_rooms - simple dump rooms\n");
+	result.push_str("GET /rooms?offset=0&limit=100 - rooms (json)\n");
+	result.push_str("GET /rooms/{room_id} - room (json)\n");
+	result.push_str("GET /rooms/{room_id}/objects?template=1&offset=0&limit=100 - room objects (json)\n");
+	result.push_str("GET /rooms/{room_id}/objects/{object_id} - room object, object_id - <id> or <id>@<member_id> (json)\n");
+	result.push_str("GET /rooms/{room_id}/members?offset=0&limit=100 - room members with network stats (json)\n");
+	result.push_str("GET /rooms/{room_id}/traffic - room outcome commands (server-sent events)\n");
+	result.push_str("POST /rooms/{room_id}/trace?member=1&minutes=10 - trace room or member commands, log target command_trace\n");
+	result.push_str("DELETE /rooms/{room_id}/trace?member=1 - disable trace\n");
+	result.push_str("GET /rooms/{room_id}/trace?offset=0&limit=100 - traced room commands (json)\n");
+	result.push_str("POST /members/{room_id}/{member_id}/emulation?preset=3g&rtt_in_ms=200&rtt_dispersion=0.1&drop_probability=0.05&drop_time_in_ms=100 - member network emulation\n");
+	result.push_str("DELETE /members/{room_id}/{member_id}/emulation - disable member network emulation\n");
 	result
 }
 
-fn full_dump_rooms(server_manager: Arc<Mutex<ServerManager>>) -> String {
+///
+/// Параметры эмуляции сети пользователя, все параметры необязательные
+///
+#[derive(Debug, Default, Deserialize)]
+struct EmulationQuery {
+	preset: Option<String>,
+	rtt_in_ms: Option<u64>,
+	rtt_dispersion: Option<f64>,
+	drop_probability: Option<f64>,
+	drop_time_in_ms: Option<u64>,
+}
+
+impl TryFrom<EmulationQuery> for MemberNetworkEmulation {
+	type Error = MemberNetworkEmulationError;
+
+	fn try_from(query: EmulationQuery) -> Result<Self, Self::Error> {
+		Self::from_request(
+			query.preset,
+			query.rtt_in_ms.unwrap_or_default(),
+			query.rtt_dispersion.unwrap_or_default(),
+			query.drop_probability.unwrap_or_default(),
+			query.drop_time_in_ms.unwrap_or_default(),
+		)
+	}
+}
+
+async fn set_member_emulation(server_manager: Arc<Mutex<ServerManager>>, id: MemberAndRoomId, query: Option<EmulationQuery>) -> Result<impl Reply, Infallible> {
+	let emulation = match query.map(MemberNetworkEmulation::try_from).transpose() {
+		Ok(emulation) => emulation,
+		Err(e) => return Ok(warp::reply::with_status(e.to_string(), StatusCode::BAD_REQUEST)),
+	};
+	let result = server_manager.lock().await.set_member_network_emulation(id, emulation);
+	Ok(match result {
+		Ok(_) => warp::reply::with_status("Ok".to_owned(), StatusCode::OK),
+		Err(ManagementTaskError::TaskExecutionError(e)) => warp::reply::with_status(e.to_string(), StatusCode::NOT_FOUND),
+		Err(e) => warp::reply::with_status(e.to_string(), StatusCode::INTERNAL_SERVER_ERROR),
+	})
+}
+
+async fn full_dump_rooms(server_manager: Arc<Mutex<ServerManager>>) -> String {
 	let mut result = String::new();
-	rooms_visitor(server_manager, |room| result.push_str(format!("{:?}", ron::to_string(&room)).as_str()));
+	let error = rooms_visitor(server_manager, |room| result.push_str(format!("{:?}", ron::to_string(&room)).as_str())).await;
+	result.push_str(error.as_str());
 	result
 }
 
-fn simple_dump_rooms(server_manager: Arc<Mutex<ServerManager>>) -> String {
+async fn simple_dump_rooms(server_manager: Arc<Mutex<ServerManager>>) -> String {
 	let mut result = String::new();
-	rooms_visitor(server_manager, |room| result.push_str(format!("{:?}-{:?}/n", room.id, room.members.len()).as_str()));
+	let error = rooms_visitor(server_manager, |room| result.push_str(format!("{:?}-{:?}\n", room.id, room.members.len()).as_str())).await;
+	result.push_str(error.as_str());
 	result
 }
 
-fn rooms_visitor<OnRoom>(server_manager: Arc<Mutex<ServerManager>>, mut on_room: OnRoom) -> String
+async fn rooms_visitor<OnRoom>(server_manager: Arc<Mutex<ServerManager>>, mut on_room: OnRoom) -> String
 where
 	OnRoom: FnMut(Room),
 {
 	let mut result = String::new();
-	let server_manager = server_manager.try_lock();
-	if let Ok(server_manager) = server_manager {
-		match server_manager.get_rooms() {
-			Ok(rooms) => {
-				let rooms: Vec<Room> = rooms.into_iter().map(|id| server_manager.dump(id)).filter_map(|item| item.ok()).filter_map(|item| item).collect();
-				for room in rooms {
-					on_room(room);
-				}
+	let server_manager = server_manager.lock().await;
+	match server_manager.get_rooms() {
+		Ok(rooms) => {
+			let rooms: Vec<Room> = rooms.into_iter().map(|id| server_manager.dump(id)).filter_map(|item| item.ok()).flatten().collect();
+			for room in rooms {
+				on_room(room);
 			}
-			Err(e) => result.push_str(format!("{:?}", e).as_str()),
 		}
-	} else {
-		result.push_str("Cannot lock server_manager");
+		Err(e) => result.push_str(format!("{:?}", e).as_str()),
 	}
 	result
 }
 
 #[cfg(test)]
 pub mod test {
-	use crate::server::debug::{full_dump_rooms, simple_dump_rooms};
+	use crate::server::debug::rooms::ObjectIdParam;
+	use crate::server::debug::{full_dump_rooms, routes, simple_dump_rooms, EmulationQuery};
 	use crate::server::manager::ServerManager;
+	use crate::server::network::MemberNetworkEmulation;
+	use crate::server::room::config::member::MemberCreateParams;
+	use crate::server::room::config::object::GameObjectCreateParams;
 	use crate::server::room::config::room::RoomCreateParams;
 	use cheetah_common::network::bind_to_free_socket;
+	use cheetah_common::network::emulator::NetworkConditionsPreset;
+	use cheetah_common::room::object::GameObjectId;
+	use cheetah_common::room::owner::GameObjectOwner;
 	use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
 	use std::sync::Arc;
+	use std::time::Duration;
 	use tokio::sync::Mutex;
+	use warp::http::StatusCode;
 
-	#[test]
-	pub fn test_simple_dump_rooms() {
+	#[tokio::test]
+	pub async fn test_simple_dump_rooms() {
 		let mut server_manager = ServerManager::new(
 			bind_to_free_socket().unwrap(),
 			ProtocolConfiguration {
@@ -88,12 +275,12 @@ pub mod test {
 			.unwrap();
 
 		let server_manager = Arc::new(Mutex::new(server_manager));
-		let result = simple_dump_rooms(server_manager.clone());
-		assert_eq!(result, format!("{:?}-0/n", room_id))
+		let result = simple_dump_rooms(server_manager.clone()).await;
+		assert_eq!(result, format!("{:?}-0\n", room_id))
 	}
 
-	#[test]
-	pub fn test_full_dump_rooms() {
+	#[tokio::test]
+	pub async fn test_full_dump_rooms() {
 		let mut server_manager = ServerManager::new(
 			bind_to_free_socket().unwrap(),
 			ProtocolConfiguration {
@@ -109,8 +296,194 @@ pub mod test {
 			.unwrap();
 
 		let server_manager = Arc::new(Mutex::new(server_manager));
-		let result = full_dump_rooms(server_manager);
+		let result = full_dump_rooms(server_manager).await;
 		assert!(result.contains("perm_room"));
 		assert!(result.contains(room_id.to_string().as_str()))
 	}
+
+	#[tokio::test]
+	pub async fn should_get_rooms_json() {
+		let mut server_manager = new_server_manager();
+		let room_id = server_manager
+			.create_room(RoomCreateParams {
+				name: "arena".to_owned(),
+				..Default::default()
+			})
+			.unwrap();
+		server_manager.create_room(RoomCreateParams::default()).unwrap();
+		let routes = routes(Arc::new(Mutex::new(server_manager)), None);
+
+		let response = warp::test::request().path("/rooms?limit=1").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::OK);
+		let page: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+		assert_eq!(page["total"], 2);
+		assert_eq!(page["items"].as_array().unwrap().len(), 1);
+		assert_eq!(page["items"][0]["id"], room_id);
+		assert_eq!(page["items"][0]["template"], "arena");
+
+		let response = warp::test::request().path(&format!("/rooms/{room_id}")).reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::OK);
+
+		let response = warp::test::request().path("/rooms/999").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+		let response = warp::test::request().path("/rooms/999/traffic").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	}
+
+	#[tokio::test]
+	pub async fn should_reply_status_by_rejection() {
+		let mut server_manager = new_server_manager();
+		let room_id = server_manager.create_room(RoomCreateParams::default()).unwrap();
+		let routes = routes(Arc::new(Mutex::new(server_manager)), None);
+
+		for path in ["/rooms?limit=abc".to_owned(), format!("/rooms/{room_id}/members?offset=-1"), format!("/rooms/{room_id}/objects/5@")] {
+			let response = warp::test::request().path(&path).reply(&routes).await;
+			assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{path}");
+		}
+		let response = warp::test::request().method("PUT").path(&format!("/rooms/{room_id}/trace")).reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+		let response = warp::test::request().method("POST").path("/rooms").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+		let response = warp::test::request().path("/unknown").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+		let response = warp::test::request().path("/").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::OK);
+	}
+
+	#[tokio::test]
+	pub async fn should_get_room_objects_and_members_json() {
+		let mut server_manager = new_server_manager();
+		let room_id = server_manager
+			.create_room(RoomCreateParams {
+				objects: vec![
+					GameObjectCreateParams {
+						id: 1,
+						template: 10,
+						..Default::default()
+					},
+					GameObjectCreateParams {
+						id: 2,
+						template: 20,
+						..Default::default()
+					},
+				],
+				..Default::default()
+			})
+			.unwrap();
+		let member_id = server_manager.create_member(room_id, MemberCreateParams::default()).unwrap();
+		let second_member_id = server_manager.create_member(room_id, MemberCreateParams::default()).unwrap();
+		let routes = routes(Arc::new(Mutex::new(server_manager)), None);
+
+		let response = warp::test::request().path(&format!("/rooms/{room_id}/objects?template=20")).reply(&routes).await;
+		let page: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+		assert_eq!(page["total"], 1);
+		assert_eq!(page["items"][0]["template_id"], 20);
+
+		let response = warp::test::request().path(&format!("/rooms/{room_id}/objects?offset=1&limit=1")).reply(&routes).await;
+		let page: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+		assert_eq!(page["total"], 2);
+		assert_eq!(page["items"].as_array().unwrap().len(), 1);
+		assert_eq!(page["items"][0]["template_id"], 20);
+		let response = warp::test::request().path("/rooms/999/objects").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+		let response = warp::test::request().path(&format!("/rooms/{room_id}/objects/1")).reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::OK);
+		let response = warp::test::request().path(&format!("/rooms/{room_id}/objects/1@{member_id}")).reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+		let response = warp::test::request().path(&format!("/rooms/{room_id}/members")).reply(&routes).await;
+		let page: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+		assert_eq!(page["items"][0]["id"], member_id);
+		assert!(page["items"][0].get("private_key").is_none());
+		assert!(page["items"][0]["stats"].is_object());
+
+		let response = warp::test::request().path(&format!("/rooms/{room_id}/members?offset=1&limit=1")).reply(&routes).await;
+		let page: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+		assert_eq!(page["total"], 2);
+		assert_eq!(page["items"].as_array().unwrap().len(), 1);
+		assert_eq!(page["items"][0]["id"], second_member_id);
+		let response = warp::test::request().path("/rooms/999/members").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+	}
+
+	#[tokio::test]
+	pub async fn should_trace_room_commands() {
+		let mut server_manager = new_server_manager();
+		let room_id = server_manager.create_room(RoomCreateParams::default()).unwrap();
+		let routes = routes(Arc::new(Mutex::new(server_manager)), None);
+
+		let response = warp::test::request().method("POST").path(&format!("/rooms/{room_id}/trace?minutes=1")).reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::OK);
+		let response = warp::test::request().method("POST").path("/rooms/999/trace").reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::NOT_FOUND);
+		for minutes in ["0", "1441", "18446744073709551615"] {
+			let response = warp::test::request().method("POST").path(&format!("/rooms/{room_id}/trace?minutes={minutes}")).reply(&routes).await;
+			assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+		}
+
+		let response = warp::test::request().path(&format!("/rooms/{room_id}/trace")).reply(&routes).await;
+		let page: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+		assert_eq!(page["total"], 0);
+
+		let response = warp::test::request().method("DELETE").path(&format!("/rooms/{room_id}/trace")).reply(&routes).await;
+		assert_eq!(response.status(), StatusCode::OK);
+	}
+
+	#[test]
+	pub fn should_parse_object_id_param() {
+		assert_eq!("5".parse::<ObjectIdParam>().unwrap(), ObjectIdParam(GameObjectId::new(5, GameObjectOwner::Room)));
+		assert_eq!("5@3".parse::<ObjectIdParam>().unwrap(), ObjectIdParam(GameObjectId::new(5, GameObjectOwner::Member(3))));
+		assert!("5@".parse::<ObjectIdParam>().is_err());
+	}
+
+	#[test]
+	pub fn should_convert_emulation_query() {
+		let query = EmulationQuery {
+			preset: Some("3g".to_owned()),
+			rtt_in_ms: Some(300),
+			..Default::default()
+		};
+		let emulation = MemberNetworkEmulation::try_from(query).unwrap();
+		assert_eq!(emulation.preset, Some(NetworkConditionsPreset::ThreeG));
+		assert_eq!(emulation.rtt, Duration::from_millis(300));
+		assert_eq!(emulation.drop_probability, 0.0);
+
+		let query = EmulationQuery {
+			preset: Some("unknown".to_owned()),
+			..Default::default()
+		};
+		assert!(MemberNetworkEmulation::try_from(query).is_err());
+
+		for query in [
+			EmulationQuery {
+				drop_probability: Some(f64::NAN),
+				..Default::default()
+			},
+			EmulationQuery {
+				rtt_dispersion: Some(1.5),
+				..Default::default()
+			},
+			EmulationQuery {
+				rtt_in_ms: Some(u64::MAX),
+				..Default::default()
+			},
+			EmulationQuery {
+				drop_time_in_ms: Some(u64::MAX),
+				..Default::default()
+			},
+		] {
+			assert!(MemberNetworkEmulation::try_from(query).is_err());
+		}
+	}
+
+	fn new_server_manager() -> ServerManager {
+		ServerManager::new(
+			bind_to_free_socket().unwrap(),
+			ProtocolConfiguration {
+				disconnect_timeout: Duration::from_secs(30),
+			},
+		)
+		.unwrap()
+	}
 }
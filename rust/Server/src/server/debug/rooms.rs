@@ -0,0 +1,337 @@
+use std::convert::{identity, Infallible};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_common::room::access::AccessGroups;
+use cheetah_common::room::object::{GameObjectId, GameObjectTemplateId};
+use cheetah_common::room::owner::GameObjectOwner;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tokio_stream::wrappers::ReceiverStream;
+use warp::http::StatusCode;
+use warp::reply::{Json, Response, WithStatus};
+use warp::sse::Event;
+use warp::Reply;
+
+use crate::server::manager::{ItemsPage, ManagementTaskError, PageRange, ServerManager};
+use crate::server::network::MemberNetworkStats;
+use crate::server::room::member::{RoomMember, RoomMemberStatus};
+use crate::server::room::Room;
+use crate::server::trace::{TraceScope, MAX_TRACE_DURATION};
+
+const DEFAULT_PAGE_LIMIT: usize = 100;
+const MAX_PAGE_LIMIT: usize = 1000;
+const DEFAULT_TRACE_DURATION_IN_MINUTES: u64 = 10;
+
+///
+/// Параметры постраничного вывода, limit ограничен [`MAX_PAGE_LIMIT`]
+///
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct PageQuery {
+	offset: Option<usize>,
+	limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct ObjectsQuery {
+	template: Option<GameObjectTemplateId>,
+	offset: Option<usize>,
+	limit: Option<usize>,
+}
+
+///
+/// Параметры трассировки команд, без member - трассируются все пользователи комнаты
+///
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct TraceQuery {
+	member: Option<RoomMemberId>,
+	minutes: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct Page<T> {
+	total: usize,
+	offset: usize,
+	limit: usize,
+	items: Vec<T>,
+}
+
+#[derive(Debug, Serialize)]
+struct RoomInfo {
+	id: RoomId,
+	template: String,
+	compression: bool,
+	member_count: usize,
+	object_count: usize,
+}
+
+///
+/// Пользователь комнаты без приватного ключа и исходящих команд
+///
+#[derive(Debug, Serialize)]
+struct MemberInfo {
+	id: RoomMemberId,
+	status: RoomMemberStatus,
+	super_member: bool,
+	groups: AccessGroups,
+	stats: Option<MemberStatsInfo>,
+}
+
+#[derive(Debug, Serialize)]
+struct MemberStatsInfo {
+	rtt_in_ms: Option<u128>,
+	packet_loss: f64,
+	retransmit_count: u64,
+	received_bytes: u64,
+	sent_bytes: u64,
+	out_command_backlog: usize,
+	unacked_reliable_frames: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorInfo {
+	error: String,
+}
+
+#[derive(Debug)]
+enum ApiError {
+	BadRequest(String),
+	NotFound(String),
+	Internal(String),
+}
+
+///
+/// Идентификатор объекта в пути запроса - `<id>` для объектов комнаты, `<id>@<member_id>` для объектов пользователя
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ObjectIdParam(pub(super) GameObjectId);
+
+#[derive(Debug, Error)]
+#[error("Wrong object id {0}, expected <id> or <id>@<member_id>")]
+pub(super) struct ObjectIdParamError(String);
+
+impl FromStr for ObjectIdParam {
+	type Err = ObjectIdParamError;
+
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		let error = || ObjectIdParamError(value.to_owned());
+		let object_id = match value.split_once('@') {
+			None => GameObjectId::new(value.parse().map_err(|_| error())?, GameObjectOwner::Room),
+			Some((id, member_id)) => GameObjectId::new(id.parse().map_err(|_| error())?, GameObjectOwner::Member(member_id.parse().map_err(|_| error())?)),
+		};
+		Ok(Self(object_id))
+	}
+}
+
+impl<T> Page<T> {
+	fn new(items: Vec<T>, offset: Option<usize>, limit: Option<usize>) -> Self {
+		let range = page_range(offset, limit);
+		Self {
+			total: items.len(),
+			offset: range.offset,
+			limit: range.limit,
+			items: items.into_iter().skip(range.offset).take(range.limit).collect(),
+		}
+	}
+
+	///
+	/// Страница, выбранная в потоке сервера
+	///
+	fn from_items_page<S>(page: ItemsPage<S>, range: PageRange, convert: impl FnMut(S) -> T) -> Self {
+		Self {
+			total: page.total,
+			offset: range.offset,
+			limit: range.limit,
+			items: page.items.into_iter().map(convert).collect(),
+		}
+	}
+}
+
+fn page_range(offset: Option<usize>, limit: Option<usize>) -> PageRange {
+	PageRange {
+		offset: offset.unwrap_or_default(),
+		limit: limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT),
+	}
+}
+
+impl From<&Room> for RoomInfo {
+	fn from(room: &Room) -> Self {
+		Self {
+			id: room.id,
+			template: room.template_name.clone(),
+			compression: room.compression,
+			member_count: room.members.len(),
+			object_count: room.objects.len(),
+		}
+	}
+}
+
+impl MemberInfo {
+	fn new(member: &RoomMember, stats: Option<&MemberNetworkStats>) -> Self {
+		Self {
+			id: member.id,
+			status: member.status.clone(),
+			super_member: member.template.super_member,
+			groups: member.template.groups,
+			stats: stats.map(MemberStatsInfo::from),
+		}
+	}
+}
+
+impl From<&MemberNetworkStats> for MemberStatsInfo {
+	fn from(stats: &MemberNetworkStats) -> Self {
+		Self {
+			rtt_in_ms: stats.rtt.map(|rtt| rtt.as_millis()),
+			packet_loss: stats.packet_loss,
+			retransmit_count: stats.retransmit_count,
+			received_bytes: stats.received_bytes,
+			sent_bytes: stats.sent_bytes,
+			out_command_backlog: stats.out_command_backlog,
+			unacked_reliable_frames: stats.unacked_reliable_frames,
+		}
+	}
+}
+
+impl From<ManagementTaskError> for ApiError {
+	fn from(e: ManagementTaskError) -> Self {
+		match e {
+			ManagementTaskError::TaskExecutionError(e) => ApiError::NotFound(e.to_string()),
+			e => ApiError::Internal(e.to_string()),
+		}
+	}
+}
+
+pub(super) async fn get_rooms(server_manager: Arc<Mutex<ServerManager>>, query: PageQuery) -> Result<WithStatus<Json>, Infallible> {
+	let server_manager = server_manager.lock().await;
+	let result = server_manager.get_rooms().map_err(ApiError::from).and_then(|mut room_ids| {
+		room_ids.sort_unstable();
+		let page = Page::new(room_ids, query.offset, query.limit);
+		let mut rooms = Vec::with_capacity(page.items.len());
+		for room_id in page.items {
+			// комната могла быть удалена после получения списка
+			if let Some(room) = server_manager.dump(room_id)? {
+				rooms.push(RoomInfo::from(&room));
+			}
+		}
+		Ok(Page {
+			total: page.total,
+			offset: page.offset,
+			limit: page.limit,
+			items: rooms,
+		})
+	});
+	Ok(reply(result))
+}
+
+pub(super) async fn get_room(server_manager: Arc<Mutex<ServerManager>>, room_id: RoomId) -> Result<WithStatus<Json>, Infallible> {
+	let result = dump_room(&server_manager, room_id).await.map(|room| RoomInfo::from(&room));
+	Ok(reply(result))
+}
+
+pub(super) async fn get_objects(server_manager: Arc<Mutex<ServerManager>>, room_id: RoomId, query: ObjectsQuery) -> Result<WithStatus<Json>, Infallible> {
+	let range = page_range(query.offset, query.limit);
+	let result = server_manager
+		.lock()
+		.await
+		.get_room_objects(room_id, query.template, range)
+		.map_err(ApiError::from)
+		.map(|page| Page::from_items_page(page, range, identity));
+	Ok(reply(result))
+}
+
+pub(super) async fn get_object(server_manager: Arc<Mutex<ServerManager>>, room_id: RoomId, object_id: String) -> Result<WithStatus<Json>, Infallible> {
+	let object_id = match object_id.parse::<ObjectIdParam>() {
+		Ok(object_id) => object_id.0,
+		Err(e) => return Ok(reply::<()>(Err(ApiError::BadRequest(e.to_string())))),
+	};
+	let result = server_manager
+		.lock()
+		.await
+		.get_room_object(room_id, object_id)
+		.map_err(ApiError::from)
+		.and_then(|object| object.ok_or_else(|| ApiError::NotFound(format!("Object {object_id:?} not found"))));
+	Ok(reply(result))
+}
+
+pub(super) async fn get_members(server_manager: Arc<Mutex<ServerManager>>, room_id: RoomId, query: PageQuery) -> Result<WithStatus<Json>, Infallible> {
+	let range = page_range(query.offset, query.limit);
+	let result = server_manager
+		.lock()
+		.await
+		.get_room_members(room_id, range)
+		.map_err(ApiError::from)
+		.map(|page| Page::from_items_page(page, range, |(member, stats)| MemberInfo::new(&member, stats.as_ref())));
+	Ok(reply(result))
+}
+
+///
+/// Поток исходящих команд комнаты (server-sent events), одно событие - команды одного пользователя за цикл сервера
+///
+pub(super) async fn get_room_traffic(server_manager: Arc<Mutex<ServerManager>>, room_id: RoomId) -> Result<Response, Infallible> {
+	let receiver = server_manager.lock().await.subscribe_room_traffic(room_id).map_err(ApiError::from);
+	Ok(match receiver {
+		Ok(receiver) => {
+			let events = ReceiverStream::new(receiver).map(|event| Event::default().event("commands").json_data(event));
+			warp::sse::reply(warp::sse::keep_alive().stream(events)).into_response()
+		}
+		Err(e) => reply::<()>(Err(e)).into_response(),
+	})
+}
+
+pub(super) async fn enable_command_trace(server_manager: Arc<Mutex<ServerManager>>, room_id: RoomId, query: TraceQuery) -> Result<WithStatus<Json>, Infallible> {
+	let scope = TraceScope { room_id, member_id: query.member };
+	let result = match trace_duration(query.minutes.unwrap_or(DEFAULT_TRACE_DURATION_IN_MINUTES)) {
+		Ok(duration) => server_manager.lock().await.enable_command_trace(scope, duration).map_err(ApiError::from),
+		Err(e) => Err(e),
+	};
+	Ok(reply(result))
+}
+
+///
+/// Время трассировки от 1 минуты до [`MAX_TRACE_DURATION`]
+///
+fn trace_duration(minutes: u64) -> Result<Duration, ApiError> {
+	minutes
+		.checked_mul(60)
+		.map(Duration::from_secs)
+		.filter(|duration| !duration.is_zero() && *duration <= MAX_TRACE_DURATION)
+		.ok_or_else(|| ApiError::BadRequest(format!("Wrong trace minutes {minutes}, expected from 1 to {}", MAX_TRACE_DURATION.as_secs() / 60)))
+}
+
+pub(super) async fn disable_command_trace(server_manager: Arc<Mutex<ServerManager>>, room_id: RoomId, query: TraceQuery) -> Result<WithStatus<Json>, Infallible> {
+	let scope = TraceScope { room_id, member_id: query.member };
+	let result = server_manager.lock().await.disable_command_trace(scope).map_err(ApiError::from);
+	Ok(reply(result))
+}
+
+pub(super) async fn get_command_trace(server_manager: Arc<Mutex<ServerManager>>, room_id: RoomId, query: PageQuery) -> Result<WithStatus<Json>, Infallible> {
+	let result = server_manager
+		.lock()
+		.await
+		.get_command_trace(room_id)
+		.map_err(ApiError::from)
+		.map(|commands| Page::new(commands, query.offset, query.limit));
+	Ok(reply(result))
+}
+
+async fn dump_room(server_manager: &Arc<Mutex<ServerManager>>, room_id: RoomId) -> Result<Room, ApiError> {
+	server_manager
+		.lock()
+		.await
+		.dump(room_id)
+		.map_err(ApiError::from)?
+		.ok_or_else(|| ApiError::NotFound(format!("Room {room_id} not found")))
+}
+
+fn reply<T: Serialize>(result: Result<T, ApiError>) -> WithStatus<Json> {
+	match result {
+		Ok(value) => warp::reply::with_status(warp::reply::json(&value), StatusCode::OK),
+		Err(ApiError::BadRequest(error)) => warp::reply::with_status(warp::reply::json(&ErrorInfo { error }), StatusCode::BAD_REQUEST),
+		Err(ApiError::NotFound(error)) => warp::reply::with_status(warp::reply::json(&ErrorInfo { error }), StatusCode::NOT_FOUND),
+		Err(ApiError::Internal(error)) => warp::reply::with_status(warp::reply::json(&ErrorInfo { error }), StatusCode::INTERNAL_SERVER_ERROR),
+	}
+}
@@ -0,0 +1,306 @@
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cheetah_common::commands::s2c::S2CCommand;
+use cheetah_common::commands::CommandWithReliabilityGuarantees;
+use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::server::room::config::member::MemberCreateParams;
+use crate::server::room::config::room::RoomCreateParams;
+use crate::server::room::member::RoomMemberStatus;
+use crate::server::room::Room;
+
+pub mod replay;
+
+///
+/// Заголовок файла журнала
+///
+pub const JOURNAL_MAGIC: &[u8; 4] = b"CHTJ";
+pub const JOURNAL_VERSION: u16 = 1;
+///
+/// Ограничение на размер записи, защита от чтения поврежденного файла
+///
+const MAX_RECORD_SIZE: usize = 64 * 1024 * 1024;
+
+///
+/// Запись журнала комнаты
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRecord {
+	///
+	/// Время записи события (unix time)
+	///
+	pub time_in_ms: u64,
+	pub event: JournalEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
+pub enum JournalEvent {
+	///
+	/// Первая запись журнала, параметры для создания комнаты при воспроизведении
+	///
+	CreateRoom {
+		room_id: RoomId,
+		template_name: String,
+		params: RoomCreateParams,
+	},
+	///
+	/// Регистрация пользователя, generated - идентификатор выдан комнатой, а не задан токеном
+	///
+	RegisterMember {
+		member_id: RoomMemberId,
+		template: MemberCreateParams,
+		generated: bool,
+	},
+	ConnectMember(RoomMemberId),
+	DisconnectMember(RoomMemberId),
+	Commands {
+		member_id: RoomMemberId,
+		commands: Vec<CommandWithReliabilityGuarantees>,
+	},
+	///
+	/// Состояние комнаты при ее удалении, используется для проверки воспроизведения
+	///
+	Snapshot(RoomSnapshot),
+	///
+	/// Повторное подключение пользователя по новому токену, меняются ключ и группы пользователя
+	///
+	ResumeMember {
+		member_id: RoomMemberId,
+		template: MemberCreateParams,
+	},
+}
+
+///
+/// Состояние комнаты для сравнения, объекты представлены командами их создания
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomSnapshot {
+	pub members: Vec<(RoomMemberId, RoomMemberStatus)>,
+	pub objects: Vec<S2CCommand>,
+}
+
+#[derive(Error, Debug)]
+pub enum JournalError {
+	#[error("Io {0:?}: {1}")]
+	Io(PathBuf, io::Error),
+	#[error("Not a room journal {0:?}")]
+	WrongMagic(PathBuf),
+	#[error("Unsupported journal version {0}")]
+	UnsupportedVersion(u16),
+	#[error("Record size {0} too large")]
+	RecordTooLarge(usize),
+	#[error("Encode/decode record {0}")]
+	Bincode(#[from] bincode::Error),
+}
+
+impl RoomSnapshot {
+	#[must_use]
+	pub fn new(room: &Room) -> Self {
+		let mut members: Vec<_> = room.members.values().map(|member| (member.id, member.status.clone())).collect();
+		members.sort_by_key(|(id, _)| *id);
+		let mut objects = Vec::new();
+		for object in room.objects.values() {
+			object.clone().collect_create_commands(&mut objects);
+		}
+		Self { members, objects }
+	}
+}
+
+///
+/// Запись журнала в файл
+///
+/// - файл создается при создании комнаты, имя файла - room-{id}-{unix time}.journal
+/// - записи буферизуются, сервер сбрасывает буфер на диск каждый цикл
+/// - приватные ключи пользователей в журнал не записываются
+///
+#[derive(Debug)]
+pub struct JournalWriter {
+	path: PathBuf,
+	out: BufWriter<File>,
+}
+
+impl JournalWriter {
+	pub fn create(dir: &Path, room_id: RoomId) -> Result<Self, JournalError> {
+		let path = dir.join(format!("room-{}-{}.journal", room_id, now_in_ms()));
+		let file = File::create(&path).map_err(|e| JournalError::Io(path.clone(), e))?;
+		let mut writer = Self { path, out: BufWriter::new(file) };
+		writer.write_bytes(JOURNAL_MAGIC)?;
+		writer.write_bytes(&JOURNAL_VERSION.to_le_bytes())?;
+		Ok(writer)
+	}
+
+	#[must_use]
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub fn write(&mut self, event: JournalEvent) -> Result<(), JournalError> {
+		let event = match event {
+			JournalEvent::RegisterMember { member_id, template, generated } => JournalEvent::RegisterMember {
+				member_id,
+				template: MemberCreateParams {
+					private_key: MemberPrivateKey([0; 32]),
+					..template
+				},
+				generated,
+			},
+			JournalEvent::ResumeMember { member_id, template } => JournalEvent::ResumeMember {
+				member_id,
+				template: MemberCreateParams {
+					private_key: MemberPrivateKey([0; 32]),
+					..template
+				},
+			},
+			event => event,
+		};
+		let data = bincode::serialize(&JournalRecord { time_in_ms: now_in_ms(), event })?;
+		self.write_bytes(&(data.len() as u32).to_le_bytes())?;
+		self.write_bytes(&data)
+	}
+
+	pub fn flush(&mut self) -> Result<(), JournalError> {
+		self.out.flush().map_err(|e| JournalError::Io(self.path.clone(), e))
+	}
+
+	fn write_bytes(&mut self, data: &[u8]) -> Result<(), JournalError> {
+		self.out.write_all(data).map_err(|e| JournalError::Io(self.path.clone(), e))
+	}
+}
+
+///
+/// Прочитать все записи журнала, незавершенная последняя запись (сервер остановлен во время записи) пропускается
+///
+pub fn read_journal(path: &Path) -> Result<Vec<JournalRecord>, JournalError> {
+	let io_error = |e| JournalError::Io(path.to_path_buf(), e);
+	let mut input = BufReader::new(File::open(path).map_err(io_error)?);
+	let mut magic = [0; 4];
+	let mut version = [0; 2];
+	input.read_exact(&mut magic).map_err(io_error)?;
+	if &magic != JOURNAL_MAGIC {
+		return Err(JournalError::WrongMagic(path.to_path_buf()));
+	}
+	input.read_exact(&mut version).map_err(io_error)?;
+	let version = u16::from_le_bytes(version);
+	if version != JOURNAL_VERSION {
+		return Err(JournalError::UnsupportedVersion(version));
+	}
+
+	let mut records = Vec::new();
+	loop {
+		let mut size = [0; 4];
+		match input.read_exact(&mut size) {
+			Ok(_) => {}
+			Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(records),
+			Err(e) => return Err(io_error(e)),
+		}
+		let size = u32::from_le_bytes(size) as usize;
+		if size > MAX_RECORD_SIZE {
+			return Err(JournalError::RecordTooLarge(size));
+		}
+		let mut data = vec![0; size];
+		match input.read_exact(&mut data) {
+			Ok(_) => records.push(bincode::deserialize(&data)?),
+			Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+				tracing::warn!("[journal] skip incomplete record in {:?}", path);
+				return Ok(records);
+			}
+			Err(e) => return Err(io_error(e)),
+		}
+	}
+}
+
+fn now_in_ms() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::io::Write;
+
+	use cheetah_common::commands::c2s::C2SCommand;
+	use cheetah_common::commands::guarantees::ReliabilityGuaranteesChannel;
+	use cheetah_common::commands::types::long::LongField;
+	use cheetah_common::commands::{BothDirectionCommand, CommandWithReliabilityGuarantees};
+	use cheetah_common::room::object::GameObjectId;
+	use cheetah_common::room::owner::GameObjectOwner;
+
+	use crate::server::journal::{read_journal, JournalError, JournalEvent, JournalWriter};
+	use crate::server::room::config::member::MemberCreateParams;
+	use crate::server::room::config::room::RoomCreateParams;
+
+	#[test]
+	fn should_write_and_read_records() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut writer = JournalWriter::create(dir.path(), 7).unwrap();
+		writer
+			.write(JournalEvent::CreateRoom {
+				room_id: 7,
+				template_name: "arena".to_owned(),
+				params: RoomCreateParams {
+					compression: true,
+					..Default::default()
+				},
+			})
+			.unwrap();
+		writer
+			.write(JournalEvent::RegisterMember {
+				member_id: 1,
+				template: MemberCreateParams::stub(Default::default()),
+				generated: true,
+			})
+			.unwrap();
+		let command = CommandWithReliabilityGuarantees {
+			reliability_guarantees: ReliabilityGuaranteesChannel::UnreliableUnordered,
+			command: BothDirectionCommand::C2S(C2SCommand::SetLong(LongField {
+				object_id: GameObjectId::new(1, GameObjectOwner::Room),
+				field_id: 1,
+				value: 100,
+			})),
+		};
+		writer
+			.write(JournalEvent::Commands {
+				member_id: 1,
+				commands: vec![command.clone()],
+			})
+			.unwrap();
+		writer.flush().unwrap();
+
+		let records = read_journal(writer.path()).unwrap();
+		assert_eq!(records.len(), 3);
+		assert!(matches!(&records[0].event, JournalEvent::CreateRoom { room_id: 7, template_name, params } if template_name == "arena" && params.compression));
+		assert!(matches!(&records[1].event, JournalEvent::RegisterMember { member_id: 1, template, generated: true } if template.private_key.0 == [0; 32]));
+		assert!(matches!(&records[2].event, JournalEvent::Commands { member_id: 1, commands } if commands == &vec![command.clone()]));
+		assert!(records[0].time_in_ms > 0);
+	}
+
+	#[test]
+	fn should_skip_incomplete_record() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut writer = JournalWriter::create(dir.path(), 1).unwrap();
+		writer.write(JournalEvent::ConnectMember(1)).unwrap();
+		writer.flush().unwrap();
+		let mut file = fs::OpenOptions::new().append(true).open(writer.path()).unwrap();
+		file.write_all(&[100, 0, 0, 0, 1, 2]).unwrap();
+
+		let records = read_journal(writer.path()).unwrap();
+		assert_eq!(records.len(), 1);
+		assert!(matches!(records[0].event, JournalEvent::ConnectMember(1)));
+	}
+
+	#[test]
+	fn should_fail_read_not_journal() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("room.journal");
+		fs::write(&path, b"not a journal").unwrap();
+		assert!(matches!(read_journal(&path), Err(JournalError::WrongMagic(_))));
+	}
+}
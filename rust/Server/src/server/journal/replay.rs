@@ -0,0 +1,315 @@
+use std::path::Path;
+
+use cheetah_game_realtime_protocol::RoomMemberId;
+use thiserror::Error;
+
+use crate::server::journal::{read_journal, JournalError, JournalEvent, JournalRecord, RoomSnapshot};
+use crate::server::room::command::ServerCommandError;
+use crate::server::room::Room;
+
+///
+/// Воспроизведение журнала комнаты offline
+///
+/// - комната создается из параметров первой записи журнала, затем последовательно выполняются записи
+/// - можно остановиться на любой записи, переход назад выполняется повторным воспроизведением с начала
+/// - при достижении записи с состоянием комнаты состояние сравнивается с текущим, при расхождении воспроизведение останавливается
+///
+#[derive(Debug)]
+pub struct JournalReplay {
+	records: Vec<JournalRecord>,
+	position: usize,
+	room: Room,
+}
+
+#[derive(Error, Debug)]
+pub enum JournalReplayError {
+	#[error("Journal {0}")]
+	Journal(#[from] JournalError),
+	#[error("Journal must start with room creation")]
+	RoomNotCreated,
+	#[error("Record {0}: unexpected room creation")]
+	UnexpectedCreateRoom(usize),
+	#[error("Record {position}: member registered with id {actual}, expected {expected}")]
+	MemberIdMismatch { position: usize, expected: RoomMemberId, actual: RoomMemberId },
+	#[error("Record {0}: {1}")]
+	RegisterMember(usize, ServerCommandError),
+	#[error("Record {0}: {1}")]
+	ResumeMember(usize, ServerCommandError),
+	#[error("Record {position}: room state mismatch, expected {expected:?}, actual {actual:?}")]
+	SnapshotMismatch {
+		position: usize,
+		expected: Box<RoomSnapshot>,
+		actual: Box<RoomSnapshot>,
+	},
+}
+
+impl JournalReplay {
+	pub fn load(path: &Path) -> Result<Self, JournalReplayError> {
+		Self::new(read_journal(path)?)
+	}
+
+	pub fn new(records: Vec<JournalRecord>) -> Result<Self, JournalReplayError> {
+		let room = Self::create_room(&records)?;
+		Ok(Self { records, position: 1, room })
+	}
+
+	#[must_use]
+	pub fn room(&self) -> &Room {
+		&self.room
+	}
+
+	#[must_use]
+	pub fn records(&self) -> &[JournalRecord] {
+		&self.records
+	}
+
+	///
+	/// Индекс следующей записи для выполнения
+	///
+	#[must_use]
+	pub fn position(&self) -> usize {
+		self.position
+	}
+
+	#[must_use]
+	pub fn is_finished(&self) -> bool {
+		self.position >= self.records.len()
+	}
+
+	///
+	/// Выполнить следующую запись, возвращает выполненную запись или None в конце журнала
+	///
+	pub fn step(&mut self) -> Result<Option<&JournalRecord>, JournalReplayError> {
+		if self.is_finished() {
+			return Ok(None);
+		}
+		let position = self.position;
+		self.position += 1;
+		match &self.records[position].event {
+			JournalEvent::CreateRoom { .. } => return Err(JournalReplayError::UnexpectedCreateRoom(position)),
+			JournalEvent::RegisterMember { member_id, template, generated } => {
+				if *generated {
+					let actual = self.room.register_member(template.clone());
+					if actual != *member_id {
+						return Err(JournalReplayError::MemberIdMismatch {
+							position,
+							expected: *member_id,
+							actual,
+						});
+					}
+				} else {
+					self.room
+						.register_member_with_id(*member_id, template.clone())
+						.map_err(|e| JournalReplayError::RegisterMember(position, e))?;
+				}
+			}
+			JournalEvent::ResumeMember { member_id, template } => {
+				self.room.resume_member(*member_id, template).map_err(|e| JournalReplayError::ResumeMember(position, e))?;
+			}
+			JournalEvent::ConnectMember(member_id) => self.room.execute_commands(*member_id, &[]),
+			JournalEvent::DisconnectMember(member_id) => {
+				// ошибка отключения на сервере только логируется, поэтому и здесь не прерываем воспроизведение
+				if let Err(e) = self.room.disconnect_member(*member_id) {
+					tracing::warn!("[journal] record {} disconnect member({}) error {}", position, member_id, e);
+				}
+			}
+			JournalEvent::Commands { member_id, commands } => self.room.execute_commands(*member_id, commands),
+			JournalEvent::Snapshot(expected) => {
+				let actual = RoomSnapshot::new(&self.room);
+				if actual != *expected {
+					return Err(JournalReplayError::SnapshotMismatch {
+						position,
+						expected: Box::new(expected.clone()),
+						actual: Box::new(actual),
+					});
+				}
+			}
+		}
+		Ok(Some(&self.records[position]))
+	}
+
+	///
+	/// Выполнить записи до заданной позиции (не включая ее)
+	///
+	pub fn run_to(&mut self, position: usize) -> Result<(), JournalReplayError> {
+		if position < self.position {
+			self.room = Self::create_room(&self.records)?;
+			self.position = 1;
+		}
+		while self.position < position.min(self.records.len()) {
+			self.step()?;
+		}
+		Ok(())
+	}
+
+	///
+	/// Выполнить все записи журнала, включая проверку итогового состояния комнаты
+	///
+	pub fn run_to_end(&mut self) -> Result<(), JournalReplayError> {
+		self.run_to(self.records.len())
+	}
+
+	fn create_room(records: &[JournalRecord]) -> Result<Room, JournalReplayError> {
+		match records.first().map(|record| &record.event) {
+			Some(JournalEvent::CreateRoom { room_id, template_name, params }) => {
+				let mut params = params.clone();
+				params.name = template_name.clone();
+				Ok(Room::new(*room_id, params))
+			}
+			_ => Err(JournalReplayError::RoomNotCreated),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+
+	use cheetah_common::commands::c2s::C2SCommand;
+	use cheetah_common::commands::guarantees::ReliabilityGuaranteesChannel;
+	use cheetah_common::commands::types::create::{C2SCreatedGameObject, CreateGameObject};
+	use cheetah_common::commands::types::long::LongField;
+	use cheetah_common::commands::{BothDirectionCommand, CommandWithReliabilityGuarantees};
+	use cheetah_common::room::access::AccessGroups;
+	use cheetah_common::room::object::GameObjectId;
+	use cheetah_common::room::owner::GameObjectOwner;
+	use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+
+	use crate::server::journal::replay::{JournalReplay, JournalReplayError};
+	use crate::server::journal::{read_journal, JournalEvent};
+	use crate::server::room::config::member::MemberCreateParams;
+	use crate::server::room::config::room::RoomCreateParams;
+	use crate::server::room::member::RoomMemberStatus;
+	use crate::server::room_registry::Rooms;
+
+	const ACCESS_GROUPS: AccessGroups = AccessGroups(0b11);
+
+	#[test]
+	fn should_replay_journal() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = record_journal(dir.path().to_path_buf());
+		let mut replay = JournalReplay::load(&path).unwrap();
+		replay.run_to_end().unwrap();
+		assert!(replay.is_finished());
+		assert_eq!(replay.room().objects.len(), 1);
+		let object = replay.room().objects.values().next().unwrap();
+		assert_eq!(object.long_fields.get(1), Some(&100));
+		assert_eq!(replay.room().members.len(), 1);
+		assert_eq!(replay.room().members.values().next().unwrap().status, RoomMemberStatus::Connected);
+	}
+
+	#[test]
+	fn should_step_to_any_record() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = record_journal(dir.path().to_path_buf());
+		let mut replay = JournalReplay::load(&path).unwrap();
+		let commands_position = replay.records().iter().position(|record| matches!(record.event, JournalEvent::Commands { .. })).unwrap();
+
+		replay.run_to(commands_position).unwrap();
+		assert!(replay.room().objects.is_empty());
+		assert!(matches!(replay.step().unwrap().unwrap().event, JournalEvent::Commands { .. }));
+		assert_eq!(replay.room().objects.len(), 1);
+
+		replay.run_to(1).unwrap();
+		assert_eq!(replay.position(), 1);
+		assert!(replay.room().members.is_empty());
+		replay.run_to_end().unwrap();
+	}
+
+	///
+	/// Повторное подключение по токену меняет группы пользователя, созданный после этого объект получает новые группы
+	///
+	#[test]
+	fn should_replay_token_rejoin() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut rooms = Rooms::default();
+		rooms.set_journal_dir(Some(dir.path().to_path_buf()));
+		let room_id = rooms.create_room(RoomCreateParams { journal: true, ..Default::default() });
+		let member = MemberAndRoomId { member_id: 100, room_id };
+		rooms.register_member_with_id(member, MemberCreateParams::stub(ACCESS_GROUPS)).unwrap();
+		rooms.execute_commands(member, &[]);
+		let rejoin_groups = AccessGroups(0b100);
+		rooms.resume_member(member, &MemberCreateParams::stub(rejoin_groups)).unwrap();
+		rooms.execute_commands(member, &[]);
+		let object_id = GameObjectId::new(1, GameObjectOwner::Member(member.member_id));
+		rooms.execute_commands(
+			member,
+			&[
+				command(C2SCommand::CreateGameObject(CreateGameObject {
+					object_id,
+					template: 1,
+					access_groups: rejoin_groups,
+				})),
+				command(C2SCommand::CreatedGameObject(Box::new(C2SCreatedGameObject::new(object_id, false, None)))),
+			],
+		);
+		rooms.force_remove_room(&room_id).unwrap();
+
+		let path = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap().path();
+		let mut replay = JournalReplay::load(&path).unwrap();
+		assert!(replay.records().iter().any(|record| matches!(record.event, JournalEvent::ResumeMember { member_id: 100, .. })));
+		replay.run_to_end().unwrap();
+		assert_eq!(replay.room().members.get(&member.member_id).unwrap().template.groups, rejoin_groups);
+		assert_eq!(replay.room().objects.len(), 1);
+	}
+
+	#[test]
+	fn should_detect_state_mismatch() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = record_journal(dir.path().to_path_buf());
+		let mut records = read_journal(&path).unwrap();
+		records.retain(|record| !matches!(record.event, JournalEvent::Commands { .. }));
+		let mut replay = JournalReplay::new(records).unwrap();
+		assert!(matches!(replay.run_to_end(), Err(JournalReplayError::SnapshotMismatch { .. })));
+	}
+
+	#[test]
+	fn should_fail_without_create_room() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = record_journal(dir.path().to_path_buf());
+		let mut records = read_journal(&path).unwrap();
+		records.remove(0);
+		assert!(matches!(JournalReplay::new(records), Err(JournalReplayError::RoomNotCreated)));
+	}
+
+	///
+	/// Комната с двумя пользователями, первый создает объект комнаты, второй отключается
+	///
+	fn record_journal(dir: PathBuf) -> PathBuf {
+		let mut rooms = Rooms::default();
+		rooms.set_journal_dir(Some(dir.clone()));
+		let room_id = rooms.create_room(RoomCreateParams { journal: true, ..Default::default() });
+		let member_a = rooms.register_member(room_id, MemberCreateParams::stub(ACCESS_GROUPS)).unwrap();
+		let member_b = rooms.register_member(room_id, MemberCreateParams::stub(ACCESS_GROUPS)).unwrap();
+		let member_a = MemberAndRoomId { member_id: member_a, room_id };
+		let member_b = MemberAndRoomId { member_id: member_b, room_id };
+		rooms.execute_commands(member_b, &[]);
+
+		let object_id = GameObjectId::new(1, GameObjectOwner::Member(member_a.member_id));
+		rooms.execute_commands(
+			member_a,
+			&[
+				command(C2SCommand::CreateGameObject(CreateGameObject {
+					object_id,
+					template: 1,
+					access_groups: ACCESS_GROUPS,
+				})),
+				command(C2SCommand::SetLong(LongField { object_id, field_id: 1, value: 100 })),
+				command(C2SCommand::CreatedGameObject(Box::new(C2SCreatedGameObject::new(object_id, true, None)))),
+			],
+		);
+		rooms.member_disconnected(&member_b).unwrap();
+		rooms.force_remove_room(&room_id).unwrap();
+
+		let path = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+		assert!(matches!(read_journal(&path).unwrap().last().unwrap().event, JournalEvent::Snapshot(_)));
+		path
+	}
+
+	fn command(command: C2SCommand) -> CommandWithReliabilityGuarantees {
+		CommandWithReliabilityGuarantees {
+			reliability_guarantees: ReliabilityGuaranteesChannel::ReliableUnordered,
+			command: BothDirectionCommand::C2S(command),
+		}
+	}
+}
@@ -2,7 +2,8 @@ use crate::server::manager::grpc;
 use crate::server::manager::grpc::proto;
 use crate::server::manager::grpc::proto::field_value::Variant;
 use crate::server::manager::grpc::proto::{GameObjectConfig, GameObjectTemplate, ItemConfig, Member, MemberStatus};
-use crate::server::room::config::{member, object, room};
+use crate::server::network::{MemberNetworkEmulation, MemberNetworkEmulationError, MemberNetworkStats};
+use crate::server::room::config::{member, object, room, templates};
 use crate::server::room::member::{RoomMember, RoomMemberStatus};
 use cheetah_common::room::access::AccessGroups;
 use cheetah_common::room::buffer::Buffer;
@@ -15,6 +16,19 @@ impl From<grpc::RoomTemplate> for room::RoomCreateParams {
 			name: source.template_name,
 			objects: source.objects.into_iter().map(From::from).collect(),
 			configs: source.configs.into_iter().map(|config| (config.template as GameObjectTemplateId, From::from(config))).collect(),
+			compression: source.compression,
+			journal: source.journal,
+		}
+	}
+}
+
+impl From<proto::CreateRoomFromTemplateRequest> for templates::RoomTemplateOverrides {
+	fn from(source: proto::CreateRoomFromTemplateRequest) -> Self {
+		Self {
+			objects: source.objects.into_iter().map(From::from).collect(),
+			configs: source.configs.into_iter().map(|config| (config.template as GameObjectTemplateId, From::from(config))).collect(),
+			compression: source.compression,
+			journal: source.journal,
 		}
 	}
 }
@@ -58,18 +72,15 @@ impl From<GameObjectTemplate> for object::GameObjectCreateParams {
 			groups: AccessGroups(source.groups),
 			doubles: fields
 				.iter()
-				.map(|(field_id, value)| if let Variant::Double(v) = value { Some((*field_id, *v)) } else { None })
-				.flatten()
+				.filter_map(|(field_id, value)| if let Variant::Double(v) = value { Some((*field_id, *v)) } else { None })
 				.collect(),
 			structures: fields
 				.iter()
-				.map(|(field_id, value)| if let Variant::Structure(v) = value { Some((*field_id, Buffer::from(v.as_ref()))) } else { None })
-				.flatten()
+				.filter_map(|(field_id, value)| if let Variant::Structure(v) = value { Some((*field_id, Buffer::from(v.as_ref()))) } else { None })
 				.collect(),
 			longs: fields
 				.iter()
-				.map(|(field_id, value)| if let Variant::Long(v) = value { Some((*field_id, *v)) } else { None })
-				.flatten()
+				.filter_map(|(field_id, value)| if let Variant::Long(v) = value { Some((*field_id, *v)) } else { None })
 				.collect(),
 		}
 	}
@@ -80,6 +91,22 @@ impl From<RoomMember> for Member {
 		Member {
 			id: value.id,
 			status: MemberStatus::from(value.status).into(),
+			stats: None,
+		}
+	}
+}
+
+impl From<MemberNetworkStats> for proto::MemberStats {
+	#[allow(clippy::cast_possible_truncation)]
+	fn from(value: MemberNetworkStats) -> Self {
+		Self {
+			rtt_in_ms: value.rtt.map(|rtt| rtt.as_millis() as u64),
+			packet_loss: value.packet_loss,
+			retransmit_count: value.retransmit_count,
+			received_bytes: value.received_bytes,
+			sent_bytes: value.sent_bytes,
+			out_command_backlog: value.out_command_backlog as u64,
+			unacked_reliable_frames: value.unacked_reliable_frames as u64,
 		}
 	}
 }
@@ -96,3 +123,11 @@ impl From<RoomMemberStatus> for MemberStatus {
 		}
 	}
 }
+
+impl TryFrom<proto::NetworkEmulation> for MemberNetworkEmulation {
+	type Error = MemberNetworkEmulationError;
+
+	fn try_from(source: proto::NetworkEmulation) -> Result<Self, Self::Error> {
+		Self::from_request(source.preset, source.rtt_in_ms, source.rtt_dispersion, source.drop_probability, source.drop_time_in_ms)
+	}
+}
@@ -1,6 +1,7 @@
 use crate::server::manager::grpc::proto::realtime_server_management_service_server::RealtimeServerManagementService;
 use crate::server::manager::grpc::proto::CreateMemberRequest;
 use crate::server::manager::grpc::proto::CreateMemberResponse;
+use crate::server::manager::grpc::proto::CreateRoomFromTemplateRequest;
 use crate::server::manager::grpc::proto::CreateSuperMemberRequest;
 use crate::server::manager::grpc::proto::DeleteMemberRequest;
 use crate::server::manager::grpc::proto::DeleteMemberResponse;
@@ -9,19 +10,26 @@ use crate::server::manager::grpc::proto::DeleteRoomResponse;
 use crate::server::manager::grpc::proto::EmptyRequest;
 use crate::server::manager::grpc::proto::GetRoomsMembersResponse;
 use crate::server::manager::grpc::proto::GetRoomsResponse;
+use crate::server::manager::grpc::proto::Member;
 use crate::server::manager::grpc::proto::ProbeRequest;
 use crate::server::manager::grpc::proto::ProbeResponse;
 use crate::server::manager::grpc::proto::RoomIdResponse;
 use crate::server::manager::grpc::proto::RoomMembersResponse;
 use crate::server::manager::grpc::proto::RoomTemplate;
+use crate::server::manager::grpc::proto::{DrainRequest, DrainResponse};
+use crate::server::manager::grpc::proto::{GetMemberStatsRequest, MemberStats};
+use crate::server::manager::grpc::proto::{SetMemberNetworkEmulationRequest, SetMemberNetworkEmulationResponse};
 use crate::server::manager::{ManagementTaskError, ManagementTaskExecutionError};
 use crate::server::room::command::ServerCommandError;
 use crate::server::room::config::member::MemberCreateParams;
+use crate::server::room::config::room::RoomCreateParams;
+use crate::server::room::config::templates::{RoomTemplates, RoomTemplatesError};
 use crate::ServerManager;
 use cheetah_common::room::access::AccessGroups;
 use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
 use cheetah_game_realtime_protocol::RoomId;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::sync::MutexGuard;
 use tonic::{Request, Response, Status};
@@ -29,32 +37,60 @@ use tonic::{Request, Response, Status};
 mod from;
 pub mod proto;
 
+#[derive(Clone)]
 pub struct RealtimeServerManagementServiceImpl {
 	pub server_manager: Arc<Mutex<ServerManager>>,
+	///
+	/// Ключ супер пользователя, создаваемого в каждой новой комнате
+	///
+	super_member_key: Option<Vec<u8>>,
+	room_templates: Option<Arc<RoomTemplates>>,
 }
 
-const SUPER_MEMBER_KEY_ENV: &str = "SUPER_MEMBER_KEY";
-
 impl RealtimeServerManagementServiceImpl {
 	#[must_use]
 	pub fn new(server_manager: Arc<Mutex<ServerManager>>) -> Self {
-		RealtimeServerManagementServiceImpl { server_manager }
+		RealtimeServerManagementServiceImpl {
+			server_manager,
+			super_member_key: None,
+			room_templates: None,
+		}
+	}
+
+	#[must_use]
+	pub fn with_room_templates(mut self, room_templates: Option<Arc<RoomTemplates>>) -> Self {
+		self.room_templates = room_templates;
+		self
+	}
+
+	#[must_use]
+	pub fn with_super_member_key(mut self, super_member_key: Option<Vec<u8>>) -> Self {
+		self.super_member_key = super_member_key;
+		self
 	}
 
 	async fn register_member(&self, room_id: RoomId, template: MemberCreateParams) -> Result<Response<CreateMemberResponse>, Status> {
 		let private_key = template.private_key.clone();
 		self.server_manager.lock().await.create_member(room_id, template).map_err(Status::from).map(|member_id| {
 			Response::new(CreateMemberResponse {
-				user_id: u64::from(member_id),
+				user_id: member_id,
 				private_key: private_key.into(),
 			})
 		})
 	}
 
-	fn create_super_member_if_need(server: &mut MutexGuard<'_, ServerManager>, room_id: RoomId) -> Result<(), ManagementTaskError> {
-		if let Ok(key_from_env) = std::env::var(SUPER_MEMBER_KEY_ENV) {
-			let key_from_env_bytes = key_from_env.as_bytes();
-			let key = key_from_env_bytes.into();
+	async fn create_room_from_params(&self, template: RoomCreateParams) -> Result<Response<RoomIdResponse>, Status> {
+		let mut server = self.server_manager.lock().await;
+		let room_id = server.create_room(template).map_err(Status::from)?;
+
+		self.create_super_member_if_need(&mut server, room_id)
+			.map(|_| Response::new(RoomIdResponse { room_id }))
+			.map_err(Status::from)
+	}
+
+	fn create_super_member_if_need(&self, server: &mut MutexGuard<'_, ServerManager>, room_id: RoomId) -> Result<(), ManagementTaskError> {
+		if let Some(key) = &self.super_member_key {
+			let key = key.as_slice().into();
 			server.create_member(room_id, MemberCreateParams::new_super_member_with_key(key))?;
 		}
 
@@ -65,13 +101,15 @@ impl RealtimeServerManagementServiceImpl {
 #[tonic::async_trait]
 impl RealtimeServerManagementService for RealtimeServerManagementServiceImpl {
 	async fn create_room(&self, request: Request<RoomTemplate>) -> Result<Response<RoomIdResponse>, Status> {
-		let mut server = self.server_manager.lock().await;
-		let template = From::from(request.into_inner());
-		let room_id = server.create_room(template).map_err(Status::from)?;
+		self.create_room_from_params(From::from(request.into_inner())).await
+	}
 
-		Self::create_super_member_if_need(&mut server, room_id)
-			.map(|_| Response::new(RoomIdResponse { room_id }))
-			.map_err(Status::from)
+	async fn create_room_from_template(&self, request: Request<CreateRoomFromTemplateRequest>) -> Result<Response<RoomIdResponse>, Status> {
+		let room_templates = self.room_templates.as_ref().ok_or_else(|| Status::failed_precondition("room templates are not configured"))?;
+		let request = request.into_inner();
+		let template_name = request.template_name.clone();
+		let template = room_templates.create_params(&template_name, From::from(request)).map_err(Status::from)?;
+		self.create_room_from_params(template).await
 	}
 
 	async fn create_member(&self, request: Request<CreateMemberRequest>) -> Result<Response<CreateMemberResponse>, Status> {
@@ -89,7 +127,7 @@ impl RealtimeServerManagementService for RealtimeServerManagementServiceImpl {
 			.lock()
 			.await
 			.delete_member(MemberAndRoomId {
-				member_id: request.get_ref().user_id.try_into().map_err(|e| Status::invalid_argument(format!("member_id is too big: {e}")))?,
+				member_id: request.get_ref().user_id,
 				room_id: request.get_ref().room_id,
 			})
 			.map(|_| Response::new(DeleteMemberResponse {}))
@@ -129,15 +167,57 @@ impl RealtimeServerManagementService for RealtimeServerManagementServiceImpl {
 				Response::new(GetRoomsMembersResponse {
 					rooms: rooms
 						.into_iter()
-						.map(|r| RoomMembersResponse {
+						.map(|mut r| RoomMembersResponse {
 							room: r.room_id,
-							members: r.members.into_iter().map(From::from).collect(),
+							members: r
+								.members
+								.into_iter()
+								.map(|member| {
+									let stats = r.stats.remove(&member.id).map(From::from);
+									Member { stats, ..From::from(member) }
+								})
+								.collect(),
 						})
 						.collect(),
 				})
 			})
 			.map_err(Status::from)
 	}
+
+	async fn drain(&self, request: Request<DrainRequest>) -> Result<Response<DrainResponse>, Status> {
+		let timeout = Duration::from_secs(request.get_ref().timeout_in_sec);
+		self.server_manager.lock().await.drain(timeout).map(|_| Response::new(DrainResponse {})).map_err(Status::from)
+	}
+
+	async fn set_member_network_emulation(&self, request: Request<SetMemberNetworkEmulationRequest>) -> Result<Response<SetMemberNetworkEmulationResponse>, Status> {
+		let request = request.into_inner();
+		let emulation = request.emulation.map(TryFrom::try_from).transpose().map_err(|e| Status::invalid_argument(format!("{e}")))?;
+		self.server_manager
+			.lock()
+			.await
+			.set_member_network_emulation(
+				MemberAndRoomId {
+					member_id: request.user_id,
+					room_id: request.room_id,
+				},
+				emulation,
+			)
+			.map(|_| Response::new(SetMemberNetworkEmulationResponse {}))
+			.map_err(Status::from)
+	}
+
+	async fn get_member_stats(&self, request: Request<GetMemberStatsRequest>) -> Result<Response<MemberStats>, Status> {
+		let request = request.into_inner();
+		self.server_manager
+			.lock()
+			.await
+			.get_member_stats(MemberAndRoomId {
+				member_id: request.user_id,
+				room_id: request.room_id,
+			})
+			.map(|stats| Response::new(stats.into()))
+			.map_err(Status::from)
+	}
 }
 
 impl From<ManagementTaskError> for Status {
@@ -148,6 +228,8 @@ impl From<ManagementTaskError> for Status {
 			ManagementTaskError::UnexpectedResultError => Status::internal("unexpected management task result type"),
 			ManagementTaskError::TaskExecutionError(ManagementTaskExecutionError::RoomNotFound(e)) => Status::not_found(e.to_string()),
 			ManagementTaskError::TaskExecutionError(ManagementTaskExecutionError::UnknownPluginName(e)) => Status::invalid_argument(e),
+			ManagementTaskError::TaskExecutionError(ManagementTaskExecutionError::ServerDraining) => Status::unavailable("server is draining"),
+			ManagementTaskError::TaskExecutionError(ManagementTaskExecutionError::MetricsRegistration(e)) => Status::internal(e),
 			ManagementTaskError::TaskExecutionError(ManagementTaskExecutionError::ServerCommandError(server_err)) => match server_err {
 				ServerCommandError::MemberNotFound(e) => Status::not_found(e.to_string()),
 				ServerCommandError::RoomNotFound(e) => Status::not_found(e.to_string()),
@@ -157,13 +239,27 @@ impl From<ManagementTaskError> for Status {
 	}
 }
 
+impl From<RoomTemplatesError> for Status {
+	fn from(e: RoomTemplatesError) -> Self {
+		match e {
+			RoomTemplatesError::NotFound(_) => Status::not_found(e.to_string()),
+			RoomTemplatesError::InvalidOverrides(_, _) => Status::invalid_argument(e.to_string()),
+			e => Status::internal(e.to_string()),
+		}
+	}
+}
+
 #[cfg(test)]
 mod test {
 	use crate::server::manager::grpc::proto::realtime_server_management_service_server::RealtimeServerManagementService;
-	use crate::server::manager::grpc::proto::{DeleteMemberRequest, DeleteRoomRequest, EmptyRequest, Member, MemberStatus, RoomMembersResponse};
-	use crate::server::manager::grpc::{RealtimeServerManagementServiceImpl, SUPER_MEMBER_KEY_ENV};
+	use crate::server::manager::grpc::proto::{
+		CreateRoomFromTemplateRequest, DeleteMemberRequest, DeleteRoomRequest, DrainRequest, EmptyRequest, GameObjectTemplate, GetMemberStatsRequest, Member, MemberStats, MemberStatus,
+		RoomMembersResponse,
+	};
+	use crate::server::manager::grpc::RealtimeServerManagementServiceImpl;
 	use crate::server::manager::ServerManager;
 	use crate::server::room::config::member::MemberCreateParams;
+	use crate::server::room::config::templates::RoomTemplates;
 	use cheetah_common::network::bind_to_free_socket;
 	use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
 	use std::sync::Arc;
@@ -211,7 +307,8 @@ mod test {
 			room: room_1,
 			members: vec![Member {
 				id: member_id,
-				status: MemberStatus::Created.into()
+				status: MemberStatus::Created.into(),
+				stats: Some(Default::default()),
 			}],
 		}));
 		assert!(rooms.rooms.contains(&RoomMembersResponse {
@@ -221,12 +318,37 @@ mod test {
 		assert_eq!(rooms.rooms.len(), 2);
 	}
 
+	#[tokio::test]
+	async fn should_get_member_stats() {
+		let server_manager = Arc::new(Mutex::new(new_server_manager()));
+		let service = RealtimeServerManagementServiceImpl::new(Arc::clone(&server_manager));
+		let room_id = server_manager.lock().await.create_room(Default::default()).unwrap();
+		let member_id = server_manager.lock().await.create_member(room_id, MemberCreateParams::default()).unwrap();
+
+		let stats = service
+			.get_member_stats(Request::new(GetMemberStatsRequest { room_id, user_id: member_id }))
+			.await
+			.unwrap()
+			.into_inner();
+		assert_eq!(stats, MemberStats::default());
+
+		let status = service.get_member_stats(Request::new(GetMemberStatsRequest { room_id, user_id: member_id + 1 })).await.unwrap_err();
+		assert_eq!(status.code(), Code::NotFound);
+		let status = service
+			.get_member_stats(Request::new(GetMemberStatsRequest {
+				room_id: room_id + 1,
+				user_id: member_id,
+			}))
+			.await
+			.unwrap_err();
+		assert_eq!(status.code(), Code::NotFound);
+	}
+
 	#[tokio::test]
 	async fn test_create_super_member() {
 		let server_manager = Arc::new(Mutex::new(new_server_manager()));
 
-		std::env::set_var(SUPER_MEMBER_KEY_ENV, "some-key");
-		let service = RealtimeServerManagementServiceImpl::new(Arc::clone(&server_manager));
+		let service = RealtimeServerManagementServiceImpl::new(Arc::clone(&server_manager)).with_super_member_key(Some(b"some-key".to_vec()));
 		let room_id = service.create_room(Request::new(Default::default())).await.unwrap().into_inner();
 
 		let dump_response = server_manager.lock().await.dump(room_id.room_id).unwrap();
@@ -243,6 +365,58 @@ mod test {
 		service.delete_room(Request::new(DeleteRoomRequest { id: room_id })).await.unwrap();
 	}
 
+	#[tokio::test]
+	async fn should_reject_create_room_after_drain() {
+		let server_manager = Arc::new(Mutex::new(new_server_manager()));
+		let service = RealtimeServerManagementServiceImpl::new(Arc::clone(&server_manager));
+		service.create_room(Request::new(Default::default())).await.unwrap();
+
+		service.drain(Request::new(DrainRequest { timeout_in_sec: 30 })).await.unwrap();
+
+		assert!(server_manager.lock().await.is_draining());
+		let status = service.create_room(Request::new(Default::default())).await.unwrap_err();
+		assert_eq!(status.code(), Code::Unavailable);
+	}
+
+	#[tokio::test]
+	async fn should_create_room_from_template() {
+		let dir = tempfile::TempDir::new().unwrap();
+		std::fs::write(dir.path().join("battle.ron"), "(objects: [(id: 1, template: 10)])").unwrap();
+		let server_manager = Arc::new(Mutex::new(new_server_manager()));
+		let service = RealtimeServerManagementServiceImpl::new(Arc::clone(&server_manager)).with_room_templates(Some(Arc::new(RoomTemplates::load(dir.path()).unwrap())));
+
+		let request = CreateRoomFromTemplateRequest {
+			template_name: "battle".to_owned(),
+			objects: vec![GameObjectTemplate {
+				id: 2,
+				template: 20,
+				groups: 0,
+				fields: Default::default(),
+			}],
+			configs: Default::default(),
+			compression: Some(true),
+			journal: None,
+		};
+		let room_id = service.create_room_from_template(Request::new(request)).await.unwrap().into_inner().room_id;
+		let room = server_manager.lock().await.dump(room_id).unwrap().unwrap();
+		assert_eq!(room.template_name, "battle");
+		assert_eq!(room.objects.len(), 2);
+		assert!(room.compression);
+
+		let request = CreateRoomFromTemplateRequest {
+			template_name: "unknown".to_owned(),
+			..Default::default()
+		};
+		assert_eq!(service.create_room_from_template(Request::new(request)).await.unwrap_err().code(), Code::NotFound);
+	}
+
+	#[tokio::test]
+	async fn should_fail_create_room_from_template_without_templates() {
+		let service = RealtimeServerManagementServiceImpl::new(Arc::new(Mutex::new(new_server_manager())));
+		let status = service.create_room_from_template(Request::new(Default::default())).await.unwrap_err();
+		assert_eq!(status.code(), Code::FailedPrecondition);
+	}
+
 	#[tokio::test]
 	async fn test_delete_room_not_exist() {
 		let server_manager = Arc::new(Mutex::new(new_server_manager()));
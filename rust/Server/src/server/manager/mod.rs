@@ -1,25 +1,38 @@
+use std::collections::HashMap;
 use std::net::UdpSocket;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{RecvTimeoutError, SendError, Sender};
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
+use cheetah_common::room::object::{GameObjectId, GameObjectTemplateId};
 use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
 use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
 use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
 use thiserror::Error;
 
+use crate::server::network::{GameSocket, MemberNetworkEmulation, MemberNetworkStats};
 use crate::server::room::command::ServerCommandError;
 use crate::server::room::config::member::MemberCreateParams;
 use crate::server::room::config::room::RoomCreateParams;
-use crate::server::room::member::RoomMember;
+use crate::server::room::config::templates::RoomTemplates;
+use crate::server::room::member::{RoomMember, RoomMemberStatus};
+use crate::server::room::object::GameObject;
 use crate::server::room::Room;
-use crate::server::room_registry::RoomNotFoundError;
+use crate::server::room_registry::{RoomNotFoundError, RoomTrafficEvent};
+use crate::server::trace::{TraceScope, TracedCommand};
+use crate::server::websocket::WebSocketListener;
 use crate::server::Server;
 
 pub mod grpc;
 
+///
+/// Размер буфера событий трафика комнаты, при переполнении события пропускаются
+///
+const ROOM_TRAFFIC_BUFFER_SIZE: usize = 1024;
+
 ///
 /// Управление сервером
 /// - запуск сервера в отдельном потоке
@@ -28,6 +41,7 @@ pub mod grpc;
 pub struct ServerManager {
 	sender: Sender<ManagementTaskChannel>,
 	halt_signal: Arc<AtomicBool>,
+	drain_signal: Arc<AtomicBool>,
 }
 
 #[derive(Debug)]
@@ -39,10 +53,48 @@ pub enum ManagementTask {
 	GetRooms,
 	GetCreatedRoomsCount,
 	GetRoomsMembers,
+	///
+	/// Страница пользователей комнаты по возрастанию идентификатора
+	///
+	GetRoomMembers(RoomId, PageRange),
+	///
+	/// Идентификаторы и статусы пользователей комнаты без копирования их данных, None - если комнаты нет
+	///
+	GetRoomMembersStatus(RoomId),
+	///
+	/// Страница объектов комнаты в порядке создания, с фильтром по шаблону объекта
+	///
+	GetRoomObjects(RoomId, Option<GameObjectTemplateId>, PageRange),
+	GetRoomObject(RoomId, GameObjectId),
 	DeleteRoom(RoomId),
+	SetMemberTokenKey(Vec<u8>),
+	SetRoomJournalDir(PathBuf),
+	///
+	/// Загруженные шаблоны комнат, используются для меток метрик по шаблонам
+	///
+	SetRoomTemplates(Arc<RoomTemplates>),
+	EnableWebSocket(WebSocketListener),
+	Drain(Duration),
+	///
+	/// None - выключить эмуляцию
+	///
+	SetMemberNetworkEmulation(MemberAndRoomId, Option<MemberNetworkEmulation>),
+	GetMemberStats(MemberAndRoomId),
+	///
+	/// Экспортировать сетевую статистику пользователей в prometheus, не более заданного количества пользователей
+	///
+	EnableMemberMetrics(usize),
+	SubscribeRoomTraffic(RoomId, tokio::sync::mpsc::Sender<RoomTrafficEvent>),
+	///
+	/// Трассировать команды комнаты или пользователя в течение заданного времени
+	///
+	EnableCommandTrace(TraceScope, Duration),
+	DisableCommandTrace(TraceScope),
+	GetCommandTrace(RoomId),
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum ManagementTaskResult {
 	CreateRoom(RoomId),
 	CreateMember(RoomMemberId),
@@ -50,14 +102,52 @@ pub enum ManagementTaskResult {
 	Dump(Option<Room>),
 	GetRooms(Vec<RoomId>),
 	GetRoomsMemberCount(Vec<RoomMembers>),
+	GetRoomMembers(ItemsPage<(RoomMember, Option<MemberNetworkStats>)>),
+	GetRoomMembersStatus(Option<Vec<(RoomMemberId, RoomMemberStatus)>>),
+	GetRoomObjects(ItemsPage<GameObject>),
+	GetRoomObject(Option<GameObject>),
 	GetCreatedRoomsCount(usize),
 	DeleteRoom,
+	SetMemberTokenKey,
+	SetRoomJournalDir,
+	SetRoomTemplates,
+	EnableWebSocket,
+	Drain,
+	SetMemberNetworkEmulation,
+	GetMemberStats(MemberNetworkStats),
+	EnableMemberMetrics,
+	SubscribeRoomTraffic,
+	EnableCommandTrace,
+	DisableCommandTrace,
+	GetCommandTrace(Vec<TracedCommand>),
+}
+
+///
+/// Диапазон элементов для постраничного вывода, страница выбирается в потоке сервера без копирования всей комнаты
+///
+#[derive(Debug, Clone, Copy)]
+pub struct PageRange {
+	pub offset: usize,
+	pub limit: usize,
+}
+
+///
+/// Страница элементов, total - количество элементов без учета [`PageRange`]
+///
+#[derive(Debug)]
+pub struct ItemsPage<T> {
+	pub total: usize,
+	pub items: Vec<T>,
 }
 
 #[derive(Debug)]
 pub struct RoomMembers {
 	pub room_id: RoomId,
 	pub members: Vec<RoomMember>,
+	///
+	/// Сетевая статистика пользователей, для которых открыта сессия
+	///
+	pub stats: HashMap<RoomMemberId, MemberNetworkStats>,
 }
 
 #[derive(Error, Debug)]
@@ -86,6 +176,10 @@ pub enum ManagementTaskExecutionError {
 	UnknownPluginName(String),
 	#[error("ServerCommandError {0}")]
 	ServerCommandError(#[from] ServerCommandError),
+	#[error("ServerDraining")]
+	ServerDraining,
+	#[error("MetricsRegistration {0}")]
+	MetricsRegistration(String),
 }
 
 pub struct ManagementTaskChannel {
@@ -104,9 +198,11 @@ impl ServerManager {
 		let (sender, receiver) = std::sync::mpsc::channel();
 		let halt_signal = Arc::new(AtomicBool::new(false));
 		let cloned_halt_signal = Arc::clone(&halt_signal);
+		let drain_signal = Arc::new(AtomicBool::new(false));
+		let cloned_drain_signal = Arc::clone(&drain_signal);
 		thread::Builder::new()
 			.name(format!("server({:?})", socket.local_addr()))
-			.spawn(move || match Server::new(socket, receiver, halt_signal, protocol_configuration) {
+			.spawn(move || match Server::new(GameSocket::Udp(socket), receiver, halt_signal, drain_signal, protocol_configuration) {
 				Ok(server) => {
 					server.run();
 					Ok(())
@@ -120,10 +216,11 @@ impl ServerManager {
 		Ok(Self {
 			sender,
 			halt_signal: cloned_halt_signal,
+			drain_signal: cloned_drain_signal,
 		})
 	}
 
-	pub(crate) fn get_rooms(&self) -> Result<Vec<RoomId>, ManagementTaskError> {
+	pub fn get_rooms(&self) -> Result<Vec<RoomId>, ManagementTaskError> {
 		self.execute_task(ManagementTask::GetRooms).map(|res| {
 			if let ManagementTaskResult::GetRooms(rooms) = res {
 				Ok(rooms)
@@ -142,7 +239,7 @@ impl ServerManager {
 		})?
 	}
 
-	pub(crate) fn get_rooms_member_count(&self) -> Result<Vec<RoomMembers>, ManagementTaskError> {
+	pub fn get_rooms_member_count(&self) -> Result<Vec<RoomMembers>, ManagementTaskError> {
 		self.execute_task(ManagementTask::GetRoomsMembers).map(|res| {
 			if let ManagementTaskResult::GetRoomsMemberCount(rooms) = res {
 				Ok(rooms)
@@ -152,6 +249,49 @@ impl ServerManager {
 		})?
 	}
 
+	pub fn get_room_members(&self, room_id: RoomId, range: PageRange) -> Result<ItemsPage<(RoomMember, Option<MemberNetworkStats>)>, ManagementTaskError> {
+		self.execute_task(ManagementTask::GetRoomMembers(room_id, range)).map(|res| {
+			if let ManagementTaskResult::GetRoomMembers(page) = res {
+				Ok(page)
+			} else {
+				Err(ManagementTaskError::UnexpectedResultError)
+			}
+		})?
+	}
+
+	///
+	/// Идентификаторы и статусы пользователей комнаты, None - если комнаты нет
+	///
+	pub fn get_room_members_status(&self, room_id: RoomId) -> Result<Option<Vec<(RoomMemberId, RoomMemberStatus)>>, ManagementTaskError> {
+		self.execute_task(ManagementTask::GetRoomMembersStatus(room_id)).map(|res| {
+			if let ManagementTaskResult::GetRoomMembersStatus(members) = res {
+				Ok(members)
+			} else {
+				Err(ManagementTaskError::UnexpectedResultError)
+			}
+		})?
+	}
+
+	pub fn get_room_objects(&self, room_id: RoomId, template: Option<GameObjectTemplateId>, range: PageRange) -> Result<ItemsPage<GameObject>, ManagementTaskError> {
+		self.execute_task(ManagementTask::GetRoomObjects(room_id, template, range)).map(|res| {
+			if let ManagementTaskResult::GetRoomObjects(page) = res {
+				Ok(page)
+			} else {
+				Err(ManagementTaskError::UnexpectedResultError)
+			}
+		})?
+	}
+
+	pub fn get_room_object(&self, room_id: RoomId, object_id: GameObjectId) -> Result<Option<GameObject>, ManagementTaskError> {
+		self.execute_task(ManagementTask::GetRoomObject(room_id, object_id)).map(|res| {
+			if let ManagementTaskResult::GetRoomObject(object) = res {
+				Ok(object)
+			} else {
+				Err(ManagementTaskError::UnexpectedResultError)
+			}
+		})?
+	}
+
 	pub fn create_room(&mut self, template: RoomCreateParams) -> Result<RoomId, ManagementTaskError> {
 		self.execute_task(ManagementTask::CreateRoom(template)).map(|res| {
 			if let ManagementTaskResult::CreateRoom(room_id) = res {
@@ -182,7 +322,109 @@ impl ServerManager {
 		})?
 	}
 
-	pub(crate) fn dump(&self, room_id: u64) -> Result<Option<Room>, ManagementTaskError> {
+	///
+	/// Разрешить подключение пользователей по токенам, подписанным ключом
+	///
+	pub fn set_member_token_key(&mut self, key: Vec<u8>) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::SetMemberTokenKey(key)).map(|_| ())
+	}
+
+	///
+	/// Каталог для журналов комнат, созданных с включенным журналом
+	///
+	pub fn set_room_journal_dir(&mut self, dir: PathBuf) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::SetRoomJournalDir(dir)).map(|_| ())
+	}
+
+	///
+	/// Шаблоны комнат, имена которых используются в метках метрик, комнаты с другими именами учитываются как other
+	///
+	pub fn set_room_templates(&mut self, room_templates: Arc<RoomTemplates>) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::SetRoomTemplates(room_templates)).map(|_| ())
+	}
+
+	///
+	/// Принимать подключения пользователей через WebSocket, в дополнение к UDP
+	///
+	pub fn enable_websocket(&mut self, listener: WebSocketListener) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::EnableWebSocket(listener)).map(|_| ())
+	}
+
+	///
+	/// Остановить сервер после завершения комнат
+	///
+	/// - новые комнаты и пользователи не принимаются
+	/// - через timeout пользователи оставшихся комнат отключаются с причиной `ServerShutdown`
+	/// - после отключения пользователей сервер останавливается
+	///
+	pub fn drain(&self, timeout: Duration) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::Drain(timeout)).map(|_| ())
+	}
+
+	///
+	/// Эмуляция плохой сети для конкретного пользователя на стороне сервера, None - выключить эмуляцию
+	///
+	pub fn set_member_network_emulation(&self, id: MemberAndRoomId, emulation: Option<MemberNetworkEmulation>) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::SetMemberNetworkEmulation(id, emulation)).map(|_| ())
+	}
+
+	pub fn get_member_stats(&self, id: MemberAndRoomId) -> Result<MemberNetworkStats, ManagementTaskError> {
+		self.execute_task(ManagementTask::GetMemberStats(id)).map(|res| {
+			if let ManagementTaskResult::GetMemberStats(stats) = res {
+				Ok(stats)
+			} else {
+				Err(ManagementTaskError::UnexpectedResultError)
+			}
+		})?
+	}
+
+	///
+	/// Экспортировать сетевую статистику пользователей в prometheus с метками комнаты и пользователя,
+	/// для ограничения количества серий экспортируется не более max_members пользователей
+	///
+	pub fn enable_member_metrics(&self, max_members: usize) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::EnableMemberMetrics(max_members)).map(|_| ())
+	}
+
+	///
+	/// Подписаться на исходящие команды комнаты, поток событий завершается при удалении комнаты
+	///
+	pub fn subscribe_room_traffic(&self, room_id: RoomId) -> Result<tokio::sync::mpsc::Receiver<RoomTrafficEvent>, ManagementTaskError> {
+		let (sender, receiver) = tokio::sync::mpsc::channel(ROOM_TRAFFIC_BUFFER_SIZE);
+		self.execute_task(ManagementTask::SubscribeRoomTraffic(room_id, sender)).map(|_| receiver)
+	}
+
+	///
+	/// Трассировать входящие и исходящие команды комнаты или пользователя в течение duration,
+	/// команды пишутся в лог с отдельной целью и доступны через [`Self::get_command_trace`]
+	///
+	pub fn enable_command_trace(&self, scope: TraceScope, duration: Duration) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::EnableCommandTrace(scope, duration)).map(|_| ())
+	}
+
+	pub fn disable_command_trace(&self, scope: TraceScope) -> Result<(), ManagementTaskError> {
+		self.execute_task(ManagementTask::DisableCommandTrace(scope)).map(|_| ())
+	}
+
+	///
+	/// Последние трассированные команды комнаты
+	///
+	pub fn get_command_trace(&self, room_id: RoomId) -> Result<Vec<TracedCommand>, ManagementTaskError> {
+		self.execute_task(ManagementTask::GetCommandTrace(room_id)).map(|res| {
+			if let ManagementTaskResult::GetCommandTrace(commands) = res {
+				Ok(commands)
+			} else {
+				Err(ManagementTaskError::UnexpectedResultError)
+			}
+		})?
+	}
+
+	#[must_use]
+	pub fn is_draining(&self) -> bool {
+		self.drain_signal.load(Ordering::Relaxed)
+	}
+
+	pub fn dump(&self, room_id: RoomId) -> Result<Option<Room>, ManagementTaskError> {
 		self.execute_task(ManagementTask::Dump(room_id)).map(|res| {
 			if let ManagementTaskResult::Dump(resp) = res {
 				Ok(resp)
@@ -206,22 +448,31 @@ impl ServerManager {
 		Arc::clone(&self.halt_signal)
 	}
 
+	///
+	/// Остановить сервер без ожидания завершения комнат, пользователи отключаются с причиной `ServerShutdown`
+	///
 	pub fn shutdown(&mut self) {
+		if let Err(e) = self.drain(Duration::ZERO) {
+			tracing::error!("Error disconnect members on shutdown {:?}", e);
+		}
 		self.halt_signal.store(true, Ordering::Relaxed);
 	}
 }
 
 #[cfg(test)]
 mod test {
+	use std::sync::atomic::Ordering;
+	use std::thread;
 	use std::time::Duration;
 
 	use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
 
 	use cheetah_common::network::bind_to_free_socket;
 
-	use crate::server::manager::ServerManager;
+	use crate::server::manager::{ManagementTaskError, ManagementTaskExecutionError, ServerManager};
 	use crate::server::room::config::member::MemberCreateParams;
 	use crate::server::room::config::room::RoomCreateParams;
+	use crate::server::room::member::RoomMemberStatus;
 
 	#[test]
 	fn should_get_rooms() {
@@ -240,6 +491,60 @@ mod test {
 		assert_eq!(member_id, 1);
 	}
 
+	#[test]
+	fn should_get_room_members_status() {
+		let mut server = new_server_manager();
+		let room_id = server.create_room(RoomCreateParams::default()).unwrap();
+		let member_id = server.create_member(room_id, MemberCreateParams::default()).unwrap();
+
+		assert_eq!(server.get_room_members_status(room_id).unwrap(), Some(vec![(member_id, RoomMemberStatus::Created)]));
+		assert_eq!(server.get_room_members_status(room_id + 1).unwrap(), None);
+	}
+
+	#[test]
+	fn should_reject_rooms_and_members_when_draining() {
+		let mut server = new_server_manager();
+		let room_id = server.create_room(RoomCreateParams::default()).unwrap();
+		server.drain(Duration::from_secs(30)).unwrap();
+		assert!(server.is_draining());
+		assert!(matches!(
+			server.create_room(RoomCreateParams::default()),
+			Err(ManagementTaskError::TaskExecutionError(ManagementTaskExecutionError::ServerDraining))
+		));
+		assert!(matches!(
+			server.create_member(room_id, MemberCreateParams::default()),
+			Err(ManagementTaskError::TaskExecutionError(ManagementTaskExecutionError::ServerDraining))
+		));
+		assert!(!server.get_halt_signal().load(Ordering::Relaxed));
+	}
+
+	#[test]
+	fn should_halt_after_drain_without_rooms() {
+		let server = new_server_manager();
+		server.drain(Duration::from_secs(30)).unwrap();
+		wait_halt(&server);
+	}
+
+	#[test]
+	fn should_halt_after_drain_timeout() {
+		let mut server = new_server_manager();
+		let room_id = server.create_room(RoomCreateParams::default()).unwrap();
+		server.create_member(room_id, MemberCreateParams::default()).unwrap();
+		server.drain(Duration::from_millis(100)).unwrap();
+		wait_halt(&server);
+	}
+
+	fn wait_halt(server: &ServerManager) {
+		let halt_signal = server.get_halt_signal();
+		for _ in 0..100 {
+			if halt_signal.load(Ordering::Relaxed) {
+				return;
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+		panic!("server not halted");
+	}
+
 	fn new_server_manager() -> ServerManager {
 		ServerManager::new(
 			bind_to_free_socket().unwrap(),
@@ -1,13 +1,20 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use prometheus::Histogram;
+use cheetah_common::commands::CommandTypeId;
+use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+use prometheus::core::Collector;
 use prometheus::HistogramOpts;
 use prometheus::IntGauge;
 use prometheus::Opts;
 use prometheus::Registry;
+use prometheus::{GaugeVec, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGaugeVec};
 use prometheus_measures_exporter::measurer::create_and_register_measurer;
 
-use crate::server::network::Network;
+use crate::server::network::{MemberNetworkStats, Network};
+use crate::server::room::command::ServerCommandError;
+use crate::server::room::config::templates::RoomTemplates;
 use crate::server::room_registry::Rooms;
 
 ///
@@ -17,13 +24,88 @@ pub struct Measurer {
 	room_count: IntGauge,
 	member_count: IntGauge,
 	object_count: IntGauge,
-	income_command_count: IntGauge,
-	outcome_command_count: IntGauge,
-	income_frame_count: IntGauge,
-	outcome_frame_count: IntGauge,
+	income_command_count: IntCounter,
+	outcome_command_count: IntCounter,
+	income_frame_count: IntCounter,
+	outcome_frame_count: IntCounter,
+	///
+	/// Значения счетчиков [`Network`] на момент предыдущего измерения, в prometheus добавляется только разница
+	///
+	measured_network_counts: NetworkCounts,
 	cycle_time: Histogram,
+	command_measurer: CommandMeasurer,
+	template_metrics: TemplateMetrics,
+	registry: Registry,
+	member_metrics: Option<MemberMetrics>,
 }
 
+#[derive(Debug, Default, Clone, Copy)]
+struct NetworkCounts {
+	income_command_count: usize,
+	outcome_command_count: usize,
+	income_frame_count: usize,
+	outcome_frame_count: usize,
+}
+
+///
+/// Измерение выполнения входящих команд по типам, передается в [`Rooms`] и вызывается при выполнении каждой команды
+///
+#[derive(Clone)]
+pub struct CommandMeasurer {
+	executed: IntCounterVec,
+	failed: IntCounterVec,
+	errors: IntCounterVec,
+	execution_time: HistogramVec,
+}
+
+///
+/// Агрегаты по шаблонам комнат, количество серий ограничено количеством шаблонов
+///
+/// - меткой служат только имена загруженных шаблонов, комнаты с остальными именами (созданные не по шаблону) учитываются в [`OTHER_TEMPLATE`]
+///
+struct TemplateMetrics {
+	rooms: IntGaugeVec,
+	members: IntGaugeVec,
+	objects: IntGaugeVec,
+	received_bytes: IntCounterVec,
+	sent_bytes: IntCounterVec,
+	///
+	/// Переданные байты пользователей на момент предыдущего измерения
+	///
+	member_bytes: HashMap<MemberAndRoomId, (u64, u64)>,
+	exported: HashSet<String>,
+	room_templates: Option<Arc<RoomTemplates>>,
+	measured_time: Option<Instant>,
+}
+
+const OTHER_TEMPLATE: &str = "other";
+
+///
+/// Сетевая статистика пользователей с метками room и member
+///
+/// - включается явно, так как количество серий растет с количеством пользователей
+/// - экспортируются не более `max_members` пользователей с наименьшими идентификаторами,
+///   серии отключившихся пользователей удаляются
+///
+struct MemberMetrics {
+	max_members: usize,
+	rtt: GaugeVec,
+	packet_loss: GaugeVec,
+	retransmit_count: IntGaugeVec,
+	received_bytes: IntGaugeVec,
+	sent_bytes: IntGaugeVec,
+	out_command_backlog: IntGaugeVec,
+	exported: HashSet<MemberAndRoomId>,
+	measured_time: Option<Instant>,
+}
+
+const MEMBER_LABELS: [&str; 2] = ["room", "member"];
+
+///
+/// Интервал обновления метрик, для которых перебираются все комнаты или пользователи, - обновлять их в каждом цикле сервера слишком дорого
+///
+const AGGREGATE_MEASURE_INTERVAL: Duration = Duration::from_secs(1);
+
 impl Default for Measurer {
 	fn default() -> Self {
 		Measurer::new(prometheus::default_registry())
@@ -37,12 +119,39 @@ impl Measurer {
 			room_count: create_and_register_measurer::<_, _>(registry, Opts::new("room_count", "Room count")),
 			member_count: create_and_register_measurer::<_, _>(registry, Opts::new("member_count", "Member count")),
 			object_count: create_and_register_measurer::<_, _>(registry, Opts::new("object_count", "Object count")),
-			income_command_count: create_and_register_measurer::<_, _>(registry, Opts::new("income_command_count", "Income command count")),
-			outcome_command_count: create_and_register_measurer::<_, _>(registry, Opts::new("outcome_command_count", "Outcome command count")),
-			income_frame_count: create_and_register_measurer::<_, _>(registry, Opts::new("income_frame_count", "Income frame count")),
-			outcome_frame_count: create_and_register_measurer::<_, _>(registry, Opts::new("outcome_frame_count", "Outcome frame count")),
+			income_command_count: create_and_register_measurer::<_, _>(registry, Opts::new("income_command_total", "Income command count")),
+			outcome_command_count: create_and_register_measurer::<_, _>(registry, Opts::new("outcome_command_total", "Outcome command count")),
+			income_frame_count: create_and_register_measurer::<_, _>(registry, Opts::new("income_frame_total", "Income frame count")),
+			outcome_frame_count: create_and_register_measurer::<_, _>(registry, Opts::new("outcome_frame_total", "Outcome frame count")),
+			measured_network_counts: Default::default(),
 			cycle_time: Self::create_execution_time(registry),
+			command_measurer: CommandMeasurer::new(registry),
+			template_metrics: TemplateMetrics::new(registry),
+			registry: registry.clone(),
+			member_metrics: None,
+		}
+	}
+
+	pub(crate) fn command_measurer(&self) -> CommandMeasurer {
+		self.command_measurer.clone()
+	}
+
+	///
+	/// Шаблоны комнат, имена которых используются в метках агрегатов по шаблонам
+	///
+	pub(crate) fn set_room_templates(&mut self, room_templates: Arc<RoomTemplates>) {
+		self.template_metrics.room_templates = Some(room_templates);
+	}
+
+	///
+	/// Включить экспорт сетевой статистики пользователей, повторный вызов меняет только ограничение количества пользователей
+	///
+	pub(crate) fn enable_member_metrics(&mut self, max_members: usize) -> Result<(), prometheus::Error> {
+		match self.member_metrics.as_mut() {
+			None => self.member_metrics = Some(MemberMetrics::new(&self.registry, max_members)?),
+			Some(member_metrics) => member_metrics.max_members = max_members,
 		}
+		Ok(())
 	}
 
 	fn create_execution_time(registry: &Registry) -> Histogram {
@@ -66,6 +175,10 @@ impl Measurer {
 		self.measure_execution_time(start_cycle_time);
 		self.measure_rooms(rooms_registry);
 		self.measure_network_server(network_server);
+		self.template_metrics.measure(network_server, rooms_registry, *start_cycle_time);
+		if let Some(member_metrics) = self.member_metrics.as_mut() {
+			member_metrics.measure(network_server, *start_cycle_time);
+		}
 	}
 
 	fn measure_execution_time(&mut self, start_cycle_time: &Instant) {
@@ -74,10 +187,18 @@ impl Measurer {
 	}
 
 	fn measure_network_server(&mut self, network_server: &Network) {
-		self.income_command_count.set(network_server.income_command_count as i64);
-		self.outcome_command_count.set(network_server.outcome_command_count as i64);
-		self.income_frame_count.set(network_server.income_frame_count as i64);
-		self.outcome_frame_count.set(network_server.outcome_frame_count as i64);
+		let current = NetworkCounts {
+			income_command_count: network_server.income_command_count,
+			outcome_command_count: network_server.outcome_command_count,
+			income_frame_count: network_server.income_frame_count,
+			outcome_frame_count: network_server.outcome_frame_count,
+		};
+		let previous = self.measured_network_counts;
+		inc_counter(&self.income_command_count, previous.income_command_count, current.income_command_count);
+		inc_counter(&self.outcome_command_count, previous.outcome_command_count, current.outcome_command_count);
+		inc_counter(&self.income_frame_count, previous.income_frame_count, current.income_frame_count);
+		inc_counter(&self.outcome_frame_count, previous.outcome_frame_count, current.outcome_frame_count);
+		self.measured_network_counts = current;
 	}
 
 	fn measure_rooms(&mut self, rooms_registry: &Rooms) {
@@ -92,3 +213,392 @@ impl Measurer {
 		self.object_count.set(object_count as i64);
 	}
 }
+
+impl CommandMeasurer {
+	fn new(registry: &Registry) -> Self {
+		Self {
+			executed: register(registry, IntCounterVec::new(Opts::new("command_executed_total", "Executed command count"), &["command"]).unwrap()),
+			failed: register(registry, IntCounterVec::new(Opts::new("command_failed_total", "Failed command count"), &["command"]).unwrap()),
+			errors: register(registry, IntCounterVec::new(Opts::new("command_error_total", "Command execution error count"), &["error"]).unwrap()),
+			execution_time: register(
+				registry,
+				HistogramVec::new(
+					HistogramOpts::new("command_execution_time", "Command execution time").buckets(vec![
+						Duration::from_micros(1).as_secs_f64(),
+						Duration::from_micros(5).as_secs_f64(),
+						Duration::from_micros(10).as_secs_f64(),
+						Duration::from_micros(50).as_secs_f64(),
+						Duration::from_micros(100).as_secs_f64(),
+						Duration::from_micros(500).as_secs_f64(),
+						Duration::from_millis(1).as_secs_f64(),
+						Duration::from_millis(10).as_secs_f64(),
+						Duration::from_millis(100).as_secs_f64(),
+					]),
+					&["command"],
+				)
+				.unwrap(),
+			),
+		}
+	}
+
+	pub(crate) fn on_command_executed(&self, command_type: CommandTypeId, execution_time: Duration, error: Option<&ServerCommandError>) {
+		let labels = [command_type.as_ref()];
+		self.executed.with_label_values(&labels).inc();
+		self.execution_time.with_label_values(&labels).observe(execution_time.as_secs_f64());
+		if let Some(error) = error {
+			self.failed.with_label_values(&labels).inc();
+			self.errors.with_label_values(&[error.as_ref()]).inc();
+		}
+	}
+}
+
+impl TemplateMetrics {
+	fn new(registry: &Registry) -> Self {
+		Self {
+			rooms: register(registry, IntGaugeVec::new(Opts::new("template_room_count", "Room count by template"), &["template"]).unwrap()),
+			members: register(registry, IntGaugeVec::new(Opts::new("template_member_count", "Member count by room template"), &["template"]).unwrap()),
+			objects: register(registry, IntGaugeVec::new(Opts::new("template_object_count", "Object count by room template"), &["template"]).unwrap()),
+			received_bytes: register(
+				registry,
+				IntCounterVec::new(Opts::new("template_received_bytes_total", "Received bytes by room template"), &["template"]).unwrap(),
+			),
+			sent_bytes: register(
+				registry,
+				IntCounterVec::new(Opts::new("template_sent_bytes_total", "Sent bytes by room template"), &["template"]).unwrap(),
+			),
+			member_bytes: Default::default(),
+			exported: Default::default(),
+			room_templates: None,
+			measured_time: None,
+		}
+	}
+
+	#[allow(clippy::cast_possible_wrap)]
+	fn measure(&mut self, network_server: &Network, rooms_registry: &Rooms, now: Instant) {
+		if self
+			.measured_time
+			.is_some_and(|measured_time| now.saturating_duration_since(measured_time) < AGGREGATE_MEASURE_INTERVAL)
+		{
+			return;
+		}
+		self.measured_time = Some(now);
+
+		let templates: HashSet<String> = self.room_templates.as_ref().map(|room_templates| room_templates.names().into_iter().collect()).unwrap_or_default();
+		let template_label = |name: &str| -> String {
+			if templates.contains(name) {
+				name.to_owned()
+			} else {
+				OTHER_TEMPLATE.to_owned()
+			}
+		};
+
+		let mut aggregates: HashMap<String, (usize, usize, usize)> = HashMap::new();
+		for (_, room) in rooms_registry.rooms() {
+			let (rooms, members, objects) = aggregates.entry(template_label(&room.template_name)).or_default();
+			*rooms += 1;
+			*members += room.members.len();
+			*objects += room.objects.len();
+		}
+
+		for template in self.exported.iter().filter(|template| !aggregates.contains_key(template.as_str())) {
+			// серия могла быть не создана, ошибка удаления не важна
+			let _ = self.rooms.remove_label_values(&[template]);
+			let _ = self.members.remove_label_values(&[template]);
+			let _ = self.objects.remove_label_values(&[template]);
+		}
+		for (template, (rooms, members, objects)) in &aggregates {
+			let labels = [template.as_str()];
+			self.rooms.with_label_values(&labels).set(*rooms as i64);
+			self.members.with_label_values(&labels).set(*members as i64);
+			self.objects.with_label_values(&labels).set(*objects as i64);
+		}
+		self.exported = aggregates.into_keys().collect();
+
+		let mut member_bytes = HashMap::with_capacity(self.member_bytes.len());
+		for (id, stats) in network_server.members_stats() {
+			let (received_bytes, sent_bytes) = self.member_bytes.get(id).copied().unwrap_or_default();
+			if let Some(room) = rooms_registry.get(&id.room_id) {
+				let template = template_label(&room.template_name);
+				let labels = [template.as_str()];
+				self.received_bytes.with_label_values(&labels).inc_by(stats.received_bytes.saturating_sub(received_bytes));
+				self.sent_bytes.with_label_values(&labels).inc_by(stats.sent_bytes.saturating_sub(sent_bytes));
+			}
+			member_bytes.insert(*id, (stats.received_bytes, stats.sent_bytes));
+		}
+		self.member_bytes = member_bytes;
+	}
+}
+
+impl MemberMetrics {
+	///
+	/// Семейства регистрируются все или ни одного, чтобы повторное включение после ошибки не упиралось в уже зарегистрированные
+	///
+	fn new(registry: &Registry, max_members: usize) -> Result<Self, prometheus::Error> {
+		let metrics = Self {
+			max_members,
+			rtt: GaugeVec::new(Opts::new("member_rtt", "Member rtt in seconds"), &MEMBER_LABELS).unwrap(),
+			packet_loss: GaugeVec::new(Opts::new("member_packet_loss", "Member income packet loss"), &MEMBER_LABELS).unwrap(),
+			retransmit_count: IntGaugeVec::new(Opts::new("member_retransmit_count", "Member retransmit count"), &MEMBER_LABELS).unwrap(),
+			received_bytes: IntGaugeVec::new(Opts::new("member_received_bytes", "Member received bytes"), &MEMBER_LABELS).unwrap(),
+			sent_bytes: IntGaugeVec::new(Opts::new("member_sent_bytes", "Member sent bytes"), &MEMBER_LABELS).unwrap(),
+			out_command_backlog: IntGaugeVec::new(Opts::new("member_out_command_backlog", "Member out commands waiting for send"), &MEMBER_LABELS).unwrap(),
+			exported: Default::default(),
+			measured_time: None,
+		};
+		for (registered, collector) in metrics.collectors().into_iter().enumerate() {
+			if let Err(e) = registry.register(collector) {
+				for collector in metrics.collectors().into_iter().take(registered) {
+					// только что зарегистрированное семейство удаляется без ошибок
+					let _ = registry.unregister(collector);
+				}
+				return Err(e);
+			}
+		}
+		Ok(metrics)
+	}
+
+	fn collectors(&self) -> [Box<dyn Collector>; 6] {
+		[
+			Box::new(self.rtt.clone()),
+			Box::new(self.packet_loss.clone()),
+			Box::new(self.retransmit_count.clone()),
+			Box::new(self.received_bytes.clone()),
+			Box::new(self.sent_bytes.clone()),
+			Box::new(self.out_command_backlog.clone()),
+		]
+	}
+
+	fn measure(&mut self, network_server: &Network, now: Instant) {
+		if self
+			.measured_time
+			.is_some_and(|measured_time| now.saturating_duration_since(measured_time) < AGGREGATE_MEASURE_INTERVAL)
+		{
+			return;
+		}
+		self.measured_time = Some(now);
+
+		let mut members: Vec<_> = network_server.members_stats().collect();
+		members.sort_unstable_by_key(|(id, _)| (id.room_id, id.member_id));
+		members.truncate(self.max_members);
+
+		let current: HashSet<MemberAndRoomId> = members.iter().map(|(id, _)| **id).collect();
+		for id in self.exported.difference(&current) {
+			self.remove(id);
+		}
+		for (id, stats) in members {
+			self.set(id, &stats);
+		}
+		self.exported = current;
+	}
+
+	#[allow(clippy::cast_possible_wrap)]
+	fn set(&self, id: &MemberAndRoomId, stats: &MemberNetworkStats) {
+		let room = id.room_id.to_string();
+		let member = id.member_id.to_string();
+		let labels = [room.as_str(), member.as_str()];
+		self.rtt.with_label_values(&labels).set(stats.rtt.unwrap_or_default().as_secs_f64());
+		self.packet_loss.with_label_values(&labels).set(stats.packet_loss);
+		self.retransmit_count.with_label_values(&labels).set(stats.retransmit_count as i64);
+		self.received_bytes.with_label_values(&labels).set(stats.received_bytes as i64);
+		self.sent_bytes.with_label_values(&labels).set(stats.sent_bytes as i64);
+		self.out_command_backlog.with_label_values(&labels).set(stats.out_command_backlog as i64);
+	}
+
+	fn remove(&self, id: &MemberAndRoomId) {
+		let room = id.room_id.to_string();
+		let member = id.member_id.to_string();
+		let labels = [room.as_str(), member.as_str()];
+		// серия могла быть не создана, ошибка удаления не важна
+		let _ = self.rtt.remove_label_values(&labels);
+		let _ = self.packet_loss.remove_label_values(&labels);
+		let _ = self.retransmit_count.remove_label_values(&labels);
+		let _ = self.received_bytes.remove_label_values(&labels);
+		let _ = self.sent_bytes.remove_label_values(&labels);
+		let _ = self.out_command_backlog.remove_label_values(&labels);
+	}
+}
+
+fn inc_counter(counter: &IntCounter, previous: usize, current: usize) {
+	counter.inc_by(current.saturating_sub(previous) as u64);
+}
+
+///
+/// Семейства с метками не поддерживаются [`create_and_register_measurer`], ошибка регистрации (например повторной) только логируется
+///
+fn register<T: Collector + Clone + 'static>(registry: &Registry, collector: T) -> T {
+	if let Err(e) = registry.register(Box::new(collector.clone())) {
+		tracing::error!("Error register prometheus measurer {:?}", e);
+	}
+	collector
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::ops::Add;
+	use std::sync::Arc;
+	use std::time::Duration;
+
+	use cheetah_common::commands::c2s::C2SCommand;
+	use cheetah_common::commands::guarantees::ReliabilityGuaranteesChannel;
+	use cheetah_common::commands::{BothDirectionCommand, CommandWithReliabilityGuarantees};
+	use cheetah_common::room::object::GameObjectId;
+	use cheetah_common::room::owner::GameObjectOwner;
+	use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
+	use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReason;
+	use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+	use prometheus::{GaugeVec, Opts, Registry};
+	use tempfile::TempDir;
+
+	use crate::server::measurer::{Measurer, AGGREGATE_MEASURE_INTERVAL};
+	use crate::server::network::{GameSocket, Instant, Network};
+	use crate::server::room::config::member::MemberCreateParams;
+	use crate::server::room::config::room::RoomCreateParams;
+	use crate::server::room::config::templates::RoomTemplates;
+	use crate::server::room_registry::Rooms;
+	use cheetah_common::network::bind_to_free_socket;
+
+	#[test]
+	fn should_measure_commands_by_type() {
+		let measurer = Measurer::new(&Registry::new());
+		let mut rooms = Rooms::default();
+		rooms.set_command_measurer(measurer.command_measurer());
+		let room_id = rooms.create_room(RoomCreateParams::default());
+		let member_id = rooms.register_member(room_id, MemberCreateParams::default()).unwrap();
+
+		let not_owned_object_id = GameObjectId::new(1, GameObjectOwner::Member(member_id + 1));
+		rooms.execute_commands(
+			MemberAndRoomId { member_id, room_id },
+			&[create_command(C2SCommand::AttachToRoom), create_command(C2SCommand::Delete(not_owned_object_id))],
+		);
+
+		let commands = &measurer.command_measurer;
+		assert_eq!(commands.executed.with_label_values(&["AttachToRoom"]).get(), 1);
+		assert_eq!(commands.failed.with_label_values(&["AttachToRoom"]).get(), 0);
+		assert_eq!(commands.executed.with_label_values(&["DeleteObject"]).get(), 1);
+		assert_eq!(commands.failed.with_label_values(&["DeleteObject"]).get(), 1);
+		assert_eq!(commands.errors.with_label_values(&["MemberNotOwnerGameObject"]).get(), 1);
+		assert_eq!(commands.execution_time.with_label_values(&["AttachToRoom"]).get_sample_count(), 1);
+	}
+
+	#[test]
+	fn should_measure_rooms_by_template() {
+		let registry = Registry::new();
+		let mut measurer = Measurer::new(&registry);
+		let templates_dir = TempDir::new().unwrap();
+		for name in ["arena.ron", "lobby.ron"] {
+			fs::write(templates_dir.path().join(name), "()").unwrap();
+		}
+		measurer.set_room_templates(Arc::new(RoomTemplates::load(templates_dir.path()).unwrap()));
+		let network = create_network();
+		let mut rooms = Rooms::default();
+		let arena_room_id = rooms.create_room(RoomCreateParams {
+			name: "arena".to_owned(),
+			..Default::default()
+		});
+		rooms.create_room(RoomCreateParams {
+			name: "arena".to_owned(),
+			..Default::default()
+		});
+		let lobby_room_id = rooms.create_room(RoomCreateParams {
+			name: "lobby".to_owned(),
+			..Default::default()
+		});
+		rooms.create_room(RoomCreateParams {
+			name: "not-template".to_owned(),
+			..Default::default()
+		});
+		rooms.register_member(arena_room_id, MemberCreateParams::default()).unwrap();
+
+		let now = Instant::now();
+		measurer.measure_cycle(&network, &rooms, &now);
+		let templates = &measurer.template_metrics;
+		assert_eq!(templates.rooms.with_label_values(&["arena"]).get(), 2);
+		assert_eq!(templates.rooms.with_label_values(&["lobby"]).get(), 1);
+		assert_eq!(templates.rooms.with_label_values(&["other"]).get(), 1);
+		assert_eq!(templates.members.with_label_values(&["arena"]).get(), 1);
+
+		rooms.force_remove_room(&lobby_room_id).unwrap();
+		measurer.measure_cycle(&network, &rooms, &now);
+		assert_eq!(count_series(&registry, "template_room_count"), 3);
+
+		measurer.measure_cycle(&network, &rooms, &now.add(AGGREGATE_MEASURE_INTERVAL));
+		assert_eq!(count_series(&registry, "template_room_count"), 2);
+	}
+
+	#[test]
+	fn should_export_network_counts_as_counters() {
+		let mut measurer = Measurer::new(&Registry::new());
+		let mut network = create_network();
+		let rooms = Rooms::default();
+
+		network.income_frame_count = 5;
+		measurer.measure_cycle(&network, &rooms, &Instant::now());
+		network.income_frame_count = 7;
+		measurer.measure_cycle(&network, &rooms, &Instant::now());
+		assert_eq!(measurer.income_frame_count.get(), 7);
+	}
+
+	#[test]
+	fn should_export_limited_member_metrics() {
+		let registry = Registry::new();
+		let mut measurer = Measurer::new(&registry);
+		let mut network = create_network();
+		for member_id in 1..=3 {
+			network.register_member(Instant::now(), 1, member_id, MemberCreateParams::default(), false);
+		}
+		let rooms = Rooms::default();
+		let now = Instant::now();
+
+		measurer.measure_cycle(&network, &rooms, &now);
+		assert_eq!(count_series(&registry, "member_sent_bytes"), 0);
+
+		measurer.enable_member_metrics(2).unwrap();
+		measurer.measure_cycle(&network, &rooms, &now);
+		assert_eq!(count_series(&registry, "member_sent_bytes"), 2);
+
+		network.disconnect_members(
+			[MemberAndRoomId { member_id: 1, room_id: 1 }, MemberAndRoomId { member_id: 2, room_id: 1 }].into_iter(),
+			DisconnectByCommandReason::MemberDeleted,
+			now,
+		);
+		measurer.measure_cycle(&network, &rooms, &now);
+		assert_eq!(count_series(&registry, "member_sent_bytes"), 2);
+
+		measurer.measure_cycle(&network, &rooms, &now.add(AGGREGATE_MEASURE_INTERVAL));
+		assert_eq!(count_series(&registry, "member_sent_bytes"), 1);
+	}
+
+	#[test]
+	fn should_fail_enable_member_metrics_on_registered_family() {
+		let registry = Registry::new();
+		let other_measurer = GaugeVec::new(Opts::new("member_sent_bytes", "Member sent bytes"), &["room", "member"]).unwrap();
+		registry.register(Box::new(other_measurer.clone())).unwrap();
+		let mut measurer = Measurer::new(&registry);
+		assert!(measurer.enable_member_metrics(2).is_err());
+
+		registry.unregister(Box::new(other_measurer)).unwrap();
+		assert!(measurer.enable_member_metrics(2).is_ok());
+	}
+
+	fn create_network() -> Network {
+		Network::new(
+			GameSocket::Udp(bind_to_free_socket().unwrap()),
+			ProtocolConfiguration {
+				disconnect_timeout: Duration::from_secs(10),
+			},
+		)
+		.unwrap()
+	}
+
+	fn create_command(command: C2SCommand) -> CommandWithReliabilityGuarantees {
+		CommandWithReliabilityGuarantees {
+			reliability_guarantees: ReliabilityGuaranteesChannel::ReliableUnordered,
+			command: BothDirectionCommand::C2S(command),
+		}
+	}
+
+	fn count_series(registry: &Registry, name: &str) -> usize {
+		registry.gather().iter().filter(|family| family.get_name() == name).map(|family| family.get_metric().len()).sum()
+	}
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use cheetah_common::network::token::{MemberToken, MemberTokenError};
+use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+use fnv::FnvBuildHasher;
+use thiserror::Error;
+
+use crate::server::room::config::member::MemberCreateParams;
+
+///
+/// Проверка токенов пользователей, подключающихся без предварительного создания через `CreateMember`
+///
+/// - токен можно использовать только один раз, использованные токены хранятся до окончания срока их действия
+///
+#[derive(Debug)]
+pub struct MemberTokenAuthenticator {
+	key: Vec<u8>,
+	used_tokens: HashMap<[u8; 32], UsedMemberToken, FnvBuildHasher>,
+}
+
+#[derive(Debug)]
+struct UsedMemberToken {
+	expires_at: u64,
+	id: MemberAndRoomId,
+}
+
+#[derive(Debug)]
+pub struct AuthenticatedMember {
+	pub id: MemberAndRoomId,
+	pub template: MemberCreateParams,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MemberTokenAuthenticationError {
+	#[error("Token {0}")]
+	Token(#[from] MemberTokenError),
+	#[error("Token already used by {0:?}")]
+	Replayed(MemberAndRoomId),
+}
+
+impl MemberTokenAuthenticator {
+	pub fn new(key: Vec<u8>) -> Self {
+		Self { key, used_tokens: Default::default() }
+	}
+
+	pub fn authenticate(&mut self, data: &[u8], now_in_sec: u64) -> Result<AuthenticatedMember, MemberTokenAuthenticationError> {
+		let token = MemberToken::decode(data)?;
+		if let Some(used) = self.used_tokens.get(token.signature()) {
+			return Err(MemberTokenAuthenticationError::Replayed(used.id));
+		}
+		token.verify(&self.key, now_in_sec)?;
+
+		let id = MemberAndRoomId {
+			member_id: token.claims.member_id(),
+			room_id: token.claims.room_id,
+		};
+		self.used_tokens.insert(
+			*token.signature(),
+			UsedMemberToken {
+				expires_at: token.claims.expires_at,
+				id,
+			},
+		);
+		Ok(AuthenticatedMember {
+			id,
+			template: MemberCreateParams {
+				super_member: false,
+				private_key: token.session_key(&self.key),
+				groups: token.claims.groups,
+				objects: Default::default(),
+			},
+		})
+	}
+
+	///
+	/// Удалить использованные токены с истекшим сроком действия, повторно их использовать уже нельзя
+	///
+	pub fn remove_expired(&mut self, now_in_sec: u64) {
+		self.used_tokens.retain(|_, used| used.expires_at > now_in_sec);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use cheetah_common::network::token::{MemberToken, MemberTokenClaims, MemberTokenError};
+	use cheetah_common::room::access::AccessGroups;
+	use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
+
+	use crate::server::member_token::{MemberTokenAuthenticationError, MemberTokenAuthenticator};
+
+	const KEY: &[u8] = b"secret";
+
+	#[test]
+	fn should_authenticate() {
+		let mut authenticator = MemberTokenAuthenticator::new(KEY.to_vec());
+		let token = MemberToken::sign(KEY, claims());
+		let member = authenticator.authenticate(&token.encode(), 10).unwrap();
+		assert_eq!(
+			member.id,
+			MemberAndRoomId {
+				member_id: token.claims.member_id(),
+				room_id: 1
+			}
+		);
+		assert_eq!(member.template.groups, AccessGroups(0b11));
+		assert_eq!(member.template.private_key.0, token.session_key(KEY).0);
+	}
+
+	#[test]
+	fn should_reject_replayed_token() {
+		let mut authenticator = MemberTokenAuthenticator::new(KEY.to_vec());
+		let token = MemberToken::sign(KEY, claims()).encode();
+		let member = authenticator.authenticate(&token, 10).unwrap();
+		assert_eq!(authenticator.authenticate(&token, 10).unwrap_err(), MemberTokenAuthenticationError::Replayed(member.id));
+	}
+
+	#[test]
+	fn should_reject_expired_token() {
+		let mut authenticator = MemberTokenAuthenticator::new(KEY.to_vec());
+		let token = MemberToken::sign(KEY, claims()).encode();
+		assert_eq!(
+			authenticator.authenticate(&token, 100).unwrap_err(),
+			MemberTokenAuthenticationError::Token(MemberTokenError::Expired(100))
+		);
+	}
+
+	#[test]
+	fn should_reject_token_with_wrong_key() {
+		let mut authenticator = MemberTokenAuthenticator::new(KEY.to_vec());
+		let token = MemberToken::sign(b"other", claims()).encode();
+		assert_eq!(
+			authenticator.authenticate(&token, 10).unwrap_err(),
+			MemberTokenAuthenticationError::Token(MemberTokenError::WrongSignature)
+		);
+	}
+
+	#[test]
+	fn should_remove_expired_used_tokens() {
+		let mut authenticator = MemberTokenAuthenticator::new(KEY.to_vec());
+		let token = MemberToken::sign(KEY, claims()).encode();
+		authenticator.authenticate(&token, 10).unwrap();
+		authenticator.remove_expired(50);
+		assert_eq!(authenticator.used_tokens.len(), 1);
+		authenticator.remove_expired(100);
+		assert!(authenticator.used_tokens.is_empty());
+	}
+
+	fn claims() -> MemberTokenClaims {
+		MemberTokenClaims {
+			room_id: 1,
+			user_id: 7,
+			groups: AccessGroups(0b11),
+			expires_at: 100,
+			nonce: 1,
+		}
+	}
+}
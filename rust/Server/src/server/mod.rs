@@ -1,6 +1,6 @@
-use crate::server::manager::{ManagementTask, ManagementTaskChannel, ManagementTaskExecutionError, ManagementTaskResult, RoomMembers};
+use crate::server::manager::{ItemsPage, ManagementTask, ManagementTaskChannel, ManagementTaskExecutionError, ManagementTaskResult, RoomMembers};
 use crate::server::measurer::Measurer;
-use crate::server::network::Network;
+use crate::server::network::{GameSocket, Network};
 use crate::server::room::command::ServerCommandError;
 use crate::server::room::config::member::MemberCreateParams;
 use crate::server::room_registry::{RoomNotFoundError, Rooms};
@@ -9,63 +9,111 @@ use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReas
 use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
 use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
 use std::cell::RefCell;
-use std::net::UdpSocket;
+use std::net::SocketAddr;
 use std::ops::Add;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{channel, Receiver};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::{io, iter, thread};
 
 pub mod debug;
+pub mod journal;
 pub mod manager;
 pub mod measurer;
+pub mod member_token;
 pub mod network;
 pub mod room;
 pub mod room_registry;
+pub mod security;
+pub mod trace;
+pub mod websocket;
 
 ///
 /// Собственно сетевой сервер, запускается в отдельном потоке, обрабатывает сетевые команды,
 /// поддерживает одновременно несколько комнат
 ///
+/// Для детерминированных тестов сервер создается без потока ([`Server::new_caller_driven`]),
+/// циклы и задачи управления выполняются вызывающим кодом с заданным временем
+///
 pub struct Server {
 	network: Network,
 	rooms: Rooms,
 	management_task_receiver: Receiver<ManagementTaskChannel>,
 	halt_signal: Arc<AtomicBool>,
+	drain_signal: Arc<AtomicBool>,
+	///
+	/// Время, до которого ожидается завершение комнат при остановке сервера
+	///
+	drain_deadline: Option<Instant>,
 	time_offset: Option<Duration>,
 	measurer: RefCell<Measurer>,
 }
 
 impl Server {
 	pub(crate) fn new(
-		socket: UdpSocket,
+		socket: GameSocket,
 		management_task_receiver: Receiver<ManagementTaskChannel>,
 		halt_signal: Arc<AtomicBool>,
+		drain_signal: Arc<AtomicBool>,
 		protocol_configuration: ProtocolConfiguration,
 	) -> Result<Self, io::Error> {
-		let measurer = Measurer::new(prometheus::default_registry()).into();
+		let measurer = Measurer::new(prometheus::default_registry());
+		let mut rooms = Rooms::new();
+		rooms.set_command_measurer(measurer.command_measurer());
 		Ok(Self {
 			network: Network::new(socket, protocol_configuration)?,
-			rooms: Rooms::new(),
+			rooms,
 			management_task_receiver,
 			halt_signal,
+			drain_signal,
+			drain_deadline: None,
 			time_offset: None,
-			measurer,
+			measurer: measurer.into(),
 		})
 	}
 
+	///
+	/// Сервер без собственного потока, время задается вызывающим кодом через [`Self::cycle`]
+	///
+	pub fn new_caller_driven(socket: GameSocket, protocol_configuration: ProtocolConfiguration) -> Result<Self, io::Error> {
+		let (_, receiver) = channel();
+		Self::new(socket, receiver, Default::default(), Default::default(), protocol_configuration)
+	}
+
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.network.local_addr()
+	}
+
 	pub fn run(mut self) {
 		while !self.halt_signal.load(Ordering::Relaxed) {
 			let now = self.get_start_cycle_time();
-			self.network.cycle(&mut self.rooms, now);
-			self.execute_management_tasks(now);
-			self.measurer.borrow_mut().measure_cycle(&self.network, &self.rooms, &now);
+			self.cycle(now);
 			Self::assert_execution_time(now);
 			Self::sleep();
 		}
 	}
 
+	///
+	/// Цикл сервера: прием и отправка пакетов, выполнение задач управления
+	///
+	pub fn cycle(&mut self, now: Instant) {
+		self.network.cycle(&mut self.rooms, now);
+		self.execute_management_tasks(now);
+		self.drain(now);
+		self.rooms.flush_journals();
+		self.rooms.tracer.remove_expired(now);
+		self.measurer.borrow_mut().measure_cycle(&self.network, &self.rooms, &now);
+	}
+
+	///
+	/// Сервер остановлен - по завершению drain
+	///
+	#[must_use]
+	pub fn is_halted(&self) -> bool {
+		self.halt_signal.load(Ordering::Relaxed)
+	}
+
 	fn get_start_cycle_time(&self) -> Instant {
 		let mut now = Instant::now();
 		if let Some(time_offset) = self.time_offset {
@@ -83,12 +131,18 @@ impl Server {
 		}
 	}
 
-	fn execute_task(&mut self, task: ManagementTask, now: Instant) -> Result<ManagementTaskResult, ManagementTaskExecutionError> {
+	pub fn execute_task(&mut self, task: ManagementTask, now: Instant) -> Result<ManagementTaskResult, ManagementTaskExecutionError> {
 		let res = match task {
-			ManagementTask::CreateRoom(template) => ManagementTaskResult::CreateRoom(self.rooms.create_room(template)),
-			ManagementTask::DeleteRoom(room_id) => self.delete_room(room_id).map(|_| ManagementTaskResult::DeleteRoom)?,
-			ManagementTask::CreateMember(room_id, member_template) => self.register_member(room_id, member_template, now).map(ManagementTaskResult::CreateMember)?,
-			ManagementTask::DeleteMember(id) => self.delete_member(id).map(|_| ManagementTaskResult::DeleteMember)?,
+			ManagementTask::CreateRoom(template) => {
+				self.check_not_draining()?;
+				ManagementTaskResult::CreateRoom(self.rooms.create_room(template))
+			}
+			ManagementTask::DeleteRoom(room_id) => self.delete_room(room_id, now).map(|_| ManagementTaskResult::DeleteRoom)?,
+			ManagementTask::CreateMember(room_id, member_template) => {
+				self.check_not_draining()?;
+				self.register_member(room_id, member_template, now).map(ManagementTaskResult::CreateMember)?
+			}
+			ManagementTask::DeleteMember(id) => self.delete_member(id, now).map(|_| ManagementTaskResult::DeleteMember)?,
 			ManagementTask::Dump(room_id) => ManagementTaskResult::Dump(self.rooms.get(&room_id).cloned()),
 			ManagementTask::GetRooms => ManagementTaskResult::GetRooms(self.rooms.rooms().map(|r| r.0).copied().collect()),
 			ManagementTask::GetRoomsMembers => ManagementTaskResult::GetRoomsMemberCount(
@@ -97,32 +151,172 @@ impl Server {
 					.map(|(room_id, room)| RoomMembers {
 						room_id: *room_id,
 						members: room.members.iter().map(|i| i.1.clone()).collect(),
+						stats: room
+							.members
+							.keys()
+							.filter_map(|member_id| {
+								let id = MemberAndRoomId {
+									member_id: *member_id,
+									room_id: *room_id,
+								};
+								self.network.member_stats(&id).map(|stats| (*member_id, stats))
+							})
+							.collect(),
 					})
 					.collect(),
 			),
+			ManagementTask::GetRoomMembers(room_id, range) => {
+				let room = self.rooms.get(&room_id).ok_or(RoomNotFoundError(room_id))?;
+				let mut member_ids: Vec<RoomMemberId> = room.members.keys().copied().collect();
+				member_ids.sort_unstable();
+				let items = member_ids
+					.iter()
+					.skip(range.offset)
+					.take(range.limit)
+					.map(|member_id| (room.members[member_id].clone(), self.network.member_stats(&MemberAndRoomId { member_id: *member_id, room_id })))
+					.collect();
+				ManagementTaskResult::GetRoomMembers(ItemsPage { total: member_ids.len(), items })
+			}
+			ManagementTask::GetRoomMembersStatus(room_id) => {
+				ManagementTaskResult::GetRoomMembersStatus(self.rooms.get(&room_id).map(|room| room.members.values().map(|member| (member.id, member.status.clone())).collect()))
+			}
+			ManagementTask::GetRoomObjects(room_id, template, range) => {
+				let room = self.rooms.get(&room_id).ok_or(RoomNotFoundError(room_id))?;
+				let objects = || room.objects.values().filter(|object| template.is_none_or(|template| object.template_id == template));
+				ManagementTaskResult::GetRoomObjects(ItemsPage {
+					total: objects().count(),
+					items: objects().skip(range.offset).take(range.limit).cloned().collect(),
+				})
+			}
+			ManagementTask::GetRoomObject(room_id, object_id) => {
+				let room = self.rooms.get(&room_id).ok_or(RoomNotFoundError(room_id))?;
+				ManagementTaskResult::GetRoomObject(room.objects.get(&object_id).cloned())
+			}
 			ManagementTask::GetCreatedRoomsCount => ManagementTaskResult::GetCreatedRoomsCount(self.rooms.created_rooms_count),
+			ManagementTask::SetMemberTokenKey(key) => {
+				self.network.set_member_token_key(key);
+				ManagementTaskResult::SetMemberTokenKey
+			}
+			ManagementTask::SetRoomJournalDir(dir) => {
+				self.rooms.set_journal_dir(Some(dir));
+				ManagementTaskResult::SetRoomJournalDir
+			}
+			ManagementTask::SetRoomTemplates(room_templates) => {
+				self.measurer.borrow_mut().set_room_templates(room_templates);
+				ManagementTaskResult::SetRoomTemplates
+			}
+			ManagementTask::EnableWebSocket(listener) => {
+				self.network.set_websocket_listener(listener);
+				ManagementTaskResult::EnableWebSocket
+			}
+			ManagementTask::Drain(timeout) => {
+				self.start_drain(now, timeout);
+				ManagementTaskResult::Drain
+			}
+			ManagementTask::SetMemberNetworkEmulation(id, emulation) => {
+				if self.rooms.get(&id.room_id).is_none() {
+					return Err(ManagementTaskExecutionError::RoomNotFound(RoomNotFoundError(id.room_id)));
+				}
+				if !self.network.set_member_emulation(&id, emulation.as_ref()) {
+					return Err(ManagementTaskExecutionError::ServerCommandError(ServerCommandError::MemberNotFound(id.member_id)));
+				}
+				ManagementTaskResult::SetMemberNetworkEmulation
+			}
+			ManagementTask::GetMemberStats(id) => {
+				if self.rooms.get(&id.room_id).is_none() {
+					return Err(ManagementTaskExecutionError::RoomNotFound(RoomNotFoundError(id.room_id)));
+				}
+				match self.network.member_stats(&id) {
+					None => return Err(ManagementTaskExecutionError::ServerCommandError(ServerCommandError::MemberNotFound(id.member_id))),
+					Some(stats) => ManagementTaskResult::GetMemberStats(stats),
+				}
+			}
+			ManagementTask::EnableMemberMetrics(max_members) => {
+				self.measurer
+					.borrow_mut()
+					.enable_member_metrics(max_members)
+					.map_err(|e| ManagementTaskExecutionError::MetricsRegistration(e.to_string()))?;
+				ManagementTaskResult::EnableMemberMetrics
+			}
+			ManagementTask::EnableCommandTrace(scope, duration) => {
+				if self.rooms.get(&scope.room_id).is_none() {
+					return Err(ManagementTaskExecutionError::RoomNotFound(RoomNotFoundError(scope.room_id)));
+				}
+				self.rooms.tracer.enable(scope, now, duration);
+				ManagementTaskResult::EnableCommandTrace
+			}
+			ManagementTask::DisableCommandTrace(scope) => {
+				self.rooms.tracer.disable(&scope);
+				ManagementTaskResult::DisableCommandTrace
+			}
+			ManagementTask::GetCommandTrace(room_id) => ManagementTaskResult::GetCommandTrace(self.rooms.tracer.get_commands(room_id)),
+			ManagementTask::SubscribeRoomTraffic(room_id, sender) => {
+				self.rooms.subscribe_traffic(room_id, sender)?;
+				ManagementTaskResult::SubscribeRoomTraffic
+			}
 		};
 		Ok(res)
 	}
 
+	fn check_not_draining(&self) -> Result<(), ManagementTaskExecutionError> {
+		if self.drain_deadline.is_some() {
+			Err(ManagementTaskExecutionError::ServerDraining)
+		} else {
+			Ok(())
+		}
+	}
+
+	///
+	/// Перейти в режим остановки - новые комнаты и пользователи не принимаются,
+	/// повторный вызов может только уменьшить время ожидания
+	///
+	fn start_drain(&mut self, now: Instant, timeout: Duration) {
+		let deadline = now.add(timeout);
+		let deadline = self.drain_deadline.map_or(deadline, |current| current.min(deadline));
+		tracing::info!("Drain server, rooms {:?}, timeout {:?}", self.rooms.rooms().len(), deadline.saturating_duration_since(now));
+		self.drain_deadline = Some(deadline);
+		self.network.set_draining();
+		self.drain_signal.store(true, Ordering::Relaxed);
+	}
+
+	///
+	/// Остановить сервер после завершения всех комнат или по истечении времени ожидания,
+	/// пользователи оставшихся комнат отключаются с причиной `ServerShutdown`
+	///
+	fn drain(&mut self, now: Instant) {
+		if let Some(deadline) = self.drain_deadline {
+			if self.rooms.rooms().len() == 0 || now >= deadline {
+				let room_ids: Vec<RoomId> = self.rooms.rooms().map(|(room_id, _)| *room_id).collect();
+				tracing::info!("Shutdown server, disconnect members from rooms {:?}", room_ids);
+				for room_id in room_ids {
+					if let Ok(room) = self.rooms.force_remove_room(&room_id) {
+						self.network.shutdown_members(room.members.into_keys().map(|member_id| MemberAndRoomId { member_id, room_id }), now);
+					}
+				}
+				self.halt_signal.store(true, Ordering::Relaxed);
+			}
+		}
+	}
+
 	fn register_member(&mut self, room_id: RoomId, member_template: MemberCreateParams, now: Instant) -> Result<RoomMemberId, RoomNotFoundError> {
 		let room_member_id = self.rooms.register_member(room_id, member_template.clone())?;
-		self.network.register_member(now, room_id, room_member_id, member_template);
+		let compression = self.rooms.get(&room_id).map(|room| room.compression).unwrap_or_default();
+		self.network.register_member(now, room_id, room_member_id, member_template, compression);
 		Ok(room_member_id)
 	}
 
 	/// удалить комнату с сервера и закрыть соединение со всеми пользователями
-	fn delete_room(&mut self, room_id: RoomId) -> Result<(), RoomNotFoundError> {
+	fn delete_room(&mut self, room_id: RoomId, now: Instant) -> Result<(), RoomNotFoundError> {
 		let room = self.rooms.force_remove_room(&room_id)?;
 		tracing::info!("Delete room {:?}, counts rooms after {:?}", room_id, self.rooms.rooms().len());
 		let ids = room.members.into_keys().map(|member_id| MemberAndRoomId { member_id, room_id });
-		self.network.disconnect_members(ids, DisconnectByCommandReason::RoomDeleted);
+		self.network.disconnect_members(ids, DisconnectByCommandReason::RoomDeleted, now);
 		Ok(())
 	}
 
 	/// закрыть соединение с пользователем и удалить его из комнаты
-	fn delete_member(&mut self, id: MemberAndRoomId) -> Result<(), ServerCommandError> {
-		self.network.disconnect_members(iter::once(id), DisconnectByCommandReason::MemberDeleted);
+	fn delete_member(&mut self, id: MemberAndRoomId, now: Instant) -> Result<(), ServerCommandError> {
+		self.network.disconnect_members(iter::once(id), DisconnectByCommandReason::MemberDeleted, now);
 		self.rooms.member_disconnected(&id)
 	}
 
@@ -1,28 +1,51 @@
 use std::collections::{HashMap, VecDeque};
 use std::io::{Error, ErrorKind};
-use std::net::{SocketAddr, UdpSocket};
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
 pub use std::time::Instant;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{io, iter};
 
 use cheetah_game_realtime_protocol::codec::cipher::Cipher;
 use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
 use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReason;
 use cheetah_game_realtime_protocol::frame::headers::{Header, Headers};
 use cheetah_game_realtime_protocol::frame::member_private_key::MemberPrivateKey;
-use cheetah_game_realtime_protocol::frame::{Frame, FrameId};
+use cheetah_game_realtime_protocol::frame::{ConnectionId, Frame, FrameId};
 use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
 use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
 
+use crate::server::member_token::{AuthenticatedMember, MemberTokenAuthenticationError, MemberTokenAuthenticator};
+use crate::server::room::command::ServerCommandError;
 use crate::server::room::config::member::MemberCreateParams;
 use crate::server::room_registry::Rooms;
+use crate::server::websocket::{WebSocketConnectionId, WebSocketListener};
 use cheetah_common::network::collectors::in_collector::InCommandsCollector;
+use cheetah_common::network::collectors::out_collector::OutCommandsCollector;
+use cheetah_common::network::emulator::{check_probability, NetworkConditionsPreset, NetworkLatencyEmulator, UnknownNetworkConditionsPreset, WrongEmulationProbability};
+use cheetah_common::network::memory::MemorySocket;
+use cheetah_common::network::quality::ConnectionQualityStatistics;
+use cheetah_common::network::token::MEMBER_TOKEN_PACKET_PREFIX;
 use cheetah_common::network::CheetahProtocol;
+use thiserror::Error;
+
+///
+/// Период удаления использованных токенов с истекшим сроком действия
+///
+const USED_MEMBER_TOKENS_CLEANUP_PERIOD: Duration = Duration::from_secs(10);
 
 pub struct Network {
 	sessions: HashMap<MemberAndRoomId, MemberSession>,
-	socket: UdpSocket,
+	socket: GameSocket,
+	websocket: Option<WebSocketListener>,
 	start_application_time: Instant,
 	frames: VecDeque<Frame>,
 	protocol_configuration: ProtocolConfiguration,
+	member_token_authenticator: Option<MemberTokenAuthenticator>,
+	last_used_member_tokens_cleanup: Instant,
+	///
+	/// Сервер останавливается - новые пользователи не принимаются
+	///
+	draining: bool,
 	pub income_command_count: usize,
 	pub outcome_command_count: usize,
 	pub income_frame_count: usize,
@@ -31,18 +54,217 @@ pub struct Network {
 
 #[derive(Debug)]
 struct MemberSession {
-	peer_address: Option<SocketAddr>,
+	peer: Option<Peer>,
 	private_key: MemberPrivateKey,
 	last_receive_frame_id: FrameId,
 	pub(crate) protocol: CheetahProtocol,
+	///
+	/// Эмуляция характеристик сети пользователя, задается администратором для воспроизведения плохой сети
+	///
+	emulator: Option<NetworkLatencyEmulator>,
+	///
+	/// Адрес последнего пакета, поставленного в очередь эмулятора
+	///
+	emulated_peer: Option<Peer>,
+	quality_statistics: ConnectionQualityStatistics,
+	received_bytes: u64,
+	sent_bytes: u64,
+}
+
+///
+/// Сетевая статистика пользователя
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemberNetworkStats {
+	///
+	/// Скользящий средний rtt, None - замеров еще недостаточно
+	///
+	pub rtt: Option<Duration>,
+	///
+	/// Доля потерянных входящих пакетов, от 0 до 1
+	///
+	pub packet_loss: f64,
+	pub retransmit_count: u64,
+	pub received_bytes: u64,
+	pub sent_bytes: u64,
+	///
+	/// Команды, ожидающие отправки
+	///
+	pub out_command_backlog: usize,
+	///
+	/// Отправленные надежные фреймы без подтверждения
+	///
+	pub unacked_reliable_frames: usize,
+}
+
+///
+/// Параметры эмуляции сети пользователя на стороне сервера, применяются к пакетам в обе стороны
+///
+/// - сначала применяется preset, затем заданные (ненулевые) задержка и потери
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemberNetworkEmulation {
+	pub preset: Option<NetworkConditionsPreset>,
+	pub rtt: Duration,
+	///
+	/// Процент случайности в RTT - 0..1
+	///
+	pub rtt_dispersion: f64,
+	pub drop_probability: f64,
+	pub drop_time: Duration,
+}
+
+///
+/// Максимальная задержка эмуляции сети пользователя
+///
+pub const MAX_EMULATION_RTT: Duration = Duration::from_secs(10);
+///
+/// Максимальная длительность потери пакетов при эмуляции сети пользователя
+///
+pub const MAX_EMULATION_DROP_TIME: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum MemberNetworkEmulationError {
+	#[error(transparent)]
+	UnknownPreset(#[from] UnknownNetworkConditionsPreset),
+	#[error("Wrong rtt dispersion: {0}")]
+	RttDispersion(WrongEmulationProbability),
+	#[error("Wrong drop probability: {0}")]
+	DropProbability(WrongEmulationProbability),
+	#[error("Rtt {0} ms exceeds max {max} ms", max = MAX_EMULATION_RTT.as_millis())]
+	Rtt(u64),
+	#[error("Drop time {0} ms exceeds max {max} ms", max = MAX_EMULATION_DROP_TIME.as_millis())]
+	DropTime(u64),
+}
+
+///
+/// Игровой сокет сервера, сеть в памяти используется для детерминированных тестов
+///
+#[derive(Debug)]
+pub enum GameSocket {
+	Udp(UdpSocket),
+	Memory(MemorySocket),
+}
+
+impl GameSocket {
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		match self {
+			GameSocket::Udp(socket) => socket.local_addr(),
+			GameSocket::Memory(socket) => Ok(socket.local_addr()),
+		}
+	}
+
+	fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+		match self {
+			GameSocket::Udp(socket) => socket.recv_from(buf),
+			GameSocket::Memory(socket) => socket.recv_from(buf),
+		}
+	}
+
+	fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+		match self {
+			GameSocket::Udp(socket) => socket.send_to(buf, addr),
+			GameSocket::Memory(socket) => socket.send_to(buf, addr),
+		}
+	}
+}
+
+///
+/// Адрес пользователя, комната не различает пользователей по транспорту
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Peer {
+	Udp(SocketAddr),
+	WebSocket(WebSocketConnectionId),
+}
+
+impl Peer {
+	///
+	/// Эмулятор хранит адрес для исходящих пакетов, при отправке из эмулятора используется текущий адрес пользователя
+	///
+	fn emulator_address(&self) -> SocketAddr {
+		match self {
+			Peer::Udp(address) => *address,
+			Peer::WebSocket(_) => SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0),
+		}
+	}
+}
+
+impl MemberNetworkEmulation {
+	///
+	/// Создать параметры эмуляции из внешнего запроса, вероятности проверяются на диапазон 0..1, задержки ограничены сверху
+	///
+	pub fn from_request(preset: Option<String>, rtt_in_ms: u64, rtt_dispersion: f64, drop_probability: f64, drop_time_in_ms: u64) -> Result<Self, MemberNetworkEmulationError> {
+		check_probability(rtt_dispersion).map_err(MemberNetworkEmulationError::RttDispersion)?;
+		check_probability(drop_probability).map_err(MemberNetworkEmulationError::DropProbability)?;
+		if u128::from(rtt_in_ms) > MAX_EMULATION_RTT.as_millis() {
+			return Err(MemberNetworkEmulationError::Rtt(rtt_in_ms));
+		}
+		if u128::from(drop_time_in_ms) > MAX_EMULATION_DROP_TIME.as_millis() {
+			return Err(MemberNetworkEmulationError::DropTime(drop_time_in_ms));
+		}
+		Ok(Self {
+			preset: preset.map(|name| name.parse()).transpose()?,
+			rtt: Duration::from_millis(rtt_in_ms),
+			rtt_dispersion,
+			drop_probability,
+			drop_time: Duration::from_millis(drop_time_in_ms),
+		})
+	}
+
+	#[must_use]
+	pub fn create_emulator(&self) -> NetworkLatencyEmulator {
+		let mut emulator = NetworkLatencyEmulator::default();
+		if let Some(preset) = self.preset {
+			emulator.configure_preset(preset);
+		}
+		if !self.rtt.is_zero() {
+			emulator.configure_rtt(self.rtt, self.rtt_dispersion);
+		}
+		if self.drop_probability > 0.0 {
+			emulator.configure_drop(self.drop_probability, self.drop_time);
+		}
+		emulator
+	}
+}
+
+impl MemberSession {
+	///
+	/// Учесть принятый фрейм в статистике и подтверждениях ReliableLatest, протокол сбрасывает состояние при смене идентификатора соединения клиентом
+	///
+	fn on_frame_received(&mut self, frame: &Frame, size: usize, previous_connection_id: ConnectionId) {
+		self.protocol.output_data_producer.on_input_collected(&mut self.protocol.input_data_handler);
+		if self.protocol.connection_id != previous_connection_id {
+			self.quality_statistics = Default::default();
+		}
+		if frame.connection_id == self.protocol.connection_id {
+			self.quality_statistics.on_frame_received(frame);
+		}
+		self.received_bytes += size as u64;
+	}
+
+	fn stats(&self) -> MemberNetworkStats {
+		MemberNetworkStats {
+			rtt: self.protocol.rtt.get_rtt(),
+			packet_loss: self.quality_statistics.packet_loss(),
+			retransmit_count: self.quality_statistics.retransmit_count,
+			received_bytes: self.received_bytes,
+			sent_bytes: self.sent_bytes,
+			out_command_backlog: self.protocol.output_data_producer.pending_commands_count(),
+			unacked_reliable_frames: self.quality_statistics.unacked_reliable_frames(),
+		}
+	}
 }
 
 impl Network {
-	pub fn new(socket: UdpSocket, protocol_configuration: ProtocolConfiguration) -> Result<Self, Error> {
-		socket.set_nonblocking(true)?;
+	pub fn new(socket: GameSocket, protocol_configuration: ProtocolConfiguration) -> Result<Self, Error> {
+		if let GameSocket::Udp(socket) = &socket {
+			socket.set_nonblocking(true)?;
+		}
 		Ok(Self {
 			sessions: Default::default(),
 			socket,
+			websocket: None,
 			start_application_time: Instant::now(),
 			frames: Default::default(),
 			income_command_count: 0,
@@ -50,13 +272,44 @@ impl Network {
 			income_frame_count: 0,
 			outcome_frame_count: 0,
 			protocol_configuration,
+			member_token_authenticator: None,
+			last_used_member_tokens_cleanup: Instant::now(),
+			draining: false,
 		})
 	}
 
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.socket.local_addr()
+	}
+
+	pub fn set_member_token_key(&mut self, key: Vec<u8>) {
+		self.member_token_authenticator = Some(MemberTokenAuthenticator::new(key));
+	}
+
+	pub fn set_websocket_listener(&mut self, listener: WebSocketListener) {
+		self.websocket = Some(listener);
+	}
+
+	pub fn set_draining(&mut self) {
+		self.draining = true;
+	}
+
 	pub fn cycle(&mut self, rooms: &mut Rooms, now: Instant) {
 		self.receive(rooms, now);
-		self.send(rooms);
+		self.send(rooms, now);
+		self.emulate(rooms, now);
 		self.process_disconnected_members(rooms, now);
+		self.remove_expired_member_tokens(now);
+	}
+
+	fn remove_expired_member_tokens(&mut self, now: Instant) {
+		if now.duration_since(self.last_used_member_tokens_cleanup) < USED_MEMBER_TOKENS_CLEANUP_PERIOD {
+			return;
+		}
+		self.last_used_member_tokens_cleanup = now;
+		if let Some(authenticator) = self.member_token_authenticator.as_mut() {
+			authenticator.remove_expired(Self::now_in_sec());
+		}
 	}
 
 	fn process_disconnected_members(&mut self, rooms: &mut Rooms, now: Instant) {
@@ -77,7 +330,7 @@ impl Network {
 	///
 	/// Отправить команды клиентам
 	///
-	fn send(&mut self, rooms: &mut Rooms) {
+	fn send(&mut self, rooms: &mut Rooms, now: Instant) {
 		rooms.collect_out_commands(|room_id, member_id, commands| {
 			self.outcome_command_count += commands.len();
 
@@ -90,56 +343,103 @@ impl Network {
 					tracing::error!("[network] member not found {:?}", id);
 				}
 				Some(session) => {
-					if session.peer_address.is_some() {
+					if session.peer.is_some() {
 						for command in commands {
 							session.protocol.output_data_producer.add_command(command.channel_type, command.command.clone());
 						}
-						self.send_frames(id);
+						self.send_frames(id, now);
 					}
 				}
 			}
 		});
 	}
 
-	fn send_frames(&mut self, id: MemberAndRoomId) {
+	fn send_frames(&mut self, id: MemberAndRoomId, now: Instant) {
 		let session = self.sessions.get_mut(&id);
 		if session.is_none() {
 			return;
 		}
 		let session = session.unwrap();
 
-		if let Some(peer_address) = session.peer_address {
+		if let Some(peer) = session.peer {
 			self.frames.clear();
-			session.protocol.collect_out_frames(Instant::now(), &mut self.frames);
+			session.protocol.output_data_producer.cycle(now);
+			session.protocol.collect_out_frames(now, &mut self.frames);
 			self.outcome_frame_count += self.frames.len();
 			for frame in &self.frames {
 				let mut buffer = [0; 512];
 				let buffer_size = frame.encode(&mut Cipher::new(&session.private_key), &mut buffer).unwrap();
-				match self.socket.send_to(&buffer[0..buffer_size], peer_address) {
-					Ok(size) => {
-						if size != buffer_size {
-							tracing::error!("[network] size mismatch in socket.send_to {:?} {:?}", buffer.len(), size);
-						}
+				session.sent_bytes += buffer_size as u64;
+				session.quality_statistics.on_frame_sent(frame);
+				match session.emulator.as_mut() {
+					None => Self::send_to_peer(&self.socket, &mut self.websocket, peer, &buffer[0..buffer_size]),
+					Some(emulator) => emulator.schedule_out(now, &buffer[0..buffer_size], peer.emulator_address()),
+				}
+			}
+		}
+	}
+
+	fn send_to_peer(socket: &GameSocket, websocket: &mut Option<WebSocketListener>, peer: Peer, buffer: &[u8]) {
+		match peer {
+			Peer::Udp(peer_address) => match socket.send_to(buffer, peer_address) {
+				Ok(size) => {
+					if size != buffer.len() {
+						tracing::error!("[network] size mismatch in socket.send_to {:?} {:?}", buffer.len(), size);
 					}
-					Err(e) => match e.kind() {
-						ErrorKind::WouldBlock => {}
-						_ => {
-							tracing::error!("[network] socket error {:?}", e);
-						}
-					},
+				}
+				Err(e) => match e.kind() {
+					ErrorKind::WouldBlock => {}
+					_ => {
+						tracing::error!("[network] socket error {:?}", e);
+					}
+				},
+			},
+			Peer::WebSocket(connection_id) => {
+				if let Some(websocket) = websocket.as_mut() {
+					websocket.send(connection_id, buffer);
 				}
 			}
 		}
 	}
 
+	///
+	/// Отправить и принять пакеты пользователей с эмуляцией сети, время задержки которых прошло
+	///
+	/// - исходящие пакеты отправляются на текущий адрес пользователя
+	/// - входящие пакеты обрабатываются повторно, минуя эмулятор
+	///
+	fn emulate(&mut self, rooms: &mut Rooms, now: Instant) {
+		let ids: Vec<_> = self.sessions.iter().filter(|(_, session)| session.emulator.is_some()).map(|(id, _)| *id).collect();
+		for id in ids {
+			while let Some(session) = self.sessions.get_mut(&id) {
+				let emulator = match session.emulator.as_mut() {
+					None => break,
+					Some(emulator) => emulator,
+				};
+				while let Some((buffer, _)) = emulator.get_out(now) {
+					if let Some(peer) = session.peer {
+						Self::send_to_peer(&self.socket, &mut self.websocket, peer, &buffer);
+					}
+				}
+				let (buffer, peer) = match (emulator.get_in(now), session.emulated_peer) {
+					(Some(buffer), Some(peer)) => (buffer, peer),
+					_ => break,
+				};
+				self.process_frame(rooms, &buffer, peer, now, false);
+			}
+		}
+	}
+
 	fn receive(&mut self, rooms: &mut Rooms, now: Instant) {
 		let mut buffer = [0; 512];
 		loop {
 			match self.socket.recv_from(&mut buffer) {
-				Ok((size, address)) => self.on_frame_receive(rooms, &buffer[0..size], address, now),
+				Ok((size, address)) => {
+					self.on_packet_receive(rooms, &buffer[0..size], Peer::Udp(address), now);
+				}
 				Err(e) => match e.kind() {
 					ErrorKind::WouldBlock => {
-						return;
+						break;
 					}
 					_ => {
 						tracing::error!("[network] error in socket.recv_from {:?}", e);
@@ -147,9 +447,27 @@ impl Network {
 				},
 			}
 		}
+		let messages = match self.websocket.as_mut() {
+			None => return,
+			Some(websocket) => {
+				websocket.cycle(now);
+				websocket.receive()
+			}
+		};
+		for (connection_id, data) in messages {
+			self.on_packet_receive(rooms, &data, Peer::WebSocket(connection_id), now);
+		}
+	}
+
+	fn on_packet_receive(&mut self, rooms: &mut Rooms, data: &[u8], peer: Peer, now: Instant) {
+		if data.starts_with(&MEMBER_TOKEN_PACKET_PREFIX) {
+			self.on_member_token_receive(rooms, &data[MEMBER_TOKEN_PACKET_PREFIX.len()..], now, Self::now_in_sec());
+		} else {
+			self.on_frame_receive(rooms, data, peer, now);
+		}
 	}
 
-	fn get_cipher(&self, headers: &Headers) -> Option<Cipher> {
+	fn get_cipher(&self, headers: &Headers) -> Option<Cipher<'_>> {
 		let member_and_room_id_header: Option<MemberAndRoomId> = headers
 			.first(|p| match p {
 				Header::MemberAndRoomId(header) => Some(header),
@@ -171,8 +489,15 @@ impl Network {
 		}
 	}
 
-	fn on_frame_receive(&mut self, rooms: &mut Rooms, source: &[u8], address: SocketAddr, now: Instant) {
-		match Frame::decode(&source, |headers| self.get_cipher(headers)) {
+	fn on_frame_receive(&mut self, rooms: &mut Rooms, source: &[u8], peer: Peer, now: Instant) {
+		self.process_frame(rooms, source, peer, now, true);
+	}
+
+	///
+	/// with_emulator - при включенной эмуляции сети пакет ставится в очередь эмулятора, а не обрабатывается
+	///
+	fn process_frame(&mut self, rooms: &mut Rooms, source: &[u8], peer: Peer, now: Instant, with_emulator: bool) {
+		match Frame::decode(source, |headers| self.get_cipher(headers)) {
 			Ok(frame) => match frame
 				.headers
 				.first(|p| match p {
@@ -189,11 +514,21 @@ impl Network {
 						tracing::error!("[network] member session not found {:?}", member_and_room_id);
 					}
 					Some(session) => {
+						if let (Peer::WebSocket(connection_id), Some(websocket)) = (peer, self.websocket.as_mut()) {
+							websocket.on_valid_frame(connection_id, now);
+						}
+						if let (true, Some(emulator)) = (with_emulator, session.emulator.as_mut()) {
+							emulator.schedule_in(now, source);
+							session.emulated_peer = Some(peer);
+							return;
+						}
 						if Self::is_possible_peer_address_changed(&frame, session) {
-							session.peer_address.replace(address);
+							session.peer.replace(peer);
 							session.last_receive_frame_id = frame.frame_id;
 						}
+						let connection_id = session.protocol.connection_id;
 						session.protocol.on_frame_received(&frame, now);
+						session.on_frame_received(&frame, source.len(), connection_id);
 						let commands = session.protocol.input_data_handler.get_ready_commands();
 						self.income_command_count += commands.len();
 						self.income_frame_count += 1;
@@ -207,33 +542,127 @@ impl Network {
 		}
 	}
 
+	///
+	/// Пользователь подключается по токену - регистрируем его в комнате
+	///
+	fn on_member_token_receive(&mut self, rooms: &mut Rooms, token: &[u8], now: Instant, now_in_sec: u64) {
+		if self.draining {
+			tracing::warn!("[network] member token rejected, server is draining");
+			return;
+		}
+		match self.member_token_authenticator.as_mut() {
+			None => {
+				tracing::error!("[network] member token received, but member token key not configured");
+			}
+			Some(authenticator) => match authenticator.authenticate(token, now_in_sec) {
+				Ok(member) => self.register_token_member(rooms, member, now),
+				// клиент повторяет отправку токена до установки соединения
+				Err(MemberTokenAuthenticationError::Replayed(id)) if self.sessions.get(&id).is_some_and(|session| session.peer.is_none()) => {}
+				Err(e) => {
+					tracing::error!("[network] member token rejected {:?}", e);
+				}
+			},
+		}
+	}
+
+	fn register_token_member(&mut self, rooms: &mut Rooms, member: AuthenticatedMember, now: Instant) {
+		let result = match rooms.register_member_with_id(member.id, member.template.clone()) {
+			// пользователь подключается по новому токену до разрыва предыдущего соединения - продолжаем его сессию
+			Err(ServerCommandError::MemberAlreadyExists(_)) => rooms.resume_member(member.id, &member.template),
+			result => result,
+		};
+		match result {
+			Ok(_) => {
+				let compression = rooms.get(&member.id.room_id).map(|room| room.compression).unwrap_or_default();
+				self.register_member(now, member.id.room_id, member.id.member_id, member.template, compression);
+			}
+			Err(e) => {
+				e.log_error(member.id.room_id, member.id.member_id);
+			}
+		}
+	}
+
+	fn now_in_sec() -> u64 {
+		SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+	}
+
 	fn is_possible_peer_address_changed(frame: &Frame, session: &mut MemberSession) -> bool {
 		frame.connection_id > session.protocol.connection_id || frame.frame_id > session.last_receive_frame_id || session.last_receive_frame_id == 0
 	}
 
-	pub fn register_member(&mut self, now: Instant, room_id: RoomId, member_id: RoomMemberId, template: MemberCreateParams) {
+	pub fn register_member(&mut self, now: Instant, room_id: RoomId, member_id: RoomMemberId, template: MemberCreateParams, compression: bool) {
+		let mut out_commands_collector = OutCommandsCollector::default();
+		out_commands_collector.set_compression(compression);
 		self.sessions.insert(
 			MemberAndRoomId { member_id, room_id },
 			MemberSession {
-				peer_address: Default::default(),
+				peer: Default::default(),
 				private_key: template.private_key,
 				last_receive_frame_id: 0,
-				protocol: CheetahProtocol::new(InCommandsCollector::new(true), Default::default(), 0, now, self.start_application_time, self.protocol_configuration),
+				protocol: CheetahProtocol::new(InCommandsCollector::new(true), out_commands_collector, 0, now, self.start_application_time, self.protocol_configuration),
+				emulator: None,
+				emulated_peer: None,
+				quality_statistics: Default::default(),
+				received_bytes: 0,
+				sent_bytes: 0,
 			},
 		);
 	}
 
+	///
+	/// Включить (Some) или выключить (None) эмуляцию сети пользователя, false - сессия пользователя не найдена
+	///
+	pub fn set_member_emulation(&mut self, id: &MemberAndRoomId, emulation: Option<&MemberNetworkEmulation>) -> bool {
+		match self.sessions.get_mut(id) {
+			None => false,
+			Some(session) => {
+				session.emulator = emulation.map(MemberNetworkEmulation::create_emulator);
+				session.emulated_peer = None;
+				true
+			}
+		}
+	}
+
+	///
+	/// Сетевая статистика пользователя, None - сессия пользователя не найдена
+	///
+	#[must_use]
+	pub fn member_stats(&self, id: &MemberAndRoomId) -> Option<MemberNetworkStats> {
+		self.sessions.get(id).map(MemberSession::stats)
+	}
+
+	pub fn members_stats(&self) -> impl Iterator<Item = (&MemberAndRoomId, MemberNetworkStats)> {
+		self.sessions.iter().map(|(id, session)| (id, session.stats()))
+	}
+
 	/// Послать `DisconnectHeader` пользователю и удалить сессию с сервера
-	pub fn disconnect_members(&mut self, member_and_room_ids: impl Iterator<Item = MemberAndRoomId>, reason: DisconnectByCommandReason) {
+	pub fn disconnect_members(&mut self, member_and_room_ids: impl Iterator<Item = MemberAndRoomId>, reason: DisconnectByCommandReason, now: Instant) {
 		for member_and_room_id in member_and_room_ids {
 			if let Some(session) = self.sessions.get_mut(&member_and_room_id) {
+				// сессия удаляется сразу, поэтому пакет с причиной отключения отправляется без эмуляции
+				session.emulator = None;
 				session.protocol.disconnect_by_command.disconnect(reason);
-				self.send_frames(member_and_room_id);
+				self.send_frames(member_and_room_id, now);
 			}
 
 			self.sessions.remove(&member_and_room_id);
 		}
 	}
+
+	///
+	/// Закрыть соединение при остановке сервера
+	///
+	/// - протокол не содержит причины `ServerShutdown`, поэтому в заголовке отправляется `MemberDeleted`,
+	///   а остановка сервера передается флагом в пакете с командами
+	///
+	pub fn shutdown_members(&mut self, member_and_room_ids: impl Iterator<Item = MemberAndRoomId>, now: Instant) {
+		for member_and_room_id in member_and_room_ids {
+			if let Some(session) = self.sessions.get_mut(&member_and_room_id) {
+				session.protocol.output_data_producer.set_server_shutdown();
+			}
+			self.disconnect_members(iter::once(member_and_room_id), DisconnectByCommandReason::MemberDeleted, now);
+		}
+	}
 }
 
 #[cfg(test)]
@@ -242,11 +671,13 @@ mod tests {
 	use std::str::FromStr;
 	use std::time::{Duration, Instant};
 
-	use crate::server::network::Network;
+	use crate::server::network::{GameSocket, MemberNetworkEmulation, Network, Peer};
 	use crate::server::room::config::member::MemberCreateParams;
 	use crate::server::room::member::{RoomMember, RoomMemberStatus};
 	use crate::server::room_registry::Rooms;
 	use cheetah_common::network::bind_to_free_socket;
+	use cheetah_common::network::token::{MemberToken, MemberTokenClaims};
+	use cheetah_common::room::access::AccessGroups;
 	use cheetah_game_realtime_protocol::codec::cipher::Cipher;
 	use cheetah_game_realtime_protocol::coniguration::ProtocolConfiguration;
 	use cheetah_game_realtime_protocol::disconnect::command::DisconnectByCommandReason;
@@ -260,7 +691,7 @@ mod tests {
 		let mut rooms = Rooms::default();
 		let buffer = [0; 512];
 		let size = 100_usize;
-		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], SocketAddr::from_str("127.0.0.1:5002").unwrap(), Instant::now());
+		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], Peer::Udp(SocketAddr::from_str("127.0.0.1:5002").unwrap()), Instant::now());
 	}
 
 	#[test]
@@ -271,7 +702,7 @@ mod tests {
 		let mut frame = Frame::new(0, 0, false, Default::default());
 		frame.headers.add(Header::MemberAndRoomId(MemberAndRoomId { member_id: 0, room_id: 0 }));
 		let size = frame.encode(&mut Cipher::new(&[0; 32].as_slice().into()), &mut buffer).unwrap();
-		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], SocketAddr::from_str("127.0.0.1:5002").unwrap(), Instant::now());
+		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], Peer::Udp(SocketAddr::from_str("127.0.0.1:5002").unwrap()), Instant::now());
 	}
 
 	#[test]
@@ -281,7 +712,7 @@ mod tests {
 		let mut buffer = [0; 512];
 		let frame = Frame::new(0, 0, false, Default::default());
 		let size = frame.encode(&mut Cipher::new(&[0; 32].as_slice().into()), &mut buffer).unwrap();
-		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], SocketAddr::from_str("127.0.0.1:5002").unwrap(), Instant::now());
+		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], Peer::Udp(SocketAddr::from_str("127.0.0.1:5002").unwrap()), Instant::now());
 	}
 
 	///
@@ -300,7 +731,7 @@ mod tests {
 			template: member_template.clone(),
 			out_commands: Default::default(),
 		};
-		udp_server.register_member(Instant::now(), 0, member.id, member.template.clone());
+		udp_server.register_member(Instant::now(), 0, member.id, member.template.clone(), false);
 
 		let mut frame = Frame::new(0, 100, false, Default::default());
 		let member_and_room_id = MemberAndRoomId { member_id: member.id, room_id: 0 };
@@ -308,16 +739,106 @@ mod tests {
 		let size = frame.encode(&mut Cipher::new(&member_template.private_key), &mut buffer).unwrap();
 
 		let addr_1 = SocketAddr::from_str("127.0.0.1:5002").unwrap();
-		let addr_2 = SocketAddr::from_str("127.0.0.1:5003").unwrap();
 
-		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], addr_1, Instant::now());
+		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], Peer::Udp(addr_1), Instant::now());
 
 		let mut frame = Frame::new(0, 10, false, Default::default());
 		frame.headers.add(Header::MemberAndRoomId(member_and_room_id));
 		let size = frame.encode(&mut Cipher::new(&member_template.private_key), &mut buffer).unwrap();
-		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], addr_2, Instant::now());
+		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], Peer::WebSocket(1), Instant::now());
+
+		assert_eq!(udp_server.sessions[&member_and_room_id].peer.unwrap(), Peer::Udp(addr_1));
+	}
+
+	///
+	/// Проверяем что пользователь может сменить транспорт, например при переподключении через WebSocket
+	///
+	#[test]
+	fn should_switch_peer_to_websocket() {
+		let mut udp_server = create_network_layer();
+		let mut rooms = Rooms::default();
+		let mut buffer = [0; 512];
+		let member_template = MemberCreateParams::new_member(Default::default(), Default::default());
+		udp_server.register_member(Instant::now(), 0, 100, member_template.clone(), false);
+		let member_and_room_id = MemberAndRoomId { member_id: 100, room_id: 0 };
+
+		for (frame_id, peer) in [(1, Peer::Udp(SocketAddr::from_str("127.0.0.1:5002").unwrap())), (2, Peer::WebSocket(1))] {
+			let mut frame = Frame::new(0, frame_id, false, Default::default());
+			frame.headers.add(Header::MemberAndRoomId(member_and_room_id));
+			let size = frame.encode(&mut Cipher::new(&member_template.private_key), &mut buffer).unwrap();
+			udp_server.on_frame_receive(&mut rooms, &buffer[0..size], peer, Instant::now());
+		}
+
+		assert_eq!(udp_server.sessions[&member_and_room_id].peer, Some(Peer::WebSocket(1)));
+	}
+
+	///
+	/// Входящий пакет обрабатывается после задержки эмулятора
+	///
+	#[test]
+	fn should_delay_incoming_frame_with_emulation() {
+		let mut udp_server = create_network_layer();
+		let mut rooms = Rooms::default();
+		let mut buffer = [0; 512];
+		let member_template = MemberCreateParams::new_member(Default::default(), Default::default());
+		let member_and_room_id = MemberAndRoomId { member_id: 100, room_id: 0 };
+		udp_server.register_member(Instant::now(), 0, member_and_room_id.member_id, member_template.clone(), false);
+		let emulation = MemberNetworkEmulation {
+			rtt: Duration::from_millis(200),
+			..Default::default()
+		};
+		assert!(udp_server.set_member_emulation(&member_and_room_id, Some(&emulation)));
+
+		let mut frame = Frame::new(0, 1, false, Default::default());
+		frame.headers.add(Header::MemberAndRoomId(member_and_room_id));
+		let size = frame.encode(&mut Cipher::new(&member_template.private_key), &mut buffer).unwrap();
+		let now = Instant::now();
+		let peer = Peer::Udp(SocketAddr::from_str("127.0.0.1:5002").unwrap());
+		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], peer, now);
+		udp_server.emulate(&mut rooms, now);
+		assert_eq!(udp_server.sessions[&member_and_room_id].peer, None);
+
+		udp_server.emulate(&mut rooms, now + Duration::from_millis(100));
+		assert_eq!(udp_server.sessions[&member_and_room_id].peer, Some(peer));
+		assert_eq!(udp_server.income_frame_count, 1);
+
+		assert!(udp_server.set_member_emulation(&member_and_room_id, None));
+		assert!(udp_server.sessions[&member_and_room_id].emulator.is_none());
+	}
 
-		assert_eq!(udp_server.sessions[&member_and_room_id].peer_address.unwrap(), addr_1);
+	#[test]
+	fn should_collect_member_stats() {
+		let mut udp_server = create_network_layer();
+		let mut rooms = Rooms::default();
+		let mut buffer = [0; 512];
+		let member_template = MemberCreateParams::new_member(Default::default(), Default::default());
+		let member_and_room_id = MemberAndRoomId { member_id: 100, room_id: 0 };
+		udp_server.register_member(Instant::now(), 0, member_and_room_id.member_id, member_template.clone(), false);
+
+		let peer = Peer::Udp(SocketAddr::from_str("127.0.0.1:5002").unwrap());
+		let mut received_bytes = 0;
+		for frame_id in [1, 2, 4] {
+			let mut frame = Frame::new(0, frame_id, false, Default::default());
+			frame.headers.add(Header::MemberAndRoomId(member_and_room_id));
+			let size = frame.encode(&mut Cipher::new(&member_template.private_key), &mut buffer).unwrap();
+			received_bytes += size as u64;
+			udp_server.on_frame_receive(&mut rooms, &buffer[0..size], peer, Instant::now());
+		}
+		udp_server.send_frames(member_and_room_id, Instant::now());
+
+		let stats = udp_server.member_stats(&member_and_room_id).unwrap();
+		assert_eq!(stats.received_bytes, received_bytes);
+		assert!(stats.sent_bytes > 0);
+		assert!((stats.packet_loss - 0.25).abs() < f64::EPSILON);
+		assert_eq!(stats.rtt, None);
+		assert_eq!(udp_server.members_stats().count(), 1);
+		assert_eq!(udp_server.member_stats(&MemberAndRoomId { member_id: 1, room_id: 0 }), None);
+	}
+
+	#[test]
+	fn should_not_set_emulation_for_unknown_member() {
+		let mut udp_server = create_network_layer();
+		assert!(!udp_server.set_member_emulation(&MemberAndRoomId { member_id: 1, room_id: 1 }, Some(&Default::default())));
 	}
 
 	#[test]
@@ -325,17 +846,110 @@ mod tests {
 		let mut udp_server = create_network_layer();
 		let member_template = MemberCreateParams::new_member(Default::default(), Default::default());
 		let member_to_delete = MemberAndRoomId { member_id: 0, room_id: 0 };
-		udp_server.register_member(Instant::now(), member_to_delete.room_id, member_to_delete.member_id, member_template.clone());
-		udp_server.register_member(Instant::now(), 0, 1, member_template);
+		udp_server.register_member(Instant::now(), member_to_delete.room_id, member_to_delete.member_id, member_template.clone(), false);
+		udp_server.register_member(Instant::now(), 0, 1, member_template, false);
 
-		udp_server.disconnect_members(vec![member_to_delete].into_iter(), DisconnectByCommandReason::MemberDeleted);
+		udp_server.disconnect_members(vec![member_to_delete].into_iter(), DisconnectByCommandReason::MemberDeleted, Instant::now());
 
 		assert!(!udp_server.sessions.contains_key(&member_to_delete), "session should be deleted");
 	}
 
+	#[test]
+	fn should_shutdown_members() {
+		let mut udp_server = create_network_layer();
+		let member = MemberAndRoomId { member_id: 0, room_id: 0 };
+		udp_server.register_member(
+			Instant::now(),
+			member.room_id,
+			member.member_id,
+			MemberCreateParams::new_member(Default::default(), Default::default()),
+			false,
+		);
+
+		udp_server.shutdown_members(vec![member].into_iter(), Instant::now());
+
+		assert!(!udp_server.sessions.contains_key(&member), "session should be deleted");
+	}
+
+	#[test]
+	fn should_not_register_member_by_token_when_draining() {
+		let mut rooms = Rooms::default();
+		let room_id = rooms.create_room(Default::default());
+		let mut udp_server = create_network_layer();
+		udp_server.set_member_token_key(TOKEN_KEY.to_vec());
+		udp_server.set_draining();
+		let token = MemberToken::sign(TOKEN_KEY, token_claims(room_id)).encode();
+
+		udp_server.on_member_token_receive(&mut rooms, &token, Instant::now(), 10);
+
+		assert!(udp_server.sessions.is_empty());
+	}
+
+	#[test]
+	fn should_register_member_by_token() {
+		let mut udp_server = create_network_layer();
+		udp_server.set_member_token_key(TOKEN_KEY.to_vec());
+		let mut rooms = Rooms::default();
+		let room_id = rooms.create_room(Default::default());
+		let token = MemberToken::sign(TOKEN_KEY, token_claims(room_id));
+		let member_and_room_id = MemberAndRoomId {
+			member_id: token.claims.member_id(),
+			room_id,
+		};
+
+		udp_server.on_member_token_receive(&mut rooms, &token.encode(), Instant::now(), 10);
+		assert!(rooms.get(&room_id).unwrap().members.contains_key(&member_and_room_id.member_id));
+		assert!(udp_server.sessions[&member_and_room_id].peer.is_none());
+
+		// клиент повторяет отправку токена до установки соединения
+		udp_server.on_member_token_receive(&mut rooms, &token.encode(), Instant::now(), 10);
+		assert_eq!(rooms.get(&room_id).unwrap().members.len(), 1);
+
+		let mut buffer = [0; 512];
+		let mut frame = Frame::new(0, 1, false, Default::default());
+		frame.headers.add(Header::MemberAndRoomId(member_and_room_id));
+		let size = frame.encode(&mut Cipher::new(&token.session_key(TOKEN_KEY)), &mut buffer).unwrap();
+		let addr = SocketAddr::from_str("127.0.0.1:5002").unwrap();
+		udp_server.on_frame_receive(&mut rooms, &buffer[0..size], Peer::Udp(addr), Instant::now());
+		assert_eq!(udp_server.sessions[&member_and_room_id].peer, Some(Peer::Udp(addr)));
+	}
+
+	#[test]
+	fn should_not_register_member_by_token_without_key() {
+		let mut udp_server = create_network_layer();
+		let mut rooms = Rooms::default();
+		let room_id = rooms.create_room(Default::default());
+		let token = MemberToken::sign(TOKEN_KEY, token_claims(room_id));
+		udp_server.on_member_token_receive(&mut rooms, &token.encode(), Instant::now(), 10);
+		assert!(rooms.get(&room_id).unwrap().members.is_empty());
+		assert!(udp_server.sessions.is_empty());
+	}
+
+	#[test]
+	fn should_not_register_member_by_token_in_unknown_room() {
+		let mut udp_server = create_network_layer();
+		udp_server.set_member_token_key(TOKEN_KEY.to_vec());
+		let mut rooms = Rooms::default();
+		let token = MemberToken::sign(TOKEN_KEY, token_claims(100));
+		udp_server.on_member_token_receive(&mut rooms, &token.encode(), Instant::now(), 10);
+		assert!(udp_server.sessions.is_empty());
+	}
+
+	const TOKEN_KEY: &[u8] = b"secret";
+
+	fn token_claims(room_id: u64) -> MemberTokenClaims {
+		MemberTokenClaims {
+			room_id,
+			user_id: 5,
+			groups: AccessGroups(0b1),
+			expires_at: 100,
+			nonce: 1,
+		}
+	}
+
 	fn create_network_layer() -> Network {
 		Network::new(
-			bind_to_free_socket().unwrap(),
+			GameSocket::Udp(bind_to_free_socket().unwrap()),
 			ProtocolConfiguration {
 				disconnect_timeout: Duration::from_millis(1000),
 			},
@@ -87,7 +87,7 @@ mod tests {
 		};
 
 		assert!(matches!(create_object(&command, &mut room, member_id), Err(ServerCommandError::Error(_))));
-		assert!(matches!(room.get_object_mut(object_id), Err(_)));
+		assert!(room.get_object_mut(object_id).is_err());
 	}
 
 	///
@@ -104,7 +104,7 @@ mod tests {
 		};
 
 		assert!(matches!(create_object(&command, &mut room, member_id), Err(ServerCommandError::Error(_))));
-		assert!(matches!(room.get_object_mut(object_id), Err(_)));
+		assert!(room.get_object_mut(object_id).is_err());
 	}
 
 	///
@@ -121,7 +121,7 @@ mod tests {
 			access_groups: AccessGroups(0b11),
 		};
 		assert!(matches!(create_object(&command, &mut room, member_id), Err(ServerCommandError::Error(_))));
-		assert!(matches!(room.get_object_mut(object_id), Err(_)));
+		assert!(room.get_object_mut(object_id).is_err());
 	}
 
 	//
@@ -77,7 +77,7 @@ mod tests {
 
 		assert!(room.get_member_out_commands_for_test(member1).is_empty());
 		assert!(matches!(
-			room.get_member_out_commands_for_test(member2).get(0),
+			room.get_member_out_commands_for_test(member2).front(),
 			Some(S2CCommand::Create(c)) if c.object_id == object_id
 		));
 
@@ -114,7 +114,7 @@ mod tests {
 		room.test_out_commands.clear();
 
 		assert!(matches!(created_object(&command, &mut room, member1), Err(ServerCommandError::Error(_))));
-		assert!(matches!(room.test_out_commands.pop_back(), None));
+		assert!(room.test_out_commands.pop_back().is_none());
 	}
 
 	///
@@ -48,8 +48,8 @@ mod tests {
 
 		delete(&object_id, &mut room, member_a_id).unwrap();
 
-		assert!(matches!(room.get_object_mut(object_id), Err(_)));
-		assert!(matches!(room.get_member_out_commands_for_test(member_a_id).pop_back(), None));
+		assert!(room.get_object_mut(object_id).is_err());
+		assert!(room.get_member_out_commands_for_test(member_a_id).pop_back().is_none());
 		assert!(matches!(room.get_member_out_commands_for_test(member_b_id).pop_back(), Some(S2CCommand::Delete(c)) if c==object_id));
 	}
 
@@ -64,7 +64,7 @@ mod tests {
 		let object_id = room.test_create_object_with_not_created_state(GameObjectOwner::Member(member_a), access_groups, Default::default()).id;
 		room.test_out_commands.clear();
 		assert!(matches!(delete(&object_id, &mut room, member_b), Err(ServerCommandError::MemberNotOwnerGameObject { .. })));
-		assert!(matches!(room.get_object_mut(object_id), Ok(_)));
-		assert!(matches!(room.test_out_commands.pop_back(), None));
+		assert!(room.get_object_mut(object_id).is_ok());
+		assert!(room.test_out_commands.pop_back().is_none());
 	}
 }
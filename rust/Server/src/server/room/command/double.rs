@@ -9,7 +9,7 @@ pub(crate) fn set(command: &DoubleField, room: &mut Room, member_id: RoomMemberI
 	let object_id = command.object_id;
 	let action = |object: &mut GameObject| {
 		object.double_fields.set(command.field_id, command.value);
-		Ok(Some(S2CCommand::SetDouble(command.clone())))
+		Ok(Some(S2CCommand::SetDouble(*command)))
 	};
 	room.send_command_from_action(object_id, member_id, None, action)
 }
@@ -49,7 +49,7 @@ mod tests {
 		let command = DoubleField {
 			object_id,
 			field_id: 10,
-			value: 100.100.into(),
+			value: 100.100,
 		};
 		set(&command, &mut room, member_id).unwrap();
 
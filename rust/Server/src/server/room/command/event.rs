@@ -17,7 +17,7 @@ pub(crate) fn send(event: &BinaryField, room: &mut Room, member_id: RoomMemberId
 pub(crate) fn send_target(target_event: &TargetEvent, room: &mut Room, member_id: RoomMemberId) -> Result<(), ServerCommandError> {
 	let object_id = target_event.event.object_id;
 	let target = target_event.target;
-	let action = |_object: &mut GameObject| Ok(Some(S2CCommand::Event(target_event.event.clone().into())));
+	let action = |_object: &mut GameObject| Ok(Some(S2CCommand::Event(target_event.event.clone())));
 	room.send_command_from_action(object_id, member_id, Some(target), action)
 }
 
@@ -51,7 +51,7 @@ mod tests {
 		};
 
 		send(&command, &mut room, member_id).unwrap();
-		assert!(matches!(room.test_out_commands.pop_back(), Some((.., S2CCommand::Event(c))) if c==command.into()));
+		assert!(matches!(room.test_out_commands.pop_back(), Some((.., S2CCommand::Event(c))) if c==command));
 	}
 
 	#[test]
@@ -85,8 +85,8 @@ mod tests {
 		};
 
 		send_target(&command, &mut room, member1).unwrap();
-		assert!(matches!(room.get_member_out_commands_for_test(member1).pop_back(), None));
+		assert!(room.get_member_out_commands_for_test(member1).pop_back().is_none());
 		assert!(matches!(room.get_member_out_commands_for_test(member2).pop_back(), Some(S2CCommand::Event(c)) if c.field_id == command.event.field_id));
-		assert!(matches!(room.get_member_out_commands_for_test(member3).pop_back(), None));
+		assert!(room.get_member_out_commands_for_test(member3).pop_back().is_none());
 	}
 }
@@ -27,7 +27,7 @@ pub(crate) fn delete(field: &DeleteField, room: &mut Room, member_id: RoomMember
 			FieldType::Event => {}
 		}
 
-		Ok(Some(S2CCommand::DeleteField(field.clone())))
+		Ok(Some(S2CCommand::DeleteField(*field)))
 	};
 	room.send_command_from_action(object_id, member_id, None, action)
 }
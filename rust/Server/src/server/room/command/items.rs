@@ -67,7 +67,7 @@ mod tests {
 		let structures = object.structures_fields.get(100).unwrap();
 		assert_eq!(*structures, VecDeque::from([command_1.value.clone(), command_2.value.clone()]));
 		assert!(matches!(room.test_out_commands.pop_back(), Some((.., S2CCommand::AddItem(c))) if c ==
-			command_1.into()));
+			command_1));
 	}
 	#[test]
 	pub(crate) fn should_capacity_items() {
@@ -84,6 +84,7 @@ mod tests {
 			)]
 			.into_iter()
 			.collect(),
+			..Default::default()
 		};
 
 		let (mut room, member_id, object_id) = setup(room_create_params, template);
@@ -1,4 +1,5 @@
 use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use strum_macros::AsRefStr;
 use thiserror::Error;
 
 use cheetah_common::commands::c2s::C2SCommand;
@@ -21,7 +22,7 @@ pub mod long;
 pub mod room;
 pub mod structure;
 
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Error, Debug, PartialEq, Eq, AsRefStr)]
 pub enum ServerCommandError {
 	#[error("{0:?}")]
 	Error(String),
@@ -38,6 +39,9 @@ pub enum ServerCommandError {
 	#[error("Member with id {0}")]
 	MemberNotFound(RoomMemberId),
 
+	#[error("Member with id {0} already exists")]
+	MemberAlreadyExists(RoomMemberId),
+
 	#[error(
 		"Member {member_id:?} with group {member_access_group:?} cannot access to \
 	object {object_id:?} with group {object_access_group:?} in room {room_id:?}"
@@ -70,7 +70,7 @@ mod tests {
 		let mut commands = room.get_member_out_commands_for_test(member_a);
 		assert!(matches!(commands.pop_front(), Some(S2CCommand::Create(c)) if c.object_id==object_a_1_id));
 		assert!(matches!(commands.pop_front(), Some(S2CCommand::Created(c)) if c.object_id==object_a_1_id));
-		assert!(matches!(commands.pop_front(), None));
+		assert!(commands.pop_front().is_none());
 	}
 
 	#[test]
@@ -11,7 +11,7 @@ pub(crate) fn set(field: &BinaryField, room: &mut Room, member_id: RoomMemberId)
 	let object_id = field.object_id;
 	let action = |object: &mut GameObject| {
 		object.structure_fields.set(field.field_id, Box::new(field.value.clone()));
-		Ok(Some(S2CCommand::SetStructure(field.clone().into())))
+		Ok(Some(S2CCommand::SetStructure(field.clone())))
 	};
 	room.send_command_from_action(object_id, member_id, None, action)
 }
@@ -43,7 +43,7 @@ mod tests {
 		let command = BinaryField {
 			object_id,
 			field_id: 100,
-			value: Buffer::from(vec![1, 2, 3, 4, 5].as_slice()).into(),
+			value: Buffer::from(vec![1, 2, 3, 4, 5].as_slice()),
 		};
 
 		structure::set(&command, &mut room, member_id).unwrap();
@@ -51,6 +51,6 @@ mod tests {
 
 		assert_eq!(**object.structure_fields.get(100).unwrap(), command.value);
 		assert!(matches!(room.test_out_commands.pop_back(), Some((.., S2CCommand::SetStructure(c))) if c == 
-			command.into()));
+			command));
 	}
 }
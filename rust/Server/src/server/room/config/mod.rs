@@ -1,3 +1,4 @@
 pub mod member;
 pub mod object;
 pub mod room;
+pub mod templates;
@@ -14,6 +14,7 @@ use crate::server::room::object::GameObject;
 use crate::server::room::Room;
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GameObjectConfig {
 	pub items_config: FnvHashMap<FieldId, ItemConfig>,
 }
@@ -70,6 +71,7 @@ impl GameObjectCreateParams {
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct GameObjectCreateParams {
 	pub id: u32,
 	pub template: GameObjectTemplateId,
@@ -1,14 +1,115 @@
 use crate::server::room::config::object::GameObjectConfig;
 use crate::server::room::config::object::GameObjectCreateParams;
-use cheetah_common::room::object::GameObjectTemplateId;
-use fnv::FnvHashMap;
+use cheetah_common::room::field::FieldId;
+use cheetah_common::room::object::{GameObjectId, GameObjectTemplateId};
+use fnv::{FnvHashMap, FnvHashSet};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 ///
 /// Шаблон для создания комнаты
 ///
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct RoomCreateParams {
+	///
+	/// Для шаблонов из каталога - имя файла без расширения
+	///
+	#[serde(skip)]
 	pub name: String,
 	pub objects: Vec<GameObjectCreateParams>,
 	pub configs: FnvHashMap<GameObjectTemplateId, GameObjectConfig>,
+	///
+	/// Сжимать пакеты с командами для участников комнаты
+	///
+	pub compression: bool,
+	///
+	/// Записывать журнал команд комнаты для воспроизведения offline, требует заданного каталога журналов на сервере
+	///
+	pub journal: bool,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum RoomTemplateError {
+	#[error("Object id {0} must be in range 1..{}", GameObjectId::CLIENT_OBJECT_ID_OFFSET)]
+	ObjectHasWrongId(u32),
+	#[error("Duplicate object id {0}")]
+	DuplicateObjectId(u32),
+	#[error("Zero capacity for item {1} of object template {0}")]
+	ItemHasZeroCapacity(GameObjectTemplateId, FieldId),
+}
+
+impl RoomCreateParams {
+	///
+	/// Проверить шаблон до создания комнаты
+	///
+	/// - идентификаторы объектов не пересекаются с идентификаторами, создаваемыми клиентами (`CLIENT_OBJECT_ID_OFFSET`)
+	/// - вместимость item полей больше 0
+	///
+	pub fn validate(&self) -> Result<(), RoomTemplateError> {
+		let mut ids = FnvHashSet::default();
+		for object in &self.objects {
+			if object.id == 0 || object.id >= GameObjectId::CLIENT_OBJECT_ID_OFFSET {
+				return Err(RoomTemplateError::ObjectHasWrongId(object.id));
+			}
+			if !ids.insert(object.id) {
+				return Err(RoomTemplateError::DuplicateObjectId(object.id));
+			}
+		}
+		for (template, config) in &self.configs {
+			if let Some((field_id, _)) = config.items_config.iter().find(|(_, item)| item.capacity == 0) {
+				return Err(RoomTemplateError::ItemHasZeroCapacity(*template, *field_id));
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::server::room::config::object::{GameObjectConfig, GameObjectCreateParams, ItemConfig};
+	use crate::server::room::config::room::{RoomCreateParams, RoomTemplateError};
+	use cheetah_common::room::object::GameObjectId;
+
+	#[test]
+	fn should_validate() {
+		let params = RoomCreateParams {
+			objects: vec![object(1), object(GameObjectId::CLIENT_OBJECT_ID_OFFSET - 1)],
+			..Default::default()
+		};
+		assert_eq!(params.validate(), Ok(()));
+	}
+
+	#[test]
+	fn should_fail_validate_when_object_has_wrong_id() {
+		for id in [0, GameObjectId::CLIENT_OBJECT_ID_OFFSET] {
+			let params = RoomCreateParams {
+				objects: vec![object(id)],
+				..Default::default()
+			};
+			assert_eq!(params.validate(), Err(RoomTemplateError::ObjectHasWrongId(id)));
+		}
+	}
+
+	#[test]
+	fn should_fail_validate_when_duplicate_object_id() {
+		let params = RoomCreateParams {
+			objects: vec![object(1), object(1)],
+			..Default::default()
+		};
+		assert_eq!(params.validate(), Err(RoomTemplateError::DuplicateObjectId(1)));
+	}
+
+	#[test]
+	fn should_fail_validate_when_item_has_zero_capacity() {
+		let mut params = RoomCreateParams::default();
+		let mut config = GameObjectConfig::default();
+		config.items_config.insert(5, ItemConfig { capacity: 0 });
+		params.configs.insert(10, config);
+		assert_eq!(params.validate(), Err(RoomTemplateError::ItemHasZeroCapacity(10, 5)));
+	}
+
+	fn object(id: u32) -> GameObjectCreateParams {
+		GameObjectCreateParams { id, ..Default::default() }
+	}
 }
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use cheetah_common::room::object::GameObjectTemplateId;
+use fnv::FnvHashMap;
+use thiserror::Error;
+
+use crate::server::room::config::object::{GameObjectConfig, GameObjectCreateParams};
+use crate::server::room::config::room::{RoomCreateParams, RoomTemplateError};
+
+///
+/// Каталог шаблонов комнат, загружаемых из RON/JSON файлов
+///
+/// - имя шаблона - имя файла без расширения
+/// - при загрузке все шаблоны проверяются, ошибка в любом файле останавливает запуск сервера
+/// - при перезагрузке ошибочные файлы пропускаются, используется предыдущая версия шаблона
+///
+#[derive(Debug)]
+pub struct RoomTemplates {
+	dir: PathBuf,
+	templates: RwLock<HashMap<String, LoadedRoomTemplate>>,
+}
+
+#[derive(Debug, Clone)]
+struct LoadedRoomTemplate {
+	content: String,
+	params: RoomCreateParams,
+}
+
+///
+/// Изменения шаблона при создании комнаты
+///
+/// - объекты с совпадающим id заменяются, остальные добавляются
+/// - конфигурации объектов заменяются по идентификатору шаблона объекта
+///
+#[derive(Debug, Default, Clone)]
+pub struct RoomTemplateOverrides {
+	pub objects: Vec<GameObjectCreateParams>,
+	pub configs: FnvHashMap<GameObjectTemplateId, GameObjectConfig>,
+	pub compression: Option<bool>,
+	pub journal: Option<bool>,
+}
+
+#[derive(Error, Debug)]
+pub enum RoomTemplatesError {
+	#[error("Read {0:?}: {1}")]
+	Io(PathBuf, io::Error),
+	#[error("Parse {0:?}: {1}")]
+	Parse(PathBuf, String),
+	#[error("Template {0:?}: {1}")]
+	Invalid(PathBuf, RoomTemplateError),
+	#[error("Duplicate template name {0} in {1:?}")]
+	DuplicateName(String, PathBuf),
+	#[error("Template {0} not found")]
+	NotFound(String),
+	#[error("Template {0} with overrides: {1}")]
+	InvalidOverrides(String, RoomTemplateError),
+}
+
+impl RoomTemplates {
+	pub fn load(dir: &Path) -> Result<Self, RoomTemplatesError> {
+		let mut templates = HashMap::new();
+		for path in Self::list_files(dir)? {
+			let name = Self::template_name(&path);
+			if templates.contains_key(&name) {
+				return Err(RoomTemplatesError::DuplicateName(name, path));
+			}
+			let content = fs::read_to_string(&path).map_err(|e| RoomTemplatesError::Io(path.clone(), e))?;
+			let params = Self::parse(&path, &name, &content)?;
+			templates.insert(name, LoadedRoomTemplate { content, params });
+		}
+		tracing::info!("Loaded room templates {:?} from {:?}", templates.keys().collect::<Vec<_>>(), dir);
+		Ok(Self {
+			dir: dir.to_path_buf(),
+			templates: RwLock::new(templates),
+		})
+	}
+
+	///
+	/// Перечитать каталог: новые и измененные файлы загружаются, удаленные - удаляются из каталога,
+	/// возвращаются ошибки файлов, которые не удалось загрузить
+	///
+	pub fn reload(&self) -> Vec<RoomTemplatesError> {
+		let files = match Self::list_files(&self.dir) {
+			Ok(files) => files,
+			Err(e) => return vec![e],
+		};
+		let mut errors = Vec::new();
+		let current = self.templates.read().unwrap().clone();
+		let mut templates = HashMap::new();
+		for path in files {
+			let name = Self::template_name(&path);
+			if templates.contains_key(&name) {
+				errors.push(RoomTemplatesError::DuplicateName(name, path));
+				continue;
+			}
+			let loaded = fs::read_to_string(&path)
+				.map_err(|e| RoomTemplatesError::Io(path.clone(), e))
+				.and_then(|content| match current.get(&name) {
+					Some(template) if template.content == content => Ok(template.clone()),
+					_ => Self::parse(&path, &name, &content).map(|params| {
+						tracing::info!("Reload room template {} from {:?}", name, path);
+						LoadedRoomTemplate { content, params }
+					}),
+				});
+			match loaded {
+				Ok(template) => {
+					templates.insert(name, template);
+				}
+				Err(e) => {
+					tracing::error!("Error reload room template {:?}", e);
+					errors.push(e);
+					if let Some(template) = current.get(&name) {
+						templates.insert(name, template.clone());
+					}
+				}
+			}
+		}
+		*self.templates.write().unwrap() = templates;
+		errors
+	}
+
+	#[must_use]
+	pub fn names(&self) -> Vec<String> {
+		let mut names: Vec<_> = self.templates.read().unwrap().keys().cloned().collect();
+		names.sort();
+		names
+	}
+
+	///
+	/// Параметры для создания комнаты по шаблону, шаблон с изменениями проверяется повторно
+	///
+	pub fn create_params(&self, name: &str, overrides: RoomTemplateOverrides) -> Result<RoomCreateParams, RoomTemplatesError> {
+		let mut params = self
+			.templates
+			.read()
+			.unwrap()
+			.get(name)
+			.map(|template| template.params.clone())
+			.ok_or_else(|| RoomTemplatesError::NotFound(name.to_owned()))?;
+		for object in overrides.objects {
+			match params.objects.iter_mut().find(|current| current.id == object.id) {
+				None => params.objects.push(object),
+				Some(current) => *current = object,
+			}
+		}
+		params.configs.extend(overrides.configs);
+		if let Some(compression) = overrides.compression {
+			params.compression = compression;
+		}
+		if let Some(journal) = overrides.journal {
+			params.journal = journal;
+		}
+		params.validate().map_err(|e| RoomTemplatesError::InvalidOverrides(name.to_owned(), e))?;
+		Ok(params)
+	}
+
+	fn list_files(dir: &Path) -> Result<Vec<PathBuf>, RoomTemplatesError> {
+		let entries = fs::read_dir(dir).map_err(|e| RoomTemplatesError::Io(dir.to_path_buf(), e))?;
+		let mut files = Vec::new();
+		for entry in entries {
+			let path = entry.map_err(|e| RoomTemplatesError::Io(dir.to_path_buf(), e))?.path();
+			if path.is_file() && matches!(path.extension().and_then(|extension| extension.to_str()), Some("ron" | "json")) {
+				files.push(path);
+			}
+		}
+		files.sort();
+		Ok(files)
+	}
+
+	fn template_name(path: &Path) -> String {
+		path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+	}
+
+	fn parse(path: &Path, name: &str, content: &str) -> Result<RoomCreateParams, RoomTemplatesError> {
+		let mut params: RoomCreateParams = if path.extension().is_some_and(|extension| extension == "json") {
+			serde_json::from_str(content).map_err(|e| RoomTemplatesError::Parse(path.to_path_buf(), e.to_string()))?
+		} else {
+			ron::from_str(content).map_err(|e| RoomTemplatesError::Parse(path.to_path_buf(), e.to_string()))?
+		};
+		params.validate().map_err(|e| RoomTemplatesError::Invalid(path.to_path_buf(), e))?;
+		params.name = name.to_owned();
+		Ok(params)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use cheetah_common::room::object::GameObjectId;
+	use tempfile::TempDir;
+
+	use crate::server::room::config::object::GameObjectCreateParams;
+	use crate::server::room::config::room::RoomTemplateError;
+	use crate::server::room::config::templates::{RoomTemplateOverrides, RoomTemplates, RoomTemplatesError};
+
+	const BATTLE: &str = "(objects: [(id: 1, template: 10, groups: (7), longs: {1: 100})], configs: {10: (items_config: {2: (capacity: 5)})})";
+	const LOBBY: &str = r#"{"objects": [{"id": 2, "template": 20}], "compression": true}"#;
+
+	#[test]
+	fn should_load_ron_and_json() {
+		let dir = templates_dir(&[("battle.ron", BATTLE), ("lobby.json", LOBBY), ("readme.txt", "-")]);
+		let templates = RoomTemplates::load(dir.path()).unwrap();
+		assert_eq!(templates.names(), vec!["battle", "lobby"]);
+
+		let battle = templates.create_params("battle", Default::default()).unwrap();
+		assert_eq!(battle.name, "battle");
+		assert_eq!(battle.objects[0].longs[&1], 100);
+		assert_eq!(battle.configs[&10].get_items_config(&2).capacity, 5);
+		assert!(!battle.compression);
+
+		let lobby = templates.create_params("lobby", Default::default()).unwrap();
+		assert_eq!(lobby.objects[0].template, 20);
+		assert!(lobby.compression);
+	}
+
+	#[test]
+	fn should_fail_load_when_template_invalid() {
+		let dir = templates_dir(&[("battle.ron", BATTLE), ("wrong.json", r#"{"objects": [{"id": 512}]}"#)]);
+		assert!(matches!(
+			RoomTemplates::load(dir.path()),
+			Err(RoomTemplatesError::Invalid(path, RoomTemplateError::ObjectHasWrongId(GameObjectId::CLIENT_OBJECT_ID_OFFSET))) if path.ends_with("wrong.json")
+		));
+
+		let dir = templates_dir(&[("wrong.ron", "(unknown: 1)")]);
+		assert!(matches!(RoomTemplates::load(dir.path()), Err(RoomTemplatesError::Parse(_, _))));
+
+		let dir = templates_dir(&[("wrong.ron", "(objects: [(id: 1, template: 10, long: {1: 100})])")]);
+		assert!(matches!(RoomTemplates::load(dir.path()), Err(RoomTemplatesError::Parse(_, _))));
+	}
+
+	#[test]
+	fn should_apply_overrides() {
+		let dir = templates_dir(&[("battle.ron", BATTLE)]);
+		let templates = RoomTemplates::load(dir.path()).unwrap();
+		let overrides = RoomTemplateOverrides {
+			objects: vec![object(1, 11), object(3, 30)],
+			configs: Default::default(),
+			compression: Some(true),
+			journal: Some(true),
+		};
+		let params = templates.create_params("battle", overrides).unwrap();
+		assert_eq!(params.objects.iter().map(|object| (object.id, object.template)).collect::<Vec<_>>(), vec![(1, 11), (3, 30)]);
+		assert!(params.compression);
+		assert!(params.journal);
+
+		let overrides = RoomTemplateOverrides {
+			objects: vec![object(0, 1)],
+			..Default::default()
+		};
+		assert!(matches!(
+			templates.create_params("battle", overrides),
+			Err(RoomTemplatesError::InvalidOverrides(_, RoomTemplateError::ObjectHasWrongId(0)))
+		));
+		assert!(matches!(templates.create_params("unknown", Default::default()), Err(RoomTemplatesError::NotFound(_))));
+	}
+
+	#[test]
+	fn should_reload() {
+		let dir = templates_dir(&[("battle.ron", BATTLE), ("lobby.json", LOBBY)]);
+		let templates = RoomTemplates::load(dir.path()).unwrap();
+
+		fs::write(dir.path().join("battle.ron"), "(objects: [(id: 5, template: 10)])").unwrap();
+		fs::write(dir.path().join("arena.ron"), "(compression: true)").unwrap();
+		fs::remove_file(dir.path().join("lobby.json")).unwrap();
+		assert!(templates.reload().is_empty());
+		assert_eq!(templates.names(), vec!["arena", "battle"]);
+		assert_eq!(templates.create_params("battle", Default::default()).unwrap().objects[0].id, 5);
+
+		// ошибочный файл не заменяет уже загруженный шаблон
+		fs::write(dir.path().join("battle.ron"), "(objects: [(id: 0)])").unwrap();
+		let errors = templates.reload();
+		assert!(matches!(errors.as_slice(), [RoomTemplatesError::Invalid(_, RoomTemplateError::ObjectHasWrongId(0))]));
+		assert_eq!(templates.create_params("battle", Default::default()).unwrap().objects[0].id, 5);
+	}
+
+	fn templates_dir(files: &[(&str, &str)]) -> TempDir {
+		let dir = TempDir::new().unwrap();
+		for (name, content) in files {
+			fs::write(dir.path().join(name), content).unwrap();
+		}
+		dir
+	}
+
+	fn object(id: u32, template: u16) -> GameObjectCreateParams {
+		GameObjectCreateParams { id, template, ..Default::default() }
+	}
+}
@@ -23,6 +23,6 @@ pub enum RoomMemberStatus {
 
 impl RoomMemberStatus {
 	pub fn is_online(&self) -> bool {
-		return *self == Self::Connected || *self == Self::Attached || *self == Self::Detached;
+		*self == Self::Connected || *self == Self::Attached || *self == Self::Detached
 	}
 }
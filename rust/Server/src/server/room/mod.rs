@@ -9,6 +9,7 @@ use fnv::{FnvBuildHasher, FnvHashMap};
 use indexmap::map::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::server::measurer::CommandMeasurer;
 use crate::server::room::command::{execute, ServerCommandError};
 use crate::server::room::config::member::MemberCreateParams;
 use crate::server::room::config::object::GameObjectConfig;
@@ -36,6 +37,7 @@ pub mod sender;
 pub struct Room {
 	pub id: RoomId,
 	pub template_name: String,
+	pub compression: bool,
 	pub members: HashMap<RoomMemberId, RoomMember, FnvBuildHasher>,
 	configs: FnvHashMap<GameObjectTemplateId, Arc<GameObjectConfig>>,
 	pub objects: IndexMap<GameObjectId, GameObject, FnvBuildHasher>,
@@ -68,6 +70,7 @@ impl Room {
 			member_id_generator: 0,
 			room_object_id_generator: 65536,
 			template_name: create_params.name.clone(),
+			compression: create_params.compression,
 			configs: create_params.configs.into_iter().map(|item| (item.0, From::from(item.1))).collect(),
 		};
 
@@ -115,6 +118,13 @@ impl Room {
 	/// то команды не-суперпользователей будут перенаправлены суперпользователям вместо выполнения.
 	///
 	pub fn execute_commands(&mut self, member_id: RoomMemberId, commands: &[CommandWithReliabilityGuarantees]) {
+		self.execute_commands_with_measurer(member_id, commands, None);
+	}
+
+	///
+	/// Обработать входящие команды с измерением количества, ошибок и времени выполнения по типам команд
+	///
+	pub(crate) fn execute_commands_with_measurer(&mut self, member_id: RoomMemberId, commands: &[CommandWithReliabilityGuarantees], measurer: Option<&CommandMeasurer>) {
 		if let Some(member) = self.members.get(&member_id) {
 			if !member.status.is_online() {
 				if let Err(e) = self.connect_member(member_id) {
@@ -131,17 +141,19 @@ impl Room {
 		for command_with_channel in commands {
 			match &command_with_channel.command {
 				BothDirectionCommand::C2S(command) => {
-					tracing::info!("execute c2s {:?}", command);
+					tracing::debug!("execute c2s {:?}", command);
 					self.current_channel.replace(From::from(&command_with_channel.reliability_guarantees));
 
 					let instant = Instant::now();
-					match execute(command, self, member_id) {
-						Ok(_) => {}
-						Err(e) => {
-							e.log_command_execute_error(command, self.id, member_id);
-						}
+					let result = execute(command, self, member_id);
+					let execution_time = instant.elapsed();
+					if let Err(e) = &result {
+						e.log_command_execute_error(command, self.id, member_id);
+					}
+					if let Some(measurer) = measurer {
+						measurer.on_command_executed(command.get_type_id(), execution_time, result.as_ref().err());
 					}
-					if instant.elapsed() > Duration::from_millis(100) {
+					if execution_time > Duration::from_millis(100) {
 						tracing::error!("Slow command {:?}", command);
 					}
 				}
@@ -171,6 +183,35 @@ impl Room {
 	pub fn register_member(&mut self, template: MemberCreateParams) -> RoomMemberId {
 		self.member_id_generator += 1;
 		let member_id = self.member_id_generator;
+		self.insert_member(member_id, template);
+		member_id
+	}
+
+	///
+	/// Зарегистрировать пользователя с заданным идентификатором, используется для пользователей подключенных по токену
+	///
+	pub fn register_member_with_id(&mut self, member_id: RoomMemberId, template: MemberCreateParams) -> Result<(), ServerCommandError> {
+		if self.members.contains_key(&member_id) {
+			return Err(ServerCommandError::MemberAlreadyExists(member_id));
+		}
+		self.insert_member(member_id, template);
+		Ok(())
+	}
+
+	///
+	/// Повторное подключение пользователя по новому токену до разрыва предыдущего соединения,
+	/// пользователь и его объекты сохраняются, меняются ключ и группы из токена
+	///
+	pub fn resume_member(&mut self, member_id: RoomMemberId, template: &MemberCreateParams) -> Result<(), ServerCommandError> {
+		let member = self.members.get_mut(&member_id).ok_or(ServerCommandError::MemberNotFound(member_id))?;
+		member.template.private_key = template.private_key.clone();
+		member.template.groups = template.groups;
+		member.out_commands.clear();
+		tracing::info!("[room({:?})] resume member({:?})", self.id, member_id);
+		Ok(())
+	}
+
+	fn insert_member(&mut self, member_id: RoomMemberId, template: MemberCreateParams) {
 		let member = RoomMember {
 			id: member_id,
 			status: RoomMemberStatus::Created,
@@ -179,7 +220,6 @@ impl Room {
 		};
 		self.members.insert(member_id, member);
 		tracing::info!("[room({:?})] register member({:?})", self.id, member_id);
-		member_id
 	}
 
 	pub fn get_member(&self, member_id: &RoomMemberId) -> Result<&RoomMember, ServerCommandError> {
@@ -9,13 +9,10 @@ use crate::server::room::object::fields::FieldValue;
 pub type Structure = Buffer;
 impl FieldValue for Box<Structure> {
 	fn into(&self, object_id: GameObjectId, field_id: FieldId, collector: &mut Vec<S2CCommand>) {
-		collector.push(S2CCommand::SetStructure(
-			BinaryField {
-				object_id,
-				field_id,
-				value: *self.clone(),
-			}
-			.into(),
-		));
+		collector.push(S2CCommand::SetStructure(BinaryField {
+			object_id,
+			field_id,
+			value: *self.clone(),
+		}));
 	}
 }
@@ -14,14 +14,11 @@ impl FieldValue for Items {
 	fn into(&self, object_id: GameObjectId, field_id: FieldId, collector: &mut Vec<S2CCommand>) {
 		self.iter()
 			.map(|item| {
-				S2CCommand::AddItem(
-					BinaryField {
-						object_id,
-						field_id,
-						value: item.clone(),
-					}
-					.into(),
-				)
+				S2CCommand::AddItem(BinaryField {
+					object_id,
+					field_id,
+					value: item.clone(),
+				})
 			})
 			.for_each(|command| collector.push(command))
 	}
@@ -134,37 +134,28 @@ mod tests {
 
 		assert_eq!(
 			commands[3],
-			S2CCommand::SetStructure(
-				BinaryField {
-					object_id: id,
-					field_id: 1,
-					value: [1, 2, 3].as_ref().into(),
-				}
-				.into()
-			)
+			S2CCommand::SetStructure(BinaryField {
+				object_id: id,
+				field_id: 1,
+				value: [1, 2, 3].as_ref().into(),
+			})
 		);
 		assert_eq!(
 			commands[4],
-			S2CCommand::AddItem(
-				BinaryField {
-					object_id: id,
-					field_id: 1,
-					value: [1, 2, 3].as_ref().into(),
-				}
-				.into()
-			)
+			S2CCommand::AddItem(BinaryField {
+				object_id: id,
+				field_id: 1,
+				value: [1, 2, 3].as_ref().into(),
+			})
 		);
 
 		assert_eq!(
 			commands[5],
-			S2CCommand::AddItem(
-				BinaryField {
-					object_id: id,
-					field_id: 1,
-					value: [4, 5, 6].as_ref().into(),
-				}
-				.into()
-			)
+			S2CCommand::AddItem(BinaryField {
+				object_id: id,
+				field_id: 1,
+				value: [4, 5, 6].as_ref().into(),
+			})
 		);
 
 		assert_eq!(commands[6], S2CCommand::Created(GameObjectCreated { object_id: id }));
@@ -142,7 +142,7 @@ mod tests {
 		room.send_to_member(&member_target_id, &commands).unwrap();
 
 		let out_commands = room.get_member_out_commands_for_test(member_target_id);
-		let command = out_commands.get(0);
+		let command = out_commands.front();
 
 		assert!(matches!(command, Some(S2CCommand::SetLong(command)) if command.field_id == allow_field_id));
 		assert_eq!(out_commands.len(), 1);
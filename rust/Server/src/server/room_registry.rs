@@ -1,20 +1,47 @@
+use crate::server::journal::{JournalEvent, JournalWriter, RoomSnapshot};
+use crate::server::measurer::CommandMeasurer;
 use crate::server::room::command::ServerCommandError;
 use crate::server::room::config::member::MemberCreateParams;
 use crate::server::room::config::room::RoomCreateParams;
 use crate::server::room::Room;
+use crate::server::trace::CommandTracer;
 use cheetah_common::commands::{CommandWithChannelType, CommandWithReliabilityGuarantees};
 use cheetah_game_realtime_protocol::others::member_id::MemberAndRoomId;
 use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
 use fnv::FnvBuildHasher;
+use serde::Serialize;
 use std::collections::hash_map::Iter;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use thiserror::Error;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::Sender;
+
+type Journals = HashMap<RoomId, JournalWriter, FnvBuildHasher>;
+type TrafficSubscribers = HashMap<RoomId, Vec<Sender<RoomTrafficEvent>>, FnvBuildHasher>;
 
 #[derive(Default)]
 pub struct Rooms {
 	rooms: HashMap<RoomId, Room, FnvBuildHasher>,
 	room_id_generator: RoomId,
 	pub created_rooms_count: usize,
+	///
+	/// Каталог журналов комнат, журнал ведется только для комнат с включенным [`RoomCreateParams::journal`]
+	///
+	journal_dir: Option<PathBuf>,
+	journals: Journals,
+	command_measurer: Option<CommandMeasurer>,
+	traffic_subscribers: TrafficSubscribers,
+	pub tracer: CommandTracer,
+}
+
+///
+/// Исходящие команды пользователя за один цикл сервера, для просмотра трафика комнаты через debug сервер
+///
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomTrafficEvent {
+	pub member_id: RoomMemberId,
+	pub commands: Vec<CommandWithChannelType>,
 }
 
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
@@ -27,9 +54,22 @@ impl Rooms {
 			rooms: Default::default(),
 			room_id_generator: 0,
 			created_rooms_count: 0,
+			journal_dir: None,
+			journals: Default::default(),
+			command_measurer: None,
+			traffic_subscribers: Default::default(),
+			tracer: Default::default(),
 		}
 	}
 
+	pub fn set_journal_dir(&mut self, dir: Option<PathBuf>) {
+		self.journal_dir = dir;
+	}
+
+	pub(crate) fn set_command_measurer(&mut self, measurer: CommandMeasurer) {
+		self.command_measurer = Some(measurer);
+	}
+
 	pub(crate) fn get(&self, room_id: &RoomId) -> Option<&Room> {
 		self.rooms.get(room_id)
 	}
@@ -41,20 +81,93 @@ impl Rooms {
 		self.room_id_generator += 1;
 		self.created_rooms_count += 1;
 		let room_id = self.room_id_generator;
+		if template.journal {
+			self.create_journal(room_id, &template);
+		}
 		let room = Room::new(room_id, template);
 		self.rooms.insert(room_id, room);
 		room_id
 	}
 
+	fn create_journal(&mut self, room_id: RoomId, template: &RoomCreateParams) {
+		let dir = match &self.journal_dir {
+			None => {
+				tracing::warn!("[rooms] journal for room({}) requested, but journal dir not configured", room_id);
+				return;
+			}
+			Some(dir) => dir,
+		};
+		match JournalWriter::create(dir, room_id) {
+			Ok(writer) => {
+				tracing::info!("[rooms] room({}) journal {:?}", room_id, writer.path());
+				self.journals.insert(room_id, writer);
+				Self::write_journal(&mut self.journals, room_id, || JournalEvent::CreateRoom {
+					room_id,
+					template_name: template.name.clone(),
+					params: template.clone(),
+				});
+			}
+			Err(e) => {
+				tracing::error!("[rooms] create journal for room({}) error {}", room_id, e);
+			}
+		}
+	}
+
 	/// удалить комнату из списка без изменений пользователей и объектов
 	pub fn force_remove_room(&mut self, room_id: &RoomId) -> Result<Room, RoomNotFoundError> {
-		self.rooms.remove(room_id).ok_or(RoomNotFoundError(*room_id))
+		let room = self.rooms.remove(room_id).ok_or(RoomNotFoundError(*room_id))?;
+		self.traffic_subscribers.remove(room_id);
+		self.tracer.disable_room(*room_id);
+		Self::write_journal(&mut self.journals, *room_id, || JournalEvent::Snapshot(RoomSnapshot::new(&room)));
+		if let Some(mut writer) = self.journals.remove(room_id) {
+			if let Err(e) = writer.flush() {
+				tracing::error!("[rooms] room({}) journal error {}", room_id, e);
+			}
+		}
+		Ok(room)
 	}
 
 	pub fn register_member(&mut self, room_id: RoomId, member_template: MemberCreateParams) -> Result<RoomMemberId, RoomNotFoundError> {
 		match self.rooms.get_mut(&room_id) {
 			None => Err(RoomNotFoundError(room_id)),
-			Some(room) => Ok(room.register_member(member_template)),
+			Some(room) => {
+				let member_id = room.register_member(member_template.clone());
+				Self::write_journal(&mut self.journals, room_id, || JournalEvent::RegisterMember {
+					member_id,
+					template: member_template,
+					generated: true,
+				});
+				Ok(member_id)
+			}
+		}
+	}
+
+	pub fn register_member_with_id(&mut self, id: MemberAndRoomId, member_template: MemberCreateParams) -> Result<(), ServerCommandError> {
+		match self.rooms.get_mut(&id.room_id) {
+			None => Err(ServerCommandError::RoomNotFound(RoomNotFoundError(id.room_id))),
+			Some(room) => {
+				room.register_member_with_id(id.member_id, member_template.clone())?;
+				Self::write_journal(&mut self.journals, id.room_id, || JournalEvent::RegisterMember {
+					member_id: id.member_id,
+					template: member_template,
+					generated: false,
+				});
+				Ok(())
+			}
+		}
+	}
+
+	pub fn resume_member(&mut self, id: MemberAndRoomId, member_template: &MemberCreateParams) -> Result<(), ServerCommandError> {
+		match self.rooms.get_mut(&id.room_id) {
+			None => Err(ServerCommandError::RoomNotFound(RoomNotFoundError(id.room_id))),
+			Some(room) => {
+				room.resume_member(id.member_id, member_template)?;
+				Self::write_journal(&mut self.journals, id.room_id, || JournalEvent::ResumeMember {
+					member_id: id.member_id,
+					template: member_template.clone(),
+				});
+				Ok(())
+			}
 		}
 	}
 
@@ -63,10 +176,44 @@ impl Rooms {
 		F: FnMut(&RoomId, &RoomMemberId, &[CommandWithChannelType]),
 	{
 		for (room_id, room) in &mut self.rooms {
+			let mut subscribers = self.traffic_subscribers.get_mut(room_id);
 			room.collect_out_commands(|member_id, commands| {
+				self.tracer.trace(*room_id, *member_id, commands.iter().map(|command| &command.command));
+				if let Some(subscribers) = subscribers.as_mut() {
+					if !commands.is_empty() {
+						Self::publish_traffic(subscribers, *member_id, commands);
+					}
+				}
 				collector(room_id, member_id, commands);
 			});
 		}
+		self.traffic_subscribers.retain(|_, subscribers| !subscribers.is_empty());
+	}
+
+	///
+	/// Подписаться на исходящие команды комнаты, подписка удаляется при удалении комнаты или закрытии получателя
+	///
+	pub fn subscribe_traffic(&mut self, room_id: RoomId, sender: Sender<RoomTrafficEvent>) -> Result<(), RoomNotFoundError> {
+		if !self.rooms.contains_key(&room_id) {
+			return Err(RoomNotFoundError(room_id));
+		}
+		self.traffic_subscribers.entry(room_id).or_default().push(sender);
+		Ok(())
+	}
+
+	fn publish_traffic(subscribers: &mut Vec<Sender<RoomTrafficEvent>>, member_id: RoomMemberId, commands: &[CommandWithChannelType]) {
+		subscribers.retain(|subscriber| {
+			let event = RoomTrafficEvent {
+				member_id,
+				commands: commands.to_vec(),
+			};
+			match subscriber.try_send(event) {
+				Ok(_) => true,
+				// подписчик не успевает читать, события пропускаются
+				Err(TrySendError::Full(_)) => true,
+				Err(TrySendError::Closed(_)) => false,
+			}
+		});
 	}
 
 	pub fn execute_commands(&mut self, member_and_room_id: MemberAndRoomId, commands: &[CommandWithReliabilityGuarantees]) {
@@ -75,7 +222,21 @@ impl Rooms {
 				tracing::error!("[rooms] on_frame_received room({}) not found", member_and_room_id.room_id);
 			}
 			Some(room) => {
-				room.execute_commands(member_and_room_id.member_id, commands);
+				let MemberAndRoomId { member_id, room_id } = member_and_room_id;
+				self.tracer.trace(room_id, member_id, commands.iter().map(|command| &command.command));
+				let is_online = |room: &Room| room.members.get(&member_id).is_some_and(|member| member.status.is_online());
+				let connect = room.members.contains_key(&member_id) && !is_online(room);
+				room.execute_commands_with_measurer(member_id, commands, self.command_measurer.as_ref());
+				if connect {
+					Self::write_journal(&mut self.journals, room_id, || JournalEvent::ConnectMember(member_id));
+				}
+				// при ошибке подключения команды не выполняются
+				if !commands.is_empty() && is_online(room) {
+					Self::write_journal(&mut self.journals, room_id, || JournalEvent::Commands {
+						member_id,
+						commands: commands.to_vec(),
+					});
+				}
 			}
 		}
 	}
@@ -84,8 +245,34 @@ impl Rooms {
 		match self.rooms.get_mut(&member_and_room_id.room_id) {
 			None => Err(ServerCommandError::RoomNotFound(RoomNotFoundError(member_and_room_id.room_id))),
 			Some(room) => {
-				room.disconnect_member(member_and_room_id.member_id)?;
-				Ok(())
+				let result = room.disconnect_member(member_and_room_id.member_id);
+				Self::write_journal(&mut self.journals, member_and_room_id.room_id, || JournalEvent::DisconnectMember(member_and_room_id.member_id));
+				result
+			}
+		}
+	}
+
+	///
+	/// Сбросить буферы журналов на диск, вызывается каждый цикл сервера
+	///
+	pub fn flush_journals(&mut self) {
+		self.journals.retain(|room_id, writer| match writer.flush() {
+			Ok(_) => true,
+			Err(e) => {
+				tracing::error!("[rooms] room({}) journal error {}, journal disabled", room_id, e);
+				false
+			}
+		});
+	}
+
+	fn write_journal<F>(journals: &mut Journals, room_id: RoomId, event: F)
+	where
+		F: FnOnce() -> JournalEvent,
+	{
+		if let Some(writer) = journals.get_mut(&room_id) {
+			if let Err(e) = writer.write(event()) {
+				tracing::error!("[rooms] room({}) journal error {}, journal disabled", room_id, e);
+				journals.remove(&room_id);
 			}
 		}
 	}
@@ -94,6 +281,9 @@ impl Rooms {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use cheetah_common::commands::c2s::C2SCommand;
+	use cheetah_common::commands::guarantees::ReliabilityGuaranteesChannel;
+	use cheetah_common::commands::BothDirectionCommand;
 
 	#[test]
 	fn should_remove_room() {
@@ -115,6 +305,36 @@ mod tests {
 		assert_eq!(rooms.created_rooms_count, 2);
 	}
 
+	#[test]
+	fn should_publish_room_traffic() {
+		let mut rooms = Rooms::default();
+		let room_id = rooms.create_room(RoomCreateParams::default());
+		let super_member_id = rooms.register_member(room_id, MemberCreateParams::new_super_member()).unwrap();
+		let member_id = rooms.register_member(room_id, MemberCreateParams::default()).unwrap();
+		let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+		rooms.subscribe_traffic(room_id, sender).unwrap();
+
+		let attach = CommandWithReliabilityGuarantees {
+			reliability_guarantees: ReliabilityGuaranteesChannel::ReliableUnordered,
+			command: BothDirectionCommand::C2S(C2SCommand::AttachToRoom),
+		};
+		rooms.execute_commands(MemberAndRoomId { member_id: super_member_id, room_id }, &[attach]);
+		rooms.collect_out_commands(|_, _, _| {});
+		while receiver.try_recv().is_ok() {}
+
+		rooms.execute_commands(MemberAndRoomId { member_id, room_id }, &[]);
+		rooms.collect_out_commands(|_, _, _| {});
+
+		let event = receiver.try_recv().unwrap();
+		assert_eq!(event.member_id, super_member_id);
+		assert!(!event.commands.is_empty());
+		assert!(receiver.try_recv().is_err());
+
+		rooms.force_remove_room(&room_id).unwrap();
+		assert!(rooms.traffic_subscribers.is_empty());
+		assert!(matches!(rooms.subscribe_traffic(room_id, tokio::sync::mpsc::channel(1).0), Err(RoomNotFoundError(_))));
+	}
+
 	#[test]
 	fn should_remove_room_room_not_found() {
 		let mut rooms = Rooms::default();
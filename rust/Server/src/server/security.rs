@@ -0,0 +1,353 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+use tokio_rustls::rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::service::Interceptor;
+use tonic::transport::{Identity, ServerTlsConfig};
+use tonic::{Request, Status};
+
+const AUTHORIZATION_HEADER: &str = "authorization";
+const BEARER_SCHEME: &str = "Bearer ";
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+///
+/// Настройки TLS для внутренних сервисов (grpc, grpc-web, debug rest)
+///
+/// - сертификат и ключ сервера в формате PEM
+/// - если задан сертификат CA клиентов - то сервер принимает только соединения с сертификатом, подписанным этим CA (mTLS)
+///
+#[derive(Debug, Clone)]
+pub struct TlsSettings {
+	pub cert: Vec<u8>,
+	pub key: Vec<u8>,
+	pub client_ca: Option<Vec<u8>>,
+}
+
+#[derive(Error, Debug)]
+pub enum TlsSettingsError {
+	#[error("Io {0}")]
+	Io(#[from] io::Error),
+	#[error("Certificate not found")]
+	CertificateNotFound,
+	#[error("Private key not found")]
+	PrivateKeyNotFound,
+	#[error("Rustls {0}")]
+	Rustls(#[from] rustls::Error),
+}
+
+impl TlsSettings {
+	#[must_use]
+	pub fn new(cert: Vec<u8>, key: Vec<u8>) -> Self {
+		Self { cert, key, client_ca: None }
+	}
+
+	#[must_use]
+	pub fn with_client_ca(mut self, client_ca: Vec<u8>) -> Self {
+		self.client_ca = Some(client_ca);
+		self
+	}
+
+	pub fn from_files(cert: &Path, key: &Path, client_ca: Option<&Path>) -> io::Result<Self> {
+		Ok(Self {
+			cert: std::fs::read(cert)?,
+			key: std::fs::read(key)?,
+			client_ca: client_ca.map(std::fs::read).transpose()?,
+		})
+	}
+
+	pub(crate) fn grpc_config(&self) -> ServerTlsConfig {
+		let config = ServerTlsConfig::new().identity(Identity::from_pem(&self.cert, &self.key));
+		match &self.client_ca {
+			None => config,
+			Some(client_ca) => config.client_ca_root(tonic::transport::Certificate::from_pem(client_ca)),
+		}
+	}
+
+	pub(crate) fn rustls_config(&self) -> Result<ServerConfig, TlsSettingsError> {
+		let certs: Vec<_> = rustls_pemfile::certs(&mut self.cert.as_slice())?.into_iter().map(Certificate).collect();
+		if certs.is_empty() {
+			return Err(TlsSettingsError::CertificateNotFound);
+		}
+		let key = rustls_pemfile::read_all(&mut self.key.as_slice())?
+			.into_iter()
+			.find_map(|item| match item {
+				rustls_pemfile::Item::RSAKey(key) | rustls_pemfile::Item::PKCS8Key(key) | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+				_ => None,
+			})
+			.ok_or(TlsSettingsError::PrivateKeyNotFound)?;
+
+		let builder = ServerConfig::builder().with_safe_defaults();
+		let config = match &self.client_ca {
+			None => builder.with_no_client_auth().with_single_cert(certs, key)?,
+			Some(client_ca) => {
+				let mut roots = RootCertStore::empty();
+				for cert in rustls_pemfile::certs(&mut client_ca.as_slice())? {
+					roots.add(&Certificate(cert))?;
+				}
+				if roots.is_empty() {
+					return Err(TlsSettingsError::CertificateNotFound);
+				}
+				builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed()).with_single_cert(certs, key)?
+			}
+		};
+		Ok(config)
+	}
+}
+
+///
+/// Проверка bearer токена во всех запросах к grpc сервису управления,
+/// без заданного токена пропускаются все запросы
+///
+#[derive(Debug, Clone, Default)]
+pub struct BearerTokenInterceptor {
+	token: Option<Arc<str>>,
+}
+
+impl BearerTokenInterceptor {
+	#[must_use]
+	pub fn new(token: Option<Arc<str>>) -> Self {
+		Self { token }
+	}
+}
+
+impl Interceptor for BearerTokenInterceptor {
+	fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+		let authorization = request.metadata().get(AUTHORIZATION_HEADER).and_then(|value| value.to_str().ok());
+		if is_authorized(self.token.as_deref(), authorization) {
+			Ok(request)
+		} else {
+			Err(Status::unauthenticated("Wrong bearer token"))
+		}
+	}
+}
+
+///
+/// Проверить значение заголовка authorization (Bearer <token>)
+///
+pub(crate) fn is_authorized(token: Option<&str>, authorization: Option<&str>) -> bool {
+	match token {
+		None => true,
+		Some(token) => authorization
+			.and_then(|authorization| {
+				let scheme = authorization.get(..BEARER_SCHEME.len())?;
+				scheme.eq_ignore_ascii_case(BEARER_SCHEME).then(|| &authorization[BEARER_SCHEME.len()..])
+			})
+			.is_some_and(|value| constant_time_eq(value.as_bytes(), token.as_bytes())),
+	}
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+///
+/// Поток TLS соединений для warp, handshake выполняется в отдельной задаче,
+/// чтобы медленный клиент не блокировал прием новых соединений
+///
+pub(crate) fn tls_incoming(listener: TcpListener, acceptor: TlsAcceptor) -> ReceiverStream<io::Result<TlsStream<tokio::net::TcpStream>>> {
+	let (sender, receiver) = mpsc::channel(16);
+	tokio::spawn(async move {
+		while !sender.is_closed() {
+			let (stream, addr) = match listener.accept().await {
+				Ok(accepted) => accepted,
+				Err(e) => {
+					tracing::error!("Error accept tcp connection {:?}", e);
+					continue;
+				}
+			};
+			let acceptor = acceptor.clone();
+			let sender = sender.clone();
+			tokio::spawn(async move {
+				match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+					Ok(Ok(stream)) => {
+						let _ = sender.send(Ok(stream)).await;
+					}
+					Ok(Err(e)) => tracing::debug!("TLS handshake with {:?} failed {:?}", addr, e),
+					Err(_) => tracing::debug!("TLS handshake with {:?} timed out", addr),
+				}
+			});
+		}
+	});
+	ReceiverStream::new(receiver)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::builder::ServerBuilder;
+	use crate::server::manager::grpc::proto::realtime_server_management_service_client::RealtimeServerManagementServiceClient;
+	use crate::server::manager::grpc::proto::EmptyRequest;
+	use crate::server::security::{is_authorized, BearerTokenInterceptor, TlsSettings, TlsSettingsError};
+	use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa};
+	use std::net::SocketAddr;
+	use std::sync::Arc;
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+	use tokio::net::TcpStream;
+	use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerName};
+	use tokio_rustls::TlsConnector;
+	use tonic::service::Interceptor;
+	use tonic::transport::{Channel, ClientTlsConfig, Endpoint, Identity};
+	use tonic::{Code, Request};
+
+	const TOKEN: &str = "secret";
+
+	#[test]
+	fn should_authorize_without_token() {
+		assert!(is_authorized(None, None));
+		assert!(is_authorized(None, Some("Bearer any")));
+	}
+
+	#[test]
+	fn should_check_bearer_token() {
+		assert!(is_authorized(Some("secret"), Some("Bearer secret")));
+		assert!(is_authorized(Some("secret"), Some("bearer secret")));
+		assert!(!is_authorized(Some("secret"), Some("Bearer other")));
+		assert!(!is_authorized(Some("secret"), Some("Bearer secret2")));
+		assert!(!is_authorized(Some("secret"), Some("Basic secret")));
+		assert!(!is_authorized(Some("secret"), Some("secret")));
+		assert!(!is_authorized(Some("secret"), None));
+	}
+
+	#[test]
+	fn should_reject_grpc_request_without_token() {
+		let mut interceptor = BearerTokenInterceptor::new(Some(Arc::from("secret")));
+		assert_eq!(interceptor.call(Request::new(())).unwrap_err().code(), Code::Unauthenticated);
+
+		let mut request = Request::new(());
+		request.metadata_mut().insert("authorization", "Bearer secret".parse().unwrap());
+		assert!(interceptor.call(request).is_ok());
+	}
+
+	#[test]
+	fn should_fail_rustls_config_without_key() {
+		let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+		let settings = TlsSettings::new(cert.serialize_pem().unwrap().into_bytes(), Vec::new());
+		assert!(matches!(settings.rustls_config(), Err(TlsSettingsError::PrivateKeyNotFound)));
+
+		let settings = TlsSettings::new(cert.serialize_pem().unwrap().into_bytes(), cert.serialize_private_key_pem().into_bytes());
+		assert!(settings.rustls_config().is_ok());
+	}
+
+	#[tokio::test]
+	async fn should_check_token_for_grpc_over_tls() {
+		let certificates = TestCertificates::new();
+		let (grpc_addr, _) = run_server(TlsSettings::new(certificates.server_cert.clone().into_bytes(), certificates.server_key.clone().into_bytes())).await;
+
+		let mut client = RealtimeServerManagementServiceClient::new(connect(grpc_addr, &certificates, None).await.unwrap());
+		let status = client.get_rooms(EmptyRequest::default()).await.unwrap_err();
+		assert_eq!(status.code(), Code::Unauthenticated);
+		assert!(client.get_rooms(request_with_token()).await.is_ok());
+
+		let plaintext = Endpoint::from_shared(format!("http://{grpc_addr}")).unwrap().connect_lazy();
+		let status = RealtimeServerManagementServiceClient::new(plaintext).get_rooms(request_with_token()).await.unwrap_err();
+		assert_eq!(status.code(), Code::Unknown, "{status:?}");
+	}
+
+	#[tokio::test]
+	async fn should_require_client_certificate_for_grpc_with_mtls() {
+		let certificates = TestCertificates::new();
+		let tls = TlsSettings::new(certificates.server_cert.clone().into_bytes(), certificates.server_key.clone().into_bytes()).with_client_ca(certificates.ca.clone().into_bytes());
+		let (grpc_addr, _) = run_server(tls).await;
+
+		// при TLS 1.3 сертификат клиента проверяется сервером после завершения handshake на стороне клиента,
+		// поэтому соединение создается отложенно и ошибка проверяется на первом запросе
+		let without_certificate = endpoint(grpc_addr, &certificates, None).unwrap().connect_lazy();
+		let status = RealtimeServerManagementServiceClient::new(without_certificate).get_rooms(request_with_token()).await.unwrap_err();
+		assert_eq!(status.code(), Code::Unknown, "{status:?}");
+
+		let identity = Identity::from_pem(&certificates.client_cert, &certificates.client_key);
+		let mut client = RealtimeServerManagementServiceClient::new(connect(grpc_addr, &certificates, Some(identity)).await.unwrap());
+		assert!(client.get_rooms(request_with_token()).await.is_ok());
+	}
+
+	#[tokio::test]
+	async fn should_check_token_for_debug_rest_over_tls() {
+		let certificates = TestCertificates::new();
+		let (_, debug_addr) = run_server(TlsSettings::new(certificates.server_cert.clone().into_bytes(), certificates.server_key.clone().into_bytes())).await;
+
+		assert!(debug_rest_get(debug_addr, &certificates, None).await.starts_with("HTTP/1.1 401"));
+		assert!(debug_rest_get(debug_addr, &certificates, Some("other")).await.starts_with("HTTP/1.1 401"));
+		let response = debug_rest_get(debug_addr, &certificates, Some(TOKEN)).await;
+		assert!(response.starts_with("HTTP/1.1 200"));
+		assert!(response.contains("/simple_dump_rooms"));
+	}
+
+	struct TestCertificates {
+		ca: String,
+		server_cert: String,
+		server_key: String,
+		client_cert: String,
+		client_key: String,
+	}
+
+	impl TestCertificates {
+		fn new() -> Self {
+			let mut ca_params = CertificateParams::new(vec![]);
+			ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+			let ca = Certificate::from_params(ca_params).unwrap();
+			let server = Certificate::from_params(CertificateParams::new(vec!["localhost".to_owned()])).unwrap();
+			let client = Certificate::from_params(CertificateParams::new(vec!["client".to_owned()])).unwrap();
+			Self {
+				ca: ca.serialize_pem().unwrap(),
+				server_cert: server.serialize_pem_with_signer(&ca).unwrap(),
+				server_key: server.serialize_private_key_pem(),
+				client_cert: client.serialize_pem_with_signer(&ca).unwrap(),
+				client_key: client.serialize_private_key_pem(),
+			}
+		}
+	}
+
+	async fn run_server(tls: TlsSettings) -> (SocketAddr, SocketAddr) {
+		let server = ServerBuilder::default().set_management_tls(tls).set_management_token(TOKEN.to_owned()).build().await.unwrap();
+		let grpc_addr = server.internal_grpc_listener.as_ref().unwrap().local_addr().unwrap();
+		let debug_addr = server.debug_rest_service_listener.as_ref().unwrap().local_addr().unwrap();
+		tokio::spawn(server.run());
+		(grpc_addr, debug_addr)
+	}
+
+	async fn connect(addr: SocketAddr, certificates: &TestCertificates, identity: Option<Identity>) -> Result<Channel, tonic::transport::Error> {
+		endpoint(addr, certificates, identity)?.connect().await
+	}
+
+	fn endpoint(addr: SocketAddr, certificates: &TestCertificates, identity: Option<Identity>) -> Result<Endpoint, tonic::transport::Error> {
+		let mut tls = ClientTlsConfig::new()
+			.ca_certificate(tonic::transport::Certificate::from_pem(&certificates.ca))
+			.domain_name("localhost");
+		if let Some(identity) = identity {
+			tls = tls.identity(identity);
+		}
+		Endpoint::from_shared(format!("https://{addr}")).unwrap().tls_config(tls)
+	}
+
+	fn request_with_token() -> Request<EmptyRequest> {
+		let mut request = Request::new(EmptyRequest::default());
+		request.metadata_mut().insert("authorization", format!("Bearer {TOKEN}").parse().unwrap());
+		request
+	}
+
+	async fn debug_rest_get(addr: SocketAddr, certificates: &TestCertificates, token: Option<&str>) -> String {
+		let mut roots = RootCertStore::empty();
+		for cert in rustls_pemfile::certs(&mut certificates.ca.as_bytes()).unwrap() {
+			roots.add(&tokio_rustls::rustls::Certificate(cert)).unwrap();
+		}
+		let config = ClientConfig::builder().with_safe_defaults().with_root_certificates(roots).with_no_client_auth();
+		let stream = TcpStream::connect(addr).await.unwrap();
+		let mut stream = TlsConnector::from(Arc::new(config)).connect(ServerName::try_from("localhost").unwrap(), stream).await.unwrap();
+
+		let authorization = token.map(|token| format!("Authorization: Bearer {token}\r\n")).unwrap_or_default();
+		let request = format!("GET / HTTP/1.1\r\nHost: localhost\r\n{authorization}Connection: close\r\n\r\n");
+		stream.write_all(request.as_bytes()).await.unwrap();
+		let mut response = String::new();
+		stream.read_to_string(&mut response).await.unwrap();
+		response
+	}
+}
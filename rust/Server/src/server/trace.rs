@@ -0,0 +1,186 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use cheetah_common::commands::BothDirectionCommand;
+use cheetah_game_realtime_protocol::{RoomId, RoomMemberId};
+use fnv::FnvBuildHasher;
+use serde::Serialize;
+
+///
+/// Количество последних трассированных команд комнаты, более старые команды удаляются
+///
+const TRACE_BUFFER_SIZE: usize = 1_000;
+
+///
+/// Максимальное время трассировки, на большее время трассировка не включается
+///
+pub const MAX_TRACE_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+///
+/// Цель логов трассировки, для вывода в отдельный файл или фильтрации
+///
+pub const TRACE_LOG_TARGET: &str = "command_trace";
+
+///
+/// Область трассировки - все пользователи комнаты или один пользователь
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceScope {
+	pub room_id: RoomId,
+	pub member_id: Option<RoomMemberId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TracedCommand {
+	pub time_in_ms: u128,
+	pub room_id: RoomId,
+	pub member_id: RoomMemberId,
+	pub command: BothDirectionCommand,
+}
+
+///
+/// Трассировка входящих и исходящих команд для выбранных комнат и пользователей
+///
+/// - области трассировки включаются администратором на заданное время
+/// - команды пишутся в лог с целью [`TRACE_LOG_TARGET`] и в кольцевой буфер комнаты,
+///   буфер доступен и после окончания трассировки, удаляется вместе с комнатой
+///
+#[derive(Debug, Default)]
+pub struct CommandTracer {
+	scopes: HashMap<TraceScope, Instant, FnvBuildHasher>,
+	commands: HashMap<RoomId, VecDeque<TracedCommand>, FnvBuildHasher>,
+}
+
+impl CommandTracer {
+	///
+	/// Включить трассировку до deadline, повторный вызов для той же области меняет deadline,
+	/// время ограничивается [`MAX_TRACE_DURATION`]
+	///
+	pub fn enable(&mut self, scope: TraceScope, now: Instant, duration: Duration) {
+		tracing::info!("[trace] enable {:?} for {:?}", scope, duration);
+		self.scopes.insert(scope, now + duration.min(MAX_TRACE_DURATION));
+	}
+
+	pub fn disable(&mut self, scope: &TraceScope) -> bool {
+		tracing::info!("[trace] disable {:?}", scope);
+		self.scopes.remove(scope).is_some()
+	}
+
+	///
+	/// Выключить трассировку комнаты и всех ее пользователей, удалить буфер команд комнаты
+	///
+	pub fn disable_room(&mut self, room_id: RoomId) {
+		self.scopes.retain(|scope, _| scope.room_id != room_id);
+		self.commands.remove(&room_id);
+	}
+
+	pub fn remove_expired(&mut self, now: Instant) {
+		self.scopes.retain(|scope, deadline| {
+			let active = *deadline > now;
+			if !active {
+				tracing::info!("[trace] expired {:?}", scope);
+			}
+			active
+		});
+	}
+
+	#[must_use]
+	pub fn is_traced(&self, room_id: RoomId, member_id: RoomMemberId) -> bool {
+		!self.scopes.is_empty() && (self.scopes.contains_key(&TraceScope { room_id, member_id: None }) || self.scopes.contains_key(&TraceScope { room_id, member_id: Some(member_id) }))
+	}
+
+	pub fn trace<'a>(&mut self, room_id: RoomId, member_id: RoomMemberId, commands: impl Iterator<Item = &'a BothDirectionCommand>) {
+		if !self.is_traced(room_id, member_id) {
+			return;
+		}
+		let time_in_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+		let buffer = self.commands.entry(room_id).or_default();
+		for command in commands {
+			tracing::info!(target: TRACE_LOG_TARGET, "[room({})] member({}) {:?}", room_id, member_id, command);
+			if buffer.len() == TRACE_BUFFER_SIZE {
+				buffer.pop_front();
+			}
+			buffer.push_back(TracedCommand {
+				time_in_ms,
+				room_id,
+				member_id,
+				command: command.clone(),
+			});
+		}
+	}
+
+	///
+	/// Трассированные команды комнаты из буфера, в порядке выполнения
+	///
+	#[must_use]
+	pub fn get_commands(&self, room_id: RoomId) -> Vec<TracedCommand> {
+		self.commands.get(&room_id).map(|buffer| buffer.iter().cloned().collect()).unwrap_or_default()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+
+	use cheetah_common::commands::c2s::C2SCommand;
+	use cheetah_common::commands::BothDirectionCommand;
+
+	use crate::server::trace::{CommandTracer, TraceScope, MAX_TRACE_DURATION, TRACE_BUFFER_SIZE};
+
+	#[test]
+	fn should_trace_only_enabled_scopes() {
+		let mut tracer = CommandTracer::default();
+		let now = Instant::now();
+		tracer.enable(TraceScope { room_id: 1, member_id: None }, now, Duration::from_secs(60));
+		tracer.enable(TraceScope { room_id: 2, member_id: Some(5) }, now, Duration::from_secs(60));
+
+		let command = BothDirectionCommand::C2S(C2SCommand::AttachToRoom);
+		tracer.trace(1, 1, [&command].into_iter());
+		tracer.trace(2, 5, [&command].into_iter());
+		tracer.trace(2, 6, [&command].into_iter());
+		tracer.trace(3, 1, [&command].into_iter());
+
+		assert_eq!(tracer.get_commands(1).len(), 1);
+		assert_eq!(tracer.get_commands(2).len(), 1);
+		assert_eq!(tracer.get_commands(2)[0].member_id, 5);
+		assert!(tracer.get_commands(3).is_empty());
+	}
+
+	#[test]
+	fn should_remove_expired_scopes() {
+		let mut tracer = CommandTracer::default();
+		let now = Instant::now();
+		tracer.enable(TraceScope { room_id: 1, member_id: None }, now, Duration::from_secs(60));
+		tracer.remove_expired(now + Duration::from_secs(30));
+		assert!(tracer.is_traced(1, 1));
+		tracer.remove_expired(now + Duration::from_secs(60));
+		assert!(!tracer.is_traced(1, 1));
+	}
+
+	#[test]
+	fn should_limit_trace_buffer() {
+		let mut tracer = CommandTracer::default();
+		tracer.enable(TraceScope { room_id: 1, member_id: None }, Instant::now(), Duration::from_secs(60));
+		tracer.enable(TraceScope { room_id: 2, member_id: None }, Instant::now(), Duration::from_secs(60));
+		let command = BothDirectionCommand::C2S(C2SCommand::AttachToRoom);
+		tracer.trace(2, 1, [&command].into_iter());
+		for _ in 0..TRACE_BUFFER_SIZE + 10 {
+			tracer.trace(1, 1, [&command].into_iter());
+		}
+		assert_eq!(tracer.get_commands(1).len(), TRACE_BUFFER_SIZE);
+		assert_eq!(tracer.get_commands(2).len(), 1);
+
+		tracer.disable_room(1);
+		assert!(tracer.get_commands(1).is_empty());
+		assert_eq!(tracer.get_commands(2).len(), 1);
+	}
+
+	#[test]
+	fn should_limit_trace_duration() {
+		let mut tracer = CommandTracer::default();
+		let now = Instant::now();
+		tracer.enable(TraceScope { room_id: 1, member_id: None }, now, Duration::MAX);
+		tracer.remove_expired(now + MAX_TRACE_DURATION);
+		assert!(!tracer.is_traced(1, 1));
+	}
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use cheetah_common::network::websocket::{WEBSOCKET_MAX_MESSAGE_SIZE, WEBSOCKET_MAX_SEND_QUEUE};
+use tungstenite::handshake::server::{NoCallback, ServerHandshake};
+use tungstenite::handshake::MidHandshake;
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::{HandshakeError, Message, WebSocket};
+
+pub type WebSocketConnectionId = u64;
+
+///
+/// Время, за которое клиент должен завершить handshake, иначе соединение закрывается
+///
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+///
+/// Время, за которое по соединению должен прийти фрейм существующей сессии, иначе соединение закрывается
+///
+const VALID_FRAME_TIMEOUT: Duration = Duration::from_secs(10);
+///
+/// Ограничения на количество незавершенных handshake, соединений (вместе с handshake) и принимаемых за цикл соединений,
+/// соединения сверх ограничений закрываются сразу после приема
+///
+const MAX_HANDSHAKES: usize = 64;
+const MAX_CONNECTIONS: usize = 4096;
+const MAX_ACCEPT_PER_CYCLE: usize = 32;
+
+///
+/// Прием WebSocket соединений для клиентов без UDP (сети с заблокированным UDP, прокси)
+///
+/// - каждый фрейм передается отдельным binary сообщением, кодирование фреймов такое же как и для UDP
+/// - listener и соединения неблокирующие, обслуживаются в потоке сервера вместе с UDP сокетом
+/// - соединение не привязано к пользователю, пользователь определяется по заголовку фрейма, так же как для UDP
+/// - сетевой слой сообщает о фреймах существующих сессий через [`WebSocketListener::on_valid_frame`],
+///   соединения без таких фреймов закрываются
+///
+#[derive(Debug)]
+pub struct WebSocketListener {
+	listener: TcpListener,
+	handshakes: Vec<(Instant, MidHandshake<ServerHandshake<TcpStream, NoCallback>>)>,
+	connections: HashMap<WebSocketConnectionId, WebSocketConnection>,
+	next_connection_id: WebSocketConnectionId,
+}
+
+#[derive(Debug)]
+struct WebSocketConnection {
+	socket: WebSocket<TcpStream>,
+	///
+	/// Время приема последнего фрейма существующей сессии или время установки соединения
+	///
+	valid_frame_time: Instant,
+}
+
+impl WebSocketListener {
+	pub fn new(listener: TcpListener) -> io::Result<Self> {
+		listener.set_nonblocking(true)?;
+		Ok(Self {
+			listener,
+			handshakes: Default::default(),
+			connections: Default::default(),
+			next_connection_id: 0,
+		})
+	}
+
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.listener.local_addr()
+	}
+
+	///
+	/// Принять новые соединения, продолжить handshake и отправить сообщения из очередей
+	///
+	pub fn cycle(&mut self, now: Instant) {
+		self.accept(now);
+		self.continue_handshakes(now);
+		self.connections.retain(|id, connection| {
+			if now.duration_since(connection.valid_frame_time) > VALID_FRAME_TIMEOUT {
+				tracing::debug!("[websocket] close connection {:?} without valid frames", id);
+				return false;
+			}
+			match connection.socket.write_pending() {
+				Ok(_) => true,
+				Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => true,
+				Err(e) => {
+					tracing::debug!("[websocket] close connection {:?} {:?}", id, e);
+					false
+				}
+			}
+		});
+	}
+
+	///
+	/// По соединению пришел фрейм существующей сессии
+	///
+	pub fn on_valid_frame(&mut self, id: WebSocketConnectionId, now: Instant) {
+		if let Some(connection) = self.connections.get_mut(&id) {
+			connection.valid_frame_time = now;
+		}
+	}
+
+	///
+	/// Получить все пришедшие binary сообщения, закрытые соединения удаляются
+	///
+	pub fn receive(&mut self) -> Vec<(WebSocketConnectionId, Vec<u8>)> {
+		let mut messages = Vec::new();
+		self.connections.retain(|id, connection| loop {
+			match connection.socket.read_message() {
+				Ok(Message::Binary(data)) => messages.push((*id, data)),
+				Ok(Message::Close(_)) => {
+					tracing::debug!("[websocket] connection {:?} closed by client", id);
+					return false;
+				}
+				Ok(_) => {}
+				Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => return true,
+				Err(e) => {
+					tracing::debug!("[websocket] close connection {:?} {:?}", id, e);
+					return false;
+				}
+			}
+		});
+		messages
+	}
+
+	///
+	/// Отправить сообщение, при переполнении очереди сообщение отбрасывается как потерянный UDP пакет
+	///
+	pub fn send(&mut self, id: WebSocketConnectionId, data: &[u8]) {
+		match self.connections.get_mut(&id) {
+			None => {
+				tracing::debug!("[websocket] connection {:?} not found", id);
+			}
+			Some(connection) => match connection.socket.write_message(Message::Binary(data.to_vec())) {
+				Ok(_) | Err(tungstenite::Error::SendQueueFull(_)) => {}
+				Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => {}
+				Err(e) => {
+					tracing::debug!("[websocket] close connection {:?} {:?}", id, e);
+					self.connections.remove(&id);
+				}
+			},
+		}
+	}
+
+	fn accept(&mut self, now: Instant) {
+		for _ in 0..MAX_ACCEPT_PER_CYCLE {
+			match self.listener.accept() {
+				Ok((stream, address)) => {
+					if self.handshakes.len() >= MAX_HANDSHAKES || self.handshakes.len() + self.connections.len() >= MAX_CONNECTIONS {
+						tracing::debug!("[websocket] too many connections, close {:?}", address);
+						continue;
+					}
+					if let Err(e) = stream.set_nonblocking(true).and_then(|_| stream.set_nodelay(true)) {
+						tracing::error!("[websocket] configure stream error {:?} {:?}", address, e);
+						continue;
+					}
+					let config = WebSocketConfig {
+						max_send_queue: Some(WEBSOCKET_MAX_SEND_QUEUE),
+						max_message_size: Some(WEBSOCKET_MAX_MESSAGE_SIZE),
+						max_frame_size: Some(WEBSOCKET_MAX_MESSAGE_SIZE),
+						..Default::default()
+					};
+					self.on_handshake_result(now, now, tungstenite::accept_with_config(stream, Some(config)));
+				}
+				Err(e) => {
+					if e.kind() != ErrorKind::WouldBlock {
+						tracing::error!("[websocket] accept error {:?}", e);
+					}
+					return;
+				}
+			}
+		}
+	}
+
+	fn continue_handshakes(&mut self, now: Instant) {
+		for (start_time, handshake) in std::mem::take(&mut self.handshakes) {
+			if now.duration_since(start_time) > HANDSHAKE_TIMEOUT {
+				tracing::debug!("[websocket] handshake timeout {:?}", handshake.get_ref().get_ref().peer_addr());
+				continue;
+			}
+			self.on_handshake_result(now, start_time, handshake.handshake());
+		}
+	}
+
+	fn on_handshake_result(&mut self, now: Instant, start_time: Instant, result: Result<WebSocket<TcpStream>, HandshakeError<ServerHandshake<TcpStream, NoCallback>>>) {
+		match result {
+			Ok(socket) => {
+				self.next_connection_id += 1;
+				self.connections.insert(self.next_connection_id, WebSocketConnection { socket, valid_frame_time: now });
+			}
+			Err(HandshakeError::Interrupted(handshake)) => {
+				self.handshakes.push((start_time, handshake));
+			}
+			Err(HandshakeError::Failure(e)) => {
+				tracing::debug!("[websocket] handshake error {:?}", e);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::{TcpListener, TcpStream};
+	use std::time::{Duration, Instant};
+
+	use tungstenite::Message;
+
+	use crate::server::websocket::{WebSocketListener, MAX_ACCEPT_PER_CYCLE, MAX_HANDSHAKES, VALID_FRAME_TIMEOUT};
+
+	#[test]
+	fn should_exchange_messages() {
+		let mut listener = WebSocketListener::new(TcpListener::bind("127.0.0.1:0").unwrap()).unwrap();
+		let address = listener.local_addr().unwrap();
+		let client = std::thread::spawn(move || {
+			let (mut socket, _) = tungstenite::client(format!("ws://{address}/"), TcpStream::connect(address).unwrap()).unwrap();
+			socket.write_message(Message::Binary(vec![1, 2, 3])).unwrap();
+			let message = socket.read_message().unwrap();
+			socket.close(None).unwrap();
+			message
+		});
+
+		let start = Instant::now();
+		let mut messages = Vec::new();
+		while messages.is_empty() && start.elapsed() < Duration::from_secs(5) {
+			listener.cycle(Instant::now());
+			messages = listener.receive();
+			std::thread::sleep(Duration::from_millis(1));
+		}
+		assert_eq!(messages, vec![(1, vec![1, 2, 3])]);
+
+		listener.send(1, &[4, 5]);
+		listener.cycle(Instant::now());
+		assert_eq!(client.join().unwrap(), Message::Binary(vec![4, 5]));
+	}
+
+	#[test]
+	fn should_remove_closed_connection() {
+		let mut listener = WebSocketListener::new(TcpListener::bind("127.0.0.1:0").unwrap()).unwrap();
+		let address = listener.local_addr().unwrap();
+		let client = std::thread::spawn(move || {
+			let (mut socket, _) = tungstenite::client(format!("ws://{address}/"), TcpStream::connect(address).unwrap()).unwrap();
+			socket.close(None).unwrap();
+			socket.write_pending().unwrap();
+		});
+
+		let start = Instant::now();
+		while (listener.next_connection_id == 0 || !listener.connections.is_empty()) && start.elapsed() < Duration::from_secs(5) {
+			listener.cycle(Instant::now());
+			listener.receive();
+			std::thread::sleep(Duration::from_millis(1));
+		}
+		client.join().unwrap();
+		assert_eq!(listener.next_connection_id, 1);
+		assert!(listener.connections.is_empty());
+		assert!(listener.handshakes.is_empty());
+	}
+
+	#[test]
+	fn should_close_connection_without_valid_frames() {
+		let mut listener = WebSocketListener::new(TcpListener::bind("127.0.0.1:0").unwrap()).unwrap();
+		let address = listener.local_addr().unwrap();
+		let client = std::thread::spawn(move || tungstenite::client(format!("ws://{address}/"), TcpStream::connect(address).unwrap()).unwrap());
+
+		let start = Instant::now();
+		while listener.connections.is_empty() && start.elapsed() < Duration::from_secs(5) {
+			listener.cycle(Instant::now());
+			std::thread::sleep(Duration::from_millis(1));
+		}
+		let _client = client.join().unwrap();
+		let now = Instant::now();
+		listener.on_valid_frame(1, now + VALID_FRAME_TIMEOUT);
+		listener.cycle(now + VALID_FRAME_TIMEOUT * 2);
+		assert_eq!(listener.connections.len(), 1);
+		listener.cycle(now + VALID_FRAME_TIMEOUT * 3);
+		assert!(listener.connections.is_empty());
+	}
+
+	#[test]
+	fn should_close_connection_with_too_large_message() {
+		let mut listener = WebSocketListener::new(TcpListener::bind("127.0.0.1:0").unwrap()).unwrap();
+		let address = listener.local_addr().unwrap();
+		let client = std::thread::spawn(move || {
+			let (mut socket, _) = tungstenite::client(format!("ws://{address}/"), TcpStream::connect(address).unwrap()).unwrap();
+			socket.write_message(Message::Binary(vec![0; 1024])).unwrap();
+			socket
+		});
+
+		let start = Instant::now();
+		while (listener.next_connection_id == 0 || !listener.connections.is_empty()) && start.elapsed() < Duration::from_secs(5) {
+			listener.cycle(Instant::now());
+			assert!(listener.receive().is_empty());
+			std::thread::sleep(Duration::from_millis(1));
+		}
+		let _client = client.join().unwrap();
+		assert!(listener.connections.is_empty());
+	}
+
+	#[test]
+	fn should_limit_accepted_connections() {
+		let mut listener = WebSocketListener::new(TcpListener::bind("127.0.0.1:0").unwrap()).unwrap();
+		let address = listener.local_addr().unwrap();
+		let _streams: Vec<_> = (0..MAX_HANDSHAKES + 10).map(|_| TcpStream::connect(address).unwrap()).collect();
+
+		let now = Instant::now();
+		listener.cycle(now);
+		assert_eq!(listener.handshakes.len(), MAX_ACCEPT_PER_CYCLE);
+		for _ in 0..10 {
+			listener.cycle(now);
+		}
+		assert_eq!(listener.handshakes.len(), MAX_HANDSHAKES);
+	}
+}